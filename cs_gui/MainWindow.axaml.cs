@@ -154,6 +154,8 @@ public partial class MainWindow : Window
             catch (Exception e) {
                 Console.WriteLine(e);
             }
+            foreach (var skipped in librariesTask.SkippedNatives)
+                Console.WriteLine($"A native library was skipped, the game may crash: {skipped}");
             
             try {
                 await jarTask.Task;
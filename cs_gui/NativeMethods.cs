@@ -80,6 +80,12 @@ public static partial class NativeMethods {
 	[DllImport(__DllName, EntryPoint = "cancel_libraries", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
 	public static extern unsafe void cancel_libraries(TaskWrapper* raw_task);
 
+	[DllImport(__DllName, EntryPoint = "skipped_natives_len", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe nuint skipped_natives_len(State* state);
+
+	[DllImport(__DllName, EntryPoint = "skipped_native", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe RefStringWrapper skipped_native(State* state, nuint index);
+
 	[DllImport(__DllName, EntryPoint = "get_assets", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
 	public static extern unsafe TaskWrapper* get_assets(State* state, ulong* total, ulong* finished);
 
@@ -92,6 +98,30 @@ public static partial class NativeMethods {
 	[DllImport(__DllName, EntryPoint = "cancel_assets", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
 	public static extern unsafe void cancel_assets(TaskWrapper* raw_task);
 
+	[DllImport(__DllName, EntryPoint = "get_collect_asset_garbage", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe TaskWrapper* get_collect_asset_garbage(State* state, bool dry_run);
+
+	[DllImport(__DllName, EntryPoint = "poll_collect_asset_garbage", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe bool poll_collect_asset_garbage(TaskWrapper* raw_task);
+
+	[DllImport(__DllName, EntryPoint = "await_collect_asset_garbage", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe NativeReturn await_collect_asset_garbage(TaskWrapper* raw_task, AssetGarbage* _return);
+
+	[DllImport(__DllName, EntryPoint = "cancel_collect_asset_garbage", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe void cancel_collect_asset_garbage(TaskWrapper* raw_task);
+
+	[DllImport(__DllName, EntryPoint = "get_prune_libraries", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe TaskWrapper* get_prune_libraries(State* state, bool dry_run);
+
+	[DllImport(__DllName, EntryPoint = "poll_prune_libraries", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe bool poll_prune_libraries(TaskWrapper* raw_task);
+
+	[DllImport(__DllName, EntryPoint = "await_prune_libraries", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe NativeReturn await_prune_libraries(TaskWrapper* raw_task, LibraryPrune* _return);
+
+	[DllImport(__DllName, EntryPoint = "cancel_prune_libraries", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+	public static extern unsafe void cancel_prune_libraries(TaskWrapper* raw_task);
+
 	[DllImport(__DllName, EntryPoint = "get_jar", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
 	public static extern unsafe TaskWrapper* get_jar(State* state, ulong* total, ulong* finished);
 
@@ -132,7 +162,7 @@ public static partial class NativeMethods {
 	public static extern unsafe bool poll_auth_loop(TaskWrapper* raw_task);
 
 	[DllImport(__DllName, EntryPoint = "await_auth_loop", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-	public static extern unsafe NativeReturn await_auth_loop(State* state, LauncherData* data, TaskWrapper* raw_task);
+	public static extern unsafe NativeReturn await_auth_loop(LauncherData* data, TaskWrapper* raw_task);
 
 	[DllImport(__DllName, EntryPoint = "cancel_auth_loop", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
 	public static extern unsafe void cancel_auth_loop(TaskWrapper* raw_task);
@@ -144,7 +174,7 @@ public static partial class NativeMethods {
 	public static extern unsafe bool poll_refresh(TaskWrapper* raw_task);
 
 	[DllImport(__DllName, EntryPoint = "await_refresh", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-	public static extern unsafe NativeReturn await_refresh(State* state, LauncherData* data, TaskWrapper* raw_task);
+	public static extern unsafe NativeReturn await_refresh(LauncherData* data, TaskWrapper* raw_task);
 
 	[DllImport(__DllName, EntryPoint = "accounts_len", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
 	public static extern unsafe nuint accounts_len(LauncherData* data);
@@ -165,13 +195,13 @@ public static partial class NativeMethods {
 	public static extern unsafe RefStringWrapper jvm_name(LauncherData* data, nuint index);
 
 	[DllImport(__DllName, EntryPoint = "add_jvm", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-	public static extern unsafe NativeReturn add_jvm(LauncherData* data, ushort* ptr, nuint len);
+	public static extern unsafe NativeReturn add_jvm(LauncherData* data, char* path_ptr, nuint path_len);
 
 	[DllImport(__DllName, EntryPoint = "remove_jvm", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
 	public static extern unsafe void remove_jvm(LauncherData* data, nuint index);
 
 	[DllImport(__DllName, EntryPoint = "read_data", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-	public static extern unsafe TaskWrapper* read_data(State* state);
+	public static extern unsafe TaskWrapper* read_data();
 
 	[DllImport(__DllName, EntryPoint = "alloc_data", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
 	public static extern unsafe LauncherData* alloc_data();
@@ -222,6 +252,18 @@ public struct State;
 [StructLayout(LayoutKind.Sequential)]
 public struct TaskWrapper;
 
+[StructLayout(LayoutKind.Sequential)]
+public struct AssetGarbage {
+	public nuint objects;
+	public ulong bytes;
+}
+
+[StructLayout(LayoutKind.Sequential)]
+public struct LibraryPrune {
+	public nuint files;
+	public ulong bytes;
+}
+
 [StructLayout(LayoutKind.Sequential)]
 public struct RustString {
 	private nuint _r1;
@@ -238,6 +280,8 @@ public enum Code : uint
 	ProfileError,
 	JvmError,
 	TomlDe,
+	Panic,
+	InvalidString,
 }
 
 public enum ReleaseType : uint
@@ -111,6 +111,7 @@ public class LibrariesTask {
                 var v = NativeMethods.await_libraries(_state, assetTask);
                 
                 if (v.code != csbindings.Code.Success) throw new RustException(v);
+                CollectSkippedNatives();
             }
         }
     }
@@ -134,10 +135,20 @@ public class LibrariesTask {
                 var v = NativeMethods.await_libraries(_state, assetTask);
                 
                 if (v.code != csbindings.Code.Success) throw new RustException(v);
+                CollectSkippedNatives();
             }
         }
     }
 
+    /// Natives that couldn't be extracted, the game may crash without them
+    public List<string> SkippedNatives { get; } = [];
+
+    private unsafe void CollectSkippedNatives() {
+        var len = NativeMethods.skipped_natives_len(_state);
+        for (nuint i = 0; i < len; i++)
+            SkippedNatives.Add(Encoding.UTF8.GetString(Program.CopyRefString(NativeMethods.skipped_native(_state, i))));
+    }
+
     public ulong Total => _total;
     public double Percentage => (double) _finished / _total;
 }
@@ -295,7 +306,7 @@ public class SafeNativeMethods {
 
     public Task GetData() => Task.Run(() => {
         unsafe {
-            var taskPtr = NativeMethods.read_data(State);
+            var taskPtr = NativeMethods.read_data();
             while (!NativeMethods.poll_data(taskPtr)) { }
 
             var ptr = NativeMethods.alloc_data();
@@ -377,7 +388,7 @@ public class SafeNativeMethods {
                     token.ThrowIfCancellationRequested();
                 }
 
-                var value = NativeMethods.await_auth_loop(State, _data, taskPointer);
+                var value = NativeMethods.await_auth_loop(_data, taskPointer);
 
                 if (value.code != csbindings.Code.Success) {
                     throw new RustException(value);
@@ -430,7 +441,7 @@ public class SafeNativeMethods {
             var task = NativeMethods.try_refresh(_data, index);
             while (!NativeMethods.poll_refresh(task)) { }
 
-            var v = NativeMethods.await_refresh(State, _data, task);
+            var v = NativeMethods.await_refresh(_data, task);
 
             if (v.code != csbindings.Code.Success) throw new RustException(v);
         }
@@ -465,7 +476,7 @@ public class SafeNativeMethods {
     public void AddJvm(string path) {
         unsafe {
             var arr = path.ToCharArray();
-            fixed (char* str = arr) NativeMethods.add_jvm(_data, (ushort*)str, (nuint)arr.Length);
+            fixed (char* str = arr) NativeMethods.add_jvm(_data, str, (nuint)arr.Length);
         }
     }
 
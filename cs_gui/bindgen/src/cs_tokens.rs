@@ -69,7 +69,11 @@ impl ToString for Scope {
                 format!("{acc}\n{}\n", s.to_string())
             });
 
-        format!("{imports}\n{name_space}{classes}\n{structs}")
+        let enums = self.name_space.enums.iter().fold(String::new(), |acc, e| {
+            format!("{acc}\n{}\n", e.to_string())
+        });
+
+        format!("{imports}\n{name_space}{classes}\n{structs}{enums}")
     }
 }
 
@@ -77,6 +81,7 @@ pub struct NameSpace {
     name: String,
     classes: Vec<Class>,
     structs: Vec<Struct>,
+    enums: Vec<Enum>,
 }
 
 impl NameSpace {
@@ -85,9 +90,18 @@ impl NameSpace {
             name,
             classes: vec![],
             structs: vec![],
+            enums: vec![],
         }
     }
 
+    pub fn add_enum(&mut self, _enum: Enum) -> &mut Enum {
+        self.enums.push(_enum);
+
+        let len = self.enums.len() - 1;
+
+        &mut self.enums[len]
+    }
+
     pub fn add_class(&mut self, class: Class) -> &mut Class {
         self.classes.push(class);
 
@@ -96,6 +110,10 @@ impl NameSpace {
         &mut self.classes[len]
     }
 
+    pub fn has_struct(&self, name: &str) -> bool {
+        self.structs.iter().any(|_struct| _struct.name == name)
+    }
+
     pub fn add_struct(&mut self, _struct: Struct) -> &mut Struct {
         self.structs.push(_struct);
 
@@ -132,6 +150,7 @@ pub enum Qualifier {
     Fixed,
     Virtual,
     Extern,
+    Async,
 }
 
 impl Qualifier {
@@ -145,6 +164,7 @@ impl Qualifier {
             Qualifier::Fixed => "fixed",
             Qualifier::Virtual => "virtual",
             Qualifier::Extern => "extern",
+            Qualifier::Async => "async",
         }
     }
 }
@@ -164,6 +184,8 @@ pub enum Type {
     Int,
     Long,
     Nint,
+    Float,
+    Double,
     Void,
     String,
     Verbatim(String),
@@ -197,6 +219,8 @@ impl Type {
             Type::Int => "int".into(),
             Type::Long => "long".into(),
             Type::Nint => "nint".into(),
+            Type::Float => "float".into(),
+            Type::Double => "double".into(),
             Type::String => "string".into(),
             Type::Void => "void".into(),
             Type::Verbatim(ty) => ty.to_owned().into(),
@@ -222,6 +246,8 @@ impl std::fmt::Display for Type {
             Type::Int => "int".into(),
             Type::Long => "long".into(),
             Type::Nint => "nint".into(),
+            Type::Float => "float".into(),
+            Type::Double => "double".into(),
             Type::String => "string".into(),
             Type::Void => "void".into(),
             _ => None,
@@ -345,6 +371,45 @@ impl ToString for Struct {
     }
 }
 
+pub struct Enum {
+    name: String,
+    ty: Type,
+    variants: Vec<(String, Option<String>)>,
+}
+
+impl Enum {
+    pub fn new(name: String, ty: Type) -> Self {
+        Self {
+            name,
+            ty,
+            variants: vec![],
+        }
+    }
+
+    /// The value is copied verbatim, and left for the C# compiler to assign if it's `None`
+    pub fn add_variant(&mut self, name: String, value: Option<String>) {
+        self.variants.push((name, value));
+    }
+}
+
+impl ToString for Enum {
+    fn to_string(&self) -> String {
+        let variants = self
+            .variants
+            .iter()
+            .fold(String::new(), |acc, (name, value)| match value {
+                Some(value) => format!("{acc}\n\t{name} = {value},"),
+                None => format!("{acc}\n\t{name},"),
+            });
+
+        format!(
+            "public enum {} : {} {{{variants}\n}}",
+            self.name,
+            self.ty.to_string()
+        )
+    }
+}
+
 pub struct Field {
     name: String,
     ty: Type,
@@ -421,12 +486,145 @@ impl Method {
     pub fn arg(&mut self, name: String, ty: Type) {
         self.args.push((ty, name))
     }
+
+    pub fn body(mut self, body: Block) -> Self {
+        self.set_body(body);
+        self
+    }
+
+    pub fn set_body(&mut self, body: Block) {
+        self.body = Some(body);
+    }
 }
 
-pub enum Block {
-    Empty,
-    Unsafe,
-    Fixed,
+#[derive(Default)]
+pub struct Block {
+    stmts: Vec<Stmt>,
+}
+
+impl Block {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stmt(mut self, stmt: Stmt) -> Self {
+        self.add_stmt(stmt);
+        self
+    }
+
+    pub fn add_stmt(&mut self, stmt: Stmt) {
+        self.stmts.push(stmt);
+    }
+
+    /// Renders the block including braces, `layer` being the indentation of the braces
+    fn render(&self, layer: usize) -> String {
+        let indents = "\t".repeat(layer);
+
+        let stmts = self.stmts.iter().fold(String::new(), |acc, stmt| {
+            format!("{acc}{}\n", stmt.render(layer + 1))
+        });
+
+        format!("{{\n{stmts}{indents}}}")
+    }
+}
+
+pub enum Stmt {
+    Expr(Expr),
+    Return(Option<Expr>),
+    Throw(Expr),
+    /// `var name = val;`, or `Type name = val;` when the type is known
+    Let(Option<Type>, String, Expr),
+    Assign(Expr, Expr),
+    Unsafe(Block),
+    /// `fixed (Type name = val) { ... }`
+    Fixed(Type, String, Expr, Block),
+    /// `using var name = val;`
+    Using(String, Expr),
+    If(Expr, Block, Option<Block>),
+}
+
+impl Stmt {
+    fn render(&self, layer: usize) -> String {
+        let indents = "\t".repeat(layer);
+
+        match self {
+            Stmt::Expr(expr) => format!("{indents}{expr};"),
+            Stmt::Return(Some(expr)) => format!("{indents}return {expr};"),
+            Stmt::Return(None) => format!("{indents}return;"),
+            Stmt::Throw(expr) => format!("{indents}throw {expr};"),
+            Stmt::Let(Some(ty), name, val) => format!("{indents}{ty} {name} = {val};"),
+            Stmt::Let(None, name, val) => format!("{indents}var {name} = {val};"),
+            Stmt::Assign(target, val) => format!("{indents}{target} = {val};"),
+            Stmt::Unsafe(block) => format!("{indents}unsafe {}", block.render(layer)),
+            Stmt::Fixed(ty, name, val, block) => {
+                format!(
+                    "{indents}fixed ({ty} {name} = {val}) {}",
+                    block.render(layer)
+                )
+            }
+            Stmt::Using(name, val) => format!("{indents}using var {name} = {val};"),
+            Stmt::If(cond, then, otherwise) => {
+                let otherwise = if let Some(otherwise) = otherwise {
+                    format!(" else {}", otherwise.render(layer))
+                } else {
+                    String::new()
+                };
+
+                format!("{indents}if ({cond}) {}{otherwise}", then.render(layer))
+            }
+        }
+    }
+}
+
+pub enum Expr {
+    Ident(String),
+    /// Already valid C#, like a literal
+    Verbatim(String),
+    Call(Box<Expr>, Vec<Expr>),
+    Field(Box<Expr>, String),
+    New(Type, Vec<Expr>),
+    Cast(Type, Box<Expr>),
+    Ref(Box<Expr>),
+    Deref(Box<Expr>),
+    Await(Box<Expr>),
+    Binary(Box<Expr>, &'static str, Box<Expr>),
+}
+
+impl Expr {
+    pub fn ident(name: impl Into<String>) -> Self {
+        Expr::Ident(name.into())
+    }
+
+    pub fn call(self, args: Vec<Expr>) -> Self {
+        Expr::Call(Box::new(self), args)
+    }
+
+    pub fn field(self, name: impl Into<String>) -> Self {
+        Expr::Field(Box::new(self), name.into())
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let args = |args: &[Expr]| {
+            args.iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            Expr::Ident(name) | Expr::Verbatim(name) => f.write_str(name),
+            Expr::Call(target, call_args) => write!(f, "{target}({})", args(call_args)),
+            Expr::Field(target, name) => write!(f, "{target}.{name}"),
+            Expr::New(ty, new_args) => write!(f, "new {ty}({})", args(new_args)),
+            Expr::Cast(ty, expr) => write!(f, "({ty}){expr}"),
+            Expr::Ref(expr) => write!(f, "&{expr}"),
+            Expr::Deref(expr) => write!(f, "*{expr}"),
+            Expr::Await(expr) => write!(f, "await {expr}"),
+            Expr::Binary(lhs, op, rhs) => write!(f, "{lhs} {op} {rhs}"),
+        }
+    }
 }
 
 pub struct Attr {
@@ -545,9 +743,9 @@ impl ToString for Class {
             // methods.append(Group::new(proc_macro2::Delimiter::Parenthesis, args));
 
             let body = if let Some(body) = &method.body {
-                todo!()
+                format!(" {}", body.render(layer))
             } else {
-                ";"
+                ";".into()
             };
 
             let strings = method
@@ -575,7 +773,6 @@ impl ToString for Class {
                 attrs.push(attr);
             }
 
-            // YOU NEED TO HANDLE METHODS AND BLOCKS RECURSIVELY IN A WAY THAT LETS YOU TRACK INDENTATION PLEASE DO NOT FORGET WHAT YOU MEAN
             let method = format!(
                 "\n{indents}{attrs}\n{indents}{vis}{qualifiers} {ret} {name}({args}){body}",
                 attrs = attrs.join("\n"),
@@ -587,25 +784,29 @@ impl ToString for Class {
             methods.push(method);
         }
 
-        let constants = self.constants.iter().fold(String::new(), |acc, constant| {
-            format!(
-                "{acc}{indents}{}{} const {} {} = {};",
-                constant.vis.as_str(),
-                constant
-                    .qualifiers
-                    .iter()
-                    .fold(String::new(), |acc, s| format!("{acc} {}", s.as_str())),
-                constant.ty.to_string(),
-                constant.name,
-                constant.val,
-            )
-        });
+        let constants: Vec<String> = self
+            .constants
+            .iter()
+            .map(|constant| {
+                format!(
+                    "{indents}{}{} const {} {} = {};",
+                    constant.vis.as_str(),
+                    constant
+                        .qualifiers
+                        .iter()
+                        .fold(String::new(), |acc, s| format!("{acc} {}", s.as_str())),
+                    constant.ty.to_string(),
+                    constant.name,
+                    constant.val,
+                )
+            })
+            .collect();
 
         let class = format!(
             "{vis} {} class {} {{\n{}\n{}\n}}",
             qualifiers.join(" "),
             self.name,
-            constants,
+            constants.join("\n"),
             methods.join("\n")
         );
 
@@ -659,3 +860,31 @@ fn test() {
 
     println!("{}", class.to_string())
 }
+
+#[test]
+fn method_body() {
+    let body = Block::new().stmt(Stmt::Fixed(
+        Type::Ptr(Box::new(Type::Char)),
+        "path_ptr".into(),
+        Expr::ident("path"),
+        Block::new().stmt(Stmt::Return(Some(Expr::ident("new_rust_state").call(
+            vec![
+                Expr::ident("path_ptr"),
+                Expr::Cast(Type::Nuint, Box::new(Expr::ident("path").field("Length"))),
+            ],
+        )))),
+    ));
+
+    let method = Method::new("NewRustState".into())
+        .vis(Vis::Public)
+        .qualifier(Qualifier::Static)
+        .qualifier(Qualifier::Unsafe)
+        .body(body);
+
+    let mut class = Class::new("NativeMethods".into()).vis(Vis::Public);
+    class.add_method(method);
+
+    assert!(class.to_string().contains(
+        "public static unsafe void NewRustState() {\n\t\tfixed (char* path_ptr = path) {\n\t\t\treturn new_rust_state(path_ptr, (nuint)path.Length);\n\t\t}\n\t}"
+    ));
+}
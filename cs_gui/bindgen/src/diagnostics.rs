@@ -0,0 +1,146 @@
+use proc_macro2::Span;
+use std::fmt::{Display, Formatter};
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Level {
+    /// The generated bindings would be wrong, generation fails
+    Error,
+    /// The generated bindings might be wrong, generation continues
+    Warning,
+}
+
+impl Level {
+    fn as_str(&self) -> &str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        }
+    }
+}
+
+pub struct Diagnostic {
+    level: Level,
+    file: &'static str,
+    line: usize,
+    column: usize,
+    item: String,
+    message: String,
+    suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn suggestion(&mut self, suggestion: impl Into<String>) -> &mut Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}: {}", self.level.as_str(), self.message)?;
+        writeln!(f, "  --> {}:{}:{}", self.file, self.line, self.column)?;
+        writeln!(f, "   | {}", self.item)?;
+        if let Some(suggestion) = &self.suggestion {
+            writeln!(f, "   = help: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects everything the generator could not translate, so a single run
+/// reports every problem at once instead of stopping at the first one
+#[derive(Default)]
+pub struct Diagnostics {
+    file: &'static str,
+    list: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the file name used for every diagnostic reported after this call
+    pub fn set_file(&mut self, file: &'static str) {
+        self.file = file;
+    }
+
+    pub fn error(
+        &mut self,
+        span: Span,
+        item: impl Display,
+        message: impl Into<String>,
+    ) -> &mut Diagnostic {
+        self.push(Level::Error, span, item, message)
+    }
+
+    pub fn warning(
+        &mut self,
+        span: Span,
+        item: impl Display,
+        message: impl Into<String>,
+    ) -> &mut Diagnostic {
+        self.push(Level::Warning, span, item, message)
+    }
+
+    fn push(
+        &mut self,
+        level: Level,
+        span: Span,
+        item: impl Display,
+        message: impl Into<String>,
+    ) -> &mut Diagnostic {
+        let start = span.start();
+        self.list.push(Diagnostic {
+            level,
+            file: self.file,
+            line: start.line,
+            column: start.column + 1,
+            item: item.to_string(),
+            message: message.into(),
+            suggestion: None,
+        });
+
+        let len = self.list.len() - 1;
+
+        &mut self.list[len]
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.list.iter().any(|d| d.level == Level::Error)
+    }
+
+    fn count(&self, level: Level) -> usize {
+        self.list.iter().filter(|d| d.level == level).count()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.list.iter()
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for diagnostic in &self.list {
+            writeln!(f, "{diagnostic}")?;
+        }
+
+        let errors = self.count(Level::Error);
+        let warnings = self.count(Level::Warning);
+
+        if errors > 0 {
+            write!(
+                f,
+                "binding generation failed with {errors} error(s) and {warnings} warning(s)"
+            )
+        } else {
+            write!(f, "binding generation finished with {warnings} warning(s)")
+        }
+    }
+}
+
+impl std::fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
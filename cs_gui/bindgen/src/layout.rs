@@ -0,0 +1,311 @@
+use crate::diagnostics::Diagnostics;
+use quote::ToTokens;
+use std::collections::HashMap;
+use std::path::Path;
+use syn::spanned::Spanned;
+use syn::{Expr, ExprLit, Fields, Item, Lit, Meta, Type};
+
+#[derive(Clone, Copy)]
+pub struct Layout {
+    pub size: usize,
+    pub align: usize,
+}
+
+impl Layout {
+    fn new(size: usize) -> Self {
+        Self { size, align: size }
+    }
+}
+
+pub struct StructLayout {
+    pub name: String,
+    /// Rust path to the struct, relative to the crate root
+    pub path: String,
+    pub layout: Layout,
+    pub offsets: Vec<(String, usize)>,
+}
+
+enum Decl {
+    Struct {
+        file: &'static str,
+        path: String,
+        fields: Vec<(String, Type)>,
+    },
+    Enum(Layout),
+}
+
+/// Tracks every `#[repr(C)]` type across all files, so fields using types
+/// from another file can still be laid out
+pub struct LayoutTable {
+    pointer_width: usize,
+    decls: HashMap<String, Decl>,
+    order: Vec<String>,
+}
+
+impl LayoutTable {
+    pub fn new(pointer_width: usize) -> Self {
+        Self {
+            pointer_width,
+            decls: HashMap::new(),
+            order: vec![],
+        }
+    }
+
+    /// `name` is the path of the file relative to `src`
+    pub fn add_file(&mut self, name: &'static str, file: &syn::File) {
+        let module = rust_module_path(name);
+        for item in &file.items {
+            match item {
+                Item::Struct(s) => {
+                    if repr(&s.attrs).as_deref() != Some("C") {
+                        continue;
+                    }
+                    let Fields::Named(named) = &s.fields else {
+                        continue;
+                    };
+                    let fields = named
+                        .named
+                        .iter()
+                        .map(|f| (f.ident.as_ref().unwrap().to_string(), f.ty.clone()))
+                        .collect();
+                    let ident = s.ident.to_string();
+                    self.order.push(ident.clone());
+                    self.decls.insert(
+                        ident.clone(),
+                        Decl::Struct {
+                            file: name,
+                            path: format!("{module}::{ident}"),
+                            fields,
+                        },
+                    );
+                }
+                Item::Enum(e) => {
+                    let layout = match repr(&e.attrs).as_deref() {
+                        Some("C" | "i32" | "u32") => Layout::new(4),
+                        Some("u8" | "i8") => Layout::new(1),
+                        Some("u16" | "i16") => Layout::new(2),
+                        Some("u64" | "i64") => Layout::new(8),
+                        _ => continue,
+                    };
+                    self.decls.insert(e.ident.to_string(), Decl::Enum(layout));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Lays out every struct in declaration order, reporting any field whose size can't be known
+    pub fn structs(&self, diagnostics: &mut Diagnostics) -> Vec<StructLayout> {
+        let mut layouts = vec![];
+        for name in &self.order {
+            let Some(Decl::Struct { file, path, fields }) = self.decls.get(name) else {
+                continue;
+            };
+
+            diagnostics.set_file(file);
+
+            let mut offset = 0usize;
+            let mut align = 1;
+            let mut offsets = vec![];
+            let mut known = true;
+            for (field, ty) in fields {
+                let Some(layout) = self.layout(ty, 0) else {
+                    diagnostics
+                        .warning(
+                            ty.span(),
+                            format!("{name}.{field}: {}", ty.to_token_stream()),
+                            "Unable to determine the layout of this field, skipping layout tests",
+                        )
+                        .suggestion("Use primitives, pointers or #[repr(C)] types for fields");
+                    known = false;
+                    break;
+                };
+                offset = offset.next_multiple_of(layout.align);
+                offsets.push((field.clone(), offset));
+                offset += layout.size;
+                align = align.max(layout.align);
+            }
+
+            if known {
+                layouts.push(StructLayout {
+                    name: name.clone(),
+                    path: path.clone(),
+                    layout: Layout {
+                        size: offset.next_multiple_of(align),
+                        align,
+                    },
+                    offsets,
+                });
+            }
+        }
+        layouts
+    }
+
+    fn layout(&self, ty: &Type, depth: usize) -> Option<Layout> {
+        // Recursive types by value can't compile in Rust anyway, this just avoids a stack overflow
+        if depth > 32 {
+            return None;
+        }
+
+        let ptr = Layout::new(self.pointer_width);
+
+        match ty {
+            Type::Ptr(_) | Type::Reference(_) | Type::BareFn(_) => Some(ptr),
+            Type::Array(array) => {
+                let Expr::Lit(ExprLit {
+                    lit: Lit::Int(len), ..
+                }) = &array.len
+                else {
+                    return None;
+                };
+                let elem = self.layout(&array.elem, depth + 1)?;
+                Some(Layout {
+                    size: elem.size * len.base10_parse::<usize>().ok()?,
+                    align: elem.align,
+                })
+            }
+            Type::Path(p) => {
+                let name = p.path.segments.last()?.ident.to_string();
+                match name.as_str() {
+                    "bool" | "u8" | "i8" => Some(Layout::new(1)),
+                    "u16" | "i16" => Some(Layout::new(2)),
+                    "u32" | "i32" | "f32" | "char" => Some(Layout::new(4)),
+                    "u64" | "i64" | "f64" => Some(Layout::new(8)),
+                    "usize" | "isize" => Some(ptr),
+                    "String" | "Vec" => Some(Layout {
+                        size: ptr.size * 3,
+                        align: ptr.align,
+                    }),
+                    _ => match self.decls.get(&name)? {
+                        Decl::Enum(layout) => Some(*layout),
+                        Decl::Struct { fields, .. } => {
+                            let mut offset = 0usize;
+                            let mut align = 1;
+                            for (_, ty) in fields {
+                                let layout = self.layout(ty, depth + 1)?;
+                                offset = offset.next_multiple_of(layout.align) + layout.size;
+                                align = align.max(layout.align);
+                            }
+                            Some(Layout {
+                                size: offset.next_multiple_of(align),
+                                align,
+                            })
+                        }
+                    },
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `lib.rs` becomes `crate`, and `internal/tasks.rs` becomes `crate::internal::tasks`
+fn rust_module_path(file: &str) -> String {
+    let path = Path::new(file).with_extension("");
+    let mut module = String::from("crate");
+    for component in path.iter().filter_map(|c| c.to_str()) {
+        if !matches!(component, "lib" | "main" | "mod" | "src") {
+            module.push_str("::");
+            module.push_str(component);
+        }
+    }
+    module
+}
+
+fn repr(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::List(meta) if meta.path.is_ident("repr") => Some(meta.tokens.to_string()),
+        _ => None,
+    })
+}
+
+/// Emits a C# class that checks every struct against the layout the Rust side expects
+pub fn cs_layout_tests(name_space: &str, layouts: &[StructLayout]) -> String {
+    let mut wrappers = String::new();
+    let mut checks = String::new();
+
+    for StructLayout {
+        name,
+        layout,
+        offsets,
+        ..
+    } in layouts
+    {
+        wrappers.push_str(&format!(
+            "\t[StructLayout(LayoutKind.Sequential)]\n\tprivate struct __Align_{name} {{\n\t\tpublic byte pad;\n\t\tpublic {name} value;\n\t}}\n\n"
+        ));
+        checks.push_str(&format!(
+            "\t\tCheck(\"sizeof({name})\", Marshal.SizeOf<{name}>(), {});\n",
+            layout.size
+        ));
+        checks.push_str(&format!(
+            "\t\tCheck(\"alignof({name})\", (int)Marshal.OffsetOf<__Align_{name}>(\"value\"), {});\n",
+            layout.align
+        ));
+        for (field, offset) in offsets {
+            checks.push_str(&format!(
+                "\t\tCheck(\"offsetof({name}.{field})\", (int)Marshal.OffsetOf<{name}>(\"{field}\"), {offset});\n"
+            ));
+        }
+    }
+
+    format!(
+        "using System;\nusing System.Runtime.InteropServices;\n\nnamespace {name_space};\n\n\
+public static class LayoutTests {{\n\
+{wrappers}\
+\tpublic static void Run() {{\n{checks}\t}}\n\n\
+\tprivate static void Check(string what, int actual, int expected) {{\n\
+\t\tif (actual != expected) {{\n\
+\t\t\tthrow new InvalidOperationException($\"{{what}}: expected {{expected}}, got {{actual}}\");\n\
+\t\t}}\n\
+\t}}\n\
+}}\n"
+    )
+}
+
+/// Emits Rust tests asserting the same layout the C# tests check
+pub fn rs_layout_tests(layouts: &[StructLayout]) -> String {
+    let mut tests = String::from("// Generated by bindgen, changes will be overwritten\n");
+    tests.push_str("use memoffset::offset_of;\n");
+
+    for StructLayout {
+        name,
+        path,
+        layout,
+        offsets,
+    } in layouts
+    {
+        tests.push_str(&format!("\n#[test]\nfn layout_{}() {{\n", snake_case(name)));
+        tests.push_str(&format!(
+            "    assert_eq!(std::mem::size_of::<{path}>(), {});\n",
+            layout.size
+        ));
+        tests.push_str(&format!(
+            "    assert_eq!(std::mem::align_of::<{path}>(), {});\n",
+            layout.align
+        ));
+        for (field, offset) in offsets {
+            tests.push_str(&format!(
+                "    assert_eq!(offset_of!({path}, {field}), {offset});\n"
+            ));
+        }
+        tests.push_str("}\n");
+    }
+
+    tests
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i != 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
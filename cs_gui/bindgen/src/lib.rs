@@ -1,18 +1,29 @@
 pub mod cs_tokens;
+pub mod diagnostics;
+pub mod layout;
 
 use cs_tokens::{Attr, Class, Method, NameSpace, VariableBuilder};
+use diagnostics::Diagnostics;
+use layout::LayoutTable;
 use quote::ToTokens;
+use syn::spanned::Spanned;
 use syn::{
-    token::Enum, Attribute, FnArg, Item, ItemEnum, ItemFn, ItemStruct, Meta, Pat, ReturnType,
-    Signature, Type,
+    parse_quote, Attribute, Expr, ExprClosure, ExprLit, ExprUnary, FnArg, GenericArgument, Item,
+    ItemConst, ItemEnum, ItemFn, ItemStruct, Lit, Meta, MetaNameValue, Pat, PatType, PathArguments,
+    ReturnType, Signature, Type, UnOp, Visibility,
 };
 
-use crate::cs_tokens::ScopeBuilder;
+use crate::cs_tokens::{Scope, ScopeBuilder};
+use proc_macro2::Span;
+use std::collections::HashMap;
+use std::path::Path;
 
 pub struct Generator {
     name_space: &'static str,
-    files: Vec<&'static str>,
+    files: Vec<(&'static str, &'static str)>,
     dll_name: &'static str,
+    split_modules: bool,
+    layout_tests: Option<(&'static str, &'static str)>,
 }
 
 impl Generator {
@@ -21,6 +32,8 @@ impl Generator {
             name_space,
             dll_name: "",
             files: vec![],
+            split_modules: false,
+            layout_tests: None,
         }
     }
 
@@ -28,22 +41,34 @@ impl Generator {
         self.dll_name = dll_name;
     }
 
-    pub fn add_file(&mut self, path: &'static str) {
-        self.files.push(path);
+    /// The name is only used to point diagnostics at the right file,
+    /// and to name the module when splitting modules
+    pub fn add_file(&mut self, name: &'static str, contents: &'static str) {
+        self.files.push((name, contents));
     }
 
-    pub fn generate(&self, path: &str) {
-        let mut scope = ScopeBuilder::new()
-            .import("System.Runtime.InteropServices".into())
-            .namespace(NameSpace::new(self.name_space.into()))
-            .build();
+    /// Emits every file other than the crate root into its own partial `NativeMethods` class,
+    /// written next to the main file as `NativeMethods.{Module}.cs`. Files that would get the
+    /// same module name, like `a/tasks.rs` and `b/tasks.rs`, fail the generation
+    pub fn split_modules(&mut self, split_modules: bool) {
+        self.split_modules = split_modules;
+    }
 
-        let name_space = scope.name_space();
+    /// Also writes a C# class and a Rust test module asserting the size, alignment and
+    /// field offsets of every `#[repr(C)]` struct, so both sides agree on the layout.
+    /// The Rust file expects to be a direct child module of the crate root
+    pub fn layout_tests(&mut self, cs_path: &'static str, rs_path: &'static str) {
+        self.layout_tests = Some((cs_path, rs_path));
+    }
+
+    /// Writes the bindings to `path`, or returns every unsupported item found if
+    /// the bindings would be wrong. Warnings are printed but don't fail the run.
+    pub fn generate(&self, path: &str) -> Result<(), Diagnostics> {
+        let mut diagnostics = Diagnostics::new();
+
+        let mut scope = self.scope();
 
-        let mut class = Class::new("NativeMethods".into())
-            .vis(cs_tokens::Vis::Public)
-            .qualifier(cs_tokens::Qualifier::Static)
-            .qualifier(cs_tokens::Qualifier::Partial);
+        let mut class = native_methods();
 
         let dll_const = VariableBuilder::new("__DllName".into())
             .vis(cs_tokens::Vis::Private)
@@ -53,8 +78,57 @@ impl Generator {
 
         class.add_constant(dll_const);
 
-        for file in &self.files {
-            parse_file(file, &mut class, name_space);
+        let mut modules = Vec::new();
+        let mut module_files = HashMap::new();
+
+        let mut layouts = LayoutTable::new(std::mem::size_of::<usize>());
+
+        for &(name, contents) in &self.files {
+            diagnostics.set_file(name);
+
+            let contents = match syn::parse_file(contents) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    diagnostics
+                        .error(e.span(), "<file>", format!("Failed to parse file: {e}"))
+                        .suggestion(
+                            "Make sure the file compiles with rustc before generating bindings",
+                        );
+                    continue;
+                }
+            };
+
+            layouts.add_file(name, &contents);
+
+            match module_name(name).filter(|_| self.split_modules) {
+                Some(module) => {
+                    // Both would be written to the same file, the second overwriting the first
+                    if let Some(first) = module_files.insert(module.clone(), name) {
+                        diagnostics
+                            .error(
+                                Span::call_site(),
+                                "<file>",
+                                format!(
+                                    "{first} and {name} both become {}",
+                                    module_path(path, &module)
+                                ),
+                            )
+                            .suggestion("Rename one of the files, or don't split modules");
+                        continue;
+                    }
+                    let mut module_scope = self.scope();
+                    let mut module_class = native_methods();
+                    let name_space = module_scope.name_space();
+                    parse_file(&contents, &mut module_class, name_space, &mut diagnostics);
+                    name_space.add_class(module_class);
+                    modules.push((module, module_scope));
+                }
+                None => parse_file(&contents, &mut class, scope.name_space(), &mut diagnostics),
+            }
+        }
+
+        if diagnostics.has_errors() {
+            return Err(diagnostics);
         }
 
         let repr_field = cs_tokens::Field::new("repr".into())
@@ -68,29 +142,235 @@ impl Generator {
 
         let rust_string = cs_tokens::Struct::new("RustString".into()).field(repr_field);
 
-        name_space.add_struct(rust_string);
+        let name_space = scope.name_space();
+        // The crate usually declares it itself, it's only added for crates that don't
+        if !name_space.has_struct("RustString") {
+            name_space.add_struct(rust_string);
+        }
         name_space.add_class(class);
 
-        let scope = scope.to_string();
+        let layout_tests = self.layout_tests.map(|(cs_path, rs_path)| {
+            let layouts = layouts.structs(&mut diagnostics);
+            (
+                (cs_path, layout::cs_layout_tests(self.name_space, &layouts)),
+                (rs_path, layout::rs_layout_tests(&layouts)),
+            )
+        });
 
-        println!("{}", scope);
+        if diagnostics.iter().next().is_some() {
+            eprintln!("{diagnostics}");
+        }
+
+        write_scope(path, &scope);
 
-        std::fs::write(path, scope.as_bytes()).unwrap();
+        if let Some(((cs_path, cs_tests), (rs_path, rs_tests))) = layout_tests {
+            write_file(cs_path, &cs_tests);
+            write_file(rs_path, &rs_tests);
+        }
+
+        for (module, scope) in &modules {
+            write_scope(&module_path(path, module), scope);
+        }
+
+        Ok(())
+    }
+
+    fn scope(&self) -> Scope {
+        ScopeBuilder::new()
+            .import("System.Runtime.InteropServices".into())
+            .namespace(NameSpace::new(self.name_space.into()))
+            .build()
     }
 }
 
-fn parse_file(file: &'static str, class: &mut Class, name_space: &mut NameSpace) {
-    let parsed = syn::parse_file(file).unwrap();
+fn native_methods() -> Class {
+    Class::new("NativeMethods".into())
+        .vis(cs_tokens::Vis::Public)
+        .qualifier(cs_tokens::Qualifier::Static)
+        .qualifier(cs_tokens::Qualifier::Partial)
+}
+
+fn write_scope(path: &str, scope: &Scope) {
+    let scope = scope.to_string();
+
+    println!("{}", scope);
+
+    write_file(path, &scope);
+}
+
+fn write_file(path: &str, contents: &str) {
+    std::fs::write(path, contents.as_bytes())
+        .unwrap_or_else(|e| panic!("Failed to write bindings to {path}: {e}"));
+}
+
+/// `internal/tasks.rs` becomes `Tasks`, and `internal/mod.rs` becomes `Internal`.
+/// The crate root has no module name, as it goes into the main file
+fn module_name(file: &str) -> Option<String> {
+    let path = Path::new(file);
+    let stem = match path.file_stem()?.to_str()? {
+        "lib" | "main" => return None,
+        "mod" => path.parent()?.file_name()?.to_str()?,
+        stem => stem,
+    };
+
+    let module = stem
+        .split('_')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let mut chars = s.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            format!("{first}{}", chars.as_str())
+        })
+        .collect();
+
+    Some(module)
+}
+
+/// `NativeMethods.cs` with the module `Tasks` becomes `NativeMethods.Tasks.cs`
+fn module_path(path: &str, module: &str) -> String {
+    let path = Path::new(path);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("NativeMethods");
+    path.with_file_name(format!("{stem}.{module}.cs"))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn parse_file(
+    parsed: &syn::File,
+    class: &mut Class,
+    name_space: &mut NameSpace,
+    diagnostics: &mut Diagnostics,
+) {
     for elm in &parsed.items {
-        handle_type(elm, class, name_space)
+        handle_type(elm, class, name_space, diagnostics)
     }
 
     for elm in &parsed.items {
-        handle_fn(elm, class, name_space)
+        handle_const(elm, class, diagnostics)
+    }
+
+    for elm in &parsed.items {
+        handle_fn(elm, class, name_space, diagnostics)
+    }
+}
+
+/// Exports `pub const` items with literal values, so values like the client id
+/// only have to be defined on the Rust side
+fn handle_const(elm: &Item, class: &mut Class, diagnostics: &mut Diagnostics) {
+    let Item::Const(ItemConst {
+        vis: Visibility::Public(_),
+        ident,
+        ty,
+        expr,
+        ..
+    }) = elm
+    else {
+        return;
+    };
+
+    let cs_type = match ty.as_ref() {
+        Type::Reference(r) if r.elem.to_token_stream().to_string() == "str" => {
+            cs_tokens::Type::String
+        }
+        ty => determinte_type(ty, &mut true, diagnostics),
+    };
+
+    if matches!(
+        cs_type,
+        cs_tokens::Type::Void
+            | cs_tokens::Type::Verbatim(_)
+            | cs_tokens::Type::Ptr(_)
+            | cs_tokens::Type::Array(_)
+            | cs_tokens::Type::FixedBuffer(..)
+    ) {
+        diagnostics
+            .warning(
+                ty.span(),
+                format!("const {ident}: {}", ty.to_token_stream()),
+                "Only primitive and string constants are exported, skipping",
+            )
+            .suggestion("Expose the value through a function instead");
+        return;
+    }
+
+    let Some(val) = cs_literal(expr) else {
+        diagnostics
+            .warning(
+                expr.span(),
+                format!("const {ident} = {}", expr.to_token_stream()),
+                "Only literal constants are exported, skipping",
+            )
+            .suggestion("Write the value as a literal so it can be copied to C#");
+        return;
+    };
+
+    // C# reads unsuffixed float literals as doubles, which don't convert to float implicitly
+    let val = match cs_type {
+        cs_tokens::Type::Float => format!("{val}f"),
+        _ => val,
+    };
+
+    let constant = VariableBuilder::new(ident.to_string())
+        .vis(cs_tokens::Vis::Public)
+        .ty(cs_type)
+        .val(val)
+        .build();
+
+    class.add_constant(constant);
+}
+
+fn cs_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Str(s) => Some(cs_string_literal(&s.value())),
+            Lit::Int(i) => Some(i.base10_digits().to_string()),
+            Lit::Float(f) => Some(f.base10_digits().to_string()),
+            Lit::Bool(b) => Some(b.value.to_string()),
+            _ => None,
+        },
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => cs_literal(expr).map(|val| format!("-{val}")),
+        Expr::Group(group) => cs_literal(&group.expr),
+        Expr::Paren(paren) => cs_literal(&paren.expr),
+        _ => None,
+    }
+}
+
+fn cs_string_literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => {
+                let mut buf = [0; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    literal.push_str(&format!("\\u{unit:04x}"));
+                }
+            }
+            c => literal.push(c),
+        }
     }
+    literal.push('"');
+    literal
 }
 
-fn handle_type(elm: &Item, class: &mut Class, name_space: &mut NameSpace) {
+fn handle_type(
+    elm: &Item,
+    class: &mut Class,
+    name_space: &mut NameSpace,
+    diagnostics: &mut Diagnostics,
+) {
     match elm {
         Item::Struct(ItemStruct {
             attrs,
@@ -107,11 +387,19 @@ fn handle_type(elm: &Item, class: &mut Class, name_space: &mut NameSpace) {
                     if last.ident == "repr" && meta.tokens.to_string() == "C" {
                         for field in fields {
                             let Some(name) = &field.ident else {
-                                unimplemented!("Unnamed fields are not supported");
+                                diagnostics
+                                    .error(
+                                        field.span(),
+                                        format!("{ident}.{}", field.ty.to_token_stream()),
+                                        "Unnamed fields are not supported",
+                                    )
+                                    .suggestion("Use a struct with named fields");
+                                continue;
                             };
                             // TODO: need to support Repr(C) types here
                             let mut safe = true;
-                            let ty = determinte_type(&field.ty, &mut safe);
+                            let ty = determinte_type(&field.ty, &mut safe, diagnostics);
+                            let fixed = matches!(ty, cs_tokens::Type::FixedBuffer(..));
                             let mut field = cs_tokens::Field::new(name.to_string())
                                 .ty(ty)
                                 .vis(cs_tokens::Vis::Public);
@@ -119,6 +407,9 @@ fn handle_type(elm: &Item, class: &mut Class, name_space: &mut NameSpace) {
                             if !safe {
                                 field.add_qualifier(cs_tokens::Qualifier::Unsafe)
                             }
+                            if fixed {
+                                field.add_qualifier(cs_tokens::Qualifier::Fixed)
+                            }
 
                             _struct.add_field(field);
                         }
@@ -134,20 +425,69 @@ fn handle_type(elm: &Item, class: &mut Class, name_space: &mut NameSpace) {
         Item::Enum(ItemEnum {
             attrs,
             variants,
-            vis,
             ident,
             ..
-        }) => {}
+        }) => {
+            let Some(repr) = attrs.iter().find_map(|attr| match &attr.meta {
+                Meta::List(meta) if meta.path.is_ident("repr") => Some(meta.tokens.to_string()),
+                _ => None,
+            }) else {
+                // Without a repr the size is unknown, so it can't be used across the boundary
+                return;
+            };
+
+            let ty = match repr.as_str() {
+                "C" => cs_tokens::Type::Int,
+                repr => match cs_rs_supported(repr) {
+                    Some(ty) => ty,
+                    None => {
+                        diagnostics.warning(
+                            ident.span(),
+                            format!("#[repr({repr})] enum {ident}"),
+                            "Unsupported enum repr, skipping",
+                        );
+                        return;
+                    }
+                },
+            };
+
+            let mut _enum = cs_tokens::Enum::new(ident.to_string(), ty);
+            for variant in variants {
+                if !variant.fields.is_empty() {
+                    diagnostics
+                        .error(
+                            variant.span(),
+                            format!("{ident}::{}", variant.ident),
+                            "Enums with fields can't be shared with C#",
+                        )
+                        .suggestion("Use a fieldless enum, and a #[repr(C)] struct for the data");
+                    return;
+                }
+
+                let value = variant
+                    .discriminant
+                    .as_ref()
+                    .map(|(_, expr)| expr.to_token_stream().to_string());
+                _enum.add_variant(variant.ident.to_string(), value);
+            }
+
+            name_space.add_enum(_enum);
+        }
         _ => {}
     }
 }
 
-pub fn handle_fn(elm: &Item, class: &mut Class, name_space: &mut NameSpace) {
+pub fn handle_fn(
+    elm: &Item,
+    class: &mut Class,
+    name_space: &mut NameSpace,
+    diagnostics: &mut Diagnostics,
+) {
     match elm {
         Item::Fn(ItemFn { attrs, sig, .. }) => {
             if !attrs.is_empty() {
                 for attr in attrs {
-                    handle_attrs(attr, sig, class);
+                    handle_attrs(attr, sig, class, diagnostics);
                 }
             }
         }
@@ -170,23 +510,60 @@ fn cs_rs_supported(maybe_supported: &str) -> Option<cs_tokens::Type> {
         "u32" => cs_tokens::Type::Uint.into(),
         "u64" => cs_tokens::Type::Ulong.into(),
         "usize" => cs_tokens::Type::Nuint.into(),
+        "f32" => cs_tokens::Type::Float.into(),
+        "f64" => cs_tokens::Type::Double.into(),
         "String" => cs_tokens::Type::String.into(),
+        // Atomics are passed as pointers to their integer
+        "AtomicU64" => cs_tokens::Type::Ulong.into(),
         _ => None,
     }
 }
 
-fn cs_argument(rust_arg: &FnArg, method: &mut Method, safe: &mut bool) {
+fn cs_argument(
+    rust_arg: &FnArg,
+    method: &mut Method,
+    safe: &mut bool,
+    diagnostics: &mut Diagnostics,
+) {
     let FnArg::Typed(t) = rust_arg else {
-        unimplemented!("Methods are unsupported")
+        diagnostics
+            .error(
+                rust_arg.span(),
+                rust_arg.to_token_stream(),
+                "Methods are unsupported",
+            )
+            .suggestion("Take the receiver as a pointer argument instead of `self`");
+        return;
     };
 
     let Pat::Ident(name) = t.pat.as_ref() else {
-        unreachable!();
+        diagnostics
+            .error(
+                t.pat.span(),
+                t.to_token_stream(),
+                "Only plain identifiers are supported as argument patterns",
+            )
+            .suggestion("Bind the argument to a name and destructure it in the body");
+        return;
     };
 
     let name = &name.ident;
 
-    let ty = determinte_type(&t.ty, safe);
+    // Borrowed strings and byte slices are expanded by the macro into a byte pointer and a length
+    if let Type::Reference(reference) = t.ty.as_ref() {
+        let elem = reference.elem.to_token_stream().to_string();
+        if elem == "str" || elem == "[u8]" {
+            *safe = false;
+            method.arg(
+                format!("{name}_ptr"),
+                cs_tokens::Type::Ptr(Box::new(cs_tokens::Type::Byte)),
+            );
+            method.arg(format!("{name}_len"), cs_tokens::Type::Nuint);
+            return;
+        }
+    }
+
+    let ty = determinte_type(&t.ty, safe, diagnostics);
 
     match ty {
         cs_tokens::Type::String => {
@@ -201,41 +578,197 @@ fn char_pointer() -> cs_tokens::Type {
     cs_tokens::Type::Ptr(Box::new(cs_tokens::Type::Char))
 }
 
-fn determinte_type(ty: &Type, safe: &mut bool) -> cs_tokens::Type {
+/// Anything that can't be translated is reported to `diagnostics`, and replaced with
+/// `void` so the rest of the file can still be checked
+fn determinte_type(ty: &Type, safe: &mut bool, diagnostics: &mut Diagnostics) -> cs_tokens::Type {
     match ty {
         Type::Ptr(ptr) => {
             *safe = false;
-            let ty = determinte_type(&ptr.elem, safe);
+            // Pointee types are opaque to C#, so generics and unknown types are fine here
+            let ty = match ptr.elem.as_ref() {
+                Type::Path(p) => {
+                    let type_name = p.path.segments.last().unwrap().ident.to_string();
+                    cs_rs_supported(&type_name).unwrap_or(cs_tokens::Type::Verbatim(type_name))
+                }
+                elem => determinte_type(elem, safe, diagnostics),
+            };
             cs_tokens::Type::Ptr(Box::new(ty))
         }
         Type::Path(p) => {
-            let type_name = p.path.segments.last().unwrap().ident.to_string();
+            let Some(last) = p.path.segments.last() else {
+                diagnostics.error(ty.span(), ty.to_token_stream(), "Empty type path");
+                return cs_tokens::Type::Void;
+            };
+
+            let type_name = last.ident.to_string();
 
             if let Some(supported) = cs_rs_supported(&type_name) {
                 supported
             } else {
-                // We should handle repr(C) types here
+                if !last.arguments.is_empty() {
+                    diagnostics
+                        .error(
+                            ty.span(),
+                            ty.to_token_stream(),
+                            "Generic types can't be passed by value across the FFI boundary",
+                        )
+                        .suggestion("Pass it behind a pointer, or wrap it in a #[repr(C)] struct");
+                } else {
+                    // We should handle repr(C) types here
+                    diagnostics
+                        .warning(
+                            ty.span(),
+                            ty.to_token_stream(),
+                            format!("`{type_name}` is emitted verbatim, its layout is not checked"),
+                        )
+                        .suggestion(
+                            "Make sure the type is #[repr(C)] and declared in a generated file",
+                        );
+                }
                 cs_tokens::Type::Verbatim(type_name)
             }
         }
-        _ => todo!("{ty:?}"),
+        Type::Reference(_) => {
+            diagnostics
+                .error(
+                    ty.span(),
+                    ty.to_token_stream(),
+                    "References are not supported",
+                )
+                .suggestion("Use a raw pointer (`*const T` or `*mut T`) instead");
+            cs_tokens::Type::Void
+        }
+        Type::Tuple(tuple) if tuple.elems.is_empty() => cs_tokens::Type::Void,
+        // Only the length has to be a literal, C# needs it to size the fixed buffer
+        Type::Array(array) => {
+            let Expr::Lit(ExprLit {
+                lit: Lit::Int(len), ..
+            }) = &array.len
+            else {
+                diagnostics
+                    .error(
+                        ty.span(),
+                        ty.to_token_stream(),
+                        "Array lengths must be literals",
+                    )
+                    .suggestion("Write the length as an integer literal");
+                return cs_tokens::Type::Void;
+            };
+            let Ok(len) = len.base10_parse() else {
+                diagnostics.error(ty.span(), ty.to_token_stream(), "Array length out of range");
+                return cs_tokens::Type::Void;
+            };
+            *safe = false;
+            let elem = determinte_type(&array.elem, safe, diagnostics);
+            cs_tokens::Type::FixedBuffer(Box::new(elem), len)
+        }
+        _ => {
+            diagnostics
+                .error(ty.span(), ty.to_token_stream(), "Unsupported type")
+                .suggestion("Use a primitive, a pointer, or a #[repr(C)] struct");
+            cs_tokens::Type::Void
+        }
     }
 }
 
-fn handle_attrs(attr: &Attribute, sig: &Signature, class: &mut Class) {
+fn handle_attrs(
+    attr: &Attribute,
+    sig: &Signature,
+    class: &mut Class,
+    diagnostics: &mut Diagnostics,
+) {
     match &attr.meta {
         Meta::Path(p) => {
+            if p.segments[0].ident == "dotnettask" {
+                for sig in task_signatures(None, sig) {
+                    create_method(&sig, class, diagnostics);
+                }
+                return;
+            }
+
             if p.segments[0].ident != "dotnetfunction" {
                 return;
             }
 
-            create_method(sig, class);
+            create_method(sig, class, diagnostics);
+        }
+        Meta::List(list) if list.path.is_ident("dotnettask") => {
+            let store = match list.parse_args::<MetaNameValue>() {
+                Ok(MetaNameValue {
+                    value: Expr::Closure(closure),
+                    ..
+                }) => closure,
+                _ => {
+                    diagnostics
+                        .error(
+                            list.span(),
+                            list.to_token_stream(),
+                            "Unable to parse the store closure",
+                        )
+                        .suggestion("Use `#[dotnettask(store = |state: *mut State, inner| ...)]`");
+                    return;
+                }
+            };
+
+            for sig in task_signatures(Some(&store), sig) {
+                create_method(&sig, class, diagnostics);
+            }
         }
         _ => {}
     }
 }
 
-fn create_method(sig: &Signature, class: &mut Class) {
+/// Mirrors the functions `#[dotnettask]` expands to, so they can be bound like any other function
+fn task_signatures(store: Option<&ExprClosure>, sig: &Signature) -> Vec<Signature> {
+    let name = &sig.ident;
+    let ret = match &sig.output {
+        ReturnType::Type(_, ty) => ty.to_token_stream(),
+        ReturnType::Default => quote::quote!(()),
+    };
+    let task = quote::quote! { *mut TaskWrapper<#ret> };
+
+    let get_inputs = sig.inputs.iter().map(|input| match input {
+        FnArg::Typed(PatType { pat, ty, .. }) => match ty.as_ref() {
+            Type::Reference(reference) => {
+                let elem = &reference.elem;
+                if reference.mutability.is_some() {
+                    quote::quote!(#pat: *mut #elem)
+                } else {
+                    quote::quote!(#pat: *const #elem)
+                }
+            }
+            ty => quote::quote!(#pat: #ty),
+        },
+        input => input.to_token_stream(),
+    });
+
+    let store_inputs: Vec<_> = store
+        .map(|closure| {
+            let params = closure.inputs.len().saturating_sub(1);
+            closure.inputs.iter().take(params).cloned().collect()
+        })
+        .unwrap_or_default();
+
+    let get_name = quote::format_ident!("get_{name}");
+    let poll_name = quote::format_ident!("poll_{name}");
+    let await_name = quote::format_ident!("await_{name}");
+    let cancel_name = quote::format_ident!("cancel_{name}");
+
+    let await_sig: Signature = if store.is_some() {
+        parse_quote!(fn #await_name(#(#store_inputs,)* raw_task: #task) -> Result<(), Error>)
+    } else {
+        parse_quote!(fn #await_name(raw_task: #task) -> #ret)
+    };
+
+    vec![
+        parse_quote!(fn #get_name(#(#get_inputs),*) -> #task),
+        parse_quote!(fn #poll_name(raw_task: #task) -> bool),
+        await_sig,
+        parse_quote!(fn #cancel_name(raw_task: #task)),
+    ]
+}
+
+fn create_method(sig: &Signature, class: &mut Class, diagnostics: &mut Diagnostics) {
     let function_name = sig.ident.to_string();
 
     let linkname_attr = Attr::new("DllImport".into())
@@ -254,17 +787,31 @@ fn create_method(sig: &Signature, class: &mut Class) {
 
     sig.inputs
         .iter()
-        .for_each(|arg| cs_argument(arg, &mut method, &mut safe));
+        .for_each(|arg| cs_argument(arg, &mut method, &mut safe, diagnostics));
+
+    let mut cs_return_type = |ty: &Type| match determinte_type(ty, &mut safe, diagnostics) {
+        cs_tokens::Type::String => cs_tokens::Type::Verbatim("RustString".into()),
+        ty => ty,
+    };
 
-    let mut cs_return_type = |ret: &ReturnType| match ret {
+    // `Result<T, E>` is turned into a `NativeReturn` by the macro, with `T` written to `_return`
+    let ret = match &sig.output {
         ReturnType::Default => cs_tokens::Type::Void,
-        ReturnType::Type(_, ty) => match determinte_type(ty, &mut safe) {
-            cs_tokens::Type::String => cs_tokens::Type::Verbatim("RustString".into()),
-            ty => ty,
+        ReturnType::Type(_, ty) => match result_ok_type(ty) {
+            Some(Type::Tuple(unit)) if unit.elems.is_empty() => {
+                cs_tokens::Type::Verbatim("NativeReturn".into())
+            }
+            Some(ok) => {
+                let ok = cs_return_type(ok);
+                method.arg("_return".into(), cs_tokens::Type::Ptr(Box::new(ok)));
+                safe = false;
+                cs_tokens::Type::Verbatim("NativeReturn".into())
+            }
+            None => cs_return_type(ty),
         },
     };
 
-    method.ret(cs_return_type(&sig.output));
+    method.ret(ret);
 
     if !safe {
         method.add_qualifier(cs_tokens::Qualifier::Unsafe);
@@ -273,12 +820,119 @@ fn create_method(sig: &Signature, class: &mut Class) {
     class.add_method(method);
 }
 
+/// Returns `T` if the type is a `Result<T, E>`
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let last = path.path.segments.last()?;
+    if last.ident != "Result" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+
+    match args.args.first()? {
+        GenericArgument::Type(ok) => Some(ok),
+        _ => None,
+    }
+}
+
 #[test]
 fn generate() {
     let mut gen = Generator::new("csbindings");
-    gen.add_file(include_str!("../../csbindings/src/lib.rs"));
-    gen.add_file(include_str!("../../csbindings/src/internal/state.rs"));
-    gen.add_file(include_str!("../../csbindings/src/internal/tasks.rs"));
+    gen.add_file("lib.rs", include_str!("../../csbindings/src/lib.rs"));
+    gen.add_file(
+        "internal/state.rs",
+        include_str!("../../csbindings/src/internal/state.rs"),
+    );
+    gen.add_file(
+        "internal/tasks.rs",
+        include_str!("../../csbindings/src/internal/tasks.rs"),
+    );
     gen.dll_name("csbindings");
-    gen.generate("NativeMethods.cs");
+    gen.layout_tests("LayoutTests.cs", "../csbindings/src/layout_tests.rs");
+    gen.generate("NativeMethods.cs").unwrap();
+}
+
+#[test]
+fn generate_split_modules() {
+    let dir = std::env::temp_dir().join("synth_bindgen_split");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("NativeMethods.cs");
+
+    let mut gen = Generator::new("csbindings");
+    gen.add_file("lib.rs", include_str!("../../csbindings/src/lib.rs"));
+    gen.add_file(
+        "internal/state.rs",
+        include_str!("../../csbindings/src/internal/state.rs"),
+    );
+    gen.add_file(
+        "internal/tasks.rs",
+        include_str!("../../csbindings/src/internal/tasks.rs"),
+    );
+    gen.dll_name("csbindings");
+    gen.split_modules(true);
+    gen.generate(path.to_str().unwrap()).unwrap();
+
+    for module in [
+        "NativeMethods.cs",
+        "NativeMethods.State.cs",
+        "NativeMethods.Tasks.cs",
+    ] {
+        let contents = std::fs::read_to_string(dir.join(module)).unwrap();
+        assert!(
+            contents.contains("static partial class NativeMethods"),
+            "{module}"
+        );
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn split_module_collision() {
+    let mut gen = Generator::new("csbindings");
+    gen.add_file("a/tasks.rs", "pub const A: i32 = 1;");
+    gen.add_file("b/tasks.rs", "pub const B: i32 = 2;");
+    gen.split_modules(true);
+    let diagnostics = gen.generate("NativeMethods.Collision.cs").unwrap_err();
+    assert!(diagnostics.has_errors());
+    assert!(diagnostics
+        .to_string()
+        .contains("a/tasks.rs and b/tasks.rs"));
+}
+
+#[test]
+fn generate_constants() {
+    let path = std::env::temp_dir().join("NativeMethods.Constants.cs");
+
+    let mut gen = Generator::new("csbindings");
+    gen.add_file(
+        "lib.rs",
+        r#"
+        pub const COUNT: i32 = -3;
+        pub const SCALE: f32 = 1.5;
+        pub const RATIO: f64 = 0.25;
+        pub const GREETING: &str = "say \"hi\"\n";
+        "#,
+    );
+    gen.dll_name("csbindings");
+    gen.generate(path.to_str().unwrap()).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    for constant in [
+        "public const int COUNT = -3;",
+        "public const float SCALE = 1.5f;",
+        "public const double RATIO = 0.25;",
+        r#"public const string GREETING = "say \"hi\"\n";"#,
+    ] {
+        assert!(contents.contains(constant), "{constant} in\n{contents}");
+    }
 }
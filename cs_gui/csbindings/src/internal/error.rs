@@ -8,6 +8,8 @@ pub enum Error {
     SerdeJson(serde_json::Error),
     Profile(account::types::ProfileError),
     TomlDe(toml::de::Error),
+    TomlSer(toml::ser::Error),
+    RateLimited(launcher_core::http::RateLimited),
 }
 
 impl From<launcher_core::Error> for Error {
@@ -17,6 +19,17 @@ impl From<launcher_core::Error> for Error {
             launcher_core::Error::Tokio(e) => Error::Tokio(e),
             launcher_core::Error::SerdeJson(e) => Error::SerdeJson(e),
             launcher_core::Error::ProfileError(e) => Error::Profile(e),
+            launcher_core::Error::RateLimited(e) => Error::RateLimited(e),
+        }
+    }
+}
+
+impl From<launcher_app::data::Error> for Error {
+    fn from(value: launcher_app::data::Error) -> Self {
+        match value {
+            launcher_app::data::Error::Io(e) => Error::Tokio(e),
+            launcher_app::data::Error::TomlDE(e) => Error::TomlDe(e),
+            launcher_app::data::Error::TomlSER(e) => Error::TomlSer(e),
         }
     }
 }
@@ -53,6 +66,8 @@ impl Display for Error {
             Error::SerdeJson(err) => err,
             Error::Profile(err) => err,
             Error::TomlDe(err) => err,
+            Error::TomlSer(err) => err,
+            Error::RateLimited(err) => err,
         };
         write!(f, "{}", str)
     }
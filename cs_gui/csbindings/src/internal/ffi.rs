@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicI64, AtomicU32, AtomicU64, AtomicUsize};
+
+/// Types that can be passed by value between Rust and C# without any conversion
+///
+/// # Safety
+/// The type has to be `#[repr(C)]` (or a primitive), and every field has to be `FfiSafe`.
+/// `#[dotnetstruct]` checks this and implements it for you, there's no need to implement it by hand
+pub unsafe trait FfiSafe {}
+
+macro_rules! ffi_safe {
+    ($($ty:ty),*) => {
+        $(unsafe impl FfiSafe for $ty {})*
+    };
+}
+
+ffi_safe!(bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+ffi_safe!(
+    AtomicBool,
+    AtomicU32,
+    AtomicU64,
+    AtomicUsize,
+    AtomicI32,
+    AtomicI64
+);
+
+unsafe impl<T> FfiSafe for *const T {}
+unsafe impl<T> FfiSafe for *mut T {}
+unsafe impl<T: FfiSafe, const N: usize> FfiSafe for [T; N] {}
@@ -1,4 +1,5 @@
 pub mod error;
-pub mod instances;
+pub mod ffi;
+pub mod panic;
 pub mod state;
 pub mod tasks;
@@ -0,0 +1,20 @@
+use std::any::Any;
+
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".into()
+    }
+}
+
+/// Unwinding into C# is undefined behavior, so functions that can't report errors abort instead
+pub fn abort(function: &str, payload: Box<dyn Any + Send>) -> ! {
+    eprintln!(
+        "{function} panicked, aborting: {}",
+        panic_message(payload.as_ref())
+    );
+    std::process::abort()
+}
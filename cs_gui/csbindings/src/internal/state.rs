@@ -8,6 +8,8 @@ pub struct State {
     pub selected_version: RwLock<Option<VersionJson>>,
     pub asset_index: RwLock<Option<AssetIndexJson>>,
     pub class_path: Option<String>,
+    /// Natives the last libraries task couldn't extract
+    pub skipped_natives: Vec<String>,
     pub jar_path: Option<String>,
     pub path: PathBuf,
     pub device_code: Option<DeviceCodeResponse>,
@@ -20,6 +22,7 @@ impl State {
             selected_version: empty_lock(),
             asset_index: empty_lock(),
             class_path: None,
+            skipped_natives: Vec::new(),
             jar_path: None,
             path: path_buf,
             device_code: None,
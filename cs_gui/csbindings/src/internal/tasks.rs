@@ -1,4 +1,4 @@
-use crate::{runtime, NativeReturn};
+use crate::runtime;
 use std::future::Future;
 use tokio::task::JoinHandle;
 
@@ -48,35 +48,12 @@ where
     unsafe { raw_task.as_ref().unwrap().inner.is_finished() }
 }
 
-pub fn await_task<T, F: Fn(T) -> NativeReturn>(
-    raw_task: *mut TaskWrapper<T>,
-    f: F,
-) -> NativeReturn {
+/// Blocks until the task is finished, consuming it
+pub fn await_task<T>(raw_task: *mut TaskWrapper<T>) -> T {
     check_task_ptr(raw_task);
     let task = unsafe { Box::from_raw(raw_task) };
 
-    let inner = runtime().block_on(task.inner).unwrap();
-
-    f(inner)
-}
-
-pub fn await_result_task<T, E, F: Fn(T) -> NativeReturn>(
-    raw_task: *mut TaskWrapper<Result<T, E>>,
-    f: F,
-) -> NativeReturn
-where
-    E: Into<NativeReturn>,
-{
-    check_task_ptr(raw_task);
-
-    let task = unsafe { Box::from_raw(raw_task) };
-
-    let inner = runtime().block_on(task.inner).unwrap();
-
-    match inner {
-        Ok(inner) => f(inner),
-        Err(e) => e.into(),
-    }
+    runtime().block_on(task.inner).unwrap()
 }
 
 pub fn cancel_task<T>(raw_task: *mut TaskWrapper<T>)
@@ -0,0 +1,50 @@
+// Generated by bindgen, changes will be overwritten
+use memoffset::offset_of;
+
+#[test]
+fn layout_native_return() {
+    assert_eq!(std::mem::size_of::<crate::NativeReturn>(), 32);
+    assert_eq!(std::mem::align_of::<crate::NativeReturn>(), 8);
+    assert_eq!(offset_of!(crate::NativeReturn, code), 0);
+    assert_eq!(offset_of!(crate::NativeReturn, error), 8);
+}
+
+#[test]
+fn layout_ref_string_wrapper() {
+    assert_eq!(std::mem::size_of::<crate::RefStringWrapper>(), 16);
+    assert_eq!(std::mem::align_of::<crate::RefStringWrapper>(), 8);
+    assert_eq!(offset_of!(crate::RefStringWrapper, char_ptr), 0);
+    assert_eq!(offset_of!(crate::RefStringWrapper, len), 8);
+}
+
+#[test]
+fn layout_owned_string_wrapper() {
+    assert_eq!(std::mem::size_of::<crate::OwnedStringWrapper>(), 24);
+    assert_eq!(std::mem::align_of::<crate::OwnedStringWrapper>(), 8);
+    assert_eq!(offset_of!(crate::OwnedStringWrapper, char_ptr), 0);
+    assert_eq!(offset_of!(crate::OwnedStringWrapper, len), 8);
+    assert_eq!(offset_of!(crate::OwnedStringWrapper, capacity), 16);
+}
+
+#[test]
+fn layout_asset_garbage() {
+    assert_eq!(std::mem::size_of::<crate::AssetGarbage>(), 16);
+    assert_eq!(std::mem::align_of::<crate::AssetGarbage>(), 8);
+    assert_eq!(offset_of!(crate::AssetGarbage, objects), 0);
+    assert_eq!(offset_of!(crate::AssetGarbage, bytes), 8);
+}
+
+#[test]
+fn layout_library_prune() {
+    assert_eq!(std::mem::size_of::<crate::LibraryPrune>(), 16);
+    assert_eq!(std::mem::align_of::<crate::LibraryPrune>(), 8);
+    assert_eq!(offset_of!(crate::LibraryPrune, files), 0);
+    assert_eq!(offset_of!(crate::LibraryPrune, bytes), 8);
+}
+
+#[test]
+fn layout_rust_string() {
+    assert_eq!(std::mem::size_of::<crate::RustString>(), 24);
+    assert_eq!(std::mem::align_of::<crate::RustString>(), 8);
+    assert_eq!(offset_of!(crate::RustString, repr), 0);
+}
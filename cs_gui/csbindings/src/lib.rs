@@ -1,47 +1,29 @@
 use internal::*;
 mod internal;
-use csmacros::{dotnet, dotnetfunction};
+#[cfg(test)]
+mod layout_tests;
+use csmacros::{dotnet, dotnetfunction, dotnetstruct, dotnettask};
 use error::Error;
-use instances::{Instance, Jvm};
-use launcher_core::account::auth::{
-    authorization_token_response, minecraft_profile_response, minecraft_response,
-    refresh_token_response, xbox_response, xbox_security_token_response,
-};
-use launcher_core::account::types::{
-    Account, AuthorizationTokenResponse, DeviceCodeResponse, MinecraftAuthenticationResponse,
-    Profile,
-};
+use launcher_app::{auth, data, Jvm, CLIENT_ID};
+use launcher_core::account::types::{Account, DeviceCodeResponse};
+use launcher_core::http::{ClientOptions, Proxy};
+use launcher_core::jvm::ProbeError;
 use launcher_core::types::{AssetIndexJson, Version, VersionJson, VersionManifest};
-use launcher_core::{account, AsyncLauncher};
-use serde::{Deserialize, Serialize};
+use launcher_core::{account, assets, libraries, AsyncLauncher, Libraries};
 use state::State;
 use std::fmt::Display;
 use std::mem::transmute;
 use std::path::PathBuf;
 use std::ptr::null_mut;
-use std::slice;
 use std::sync::atomic::AtomicU64;
-use std::sync::OnceLock;
-use std::time::{Duration, SystemTime};
+use std::sync::{Arc, OnceLock};
 use tasks::{await_task, cancel_task, get_task, poll_task};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::runtime::Runtime;
 
-use crate::internal::tasks::await_result_task;
 pub use tasks::TaskWrapper;
 
-#[derive(Default, Deserialize, Serialize, Debug)]
-pub struct LauncherData {
-    jvms: Vec<Jvm>,
-    accounts: Vec<AccRefreshPair>,
-    instances: Vec<Instance>,
-}
-
-#[derive(Deserialize, Serialize, Debug)]
-pub struct AccRefreshPair {
-    account: Account,
-    refresh_token: String,
-}
+/// The same launcher_data.toml the other frontends use
+pub type LauncherData = launcher_app::LauncherData;
 
 pub fn runtime() -> &'static Runtime {
     static LOCK: OnceLock<Runtime> = OnceLock::new();
@@ -50,7 +32,23 @@ pub fn runtime() -> &'static Runtime {
 
 fn client() -> &'static reqwest::Client {
     static LOCK: OnceLock<reqwest::Client> = OnceLock::new();
-    LOCK.get_or_init(reqwest::Client::new)
+    LOCK.get_or_init(|| {
+        // The proxy and certificates are set up in the other frontends, they're only read here
+        let settings = LauncherData::load(&data::data_file())
+            .map(|data| data.settings)
+            .unwrap_or_default();
+        let proxy: Proxy = settings
+            .get("proxy")
+            .and_then(|proxy| proxy.clone().try_into().ok())
+            .unwrap_or_default();
+        let certificates: Vec<PathBuf> = settings
+            .get("certificates")
+            .and_then(|certificates| certificates.clone().try_into().ok())
+            .unwrap_or_default();
+        data::client_options(&proxy, &certificates, &data::secrets())
+            .build()
+            .unwrap_or_else(|_| ClientOptions::default().build().unwrap())
+    })
 }
 
 fn launcher() -> &'static AsyncLauncher {
@@ -76,8 +74,23 @@ impl NativeReturn {
             error: String::new(),
         }
     }
+
+    fn from_panic(payload: Box<dyn std::any::Any + Send>) -> Self {
+        Self {
+            code: Code::Panic,
+            error: panic::panic_message(payload.as_ref()),
+        }
+    }
+
+    fn invalid_string(e: impl Display) -> Self {
+        Self {
+            code: Code::InvalidString,
+            error: e.to_string(),
+        }
+    }
 }
 
+#[dotnet]
 #[repr(C)]
 pub enum Code {
     Success,
@@ -87,6 +100,8 @@ pub enum Code {
     ProfileError,
     JvmError,
     TomlDe,
+    Panic,
+    InvalidString,
 }
 
 impl From<Error> for NativeReturn {
@@ -97,6 +112,9 @@ impl From<Error> for NativeReturn {
             Error::SerdeJson(e) => (Code::SerdeError, e),
             Error::Profile(e) => (Code::ProfileError, e),
             Error::TomlDe(e) => (Code::TomlDe, e),
+            Error::TomlSer(e) => (Code::SerdeError, e),
+            // The message says when to try again
+            Error::RateLimited(e) => (Code::RequestError, e),
         };
 
         Self {
@@ -106,6 +124,7 @@ impl From<Error> for NativeReturn {
     }
 }
 
+#[dotnet(mirror = launcher_core::types::Type)]
 #[repr(C)]
 pub enum ReleaseType {
     OldAlpha,
@@ -114,25 +133,16 @@ pub enum ReleaseType {
     Snapshot,
 }
 
-impl From<launcher_core::types::Type> for ReleaseType {
-    fn from(value: launcher_core::types::Type) -> Self {
-        match value {
-            launcher_core::types::Type::OldAlpha => ReleaseType::OldAlpha,
-            launcher_core::types::Type::OldBeta => ReleaseType::OldBeta,
-            launcher_core::types::Type::Release => ReleaseType::Release,
-            launcher_core::types::Type::Snapshot => ReleaseType::Snapshot,
-        }
-    }
-}
-
 pub struct VersionErased;
 
+#[dotnetstruct]
 #[repr(C)]
 pub struct RefStringWrapper {
     pub char_ptr: *const u8,
     pub len: usize,
 }
 
+#[dotnetstruct]
 #[repr(C)]
 pub struct OwnedStringWrapper {
     pub char_ptr: *mut u8,
@@ -197,14 +207,12 @@ pub fn poll_manifest_task(raw_task: *const ManifestTaskWrapper) -> bool {
 pub unsafe fn await_version_manifest(
     state: *mut State,
     raw_task: *mut ManifestTaskWrapper,
-) -> NativeReturn {
-    await_result_task(raw_task as *mut ManifestTask, |inner| {
-        let state = &*state;
-        let mut lock = state.version_manifest.blocking_write();
-        *lock = Box::leak(Box::new(Some(inner)));
-        drop(lock);
-        NativeReturn::success()
-    })
+) -> Result<(), Error> {
+    let inner = await_task(raw_task as *mut ManifestTask)?;
+    let state = &*state;
+    let mut lock = state.version_manifest.blocking_write();
+    *lock = Box::leak(Box::new(Some(inner)));
+    Ok(())
 }
 
 #[dotnetfunction]
@@ -301,231 +309,125 @@ pub unsafe extern "C" fn version_type(version: *const VersionErased) -> ReleaseT
     version.version_type.into()
 }
 
-#[dotnetfunction]
-/// # Safety
-pub unsafe extern "C" fn get_version_task(
-    state: *mut State,
-    version: *const VersionErased,
-) -> *mut TaskWrapper<Result<VersionJson, Error>> {
-    let state = &*state;
-    let version = &*(version as *const Version);
-    get_task(async move {
-        Ok(launcher()
-            .get_version_json(version, &state.path.join("versions"))
-            .await?)
-    })
-}
-
-#[dotnetfunction]
-///# Safety
-///# The task cannot be null, and has to be a version task.
-///# The type cannot be checked by the Rust or C# compiler, and must instead be checked by the programmer.
-pub unsafe extern "C" fn poll_version_task(
-    raw_task: *const TaskWrapper<Result<VersionJson, Error>>,
-) -> bool {
-    poll_task(raw_task)
-}
-
-#[dotnetfunction]
-/// # Safety
-pub unsafe fn await_version_task(
-    state: *mut State,
-    raw_task: *mut TaskWrapper<Result<VersionJson, Error>>,
-) -> NativeReturn {
-    let state = &*state;
-    await_result_task(raw_task, |inner| {
-        let mut writer = state.selected_version.blocking_write();
-        *writer = Some(inner);
-        drop(writer);
-        NativeReturn::success()
-    })
-}
-
-#[dotnetfunction]
-/// # Safety
-/// This will drop a version task regardless of completion, this is only used when cancelling
-pub unsafe extern "C" fn cancel_version_task(
-    raw_task: *mut TaskWrapper<Result<VersionJson, Error>>,
-) {
-    cancel_task(raw_task)
-}
-
-#[dotnetfunction]
-/// # Safety
-pub unsafe extern "C" fn get_asset_index(
-    state: *mut State,
-) -> *mut TaskWrapper<Result<AssetIndexJson, Error>> {
-    let state = &*state;
-    get_task(async move {
-        let version = &state.selected_version;
-        let path = &state.path.join("assets");
-        let tmp = version.read().await;
-        let version = tmp.as_ref().unwrap();
-        Ok(launcher()
-            .get_asset_index_json(&version.asset_index, path)
-            .await?)
-    })
-}
-
-#[dotnetfunction]
-/// # Safety
-pub extern "C" fn poll_asset_index(
-    raw_task: *const TaskWrapper<Result<AssetIndexJson, Error>>,
-) -> bool {
-    poll_task(raw_task)
-}
-
-#[dotnetfunction]
+#[dotnettask(store = |state: *mut State, inner| {
+    *(*state).selected_version.blocking_write() = Some(inner);
+})]
 /// # Safety
-pub unsafe extern "C" fn await_asset_index(
-    state: *mut State,
-    raw_task: *mut TaskWrapper<Result<AssetIndexJson, Error>>,
-) -> NativeReturn {
-    let state = &*state;
-    await_result_task(raw_task, |inner| {
-        let mut writer = state.asset_index.blocking_write();
-        *writer = Some(inner);
-        drop(writer);
-        NativeReturn::success()
-    })
+/// The version has to come from `get_version`, and outlive the task
+pub async fn version_task(state: &State, version: &VersionErased) -> Result<VersionJson, Error> {
+    let version = unsafe { &*(version as *const VersionErased as *const Version) };
+    Ok(launcher()
+        .get_version_json(version, &state.path.join("versions"))
+        .await?)
 }
 
-#[dotnetfunction]
-/// # Safety
-pub unsafe extern "C" fn cancel_asset_index(
-    raw_task: *mut TaskWrapper<Result<AssetIndexJson, Error>>,
-) {
-    cancel_task(raw_task)
+#[dotnettask(store = |state: *mut State, inner| {
+    *(*state).asset_index.blocking_write() = Some(inner);
+})]
+pub async fn asset_index(state: &State) -> Result<AssetIndexJson, Error> {
+    let version = state.selected_version.read().await;
+    let version = version.as_ref().unwrap();
+    Ok(launcher()
+        .get_asset_index_json(&version.asset_index, &state.path.join("assets"))
+        .await?)
 }
 
-#[dotnetfunction]
-/// # Safety
+#[dotnettask(store = |state: *mut State, inner| {
+    (*state).class_path = Some(inner.class_path);
+    (*state).skipped_natives = inner.skipped_natives;
+})]
 /// Total and Finished will be treated like atomics
-pub unsafe extern "C" fn get_libraries(
-    state: *mut State,
-    total: *mut u64,
-    finished: *mut u64,
-) -> *mut TaskWrapper<Result<String, Error>> {
-    let state = &*state;
-    let total = AtomicU64::from_ptr(total);
-    let finished = AtomicU64::from_ptr(finished);
-    get_task(async move {
-        let binding = state.selected_version.read().await;
-        let version = binding.as_ref().unwrap();
-        Ok(launcher()
-            .download_libraries_and_get_path(
-                version.libraries(),
-                &state.path.join("libraries"),
-                &state.path.join("natives"),
-                total,
-                finished,
-            )
-            .await?)
-    })
-}
-
-#[dotnetfunction]
-pub extern "C" fn poll_libraries(raw_task: *const TaskWrapper<Result<String, Error>>) -> bool {
-    poll_task(raw_task)
+pub async fn libraries(
+    state: &State,
+    total: &AtomicU64,
+    finished: &AtomicU64,
+) -> Result<Libraries, Error> {
+    let binding = state.selected_version.read().await;
+    let version = binding.as_ref().unwrap();
+    Ok(launcher()
+        .download_libraries_and_get_path(
+            version.libraries(),
+            &state.path.join("libraries"),
+            &state.path.join("natives"),
+            total,
+            finished,
+        )
+        .await?)
 }
 
 #[dotnetfunction]
+/// How many natives the last libraries task skipped, the game may crash without them
+///
 /// # Safety
-pub unsafe fn await_libraries(
-    state: *mut State,
-    raw_task: *mut TaskWrapper<Result<String, Error>>,
-) -> NativeReturn {
-    await_result_task(raw_task, |inner| {
-        let state = &mut *state;
-        state.class_path = Some(inner);
-        NativeReturn::success()
-    })
-}
-
-#[dotnetfunction]
-pub extern "C" fn cancel_libraries(raw_task: *mut TaskWrapper<Result<(), Error>>) {
-    cancel_task(raw_task)
+pub unsafe fn skipped_natives_len(state: *mut State) -> usize {
+    (&*state).skipped_natives.len()
 }
 
 #[dotnetfunction]
 /// # Safety
-/// # Total and Finished will be treated like atomics
-pub unsafe extern "C" fn get_assets(
-    state: *mut State,
-    total: *mut u64,
-    finished: *mut u64,
-) -> *mut TaskWrapper<Result<(), Error>> {
-    let state = &*state;
-    let total = AtomicU64::from_ptr(total);
-    let finished = AtomicU64::from_ptr(finished);
-    get_task(async move {
-        let binding = state.asset_index.read().await;
-        let asset_index = binding.as_ref().unwrap();
-        Ok(launcher()
-            .download_and_store_asset_index(
-                asset_index,
-                &state.path.join("assets"),
-                total,
-                finished,
-            )
-            .await?)
-    })
-}
-
-#[dotnetfunction]
-pub extern "C" fn poll_assets(raw_task: *const TaskWrapper<Result<(), Error>>) -> bool {
-    poll_task(raw_task)
-}
-
-#[dotnetfunction]
-pub extern "C" fn await_assets(raw_task: *mut TaskWrapper<Result<(), Error>>) -> NativeReturn {
-    await_result_task(raw_task, |_| NativeReturn::success())
+pub unsafe fn skipped_native(state: *mut State, index: usize) -> RefStringWrapper {
+    (&*state).skipped_natives[index].as_str().into()
 }
 
-#[dotnetfunction]
-pub extern "C" fn cancel_assets(raw_task: *mut TaskWrapper<Result<(), Error>>) {
-    cancel_task(raw_task)
+#[dotnettask]
+/// Total and Finished will be treated like atomics
+pub async fn assets(state: &State, total: &AtomicU64, finished: &AtomicU64) -> Result<(), Error> {
+    let binding = state.asset_index.read().await;
+    let asset_index = binding.as_ref().unwrap();
+    Ok(launcher()
+        .download_and_store_asset_index(asset_index, &state.path.join("assets"), total, finished)
+        .await?)
 }
 
-#[dotnetfunction]
-/// # Safety
-pub unsafe extern "C" fn get_jar(
-    state: *mut State,
-    total: *mut u64,
-    finished: *mut u64,
-) -> *mut TaskWrapper<Result<String, Error>> {
-    let state = &*state;
-    let total = AtomicU64::from_ptr(total);
-    let finished = AtomicU64::from_ptr(finished);
-    get_task(async move {
-        let binding = &state.selected_version.read().await;
-        let version = binding.as_ref().unwrap();
-        Ok(launcher()
-            .download_jar(version, &state.path.join("versions"), total, finished)
-            .await?)
+#[dotnetstruct]
+#[repr(C)]
+/// What the asset collection found, and removed unless it was a dry run
+pub struct AssetGarbage {
+    pub objects: usize,
+    pub bytes: u64,
+}
+
+#[dotnettask]
+/// Removes assets no downloaded version uses, a dry run only counts them
+pub async fn collect_asset_garbage(state: &State, dry_run: bool) -> Result<AssetGarbage, Error> {
+    let assets_dir = state.path.join("assets");
+    let report = tokio::task::spawn_blocking(move || assets::collect_garbage(&assets_dir, dry_run))
+        .await
+        .map_err(|e| Error::Tokio(std::io::Error::other(e)))??;
+    Ok(AssetGarbage {
+        objects: report.objects,
+        bytes: report.bytes,
     })
 }
 
-#[dotnetfunction]
-pub extern "C" fn poll_jar(raw_task: *mut TaskWrapper<Result<String, Error>>) -> bool {
-    poll_task(raw_task)
-}
-
-#[dotnetfunction]
-/// # Safety
-pub unsafe fn await_jar(
-    state: *mut State,
-    raw_task: *mut TaskWrapper<Result<String, Error>>,
-) -> NativeReturn {
-    await_result_task(raw_task, |inner| {
-        (&mut *state).jar_path = Some(inner);
-        NativeReturn::success()
+#[dotnetstruct]
+#[repr(C)]
+/// What the library pruning found, and removed unless it was a dry run
+pub struct LibraryPrune {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[dotnettask]
+/// Removes libraries and natives no downloaded version uses, a dry run only counts them
+pub async fn prune_libraries(state: &State, dry_run: bool) -> Result<LibraryPrune, Error> {
+    let report = libraries::prune(&state.path, dry_run).await?;
+    Ok(LibraryPrune {
+        files: report.files,
+        bytes: report.bytes,
     })
 }
 
-#[dotnetfunction]
-pub extern "C" fn cancel_jar(raw_task: *mut TaskWrapper<Result<String, Error>>) {
-    cancel_task(raw_task)
+#[dotnettask(store = |state: *mut State, inner| {
+    (*state).jar_path = Some(inner);
+})]
+/// Total and Finished will be treated like atomics
+pub async fn jar(state: &State, total: &AtomicU64, finished: &AtomicU64) -> Result<String, Error> {
+    let binding = state.selected_version.read().await;
+    let version = binding.as_ref().unwrap();
+    Ok(launcher()
+        .download_jar(version, &state.path.join("versions"), total, finished)
+        .await?)
 }
 
 #[dotnetfunction]
@@ -544,7 +446,7 @@ pub unsafe extern "C" fn play(
     let directory = &state.path;
     let class_path = state.class_path.as_ref().unwrap();
     let jar_path = state.jar_path.as_ref().unwrap();
-    launcher_core::launch_game(
+    let result = launcher_core::launch_game(
         &jvm.path,
         version_json,
         directory,
@@ -555,7 +457,13 @@ pub unsafe extern "C" fn play(
         "synth_launcher",
         "0",
         &format!("{class_path}{jar_path}"),
+        &[],
     );
+
+    // The game keeps running after the handle is dropped
+    if let Err(e) = result {
+        eprintln!("Failed to launch the game: {e}");
+    }
 }
 
 #[dotnetfunction]
@@ -572,7 +480,7 @@ pub unsafe extern "C" fn play_default_jvm(
     let directory = &state.path;
     let class_path = state.class_path.as_ref().unwrap();
     let jar_path = state.jar_path.as_ref().unwrap();
-    launcher_core::launch_game(
+    let result = launcher_core::launch_game(
         "java",
         version_json,
         directory,
@@ -583,36 +491,20 @@ pub unsafe extern "C" fn play_default_jvm(
         "synth_launcher",
         "0",
         &format!("{class_path}{jar_path}"),
+        &[],
     );
-}
-
-pub const CLIENT_ID: &str = "04bc8538-fc3c-4490-9e61-a2b3f4cbcf5c";
 
-#[dotnetfunction]
-pub extern "C" fn get_device_response() -> *mut TaskWrapper<Result<DeviceCodeResponse, Error>> {
-    get_task(async { Ok(account::auth::device_response(client(), CLIENT_ID).await?) })
-}
-
-#[dotnetfunction]
-/// # Safety
-pub unsafe extern "C" fn poll_device_response(
-    raw_task: *const TaskWrapper<Result<DeviceCodeResponse, Error>>,
-) -> bool {
-    poll_task(raw_task)
+    // The game keeps running after the handle is dropped
+    if let Err(e) = result {
+        eprintln!("Failed to launch the game: {e}");
+    }
 }
 
-#[dotnetfunction]
-/// # Safety
-pub unsafe fn await_device_response(
-    state: *mut State,
-    raw_task: *mut TaskWrapper<Result<DeviceCodeResponse, Error>>,
-) -> NativeReturn {
-    await_result_task(raw_task, |inner| {
-        let state = &mut *state;
-        state.device_code = Some(inner);
-
-        NativeReturn::success()
-    })
+#[dotnettask(store = |state: *mut State, inner| {
+    (*state).device_code = Some(inner);
+})]
+pub async fn device_response() -> Result<DeviceCodeResponse, Error> {
+    Ok(account::auth::device_response(client(), CLIENT_ID).await?)
 }
 
 #[dotnetfunction]
@@ -641,25 +533,19 @@ pub unsafe fn get_url(state: *mut State) -> RefStringWrapper {
 /// # Safety
 pub unsafe extern "C" fn start_auth_loop(
     state: *mut State,
-) -> *mut TaskWrapper<Result<AccRefreshPair, Error>> {
+) -> *mut TaskWrapper<Result<(Account, String), Error>> {
     let state = &*state;
     get_task(async {
         let device_response = state.device_code.as_ref().unwrap();
-        let auth_res = loop {
-            let device_code = &device_response.device_code;
-            let auth_hook = authorization_token_response(client(), device_code, CLIENT_ID).await;
-            if let Ok(t) = auth_hook {
-                break t;
-            }
-        };
-        auth(auth_res).await
+        let token = auth::wait_for_code(client(), CLIENT_ID, device_response).await;
+        Ok(auth::account(client(), token).await?)
     })
 }
 
 #[dotnetfunction]
 /// # Safety
 pub extern "C" fn poll_auth_loop(
-    raw_task: *mut TaskWrapper<Result<AccRefreshPair, Error>>,
+    raw_task: *mut TaskWrapper<Result<(Account, String), Error>>,
 ) -> bool {
     poll_task(raw_task)
 }
@@ -667,34 +553,20 @@ pub extern "C" fn poll_auth_loop(
 #[dotnetfunction]
 /// # Safety
 pub unsafe fn await_auth_loop(
-    state: *const State,
     data: *mut LauncherData,
-    raw_task: *mut TaskWrapper<Result<AccRefreshPair, Error>>,
-) -> NativeReturn {
-    await_result_task(raw_task, |inner| {
-        let data = &mut *data;
-        for account in &mut data.accounts {
-            if account.account.profile.id == inner.account.profile.id {
-                *account = inner;
-                return NativeReturn::success();
-            }
-        }
-
-        data.accounts.push(inner);
-        if let Err(e) = std::fs::write(
-            (&*state).path.join("launcher_data.toml"),
-            toml::to_string_pretty(&data).unwrap().as_bytes(),
-        ) {
-            return Error::from(e).into();
-        };
-        NativeReturn::success()
-    })
+    raw_task: *mut TaskWrapper<Result<(Account, String), Error>>,
+) -> Result<(), Error> {
+    let (account, refresh_token) = await_task(raw_task)?;
+    let data = &mut *data;
+    data.set_account(account, refresh_token, &data::secrets());
+    data.save(&data::data_file())?;
+    Ok(())
 }
 
 #[dotnetfunction]
 /// # Safety
 pub unsafe extern "C" fn cancel_auth_loop(
-    raw_task: *mut TaskWrapper<Result<AccRefreshPair, Error>>,
+    raw_task: *mut TaskWrapper<Result<(Account, String), Error>>,
 ) {
     cancel_task(raw_task)
 }
@@ -704,20 +576,21 @@ pub unsafe extern "C" fn cancel_auth_loop(
 pub unsafe extern "C" fn try_refresh(
     data: *const LauncherData,
     index: usize,
-) -> *mut TaskWrapper<Result<(AccRefreshPair, usize), Error>> {
+) -> *mut TaskWrapper<Result<(Account, String), Error>> {
     let data = &*data;
+    let refresh_token = data.accounts[index].refresh_token(&data::secrets());
     get_task(async move {
-        let guard = data;
-        let profile = &guard.accounts[index];
-
-        let refresh = refresh_token_response(client(), &profile.refresh_token, CLIENT_ID).await?;
-        auth(refresh).await.map(|a| (a, index))
+        // Without a token the account has to be logged in again
+        let refresh_token = refresh_token.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "No refresh token is saved")
+        })?;
+        Ok(auth::refresh(client(), CLIENT_ID, &refresh_token).await?)
     })
 }
 
 #[dotnetfunction]
 pub extern "C" fn poll_refresh(
-    raw_task: *mut TaskWrapper<Result<(AccRefreshPair, usize), Error>>,
+    raw_task: *mut TaskWrapper<Result<(Account, String), Error>>,
 ) -> bool {
     poll_task(raw_task)
 }
@@ -725,21 +598,14 @@ pub extern "C" fn poll_refresh(
 #[dotnetfunction]
 /// # Safety
 pub unsafe extern "C" fn await_refresh(
-    state: *const State,
     data: *mut LauncherData,
-    raw_task: *mut TaskWrapper<Result<(AccRefreshPair, usize), Error>>,
-) -> NativeReturn {
-    await_result_task(raw_task, |(inner, idx)| {
-        let data = &mut *data;
-        let state = &*state;
-        data.accounts[idx] = inner;
-        std::fs::write(
-            state.path.join("launcher_data.toml"),
-            toml::to_string_pretty(&data).unwrap().as_bytes(),
-        )
-        .unwrap();
-        NativeReturn::success()
-    })
+    raw_task: *mut TaskWrapper<Result<(Account, String), Error>>,
+) -> Result<(), Error> {
+    let (account, refresh_token) = await_task(raw_task)?;
+    let data = &mut *data;
+    data.set_account(account, refresh_token, &data::secrets());
+    data.save(&data::data_file())?;
+    Ok(())
 }
 
 #[dotnetfunction]
@@ -751,7 +617,7 @@ pub unsafe fn accounts_len(data: *mut LauncherData) -> usize {
 #[dotnetfunction]
 /// # Safety
 pub unsafe fn remove_account(data: *mut LauncherData, index: usize) {
-    (&mut *data).accounts.remove(index);
+    (&mut *data).remove_account(index, &data::secrets());
 }
 
 #[dotnetfunction]
@@ -769,12 +635,7 @@ pub unsafe fn get_account_name(data: *mut LauncherData, index: usize) -> RefStri
 #[dotnetfunction]
 /// # Safety
 pub unsafe extern "C" fn needs_refresh(data: *mut LauncherData, index: usize) -> bool {
-    let data = &*data;
-    data.accounts[index].account.expiry
-        <= SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
+    (&*data).accounts[index].needs_refresh()
 }
 
 #[dotnetfunction]
@@ -791,19 +652,10 @@ pub unsafe extern "C" fn jvm_name(data: *mut LauncherData, index: usize) -> RefS
 
 #[dotnetfunction]
 /// # Safety
-pub unsafe fn add_jvm(data: *mut LauncherData, ptr: *const u16, len: usize) -> NativeReturn {
-    assert_eq!(ptr.align_offset(std::mem::align_of::<&[u16]>()), 0);
-    let string = String::from_utf16(slice::from_raw_parts(ptr, len)).unwrap();
-    match get_vendor_major_version(&string) {
-        Ok((vendor, version)) => {
-            (&mut *data).jvms.push(Jvm {
-                path: string,
-                name: format!("{vendor} {version}"),
-            });
-            NativeReturn::success()
-        }
-        Err(e) => e.into(),
-    }
+pub unsafe fn add_jvm(data: *mut LauncherData, path: String) -> Result<(), ProbeError> {
+    let jvm = Jvm::detect(path)?;
+    (&mut *data).jvms.push(Arc::new(jvm));
+    Ok(())
 }
 
 #[dotnetfunction]
@@ -812,137 +664,23 @@ pub unsafe extern "C" fn remove_jvm(data: *mut LauncherData, index: usize) {
     (&mut *data).jvms.remove(index);
 }
 
-pub enum JvmError {
-    Io(std::io::Error),
-    Fail(String),
-}
-
-impl From<JvmError> for NativeReturn {
-    fn from(value: JvmError) -> Self {
-        let code = Code::JvmError;
-
-        let str: &dyn Display = match &value {
-            JvmError::Io(e) => e,
-            JvmError::Fail(e) => e,
-        };
-
+impl From<ProbeError> for NativeReturn {
+    fn from(value: ProbeError) -> Self {
         NativeReturn {
-            code,
-            error: str.to_string().into(),
+            code: Code::JvmError,
+            error: value.to_string().into(),
         }
     }
 }
 
-impl From<std::io::Error> for JvmError {
-    fn from(value: std::io::Error) -> Self {
-        JvmError::Io(value)
-    }
-}
-
-fn get_vendor_major_version(jvm: &str) -> Result<(String, u32), JvmError> {
-    /// Compiled Java byte-code to check for the current Java Version
-    /// Source can be found in VersionPrinter.java
-    const CHECKER_CLASS: &[u8] = include_bytes!("VersionPrinter.class");
-
-    let tmp = std::env::temp_dir();
-    let checker_class_file = tmp.join("VersionPrinter.class");
-    std::fs::write(checker_class_file, CHECKER_CLASS).unwrap();
-    let io = std::process::Command::new(jvm)
-        .env_clear()
-        .current_dir(tmp)
-        .args(["-DFile.Encoding=UTF-8", "VersionPrinter"])
-        .output()?;
-
-    if !io.stderr.is_empty() {
-        return Err(JvmError::Fail(String::from_utf8(io.stderr).unwrap()));
-    }
-
-    if !io.status.success() {
-        return Err(JvmError::Fail(io.status.to_string()));
-    }
-
-    let string = String::from_utf8(io.stdout).unwrap();
-
-    let (version, name) = unsafe { string.split_once('\n').unwrap_unchecked() };
-
-    let mut split = version.split('.');
-    let next = split.next().unwrap();
-    let version = if next == "1" {
-        split.next().unwrap()
-    } else {
-        next
-    };
-
-    let name = name.to_string();
-    let version = version.parse().unwrap_or(0);
-
-    Ok((name, version))
-}
-
-async fn auth(auth_res: AuthorizationTokenResponse) -> Result<AccRefreshPair, Error> {
-    let xbox_response = xbox_response(client(), &auth_res.access_token).await?;
-
-    let xbox_secure_token_res =
-        xbox_security_token_response(client(), &xbox_response.token).await?;
-
-    let claims = &xbox_secure_token_res.display_claims;
-    let token = &xbox_secure_token_res.token;
-    let mc_res = minecraft_response(claims, token, client()).await?;
-
-    // This is literally not worth checking lol, the next endpoint will do it but better
-    // let ownership_check = minecraft_ownership_response(&mc_res.access_token, client()).await?;
-
-    let profile = minecraft_profile_response(&mc_res.access_token, client()).await?;
-
-    Ok(profile_to_account(profile, auth_res, mc_res))
-}
-
-fn profile_to_account(
-    profile: Profile,
-    auth_res: AuthorizationTokenResponse,
-    mc_res: MinecraftAuthenticationResponse,
-) -> AccRefreshPair {
-    let expires_in = Duration::from_secs(auth_res.expires_in);
-    let system_time = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap();
-    let combined_duration = system_time + expires_in;
-
-    let account = Account {
-        active: true,
-        expiry: combined_duration.as_secs(),
-        access_token: mc_res.access_token,
-        profile,
-    };
-
-    AccRefreshPair {
-        account,
-        refresh_token: auth_res.refresh_token,
-    }
-}
-
 #[dotnetfunction]
-pub unsafe fn read_data(state: *const State) -> *mut TaskWrapper<Result<LauncherData, Error>> {
-    let state = &*state;
+pub unsafe fn read_data() -> *mut TaskWrapper<Result<LauncherData, Error>> {
     get_task(async {
-        let path = state.path.join("launcher_data.toml");
-        let exists = path.exists() && path.is_file();
-        let mut file = tokio::fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .read(true)
-            .open(path)
-            .await?;
-        if exists {
-            let mut string = String::with_capacity(file.metadata().await?.len() as usize);
-            file.read_to_string(&mut string).await?;
-            Ok(toml::from_str(&string)?)
-        } else {
-            let default = LauncherData::default();
-            file.write_all(toml::to_string_pretty(&default).unwrap().as_bytes())
-                .await?;
-            Ok(default)
+        let mut data = LauncherData::load(&data::data_file())?;
+        if data.migrate_tokens(&data::secrets()) {
+            data.save(&data::data_file())?;
         }
+        Ok(data)
     })
 }
 
@@ -958,19 +696,15 @@ pub unsafe extern "C" fn poll_data(
     poll_task(raw_task)
 }
 
-/// If this is a success, we smuggle the pointer through the error
+/// On success the data is written to the out parameter, which should come from `alloc_data`
 #[dotnetfunction]
 pub unsafe fn await_data(
     raw_task: *mut TaskWrapper<Result<LauncherData, Error>>,
-    data: *mut LauncherData,
-) -> NativeReturn {
-    await_result_task(raw_task, |inner| {
-        let data = &mut *data;
-        *data = inner;
-        NativeReturn::success()
-    })
+) -> Result<LauncherData, Error> {
+    await_task(raw_task)
 }
 
+#[dotnetstruct]
 #[repr(C)]
 pub struct RustString {
     repr: [usize; 3],
@@ -978,9 +712,7 @@ pub struct RustString {
 
 impl RustString {
     fn as_mut_string(&mut self) -> &mut String {
-        unsafe {
-            transmute(self)
-        }
+        unsafe { transmute(self) }
     }
 }
 
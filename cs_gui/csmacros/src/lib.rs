@@ -4,8 +4,9 @@ use std::str::FromStr;
 use syn::punctuated::Punctuated;
 use syn::token::{Colon, Comma, Mut, Star};
 use syn::{
-    parse_macro_input, Expr, FnArg, Ident, Item, ItemFn, ItemStruct, Local, LocalInit, Pat,
-    PatIdent, PatType, ReturnType, Stmt, Type, TypePtr,
+    parse_macro_input, parse_quote, Expr, Fields, FnArg, GenericArgument, Ident, Item, ItemEnum,
+    ItemFn, ItemStruct, MetaNameValue, Pat, PatIdent, PatType, Path, PathArguments, ReturnType,
+    Type, TypePtr,
 };
 
 #[proc_macro_attribute]
@@ -14,9 +15,218 @@ pub fn dotnetstruct(
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
     let struc = parse_macro_input!(item as ItemStruct);
-    let fields = &struc.fields;
 
-    quote!(#struc).into()
+    match validate_struct(&struc) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Checks the struct can be shared with C# as is, and registers it for bindgen.
+///
+/// Types that can never be FFI-safe are rejected here with a useful message, everything
+/// else is checked by requiring `FfiSafe` for every field
+fn validate_struct(struc: &ItemStruct) -> syn::Result<TokenStream> {
+    let is_repr_c = struc.attrs.iter().any(|attr| {
+        attr.path().is_ident("repr")
+            && attr
+                .parse_args_with(Punctuated::<Ident, Comma>::parse_terminated)
+                .is_ok_and(|reprs| reprs.iter().any(|repr| repr == "C"))
+    });
+
+    if !is_repr_c {
+        return Err(syn::Error::new_spanned(
+            &struc.ident,
+            "#[dotnetstruct] requires #[repr(C)], the layout would not match the C# struct otherwise",
+        ));
+    }
+
+    if !struc.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &struc.generics,
+            "Generic structs can't be exported to C#",
+        ));
+    }
+
+    let Fields::Named(fields) = &struc.fields else {
+        return Err(syn::Error::new_spanned(
+            &struc.fields,
+            "#[dotnetstruct] only supports structs with named fields",
+        ));
+    };
+
+    let mut names = vec![];
+    let mut types = vec![];
+    for field in &fields.named {
+        check_field_type(&field.ty)?;
+        names.push(field.ident.as_ref().unwrap().to_string());
+        types.push(&field.ty);
+    }
+
+    let name = &struc.ident;
+    let type_names = types.iter().map(|ty| ty.to_token_stream().to_string());
+
+    Ok(quote! {
+        #struc
+
+        #[allow(dead_code)]
+        const _: () = {
+            fn assert_ffi_safe<T: crate::internal::ffi::FfiSafe>() {}
+            fn assert_fields() {
+                #(assert_ffi_safe::<#types>();)*
+            }
+        };
+
+        unsafe impl crate::internal::ffi::FfiSafe for #name {}
+
+        impl #name {
+            /// Field names and their Rust types in declaration order, as read by bindgen
+            #[doc(hidden)]
+            pub const DOTNET_FIELDS: &'static [(&'static str, &'static str)] = &[
+                #((#names, #type_names)),*
+            ];
+        }
+    })
+}
+
+/// Validates a fieldless enum, and generates conversions to and from the mirrored enum by variant name
+fn expand_enum(en: &ItemEnum, mirror: Option<&Path>) -> syn::Result<TokenStream> {
+    let repr = en.attrs.iter().find_map(|attr| {
+        if attr.path().is_ident("repr") {
+            attr.parse_args::<Ident>().ok()
+        } else {
+            None
+        }
+    });
+
+    match repr {
+        Some(repr)
+            if ["C", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"]
+                .iter()
+                .any(|r| repr == r) => {}
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &en.ident,
+                "#[dotnet] enums require #[repr(C)] or an integer repr, the size would not match the C# enum otherwise",
+            ))
+        }
+    }
+
+    if let Some(variant) = en.variants.iter().find(|v| !v.fields.is_empty()) {
+        return Err(syn::Error::new_spanned(
+            variant,
+            "Only fieldless enums can be shared with C#",
+        ));
+    }
+
+    let name = &en.ident;
+    let variants: Vec<&Ident> = en.variants.iter().map(|v| &v.ident).collect();
+    let variant_names = variants.iter().map(|v| v.to_string());
+
+    let conversions = mirror.map(|mirror| {
+        quote! {
+            impl From<#mirror> for #name {
+                fn from(value: #mirror) -> Self {
+                    match value {
+                        #(#mirror::#variants => #name::#variants,)*
+                    }
+                }
+            }
+
+            impl From<#name> for #mirror {
+                fn from(value: #name) -> Self {
+                    match value {
+                        #(#name::#variants => #mirror::#variants,)*
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #en
+
+        unsafe impl crate::internal::ffi::FfiSafe for #name {}
+
+        impl #name {
+            /// Variant names in declaration order, as read by bindgen
+            #[doc(hidden)]
+            pub const DOTNET_VARIANTS: &'static [&'static str] = &[#(#variant_names),*];
+        }
+
+        #conversions
+    })
+}
+
+/// Validates the struct like #[dotnetstruct], and generates a conversion from the mirrored struct,
+/// converting each field with `Into`
+fn expand_struct(struc: &ItemStruct, mirror: Option<&Path>) -> syn::Result<TokenStream> {
+    let validated = validate_struct(struc)?;
+
+    let name = &struc.ident;
+    let fields: Vec<&Ident> = struc
+        .fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref())
+        .collect();
+
+    let conversion = mirror.map(|mirror| {
+        quote! {
+            impl From<#mirror> for #name {
+                fn from(value: #mirror) -> Self {
+                    Self {
+                        #(#fields: value.#fields.into(),)*
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #validated
+
+        #conversion
+    })
+}
+
+fn check_field_type(ty: &Type) -> syn::Result<()> {
+    match ty {
+        Type::Reference(_) => Err(syn::Error::new_spanned(
+            ty,
+            "References can't be shared with C#, use a raw pointer instead",
+        )),
+        Type::Slice(_) | Type::TraitObject(_) => Err(syn::Error::new_spanned(
+            ty,
+            "Unsized types can't be shared with C#, use a pointer and a length instead",
+        )),
+        Type::Tuple(_) => Err(syn::Error::new_spanned(
+            ty,
+            "Tuples have no defined layout, use a #[repr(C)] struct instead",
+        )),
+        Type::Array(array) => check_field_type(&array.elem),
+        Type::Path(path) => {
+            let Some(last) = path.path.segments.last() else {
+                return Ok(());
+            };
+            match last.ident.to_string().as_str() {
+                "String" | "Vec" | "Box" | "Rc" | "Arc" | "HashMap" => {
+                    Err(syn::Error::new_spanned(
+                        ty,
+                        format!(
+                            "`{}` has no defined layout, use a #[repr(C)] wrapper like OwnedStringWrapper instead",
+                            last.ident
+                        ),
+                    ))
+                }
+                "char" => Err(syn::Error::new_spanned(
+                    ty,
+                    "Rust chars are 4 bytes while C# chars are 2, use u32 or u16 instead",
+                )),
+                _ => Ok(()),
+            }
+        }
+        _ => Ok(()),
+    }
 }
 
 #[proc_macro_attribute]
@@ -25,14 +235,19 @@ pub fn dotnetfunction(
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
     let fun = parse_macro_input!(item as ItemFn);
+
+    expand_function(&fun).into()
+}
+
+fn expand_function(fun: &ItemFn) -> TokenStream {
     let inputs = &fun.sig.inputs;
-    let mut strings = vec![];
+    let mut decoded = vec![];
     let mut new_inputs: Punctuated<FnArg, Comma> = Punctuated::new();
     inputs.iter().for_each(|fn_arg| match fn_arg {
         FnArg::Receiver(_) => unreachable!(),
         FnArg::Typed(t) => {
-            if let Some(line) = handle_input_types(t, &mut new_inputs) {
-                strings.push(line);
+            if let Some(arg) = handle_input_types(t, &mut new_inputs) {
+                decoded.push(arg);
             } else {
                 new_inputs.push(fn_arg.clone())
             }
@@ -40,13 +255,83 @@ pub fn dotnetfunction(
     });
 
     let name = &fun.sig.ident;
-    let output = &fun.sig.output;
     let block = &fun.block;
     let vis = &fun.vis;
     let attrs = &fun.attrs;
     let safety = &fun.sig.unsafety;
 
-    let expanded = quote! {
+    let on_error = match decode_error_return(&fun.sig.output) {
+        Ok(on_error) => on_error,
+        Err(message) => {
+            if let Some(arg) = decoded.iter().find(|arg| arg.fallible) {
+                return syn::Error::new_spanned(&arg.pat, message).into_compile_error();
+            }
+            quote! { unreachable!() }
+        }
+    };
+
+    let strings: Vec<TokenStream> = decoded
+        .iter()
+        .map(
+            |DecodedArg {
+                 pat,
+                 value,
+                 fallible,
+             }| {
+                if *fallible {
+                    quote! {
+                        let #pat = match #value {
+                            Ok(value) => value,
+                            Err(e) => #on_error,
+                        };
+                    }
+                } else {
+                    quote! { let #pat = #value; }
+                }
+            },
+        )
+        .collect();
+
+    let (output, block) = match &fun.sig.output {
+        ReturnType::Type(_, ty) => match result_ok_type(ty) {
+            Some(ok) => {
+                let store = if is_unit(ok) {
+                    quote! { let _ = value; }
+                } else {
+                    new_inputs.push(parse_quote!(_return: *mut #ok));
+                    quote! {
+                        #[allow(unused_unsafe)]
+                        unsafe { _return.write(value) };
+                    }
+                };
+
+                // Panics are reported like any other error
+                let output = quote! { -> crate::NativeReturn };
+                let block = quote! {{
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(
+                        move || -> #ty #block,
+                    ));
+                    match result {
+                        Ok(Ok(value)) => {
+                            #store
+                            crate::NativeReturn::success()
+                        }
+                        Ok(Err(e)) => e.into(),
+                        Err(panic) => crate::NativeReturn::from_panic(panic),
+                    }
+                }};
+
+                (output, block)
+            }
+            None => (
+                fun.sig.output.to_token_stream(),
+                abort_on_panic(name, block),
+            ),
+        },
+        ReturnType::Default => (quote! {}, abort_on_panic(name, block)),
+    };
+
+    quote! {
         #[no_mangle]
         #[allow(improper_ctypes)]
         #[allow(improper_ctypes_definitions)]
@@ -55,24 +340,230 @@ pub fn dotnetfunction(
             #(#strings)*
             #block
         }
+    }
+}
+
+/// What a function returns when a string argument can't be decoded, `e` being the decoding error
+fn decode_error_return(output: &ReturnType) -> Result<TokenStream, &'static str> {
+    match output {
+        ReturnType::Default => Ok(quote! {{
+            eprintln!("Invalid string argument: {e}");
+            return;
+        }}),
+        ReturnType::Type(_, ty) if result_ok_type(ty).is_some() => Ok(quote! {
+            return crate::NativeReturn::invalid_string(e)
+        }),
+        ReturnType::Type(_, ty) if matches!(ty.as_ref(), Type::Ptr(_)) => Ok(quote! {
+            return std::ptr::null_mut::<()>() as _
+        }),
+        ReturnType::Type(..) => Err(
+            "Functions taking strings have to return a Result, a pointer or nothing, so invalid strings can be reported",
+        ),
+    }
+}
+
+/// Functions that can't return an error abort on panic, as unwinding into C# is undefined behavior
+fn abort_on_panic(name: &Ident, block: &syn::Block) -> TokenStream {
+    quote! {{
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || #block));
+        match result {
+            Ok(value) => value,
+            Err(panic) => crate::internal::panic::abort(stringify!(#name), panic),
+        }
+    }}
+}
+
+/// Turns an async fn into the `get_`, `poll_`, `await_` and `cancel_` functions used to drive it from C#.
+///
+/// Reference arguments become pointers, as the future has to be `'static` to be spawned.
+/// The await function writes the result to an out parameter, unless a store closure is given,
+/// `#[dotnettask(store = |state: *mut State, inner| ...)]`, in which case every argument
+/// but the last is added to the await function, and the last one receives the result
+#[proc_macro_attribute]
+pub fn dotnettask(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let fun = parse_macro_input!(item as ItemFn);
+    let store = if args.is_empty() {
+        None
+    } else {
+        let meta = parse_macro_input!(args as MetaNameValue);
+        if !meta.path.is_ident("store") {
+            return syn::Error::new_spanned(meta.path, "expected `store = |..| ..`")
+                .into_compile_error()
+                .into();
+        }
+        let Expr::Closure(closure) = meta.value else {
+            return syn::Error::new_spanned(meta.value, "`store` has to be a closure")
+                .into_compile_error()
+                .into();
+        };
+        Some(closure)
+    };
+
+    if fun.sig.asyncness.is_none() {
+        return syn::Error::new_spanned(fun.sig.fn_token, "#[dotnettask] expects an async fn")
+            .into_compile_error()
+            .into();
+    }
+
+    let ReturnType::Type(_, ret) = &fun.sig.output else {
+        return syn::Error::new_spanned(
+            &fun.sig,
+            "#[dotnettask] functions have to return a Result",
+        )
+        .into_compile_error()
+        .into();
+    };
+    let Some(err) = result_err_type(ret) else {
+        return syn::Error::new_spanned(ret, "#[dotnettask] functions have to return a Result")
+            .into_compile_error()
+            .into();
     };
 
-    proc_macro::TokenStream::from(expanded)
+    let name = &fun.sig.ident;
+    let vis = &fun.vis;
+    let task = quote! { *mut crate::TaskWrapper<#ret> };
+
+    let mut get_inputs: Punctuated<FnArg, Comma> = Punctuated::new();
+    let mut get_args: Punctuated<TokenStream, Comma> = Punctuated::new();
+    for input in &fun.sig.inputs {
+        let FnArg::Typed(input) = input else {
+            return syn::Error::new_spanned(input, "Methods are not supported!")
+                .into_compile_error()
+                .into();
+        };
+        let pat = &input.pat;
+        match input.ty.as_ref() {
+            Type::Reference(reference) => {
+                let elem = &reference.elem;
+                if reference.mutability.is_some() {
+                    get_inputs.push(parse_quote!(#pat: *mut #elem));
+                    get_args.push(quote!(&mut *#pat));
+                } else {
+                    get_inputs.push(parse_quote!(#pat: *const #elem));
+                    get_args.push(quote!(&*#pat));
+                }
+            }
+            _ => {
+                get_inputs.push(FnArg::Typed(input.clone()));
+                get_args.push(quote!(#pat));
+            }
+        }
+    }
+
+    let get_name = quote::format_ident!("get_{name}");
+    let poll_name = quote::format_ident!("poll_{name}");
+    let await_name = quote::format_ident!("await_{name}");
+    let cancel_name = quote::format_ident!("cancel_{name}");
+
+    let get: ItemFn = parse_quote! {
+        /// # Safety
+        /// Pointer arguments have to be valid until the task is awaited or cancelled
+        #vis unsafe fn #get_name(#get_inputs) -> #task {
+            crate::internal::tasks::get_task(#name(#get_args))
+        }
+    };
+
+    let poll: ItemFn = parse_quote! {
+        /// The task cannot be null, and has to come from the matching get function
+        #vis fn #poll_name(raw_task: #task) -> bool {
+            crate::internal::tasks::poll_task(raw_task)
+        }
+    };
+
+    let await_fn: ItemFn = match store {
+        Some(closure) => {
+            let mut store_inputs = vec![];
+            let mut store_args = vec![];
+            let params = closure.inputs.len().saturating_sub(1);
+            for param in closure.inputs.iter().take(params) {
+                let Pat::Type(param) = param else {
+                    return syn::Error::new_spanned(param, "store arguments need a type")
+                        .into_compile_error()
+                        .into();
+                };
+                let pat = &param.pat;
+                store_inputs.push(FnArg::Typed(param.clone()));
+                store_args.push(quote!(#pat));
+            }
+
+            parse_quote! {
+                /// # Safety
+                /// The task cannot be null, and is consumed by this function
+                #vis unsafe fn #await_name(#(#store_inputs,)* raw_task: #task) -> Result<(), #err> {
+                    let value = crate::internal::tasks::await_task(raw_task)?;
+                    (#closure)(#(#store_args,)* value);
+                    Ok(())
+                }
+            }
+        }
+        None => parse_quote! {
+            /// # Safety
+            /// The task cannot be null, and is consumed by this function
+            #vis unsafe fn #await_name(raw_task: #task) -> #ret {
+                crate::internal::tasks::await_task(raw_task)
+            }
+        },
+    };
+
+    let cancel: ItemFn = parse_quote! {
+        /// # Safety
+        /// The task cannot be null, and is consumed by this function
+        #vis unsafe fn #cancel_name(raw_task: #task) {
+            crate::internal::tasks::cancel_task(raw_task)
+        }
+    };
+
+    let get = expand_function(&get);
+    let poll = expand_function(&poll);
+    let await_fn = expand_function(&await_fn);
+    let cancel = expand_function(&cancel);
+
+    quote! {
+        #fun
+
+        #get
+
+        #poll
+
+        #await_fn
+
+        #cancel
+    }
+    .into()
 }
 
 #[proc_macro_attribute]
 pub fn dotnet(
-    _args: proc_macro::TokenStream,
+    args: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let mirror = if args.is_empty() {
+        None
+    } else {
+        let meta = parse_macro_input!(args as MetaNameValue);
+        match meta.value {
+            Expr::Path(path) if meta.path.is_ident("mirror") => Some(path.path),
+            _ => {
+                return syn::Error::new_spanned(meta, "expected `mirror = path::to::Type`")
+                    .into_compile_error()
+                    .into()
+            }
+        }
+    };
+
     let item = syn::parse_macro_input!(item as Item);
     match item {
-        Item::Enum(_en) => {
-            todo!()
-        }
-        Item::Struct(_struc) => {
-            todo!()
-        }
+        Item::Enum(en) => match expand_enum(&en, mirror.as_ref()) {
+            Ok(tokens) => tokens.into(),
+            Err(e) => e.into_compile_error().into(),
+        },
+        Item::Struct(struc) => match expand_struct(&struc, mirror.as_ref()) {
+            Ok(tokens) => tokens.into(),
+            Err(e) => e.into_compile_error().into(),
+        },
         Item::Fn(func) => {
             let sig = &func.sig;
             let mut new_args: Punctuated<FnArg, Comma> = Punctuated::new();
@@ -122,6 +613,52 @@ pub fn dotnet(
     }
 }
 
+/// Returns `T` if the type is a `Result<T, E>`
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let last = path.path.segments.last()?;
+    if last.ident != "Result" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+
+    match args.args.first()? {
+        GenericArgument::Type(ok) => Some(ok),
+        _ => None,
+    }
+}
+
+/// Returns `E` if the type is a `Result<T, E>`
+fn result_err_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let last = path.path.segments.last()?;
+    if last.ident != "Result" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+
+    match args.args.iter().nth(1)? {
+        GenericArgument::Type(err) => Some(err),
+        _ => None,
+    }
+}
+
+fn is_unit(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(tuple) if tuple.elems.is_empty())
+}
+
 fn arguments<'a>(
     inputs: &'a Punctuated<FnArg, Comma>,
     new_inputs: &mut Punctuated<FnArg, Comma>,
@@ -161,57 +698,59 @@ fn arguments<'a>(
     args
 }
 
-fn handle_input_types(t: &PatType, inputs: &mut Punctuated<FnArg, Comma>) -> Option<Stmt> {
-    match t.ty.as_ref() {
-        Type::Path(ty) => {
-            if let Some(path) = ty.path.segments.last() {
-                match &path.ident.to_string()[..] {
-                    "String" => {
-                        let name = t.pat.to_token_stream().to_string();
-                        let arg = FnArg::Typed(PatType {
-                            attrs: vec![],
-                            pat: Box::new(Pat::Verbatim(
-                                TokenStream::from_str(&format!("{name}_ptr")).unwrap(),
-                            )),
-                            ty: Box::new(Type::Ptr(TypePtr {
-                                star_token: Default::default(),
-                                const_token: Some(Default::default()),
-                                mutability: None,
-                                elem: Box::new(Type::Verbatim(
-                                    TokenStream::from_str("u16").unwrap(),
-                                )),
-                            })),
-                            colon_token: Default::default(),
-                        });
-                        let len = FnArg::Typed(PatType {
-                            attrs: vec![],
-                            pat: Box::new(Pat::Verbatim(
-                                TokenStream::from_str(&format!("{name}_len")).unwrap(),
-                            )),
-                            ty: Box::new(Type::Verbatim(TokenStream::from_str("usize").unwrap())),
-                            colon_token: Default::default(),
-                        });
-                        inputs.push(arg);
-                        inputs.push(len);
-                        let line = Stmt::Local(Local {
-                            attrs: vec![],
-                            let_token: Default::default(),
-                            pat: Pat::Verbatim(TokenStream::from_str(&name).unwrap()),
-                            init: Some(LocalInit {
-                                eq_token: Default::default(),
-                                expr: Box::new(Expr::Verbatim(TokenStream::from_str(&format!("String::from_utf16(std::slice::from_raw_parts({name}_ptr, {name}_len))")).unwrap())),
-                                diverge: None,
-                            }),
-                            semi_token: Default::default(),
-                        });
-                        Some(line)
-                    }
-                    _ => None,
-                }
+struct DecodedArg {
+    pat: Box<Pat>,
+    /// Evaluates to a `Result` if `fallible` is set, and to the argument otherwise
+    value: TokenStream,
+    fallible: bool,
+}
+
+/// Strings and byte slices are passed from C# as a pointer and a length.
+/// `String` is decoded from UTF-16, `&str` has to be UTF-8, and `&[u8]` is borrowed as is
+fn handle_input_types(t: &PatType, inputs: &mut Punctuated<FnArg, Comma>) -> Option<DecodedArg> {
+    let name = t.pat.to_token_stream().to_string();
+    let ptr = quote::format_ident!("{name}_ptr");
+    let len = quote::format_ident!("{name}_len");
+
+    let (elem, value, fallible) = match t.ty.as_ref() {
+        Type::Reference(reference) => match reference.elem.as_ref() {
+            Type::Path(path) if path.path.is_ident("str") => {
+                (quote!(u8), quote!(std::str::from_utf8(slice)), true)
+            }
+            Type::Slice(slice) if slice.elem.to_token_stream().to_string() == "u8" => {
+                (quote!(u8), quote!(slice), false)
+            }
+            _ => return None,
+        },
+        Type::Path(path) if path.path.segments.last()?.ident == "String" => {
+            (quote!(u16), quote!(String::from_utf16(slice)), true)
+        }
+        _ => return None,
+    };
+
+    inputs.push(parse_quote!(#ptr: *const #elem));
+    inputs.push(parse_quote!(#len: usize));
+
+    // Borrowing the pointer argument ties the slice to the call, so it can't escape
+    // the function like the unbounded lifetime from `from_raw_parts` would let it
+    let value = quote! {{
+        #[inline(always)]
+        unsafe fn borrow<'a, T>(ptr: &'a *const T, len: usize) -> &'a [T] {
+            if len == 0 {
+                &[]
             } else {
-                None
+                std::slice::from_raw_parts(*ptr, len)
             }
         }
-        _ => None,
-    }
+
+        #[allow(unused_unsafe)]
+        let slice = unsafe { borrow(&#ptr, #len) };
+        #value
+    }};
+
+    Some(DecodedArg {
+        pat: t.pat.clone(),
+        value,
+        fallible,
+    })
 }
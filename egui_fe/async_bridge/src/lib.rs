@@ -1,11 +1,286 @@
 pub use async_channel::TryRecvError;
-use std::pin::Pin;
+use async_channel::TrySendError;
+use futures::future::{AbortHandle, AbortRegistration, Abortable, Either};
+use futures::FutureExt;
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::AssertUnwindSafe;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often [`ProgressSender::watch`] reads the progress
+const WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+// Callbacks and futures are boxed the same way, callbacks just don't have a response
+type BoxedFuture<R> = Pin<Box<dyn std::future::Future<Output = Option<R>> + Send>>;
 
 enum InternalMessage<M, R> {
     Message(M),
-    Callback(Pin<Box<dyn std::future::Future<Output = ()> + Send>>),
-    CallbackWithResponse(Pin<Box<dyn std::future::Future<Output = R> + Send>>),
-    Future(Pin<Box<dyn std::future::Future<Output = R> + Send>>),
+    Future(BoxedFuture<R>),
+}
+
+// What the worker threads are sent, the registration lets the handle abort the work
+struct Task<M, R> {
+    handle: TaskHandle,
+    registration: AbortRegistration,
+    message: InternalMessage<M, R>,
+}
+
+/// A message, callback or future given to the runtime. Aborting it stops the work at its
+/// next await, and drops its response if it was already sent
+#[derive(Clone, Debug)]
+pub struct TaskHandle {
+    id: u64,
+    abort: AbortHandle,
+}
+
+impl TaskHandle {
+    /// Unique for each runtime, in the order the work was given
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn abort(&self) {
+        self.abort.abort();
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.abort.is_aborted()
+    }
+}
+
+/// Given to a future started with [`Runtime::spawn_with_progress`], every update wakes the UI
+pub struct ProgressSender<P> {
+    tx: async_channel::Sender<P>,
+    wake: Arc<dyn Fn() + Send + Sync>,
+}
+
+impl<P> Clone for ProgressSender<P> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            wake: self.wake.clone(),
+        }
+    }
+}
+
+impl<P> ProgressSender<P> {
+    /// Nothing happens once the handle is dropped
+    pub fn send(&self, progress: P) {
+        if self.tx.try_send(progress).is_ok() {
+            (self.wake)();
+        }
+    }
+
+    /// Runs the future, sending what `read` returns every so often until it's done.
+    /// For progress kept in atomics, like launcher_core's `DownloadStats`
+    pub async fn watch<T>(
+        &self,
+        future: impl std::future::Future<Output = T>,
+        read: impl Fn() -> P,
+    ) -> T {
+        let mut future = pin!(future);
+        loop {
+            let tick = pin!(tokio::time::sleep(WATCH_INTERVAL));
+            match futures::future::select(future.as_mut(), tick).await {
+                Either::Left((output, _)) => return output,
+                Either::Right(_) => self.send(read()),
+            }
+        }
+    }
+}
+
+/// The task of [`Runtime::spawn_with_progress`], and the updates it sends
+pub struct ProgressHandle<P> {
+    task: TaskHandle,
+    rx: async_channel::Receiver<P>,
+    latest: RefCell<Option<P>>,
+}
+
+impl<P: Clone> ProgressHandle<P> {
+    pub fn task(&self) -> &TaskHandle {
+        &self.task
+    }
+
+    /// The newest update, None until the first one is sent
+    pub fn latest(&self) -> Option<P> {
+        let mut latest = self.latest.borrow_mut();
+        while let Ok(progress) = self.rx.try_recv() {
+            *latest = Some(progress);
+        }
+        latest.clone()
+    }
+}
+
+/// What happens when a bounded channel is full, for work sent by the UI and for responses
+/// sent back by the workers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backpressure {
+    /// The sender waits for room, the UI thread included
+    #[default]
+    Block,
+    /// The oldest waiting work or response is dropped, dropped work has its handle aborted
+    DropOldest,
+    /// The new work or response is dropped, dropped work comes back with an aborted handle
+    Error,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    pub capacity: usize,
+    pub policy: Backpressure,
+}
+
+/// How many finished tasks [`Metrics::durations`] keeps
+#[cfg(feature = "durations")]
+pub const KEPT_DURATIONS: usize = 64;
+
+/// How much is waiting in the channels, how much is running or has finished,
+/// and how much was dropped because the channels were full
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    pub waiting_tasks: usize,
+    pub waiting_responses: usize,
+    pub active_tasks: usize,
+    /// Tasks that ran to the end, aborted and panicking ones aren't counted
+    pub completed: u64,
+    pub dropped: u64,
+    /// How long the last [`KEPT_DURATIONS`] completed tasks ran, oldest first
+    #[cfg(feature = "durations")]
+    pub durations: Vec<TaskDuration>,
+}
+
+/// How long the task with that id ran, from a worker picking it up until it finished
+#[cfg(feature = "durations")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskDuration {
+    pub id: u64,
+    pub took: Duration,
+}
+
+// Shared with the workers, which update most of them
+#[derive(Default)]
+struct Counters {
+    active: AtomicUsize,
+    completed: AtomicU64,
+    dropped: AtomicU64,
+    #[cfg(feature = "durations")]
+    durations: Mutex<std::collections::VecDeque<TaskDuration>>,
+}
+
+impl Counters {
+    #[cfg(feature = "durations")]
+    fn record(&self, id: u64, took: Duration) {
+        let mut durations = self.durations.lock().unwrap();
+        if durations.len() == KEPT_DURATIONS {
+            durations.pop_front();
+        }
+        durations.push_back(TaskDuration { id, took });
+    }
+}
+
+/// A time limit for each attempt of a future, and how often it's started again after
+/// running out of time. The wait between attempts doubles after each one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeout {
+    pub limit: Duration,
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl Timeout {
+    /// A single attempt
+    pub const fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            retries: 0,
+            backoff: Duration::ZERO,
+        }
+    }
+
+    pub const fn with_retries(self, retries: u32, backoff: Duration) -> Self {
+        Self {
+            retries,
+            backoff,
+            ..self
+        }
+    }
+}
+
+/// Given to the `on_timeout` of [`Runtime::future_with_timeout`] once every attempt ran out
+/// of time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimedOut {
+    pub attempts: u32,
+    pub limit: Duration,
+}
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Request timed out, {} attempts took longer than {}s",
+            self.attempts,
+            self.limit.as_secs_f32()
+        )
+    }
+}
+
+/// A task that panicked instead of returning a response, the worker carries on with the next
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Panicked {
+    pub id: u64,
+    pub message: String,
+}
+
+impl Panicked {
+    fn new(id: u64, panic: &(dyn Any + Send)) -> Self {
+        let message = if let Some(message) = panic.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = panic.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Unknown panic".to_string()
+        };
+        Self { id, message }
+    }
+}
+
+impl std::fmt::Display for Panicked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "A background task panicked: {}", self.message)
+    }
+}
+
+/// Workers take the oldest work from the highest lane that has any, so a token refresh
+/// doesn't wait behind a pile of downloads
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Priority {
+    /// Auth and anything else the UI is blocked on
+    High = 0,
+    #[default]
+    Normal = 1,
+    /// Bulk downloads
+    Low = 2,
+}
+
+// Lanes are indexed by priority, and a bounded runtime gives each its own capacity
+type Lanes<T> = [T; 3];
+
+/// The next task from the highest lane that has one, or whichever gets one first.
+/// None once the lanes are closed and empty
+async fn next_task<T>(lanes: &Lanes<async_channel::Receiver<T>>) -> Option<T> {
+    for lane in lanes {
+        if let Ok(task) = lane.try_recv() {
+            return Some(task);
+        }
+    }
+    // The lanes are only closed together, so one closed lane means there's nothing left
+    let waiting = lanes.iter().map(|lane| Box::pin(lane.recv()));
+    let (next, _, _) = futures::future::select_all(waiting).await;
+    next.ok()
 }
 
 pub struct Runtime<M, R, S>
@@ -14,10 +289,20 @@ where
     R: Send + 'static,
     M: Send + 'static,
 {
-    _rt: tokio::runtime::Runtime,
-    tx: async_channel::Sender<InternalMessage<M, R>>,
-    rx: async_channel::Receiver<R>,
+    rt: tokio::runtime::Runtime,
+    // Taken by Runtime::shutdown
+    workers: Mutex<Vec<tokio::task::JoinHandle<()>>>,
+    tx: Lanes<async_channel::Sender<Task<M, R>>>,
+    rx: async_channel::Receiver<(TaskHandle, R)>,
+    // Keeps the responses open once the workers are gone, so try_recv stays Empty after
+    // Runtime::shutdown instead of turning Closed
+    _responses: async_channel::Sender<(TaskHandle, R)>,
+    panics: async_channel::Receiver<Panicked>,
     state: &'static S,
+    next_id: AtomicU64,
+    wake: Arc<dyn Fn() + Send + Sync>,
+    policy: Backpressure,
+    counters: Arc<Counters>,
 }
 
 impl<M, R, S> Runtime<M, R, S>
@@ -26,109 +311,366 @@ where
     R: Send + 'static,
     M: Send + 'static,
 {
-    /// Creates a new Runtime for egui, allowing you to define how you react to events
-    /// in the form of returning a struct, which will then get sent back to your egui thread
-    pub fn new<'a, F, T>(
+    /// Creates a new Runtime, allowing you to define how you react to events
+    /// in the form of returning a struct, which will then get sent back to your UI thread.
+    /// `wake` is called after each of them, so the UI knows to check [`Runtime::try_recv`]
+    pub fn new<'a, F, T, W>(
         thread_count: usize,
         state: &'static S,
-        ctx: eframe::egui::Context,
+        wake: W,
         event_loop: F,
         rt: tokio::runtime::Runtime,
     ) -> Runtime<M, R, S>
     where
         F: Fn(M, &'a S) -> T + Clone + Send + Sync + 'static,
         T: std::future::Future<Output = R> + Send + 'a,
+        W: Fn() + Send + Sync + 'static,
     {
-        let (tx, rx_thread) = async_channel::unbounded();
-        let (tx_thread, rx) = async_channel::unbounded();
+        Self::with_bounds(thread_count, state, wake, event_loop, rt, None)
+    }
+
+    /// Like [`Runtime::new`], with channels that hold at most `bounds.capacity` entries
+    pub fn new_bounded<'a, F, T, W>(
+        thread_count: usize,
+        state: &'static S,
+        wake: W,
+        event_loop: F,
+        rt: tokio::runtime::Runtime,
+        bounds: Bounds,
+    ) -> Runtime<M, R, S>
+    where
+        F: Fn(M, &'a S) -> T + Clone + Send + Sync + 'static,
+        T: std::future::Future<Output = R> + Send + 'a,
+        W: Fn() + Send + Sync + 'static,
+    {
+        Self::with_bounds(thread_count, state, wake, event_loop, rt, Some(bounds))
+    }
+
+    fn with_bounds<'a, F, T, W>(
+        thread_count: usize,
+        state: &'static S,
+        wake: W,
+        event_loop: F,
+        rt: tokio::runtime::Runtime,
+        bounds: Option<Bounds>,
+    ) -> Runtime<M, R, S>
+    where
+        F: Fn(M, &'a S) -> T + Clone + Send + Sync + 'static,
+        T: std::future::Future<Output = R> + Send + 'a,
+        W: Fn() + Send + Sync + 'static,
+    {
+        let wake: Arc<dyn Fn() + Send + Sync> = Arc::new(wake);
+        let channel = || match bounds {
+            Some(bounds) => async_channel::bounded(bounds.capacity),
+            None => async_channel::unbounded(),
+        };
+        let lanes: Lanes<(_, async_channel::Receiver<Task<M, R>>)> =
+            [channel(), channel(), channel()];
+        let tx = lanes.clone().map(|(tx, _)| tx);
+        let rx_thread = lanes.map(|(_, rx)| rx);
+        let (tx_thread, rx) = match bounds {
+            Some(bounds) => async_channel::bounded(bounds.capacity),
+            None => async_channel::unbounded(),
+        };
+        // An unbounded channel is never full, so the policy doesn't matter
+        let policy = bounds.map(|bounds| bounds.policy).unwrap_or_default();
+        let counters = Arc::new(Counters::default());
+        let (panic_tx, panics) = async_channel::unbounded();
+        let mut workers = Vec::with_capacity(thread_count);
 
         for _ in 0..thread_count {
             let (tx, rx) = (tx_thread.clone(), rx_thread.clone());
             let event_loop = event_loop.clone();
-            let ctx = ctx.clone();
+            let wake = wake.clone();
+            let counters = counters.clone();
+            let panic_tx = panic_tx.clone();
 
-            rt.spawn(async move {
-                let ctx = ctx;
-                loop {
-                    if let Ok(i_message) = rx.recv().await {
-                        match i_message {
+            let worker = rt.spawn(async move {
+                // Stops once the channel is closed and emptied by Runtime::shutdown
+                while let Some(task) = next_task(&rx).await {
+                    let Task {
+                        handle,
+                        registration,
+                        message,
+                    } = task;
+                    let run = async {
+                        match message {
                             InternalMessage::Message(message) => {
-                                tx.send(event_loop(message, state).await).await.unwrap();
-                            }
-                            InternalMessage::Callback(mut fut) => {
-                                let mut poll = futures::poll!(&mut fut);
-                                while poll.is_pending() {
-                                    poll = futures::poll!(&mut fut);
-                                }
-                            }
-                            InternalMessage::CallbackWithResponse(fut) => {
-                                tx.send(fut.await).await.unwrap();
-                            }
-                            InternalMessage::Future(future) => {
-                                tx.send(future.await).await.unwrap();
+                                Some(event_loop(message, state).await)
                             }
+                            InternalMessage::Future(future) => future.await,
                         }
+                    };
+                    let run = Abortable::new(run, registration);
+                    counters.active.fetch_add(1, Ordering::Relaxed);
+                    #[cfg(feature = "durations")]
+                    let started = std::time::Instant::now();
 
-                        ctx.request_repaint();
+                    // None if there's no response, or the task was aborted
+                    let response = match AssertUnwindSafe(run).catch_unwind().await {
+                        Ok(Ok(response)) => {
+                            counters.completed.fetch_add(1, Ordering::Relaxed);
+                            #[cfg(feature = "durations")]
+                            counters.record(handle.id, started.elapsed());
+                            response
+                        }
+                        Ok(Err(_aborted)) => None,
+                        Err(panic) => {
+                            let _ = panic_tx.try_send(Panicked::new(handle.id, &*panic));
+                            None
+                        }
+                    };
+                    counters.active.fetch_sub(1, Ordering::Relaxed);
+
+                    if let Some(response) = response {
+                        let response = (handle, response);
+                        let full = match policy {
+                            Backpressure::Block => {
+                                tx.send(response).await.unwrap();
+                                false
+                            }
+                            Backpressure::DropOldest => tx.force_send(response).unwrap().is_some(),
+                            Backpressure::Error => tx.try_send(response).is_err(),
+                        };
+                        if full {
+                            counters.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
                     }
+                    wake();
                 }
             });
+            workers.push(worker);
         }
 
         Runtime {
-            _rt: rt,
+            rt,
+            workers: Mutex::new(workers),
             tx,
             rx,
+            _responses: tx_thread,
+            panics,
             state,
+            next_id: AtomicU64::new(0),
+            wake,
+            policy,
+            counters,
         }
     }
 
-    pub fn send_with_message(&self, msg: M) {
-        self.tx
-            .send_blocking(InternalMessage::Message(msg))
-            .expect("There should be no way to close the channel on the other end here")
+    fn send(&self, message: InternalMessage<M, R>, priority: Priority) -> TaskHandle {
+        let (abort, registration) = AbortHandle::new_pair();
+        let handle = TaskHandle {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            abort,
+        };
+        let task = Task {
+            handle: handle.clone(),
+            registration,
+            message,
+        };
+        // The channel is only closed by Runtime::shutdown, work sent after it is refused
+        let tx = &self.tx[priority as usize];
+        let refused = match self.policy {
+            Backpressure::Block => tx.send_blocking(task).err().map(|e| e.into_inner()),
+            Backpressure::DropOldest => match tx.force_send(task) {
+                Ok(Some(oldest)) => {
+                    oldest.handle.abort();
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    None
+                }
+                Ok(None) => None,
+                Err(e) => Some(e.into_inner()),
+            },
+            Backpressure::Error => match tx.try_send(task) {
+                Ok(()) => None,
+                Err(TrySendError::Full(task)) => {
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    Some(task)
+                }
+                Err(TrySendError::Closed(task)) => Some(task),
+            },
+        };
+        if let Some(refused) = refused {
+            refused.handle.abort();
+        }
+        handle
     }
 
-    pub fn callback<F, Fut>(&self, callback: F)
+    /// Stops accepting work and waits for the workers to finish what was already sent, so
+    /// nothing is cut off halfway through writing a file. Whatever is still running at the
+    /// deadline is aborted. Returns whether everything finished in time. Responses that were
+    /// sent can still be received, after that [`Runtime::try_recv`] reports Empty.
+    /// It blocks, so it can't be called from inside the runtime
+    pub fn shutdown(&self, deadline: Duration) -> bool {
+        for lane in &self.tx {
+            lane.close();
+        }
+        let mut workers = std::mem::take(&mut *self.workers.lock().unwrap());
+
+        let finished = self.rt.block_on(async {
+            let all = futures::future::join_all(workers.iter_mut());
+            tokio::time::timeout(deadline, all).await.is_ok()
+        });
+        if !finished {
+            for worker in &workers {
+                worker.abort();
+            }
+        }
+        finished
+    }
+
+    /// A snapshot of the counters, for a diagnostics view
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            waiting_tasks: self.tx.iter().map(|lane| lane.len()).sum(),
+            waiting_responses: self.rx.len(),
+            active_tasks: self.counters.active.load(Ordering::Relaxed),
+            completed: self.counters.completed.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            #[cfg(feature = "durations")]
+            durations: self
+                .counters
+                .durations
+                .lock()
+                .unwrap()
+                .iter()
+                .copied()
+                .collect(),
+        }
+    }
+
+    pub fn send_with_message(&self, msg: M) -> TaskHandle {
+        self.send_with_priority(msg, Priority::Normal)
+    }
+
+    pub fn send_with_priority(&self, msg: M, priority: Priority) -> TaskHandle {
+        self.send(InternalMessage::Message(msg), priority)
+    }
+
+    pub fn callback<F, Fut>(&self, callback: F) -> TaskHandle
     where
         F: Fn(&S) -> Fut,
         Fut: std::future::Future<Output = ()> + Send + 'static,
     {
-        self.tx
-            .send_blocking(InternalMessage::Callback(Box::pin(callback(self.state))))
-            .expect("There should be no way to close the channel on the other end here")
+        let callback = callback(self.state);
+        let future: BoxedFuture<R> = Box::pin(async move {
+            callback.await;
+            None
+        });
+        self.send(InternalMessage::Future(future), Priority::Normal)
     }
 
-    pub fn callback_response<'a, F, Fut>(&self, callback: F)
+    pub fn callback_response<'a, F, Fut>(&self, callback: F) -> TaskHandle
     where
         F: Fn(&'a S) -> Fut,
         Fut: std::future::Future<Output = R> + Send + 'static,
     {
-        self.tx
-            .send_blocking(InternalMessage::CallbackWithResponse(Box::pin(callback(
-                self.state,
-            ))))
-            .expect("There should be no way to close the channel on the other end here")
+        self.future(callback(self.state))
+    }
+
+    pub fn future<Fut>(&self, future: Fut) -> TaskHandle
+    where
+        Fut: std::future::Future<Output = R> + Send + 'static,
+    {
+        self.future_with_priority(future, Priority::Normal)
     }
 
-    pub fn future<Fut>(&self, future: Fut)
+    pub fn future_with_priority<Fut>(&self, future: Fut, priority: Priority) -> TaskHandle
     where
         Fut: std::future::Future<Output = R> + Send + 'static,
     {
-        self.tx
-            .send_blocking(InternalMessage::Future(Box::pin(future)))
-            .expect("There should be no way to close the channel on the other end here");
+        let future: BoxedFuture<R> = Box::pin(async move { Some(future.await) });
+        self.send(InternalMessage::Future(future), priority)
     }
 
+    /// Like [`Runtime::future`], `future` is called again for each attempt. Only running out
+    /// of time is retried, the response is whatever `on_timeout` makes of the last timeout
+    pub fn future_with_timeout<F, Fut, T>(
+        &self,
+        timeout: Timeout,
+        future: F,
+        on_timeout: T,
+    ) -> TaskHandle
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = R> + Send + 'static,
+        T: FnOnce(TimedOut) -> R + Send + 'static,
+    {
+        self.future(async move {
+            let mut backoff = timeout.backoff;
+            for attempt in 0..=timeout.retries {
+                if attempt > 0 {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                if let Ok(response) = tokio::time::timeout(timeout.limit, future()).await {
+                    return response;
+                }
+            }
+
+            on_timeout(TimedOut {
+                attempts: timeout.retries + 1,
+                limit: timeout.limit,
+            })
+        })
+    }
+
+    /// Like [`Runtime::future`], the future gets a sender for updates the UI can read
+    /// from the returned handle while it runs
+    pub fn spawn_with_progress<P, F, Fut>(&self, spawn: F) -> ProgressHandle<P>
+    where
+        P: Send + 'static,
+        F: FnOnce(ProgressSender<P>) -> Fut,
+        Fut: std::future::Future<Output = R> + Send + 'static,
+    {
+        let (tx, rx) = async_channel::unbounded();
+        let sender = ProgressSender {
+            tx,
+            wake: self.wake.clone(),
+        };
+        ProgressHandle {
+            task: self.future(spawn(sender)),
+            rx,
+            latest: RefCell::new(None),
+        }
+    }
+
+    /// The next response of a task that wasn't aborted
     pub fn try_recv(&self) -> Result<R, TryRecvError> {
-        self.rx.try_recv()
+        self.try_recv_with_handle().map(|(_, response)| response)
+    }
+
+    /// The next task that panicked, they don't send a response
+    pub fn try_recv_panic(&self) -> Option<Panicked> {
+        self.panics.try_recv().ok()
+    }
+
+    /// Like [`Runtime::try_recv`], with the handle of the task the response is from
+    pub fn try_recv_with_handle(&self) -> Result<(TaskHandle, R), TryRecvError> {
+        loop {
+            let (handle, response) = self.rx.try_recv()?;
+            // Aborted after it finished, but before the response was read
+            if !handle.is_aborted() {
+                return Ok((handle, response));
+            }
+        }
     }
 }
 
+/// Requests a repaint, so egui polls the runtime again
+#[cfg(feature = "egui")]
+pub fn egui_wake(ctx: eframe::egui::Context) -> impl Fn() + Send + Sync + 'static {
+    move || ctx.request_repaint()
+}
+
 #[cfg(test)]
 mod test {
+    use super::{
+        Backpressure, Bounds, Metrics, Priority, Runtime, TimedOut, Timeout, TryRecvError,
+    };
     use futures::poll;
     use std::pin::pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::task::Poll;
     use std::time::Duration;
 
@@ -154,6 +696,257 @@ mod test {
         println!("{:?}", runtime.block_on(future));
     }
 
+    #[test]
+    fn wake_test() {
+        static WAKES: AtomicUsize = AtomicUsize::new(0);
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let wake = || {
+            WAKES.fetch_add(1, Ordering::SeqCst);
+        };
+        let runtime: Runtime<u32, u32, ()> =
+            Runtime::new(1, &(), wake, |message, _| async move { message * 2 }, rt);
+
+        runtime.send_with_message(21);
+        // The response is sent before the wake
+        while WAKES.load(Ordering::SeqCst) == 0 {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(runtime.try_recv(), Ok(42));
+    }
+
+    #[test]
+    fn abort_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime: Runtime<(), u32, ()> = Runtime::new(2, &(), || (), |_, _| async { 0 }, rt);
+
+        let slow = runtime.future(async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            1
+        });
+        let fast = runtime.future(async { 2 });
+        assert_ne!(slow.id(), fast.id());
+
+        slow.abort();
+        // The fast response is already sent, but is dropped once it's aborted too
+        std::thread::sleep(Duration::from_millis(100));
+        fast.abort();
+        assert_eq!(runtime.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn progress_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime: Runtime<(), u32, ()> = Runtime::new(1, &(), || (), |_, _| async { 0 }, rt);
+
+        let handle = runtime.spawn_with_progress(|progress| async move {
+            progress.send(50);
+            progress.send(100);
+            3
+        });
+        let response = loop {
+            if let Ok(response) = runtime.try_recv() {
+                break response;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(response, 3);
+        // Only the newest update is kept, and read again until there's another
+        assert_eq!(handle.latest(), Some(100));
+        assert_eq!(handle.latest(), Some(100));
+    }
+
+    #[test]
+    fn bounds_test() {
+        // Three responses into a channel with room for one, nothing reads them in between
+        fn fill(policy: Backpressure) -> (Metrics, u32) {
+            let wakes: &'static AtomicUsize = Box::leak(Box::new(AtomicUsize::new(0)));
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let wake = || {
+                wakes.fetch_add(1, Ordering::SeqCst);
+            };
+            let bounds = Bounds {
+                capacity: 1,
+                policy,
+            };
+            let runtime: Runtime<(), u32, ()> =
+                Runtime::new_bounded(1, &(), wake, |_, _| async { 0 }, rt, bounds);
+
+            for response in 1..=3 {
+                runtime.future(async move { response });
+                // Waits for each one, so the work itself is never dropped
+                while wakes.load(Ordering::SeqCst) < response as usize {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            }
+            (runtime.metrics(), runtime.try_recv().unwrap())
+        }
+
+        let (metrics, response) = fill(Backpressure::DropOldest);
+        assert_eq!((metrics.waiting_responses, metrics.dropped), (1, 2));
+        assert_eq!(response, 3);
+
+        let (metrics, response) = fill(Backpressure::Error);
+        assert_eq!((metrics.waiting_responses, metrics.dropped), (1, 2));
+        assert_eq!(response, 1);
+    }
+
+    #[test]
+    fn timeout_test() {
+        #[derive(Debug, PartialEq)]
+        enum Response {
+            Value(u32),
+            TimedOut(TimedOut),
+        }
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime: Runtime<(), Response, ()> =
+            Runtime::new(1, &(), || (), |_, _| async { Response::Value(0) }, rt);
+        let recv = || loop {
+            if let Ok(response) = runtime.try_recv() {
+                break response;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        let timeout =
+            Timeout::new(Duration::from_millis(50)).with_retries(2, Duration::from_millis(10));
+        runtime.future_with_timeout(
+            timeout,
+            || async {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Response::Value(1)
+            },
+            Response::TimedOut,
+        );
+        let timed_out = TimedOut {
+            attempts: 3,
+            limit: Duration::from_millis(50),
+        };
+        assert_eq!(recv(), Response::TimedOut(timed_out));
+
+        // Only the first attempt is slow
+        let attempts: &'static AtomicUsize = Box::leak(Box::new(AtomicUsize::new(0)));
+        runtime.future_with_timeout(
+            timeout,
+            move || async move {
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                }
+                Response::Value(2)
+            },
+            Response::TimedOut,
+        );
+        assert_eq!(recv(), Response::Value(2));
+    }
+
+    #[test]
+    fn shutdown_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime: Runtime<(), u32, ()> = Runtime::new(2, &(), || (), |_, _| async { 0 }, rt);
+
+        runtime.future(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            1
+        });
+        assert!(runtime.shutdown(Duration::from_secs(5)));
+        assert_eq!(runtime.try_recv(), Ok(1));
+        assert_eq!(runtime.try_recv(), Err(TryRecvError::Empty));
+        // Work sent afterwards is refused
+        assert!(runtime.future(async { 2 }).is_aborted());
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime: Runtime<(), u32, ()> = Runtime::new(1, &(), || (), |_, _| async { 0 }, rt);
+        runtime.future(async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            1
+        });
+        assert!(!runtime.shutdown(Duration::from_millis(50)));
+        assert_eq!(runtime.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn panic_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime: Runtime<(), u32, ()> = Runtime::new(1, &(), || (), |_, _| async { 0 }, rt);
+
+        let handle = runtime.future(async { panic!("broken") });
+        // The only worker is still there afterwards
+        runtime.future(async { 1 });
+        let response = loop {
+            if let Ok(response) = runtime.try_recv() {
+                break response;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(response, 1);
+
+        let panicked = runtime.try_recv_panic().unwrap();
+        assert_eq!(panicked.id, handle.id());
+        assert_eq!(panicked.message, "broken");
+    }
+
+    #[test]
+    fn priority_test() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime: Runtime<(), u32, ()> = Runtime::new(1, &(), || (), |_, _| async { 0 }, rt);
+        let recv = || loop {
+            if let Ok(response) = runtime.try_recv() {
+                break response;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        // Keeps the only worker busy while the rest is queued
+        runtime.future(async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            0
+        });
+        std::thread::sleep(Duration::from_millis(20));
+        runtime.future_with_priority(async { 1 }, Priority::Low);
+        runtime.future(async { 2 });
+        runtime.future_with_priority(async { 3 }, Priority::High);
+
+        let order: Vec<_> = (0..4).map(|_| recv()).collect();
+        assert_eq!(order, [0, 3, 2, 1]);
+    }
+
+    #[test]
+    fn metrics_test() {
+        let wakes: &'static AtomicUsize = Box::leak(Box::new(AtomicUsize::new(0)));
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let wake = || {
+            wakes.fetch_add(1, Ordering::SeqCst);
+        };
+        let runtime: Runtime<(), u32, ()> = Runtime::new(1, &(), wake, |_, _| async { 0 }, rt);
+
+        let slow = runtime.future(async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            1
+        });
+        runtime.future(async { 2 });
+        std::thread::sleep(Duration::from_millis(20));
+        let metrics = runtime.metrics();
+        assert_eq!((metrics.active_tasks, metrics.waiting_tasks), (1, 1));
+
+        // Wakes after each task, once its counters are updated
+        while wakes.load(Ordering::SeqCst) < 2 {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let metrics = runtime.metrics();
+        assert_eq!((metrics.active_tasks, metrics.waiting_tasks), (0, 0));
+        assert_eq!((metrics.completed, metrics.waiting_responses), (2, 2));
+        assert!(!slow.is_aborted());
+
+        #[cfg(feature = "durations")]
+        {
+            let slowest = metrics.durations.iter().max_by_key(|d| d.took).unwrap();
+            assert_eq!(metrics.durations.len(), 2);
+            assert_eq!(slowest.id, slow.id());
+            assert!(slowest.took >= Duration::from_millis(100));
+        }
+    }
+
     #[test]
     fn cancel_test() {
         let (abort_handle, abort_registration) = futures::future::AbortHandle::new_pair();
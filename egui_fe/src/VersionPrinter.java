@@ -1 +0,0 @@
-public class VersionPrinter { public static void main(String[] args) {System.out.print(System.getProperty("java.version") + '\n' + System.getProperty("java.vendor"));}}
\ No newline at end of file
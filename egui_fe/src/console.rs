@@ -0,0 +1,245 @@
+use crate::i18n::tr;
+use eframe::egui::{self, Color32, RichText, TextStyle, ViewportClass};
+use launcher_app::Hooks;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, ExitStatus};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Severity {
+    /// Matches the `[12:00:00] [Render thread/WARN]: ...` format Minecraft logs with
+    fn parse(line: &str, stderr: bool) -> Self {
+        if line.contains("/ERROR]") || line.contains("/FATAL]") {
+            Severity::Error
+        } else if line.contains("/WARN]") {
+            Severity::Warn
+        } else if line.contains("/DEBUG]") || line.contains("/TRACE]") {
+            Severity::Debug
+        } else if line.contains("/INFO]") || !stderr {
+            Severity::Info
+        } else {
+            // Stack traces and JVM errors come without a level
+            Severity::Error
+        }
+    }
+
+    fn color(self) -> Color32 {
+        match self {
+            Severity::Error => Color32::LIGHT_RED,
+            Severity::Warn => Color32::GOLD,
+            Severity::Info => Color32::GRAY,
+            Severity::Debug => Color32::DARK_GRAY,
+        }
+    }
+}
+
+pub struct LogLine {
+    pub text: String,
+    pub severity: Severity,
+}
+
+/// Output of a running game, read on background threads so the UI never blocks on the pipes
+pub struct Console {
+    title: String,
+    // Index of the instance that launched the game, None for quick play
+    pub instance: Option<usize>,
+    // Crash reports older than this belong to an earlier launch
+    pub started: SystemTime,
+    child: Child,
+    status: Option<ExitStatus>,
+    // Stopped from the launcher, so a non-zero exit isn't a crash
+    stopped: bool,
+    lines: Arc<Mutex<Vec<LogLine>>>,
+    search: String,
+    pub open: bool,
+    // Shown in its own OS window instead of inside the launcher
+    detached: bool,
+    // The post-exit one runs when the game exits
+    hooks: Hooks,
+}
+
+impl Console {
+    /// Expects a child spawned with piped stdout and stderr
+    pub fn new(title: String, mut child: Child, instance: Option<usize>, hooks: Hooks) -> Self {
+        let lines = Arc::new(Mutex::new(vec![]));
+
+        if let Some(stdout) = child.stdout.take() {
+            read_lines(stdout, lines.clone(), false);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            read_lines(stderr, lines.clone(), true);
+        }
+
+        Self {
+            title,
+            instance,
+            started: SystemTime::now(),
+            child,
+            status: None,
+            stopped: false,
+            lines,
+            search: String::new(),
+            open: true,
+            detached: false,
+            hooks,
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        self.status.is_none()
+    }
+
+    pub fn kill(&mut self) -> std::io::Result<()> {
+        if self.running() {
+            self.child.kill()?;
+            self.stopped = true;
+        }
+        Ok(())
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stopped
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    /// The last `count` lines, for when the game dies without writing a crash report
+    pub fn tail(&self, count: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap();
+        let start = lines.len().saturating_sub(count);
+        lines[start..]
+            .iter()
+            .map(|line| line.text.clone())
+            .collect()
+    }
+
+    /// Also checks whether the game exited, so it has to be called every frame even when closed.
+    /// Returns the exit status on the frame the game exits
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<ExitStatus> {
+        let mut exited = None;
+        if self.status.is_none() {
+            self.status = self.child.try_wait().ok().flatten();
+            exited = self.status;
+            if exited.is_some() {
+                self.hooks.run_post_exit();
+            }
+            // The reader threads have no way to wake the UI, so poll while the game runs
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        if !self.open {
+            return exited;
+        }
+
+        let title = match self.status {
+            Some(status) => format!("{} ({status})", self.title),
+            None => self.title.clone(),
+        };
+
+        if !self.detached {
+            self.window(ctx, title);
+            return exited;
+        }
+
+        let builder = egui::ViewportBuilder::default()
+            .with_title(&title)
+            .with_inner_size([600.0, 400.0]);
+        let viewport = egui::ViewportId::from_hash_of(("Console", self.child.id()));
+
+        ctx.show_viewport_immediate(viewport, builder, |ctx, class| {
+            // Backends without multiple windows draw the viewport inside the main one
+            if class == ViewportClass::Embedded {
+                self.window(ctx, title);
+                return;
+            }
+
+            egui::CentralPanel::default().show(ctx, |ui| self.contents(ui));
+
+            // Closing the OS window puts the console back into the launcher
+            if ctx.input(|i| i.viewport().close_requested()) {
+                self.detached = false;
+            }
+        });
+
+        exited
+    }
+
+    fn window(&mut self, ctx: &egui::Context, title: String) {
+        let mut open = self.open;
+        egui::Window::new(title)
+            .id(egui::Id::new(("Console", self.child.id())))
+            .default_size([600.0, 400.0])
+            .open(&mut open)
+            .show(ctx, |ui| self.contents(ui));
+        self.open &= open;
+    }
+
+    fn contents(&mut self, ui: &mut egui::Ui) {
+        let lines = self.lines.lock().unwrap();
+        let search = self.search.to_lowercase();
+        let matches =
+            |line: &&LogLine| search.is_empty() || line.text.to_lowercase().contains(&search);
+
+        ui.horizontal(|ui| {
+            ui.label(tr("console.search"));
+            ui.text_edit_singleline(&mut self.search);
+
+            if ui.button(tr("console.copy")).clicked() {
+                let text = lines
+                    .iter()
+                    .filter(matches)
+                    .map(|line| line.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.ctx().copy_text(text);
+            }
+
+            let detach = if self.detached {
+                tr("viewport.attach")
+            } else {
+                tr("viewport.detach")
+            };
+            if ui.button(detach).clicked() {
+                self.detached = !self.detached;
+            }
+        });
+
+        ui.separator();
+
+        egui::ScrollArea::both()
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in lines.iter().filter(matches) {
+                    let text = RichText::new(&line.text)
+                        .text_style(TextStyle::Monospace)
+                        .color(line.severity.color());
+                    ui.label(text);
+                }
+            });
+    }
+}
+
+fn read_lines<R: Read + Send + 'static>(pipe: R, lines: Arc<Mutex<Vec<LogLine>>>, stderr: bool) {
+    std::thread::spawn(move || {
+        // Ends when the game exits and closes the pipe
+        for text in BufReader::new(pipe).lines().map_while(Result::ok) {
+            let severity = Severity::parse(&text, stderr);
+            lines.lock().unwrap().push(LogLine { text, severity });
+        }
+    });
+}
@@ -0,0 +1,107 @@
+use crate::console::Console;
+use crate::i18n::{tr, tr_args};
+use eframe::egui::{self, Color32, RichText, TextStyle};
+use launcher_core::crash::{find_crash_report, CrashReport};
+use std::path::Path;
+use std::process::ExitStatus;
+
+/// How many output lines are shown when there is no crash report
+const TAIL_LINES: usize = 20;
+
+/// Shown when a game exits with a non-zero code
+pub struct CrashDialog {
+    title: String,
+    status: ExitStatus,
+    report: Option<CrashReport>,
+    // Output of the game, used when it died without a report, e.g. a JVM crash
+    tail: Vec<String>,
+    pub open: bool,
+}
+
+impl CrashDialog {
+    pub fn new(status: ExitStatus, game_dir: &Path, console: &Console) -> std::io::Result<Self> {
+        let report = match find_crash_report(game_dir, console.started)? {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path)?;
+                Some(CrashReport::parse(path, &text))
+            }
+            None => None,
+        };
+
+        let tail = if report.is_none() {
+            console.tail(TAIL_LINES)
+        } else {
+            vec![]
+        };
+
+        Ok(Self {
+            title: console.title().to_string(),
+            status,
+            report,
+            tail,
+            open: true,
+        })
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        egui::Window::new(tr_args("crash.title", &[("name", &self.title)]))
+            .id(egui::Id::new(("Crash", &self.title)))
+            .default_width(500.0)
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.label(tr_args("crash.exit_status", &[("status", &self.status)]));
+
+                let Some(report) = &self.report else {
+                    ui.label(tr("crash.no_report"));
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for line in &self.tail {
+                            ui.label(RichText::new(line).text_style(TextStyle::Monospace));
+                        }
+                    });
+                    return;
+                };
+
+                egui::Grid::new("Crash Grid").num_columns(2).show(ui, |ui| {
+                    if let Some(description) = &report.description {
+                        ui.label(tr("crash.description"));
+                        ui.label(description);
+                        ui.end_row();
+                    }
+
+                    if !report.suspected_mods.is_empty() {
+                        ui.label(tr("crash.suspected_mods"));
+                        ui.vertical(|ui| {
+                            for name in &report.suspected_mods {
+                                ui.colored_label(Color32::GOLD, name);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+
+                if let Some(exception) = &report.exception {
+                    ui.separator();
+                    let text = RichText::new(exception)
+                        .text_style(TextStyle::Monospace)
+                        .color(Color32::LIGHT_RED);
+                    ui.label(text);
+                    for frame in &report.stack {
+                        ui.label(RichText::new(frame).text_style(TextStyle::Monospace));
+                    }
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button(tr("crash.open_report")).clicked() {
+                        let url = format!("file://{}", report.path.display());
+                        ctx.open_url(egui::OpenUrl::new_tab(url));
+                    }
+
+                    if ui.button(tr("crash.copy_path")).clicked() {
+                        ctx.copy_text(report.path.display().to_string());
+                    }
+                });
+            });
+    }
+}
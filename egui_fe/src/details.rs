@@ -0,0 +1,273 @@
+use crate::i18n::{tr, tr_args};
+use crate::instances::Instance;
+use crate::{format_bytes, format_duration};
+use eframe::egui::{self, Button, RichText};
+use launcher_core::archives::{self, Archive};
+use launcher_core::resource_packs::{self, ResourcePack};
+use launcher_core::saves::{self, Backup, World};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Tab {
+    Worlds,
+    ResourcePacks,
+    // Backups of the whole instance
+    Archives,
+}
+
+/// The worlds, resource packs and backups of one instance
+pub struct InstanceDetails {
+    pub instance: usize,
+    pub tab: Tab,
+    pub saves_dir: PathBuf,
+    pub backups_dir: PathBuf,
+    packs_dir: PathBuf,
+    pub directory: PathBuf,
+    archives_dir: PathBuf,
+    worlds: Vec<World>,
+    backups: Vec<Backup>,
+    pub packs: Vec<ResourcePack>,
+    archives: Vec<Archive>,
+    // World folders being backed up, and world and instance backups being restored
+    pub busy: HashSet<PathBuf>,
+    // Restore was clicked once, the second click replaces the world
+    confirm_restore: Option<PathBuf>,
+    pub open: bool,
+}
+
+pub enum DetailsAction {
+    // The world's folder
+    Backup(PathBuf),
+    Restore(PathBuf),
+    // Index into the packs
+    SetEnabled(usize, bool),
+    Remove(usize),
+    // Backs up the whole instance
+    Archive,
+    RestoreArchive(PathBuf),
+    Refresh,
+}
+
+impl InstanceDetails {
+    pub fn new(index: usize, instance: &Instance, launcher_path: &Path) -> Self {
+        Self {
+            instance: index,
+            tab: Tab::Worlds,
+            saves_dir: instance.saves_dir(launcher_path),
+            backups_dir: instance.backups_dir(launcher_path),
+            packs_dir: instance.resource_packs_dir(launcher_path),
+            directory: instance.directory(launcher_path),
+            archives_dir: instance.archives_dir(launcher_path),
+            worlds: Vec::new(),
+            backups: Vec::new(),
+            packs: Vec::new(),
+            archives: Vec::new(),
+            busy: HashSet::new(),
+            confirm_restore: None,
+            open: true,
+        }
+    }
+
+    /// Reads the folders again, the game changes them while it runs
+    pub fn refresh(&mut self) -> Result<(), launcher_core::Error> {
+        self.worlds = saves::list_worlds(&self.saves_dir)?;
+        self.backups = saves::list_backups(&self.backups_dir)?;
+        self.packs = resource_packs::list_packs(&self.packs_dir)?;
+        self.archives = archives::list_archives(&self.archives_dir)?;
+        Ok(())
+    }
+
+    /// `running` disables restoring, the game would overwrite the restored files
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        instance: &Instance,
+        running: bool,
+    ) -> Option<DetailsAction> {
+        let mut action = None;
+
+        egui::Window::new(tr_args("details.title", &[("name", &instance.name)]))
+            .id(egui::Id::new("Instance Details"))
+            .default_size([450.0, 400.0])
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, Tab::Worlds, tr("details.worlds"));
+                    ui.selectable_value(
+                        &mut self.tab,
+                        Tab::ResourcePacks,
+                        tr("details.resource_packs"),
+                    );
+                    ui.selectable_value(&mut self.tab, Tab::Archives, tr("details.archives"));
+                    if ui.button(tr("details.refresh")).clicked() {
+                        action = Some(DetailsAction::Refresh);
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink(false)
+                    .show(ui, |ui| match self.tab {
+                        Tab::Worlds => {
+                            worlds(
+                                ui,
+                                &self.worlds,
+                                &self.backups,
+                                &self.busy,
+                                &mut self.confirm_restore,
+                                running,
+                                &mut action,
+                            );
+                        }
+                        Tab::ResourcePacks => packs(ui, &self.packs, &mut action),
+                        Tab::Archives => {
+                            instance_archives(
+                                ui,
+                                &self.archives,
+                                &self.busy,
+                                &mut self.confirm_restore,
+                                running,
+                                &mut action,
+                            );
+                        }
+                    });
+            });
+
+        action
+    }
+}
+
+fn worlds(
+    ui: &mut egui::Ui,
+    worlds: &[World],
+    backups: &[Backup],
+    busy: &HashSet<PathBuf>,
+    confirm_restore: &mut Option<PathBuf>,
+    running: bool,
+    action: &mut Option<DetailsAction>,
+) {
+    if worlds.is_empty() {
+        ui.label(tr("details.no_worlds"));
+    }
+
+    for world in worlds {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&world.name).strong());
+            if let Some(played) = world.last_played {
+                ui.weak(tr_args("details.played", &[("time", &ago(played))]));
+            }
+            if busy.contains(&world.directory) {
+                ui.spinner();
+            } else if ui.button(tr("details.backup")).clicked() {
+                *action = Some(DetailsAction::Backup(world.directory.clone()));
+            }
+        });
+    }
+
+    ui.separator();
+    ui.heading(tr("details.backups"));
+    if backups.is_empty() {
+        ui.label(tr("details.no_backups"));
+    }
+
+    for backup in backups {
+        ui.horizontal(|ui| {
+            ui.label(&backup.world);
+            ui.weak(tr_args(
+                "details.created",
+                &[("time", &ago(backup.created))],
+            ));
+
+            if busy.contains(&backup.path) {
+                ui.spinner();
+                return;
+            }
+            let confirming = confirm_restore.as_ref() == Some(&backup.path);
+            let text = if confirming {
+                tr("details.confirm_restore")
+            } else {
+                tr("details.restore")
+            };
+            if ui.add_enabled(!running, Button::new(text)).clicked() {
+                if confirming {
+                    *confirm_restore = None;
+                    *action = Some(DetailsAction::Restore(backup.path.clone()));
+                } else {
+                    *confirm_restore = Some(backup.path.clone());
+                }
+            }
+        });
+    }
+}
+
+fn packs(ui: &mut egui::Ui, packs: &[ResourcePack], action: &mut Option<DetailsAction>) {
+    if packs.is_empty() {
+        ui.label(tr("details.no_packs"));
+    }
+
+    for (idx, pack) in packs.iter().enumerate() {
+        ui.horizontal(|ui| {
+            let mut enabled = pack.enabled;
+            if ui.checkbox(&mut enabled, &pack.name).changed() {
+                *action = Some(DetailsAction::SetEnabled(idx, enabled));
+            }
+            if ui.small_button(tr("common.remove")).clicked() {
+                *action = Some(DetailsAction::Remove(idx));
+            }
+        });
+    }
+}
+
+fn instance_archives(
+    ui: &mut egui::Ui,
+    archives: &[Archive],
+    busy: &HashSet<PathBuf>,
+    confirm_restore: &mut Option<PathBuf>,
+    running: bool,
+    action: &mut Option<DetailsAction>,
+) {
+    if ui.button(tr("details.archive")).clicked() {
+        *action = Some(DetailsAction::Archive);
+    }
+    ui.separator();
+
+    if archives.is_empty() {
+        ui.label(tr("details.no_archives"));
+    }
+
+    for archive in archives {
+        ui.horizontal(|ui| {
+            ui.label(tr_args(
+                "details.created",
+                &[("time", &ago(archive.created))],
+            ));
+            ui.weak(format_bytes(archive.size as f64));
+
+            if busy.contains(&archive.path) {
+                ui.spinner();
+                return;
+            }
+            let confirming = confirm_restore.as_ref() == Some(&archive.path);
+            let text = if confirming {
+                tr("details.confirm_restore_archive")
+            } else {
+                tr("details.restore")
+            };
+            if ui.add_enabled(!running, Button::new(text)).clicked() {
+                if confirming {
+                    *confirm_restore = None;
+                    *action = Some(DetailsAction::RestoreArchive(archive.path.clone()));
+                } else {
+                    *confirm_restore = Some(archive.path.clone());
+                }
+            }
+        });
+    }
+}
+
+fn ago(time: SystemTime) -> String {
+    let elapsed = time.elapsed().unwrap_or_default().as_secs();
+    format_duration(elapsed)
+}
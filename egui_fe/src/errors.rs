@@ -0,0 +1,102 @@
+use crate::i18n::tr;
+use crate::Error;
+use eframe::egui;
+use launcher_core::loader::Loader;
+use launcher_core::types::Version;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// An error shown in the banner until it's dismissed or retried
+pub struct Failure {
+    pub error: Error,
+    // What to start again when Retry is clicked, None hides the button
+    pub retry: Option<Retry>,
+}
+
+/// The operation a failure came from
+pub enum Retry {
+    // The version manifest
+    Versions,
+    // The json of the version picked for quick play
+    Version(Arc<Version>),
+    // Whatever is missing for the download queue entry with the id
+    Prepare(u64),
+    Login,
+    // The last query typed into the mod browser
+    ModSearch,
+    ImportPack(Arc<PathBuf>),
+    InstallJava {
+        component: String,
+        major: u32,
+        for_instance: bool,
+    },
+    News,
+    // The loader versions listed in the instance editor
+    LoaderVersions,
+    InstallLoader {
+        loader: Loader,
+        game_version: String,
+        loader_version: String,
+    },
+}
+
+impl Failure {
+    pub fn retry(error: impl Into<Error>, retry: Retry) -> Self {
+        Self {
+            error: error.into(),
+            retry: Some(retry),
+        }
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Self { error, retry: None }
+    }
+}
+
+impl From<launcher_core::Error> for Failure {
+    fn from(error: launcher_core::Error) -> Self {
+        Error::from(error).into()
+    }
+}
+
+impl From<tokio::io::Error> for Failure {
+    fn from(error: tokio::io::Error) -> Self {
+        Error::from(error).into()
+    }
+}
+
+/// Lists every failure across the top of the window, returns the operation to retry if
+/// one was clicked. Dismissed and retried failures are removed
+pub fn banner(ctx: &egui::Context, failures: &mut Vec<Failure>) -> Option<Retry> {
+    if failures.is_empty() {
+        return None;
+    }
+
+    let mut retried = None;
+    let mut dismissed = None;
+
+    egui::TopBottomPanel::top("Error Banner").show(ctx, |ui| {
+        for (idx, failure) in failures.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button(tr("error.dismiss")).clicked() {
+                    dismissed = Some(idx);
+                }
+                if failure.retry.is_some() && ui.small_button(tr("error.retry")).clicked() {
+                    retried = Some(idx);
+                }
+                ui.colored_label(ui.visuals().error_fg_color, failure.error.to_string());
+            });
+        }
+    });
+
+    if let Some(idx) = retried {
+        return failures.remove(idx).retry;
+    }
+    if let Some(idx) = dismissed {
+        failures.remove(idx);
+    }
+
+    None
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// Index into `Language::ALL` of the language `tr` reads from
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static BUNDLES: [OnceLock<Bundle>; Language::ALL.len()] = [OnceLock::new(), OnceLock::new()];
+
+type Bundle = HashMap<&'static str, &'static str>;
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// The name of the language in itself, so it can be found without reading the current one
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    fn index(self) -> usize {
+        Language::ALL.iter().position(|l| *l == self).unwrap()
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../locales/en.lang"),
+            Language::German => include_str!("../locales/de.lang"),
+        }
+    }
+
+    fn bundle(self) -> &'static Bundle {
+        BUNDLES[self.index()].get_or_init(|| parse(self.source()))
+    }
+}
+
+/// Changes the language of every string read with `tr` afterwards
+pub fn set_language(language: Language) {
+    CURRENT.store(language.index(), Ordering::Relaxed);
+}
+
+/// Looks up a UI string, falling back to English, then to the key itself so a missing
+/// string shows up in the UI instead of an empty label
+pub fn tr(key: &'static str) -> &'static str {
+    let current = Language::ALL[CURRENT.load(Ordering::Relaxed)];
+    current
+        .bundle()
+        .get(key)
+        .or_else(|| Language::English.bundle().get(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+/// [`tr`] with every `{name}` in the string replaced by its value
+pub fn tr_args(key: &'static str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    let mut string = tr(key).to_string();
+    for (name, value) in args {
+        string = string.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    string
+}
+
+/// Bundles are `key = value` lines, `#` starts a comment
+fn parse(source: &'static str) -> Bundle {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim(), value.trim()))
+        })
+        .collect()
+}
@@ -0,0 +1,124 @@
+use crate::i18n::tr;
+use eframe::egui::{self, Button, Image, ImageButton, TextureOptions};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Icons that ship with the launcher, written to the icons folder when picked
+pub const BUILT_IN: [(&str, &[u8]); 8] = [
+    ("grass", include_bytes!("../icons/grass.png")),
+    ("stone", include_bytes!("../icons/stone.png")),
+    ("planks", include_bytes!("../icons/planks.png")),
+    ("diamond", include_bytes!("../icons/diamond.png")),
+    ("gold", include_bytes!("../icons/gold.png")),
+    ("tnt", include_bytes!("../icons/tnt.png")),
+    ("creeper", include_bytes!("../icons/creeper.png")),
+    ("water", include_bytes!("../icons/water.png")),
+];
+
+const ICON_SIZE: f32 = 48.0;
+
+/// Picks the icon of the instance being edited
+pub struct IconPicker {
+    // pack.png in the instance folder, modpacks often ship one
+    pack_png: Option<PathBuf>,
+    pub open: bool,
+}
+
+pub enum IconAction {
+    // Index into `BUILT_IN`
+    BuiltIn(usize),
+    File(PathBuf),
+    Clear,
+}
+
+impl IconPicker {
+    pub fn new(instance_dir: &Path) -> Self {
+        let pack_png = instance_dir.join("pack.png");
+        Self {
+            pack_png: pack_png.is_file().then_some(pack_png),
+            open: true,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<IconAction> {
+        let mut action = None;
+
+        egui::Window::new(tr("icons.title"))
+            .auto_sized()
+            .collapsible(false)
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                egui::Grid::new("Built In Icons").show(ui, |ui| {
+                    for (idx, (name, bytes)) in BUILT_IN.iter().enumerate() {
+                        let image = Image::from_bytes(format!("bytes://icons/{name}.png"), *bytes)
+                            .texture_options(TextureOptions::NEAREST)
+                            .fit_to_exact_size([ICON_SIZE, ICON_SIZE].into());
+                        if ui.add(ImageButton::new(image)).clicked() {
+                            action = Some(IconAction::BuiltIn(idx));
+                        }
+                        if idx % 4 == 3 {
+                            ui.end_row();
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button(tr("icons.file")).clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Image", &["png", "jpg", "jpeg"])
+                            .pick_file()
+                        {
+                            action = Some(IconAction::File(path));
+                        }
+                    }
+
+                    let button = Button::new(tr("icons.pack_png"));
+                    if ui.add_enabled(self.pack_png.is_some(), button).clicked() {
+                        action = self.pack_png.clone().map(IconAction::File);
+                    }
+
+                    if ui.button(tr("icons.clear")).clicked() {
+                        action = Some(IconAction::Clear);
+                    }
+                });
+            });
+
+        if action.is_some() {
+            self.open = false;
+        }
+
+        action
+    }
+}
+
+/// Copies an icon into the launcher's icons folder, so the instance keeps it when the
+/// original moves. Files are named after their contents, the same picture is stored once
+pub fn store(launcher_path: &Path, action: IconAction) -> std::io::Result<Option<PathBuf>> {
+    let (bytes, extension) = match action {
+        IconAction::BuiltIn(idx) => (BUILT_IN[idx].1.to_vec(), "png".to_string()),
+        IconAction::File(path) => {
+            let extension = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("png")
+                .to_ascii_lowercase();
+            (std::fs::read(path)?, extension)
+        }
+        IconAction::Clear => return Ok(None),
+    };
+
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let directory = launcher_path.join("icons");
+    let path = directory.join(format!("{:016x}.{extension}", hasher.finish()));
+
+    if !path.exists() {
+        std::fs::create_dir_all(&directory)?;
+        std::fs::write(&path, bytes)?;
+    }
+
+    Ok(Some(path))
+}
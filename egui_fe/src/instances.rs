@@ -1,31 +1,36 @@
+use launcher_app::instance::HistoryEntry;
+use launcher_core::loader::Loader;
 use launcher_core::types::Version;
-use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::rc::Rc;
 use std::sync::Arc;
 
-#[derive(Deserialize, Serialize)]
-pub struct Instance {
-    pub name: String,
-    pub image: Option<PathBuf>,
-    pub jvm: Rc<Jvm>,
-    pub version: Arc<Version>,
-    pub path: PathBuf,
-    pub mod_loader: Option<Loader>,
-    pub jvm_args: Vec<String>,
-    pub env_args: Vec<String>,
-}
+pub use launcher_app::{Hooks, InstalledMod, Instance, Jvm, Memory, Resolution};
 
 #[derive(Default)]
 pub struct InstanceBuilder {
     pub name: String,
     pub image: Option<String>,
-    pub jvm: Rc<Jvm>,
+    pub jvm: Arc<Jvm>,
     pub version: Option<Arc<Version>>,
     pub path: String,
     pub mod_loader: Option<Loader>,
+    pub loader_version: Option<String>,
+    pub track_latest_loader: bool,
     pub jvm_args: String,
     pub env_args: String,
+    // Empty for no group
+    pub group: String,
+    // Not editable, kept so editing an instance doesn't forget them
+    pub mods: Vec<InstalledMod>,
+    pub last_played: Option<u64>,
+    pub playtime: u64,
+    pub memory: Option<Memory>,
+    pub resolution: Option<Resolution>,
+    pub hooks: Hooks,
+    // Profile id, None launches with the selected account
+    pub account: Option<String>,
+    pub legacy_skin_fix: bool,
+    pub history: Vec<HistoryEntry>,
 }
 
 impl InstanceBuilder {
@@ -49,11 +54,11 @@ impl InstanceBuilder {
         &mut self.image
     }
 
-    pub fn jvm(&self) -> &Rc<Jvm> {
+    pub fn jvm(&self) -> &Arc<Jvm> {
         &self.jvm
     }
 
-    pub fn jvm_mut(&mut self) -> &mut Rc<Jvm> {
+    pub fn jvm_mut(&mut self) -> &mut Arc<Jvm> {
         &mut self.jvm
     }
 
@@ -97,6 +102,14 @@ impl InstanceBuilder {
         &mut self.env_args
     }
 
+    pub fn group(&self) -> &String {
+        &self.group
+    }
+
+    pub fn group_mut(&mut self) -> &mut String {
+        &mut self.group
+    }
+
     pub fn build(self) -> Instance {
         Instance {
             name: self.name,
@@ -105,28 +118,50 @@ impl InstanceBuilder {
             version: self.version.unwrap(),
             path: PathBuf::from(self.path),
             mod_loader: self.mod_loader,
+            loader_version: self.mod_loader.and(self.loader_version),
+            track_latest_loader: self.track_latest_loader,
             jvm_args: self.jvm_args.split(' ').map(String::from).collect(),
             env_args: self.env_args.split(' ').map(String::from).collect(),
+            mods: self.mods,
+            group: Some(self.group.trim().to_string()).filter(|group| !group.is_empty()),
+            last_played: self.last_played,
+            playtime: self.playtime,
+            memory: self.memory,
+            resolution: self.resolution,
+            hooks: self.hooks,
+            account: self.account,
+            legacy_skin_fix: self.legacy_skin_fix,
+            history: self.history,
         }
     }
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq)]
-pub enum Loader {
-    Fabric,
-}
-
-#[derive(Deserialize, Serialize)]
-pub struct Jvm {
-    pub path: String,
-    pub name: String,
-}
-
-impl Default for Jvm {
-    fn default() -> Self {
+impl From<&Instance> for InstanceBuilder {
+    fn from(value: &Instance) -> Self {
         Self {
-            path: "java".into(),
-            name: "Default".into(),
+            name: value.name.clone(),
+            image: value
+                .image
+                .as_ref()
+                .map(|image| image.to_string_lossy().to_string()),
+            jvm: value.jvm.clone(),
+            version: Some(value.version.clone()),
+            path: value.path.to_string_lossy().to_string(),
+            mod_loader: value.mod_loader,
+            loader_version: value.loader_version.clone(),
+            track_latest_loader: value.track_latest_loader,
+            jvm_args: value.jvm_args.join(" "),
+            env_args: value.env_args.join(" "),
+            mods: value.mods.clone(),
+            group: value.group.clone().unwrap_or_default(),
+            last_played: value.last_played,
+            playtime: value.playtime,
+            memory: value.memory,
+            resolution: value.resolution,
+            hooks: value.hooks.clone(),
+            account: value.account.clone(),
+            legacy_skin_fix: value.legacy_skin_fix,
+            history: value.history.clone(),
         }
     }
 }
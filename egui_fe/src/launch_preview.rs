@@ -0,0 +1,90 @@
+use crate::i18n::{tr, tr_args};
+use eframe::egui::{self, TextStyle};
+use launcher_core::account::types::Account;
+use std::process::Command;
+
+/// Shown in place of the access token, so the command can be shared
+const REDACTED: &str = "<redacted>";
+
+/// The command an instance would launch with, for debugging arguments without starting it
+pub struct LaunchPreview {
+    title: String,
+    command: String,
+    // `KEY=value`, only the variables the launcher sets
+    env: Vec<String>,
+    pub open: bool,
+}
+
+impl LaunchPreview {
+    pub fn new(title: String, command: &Command, account: &Account) -> Self {
+        let env = command
+            .get_envs()
+            .filter_map(|(key, value)| {
+                Some(format!(
+                    "{}={}",
+                    key.to_string_lossy(),
+                    value?.to_string_lossy()
+                ))
+            })
+            .collect();
+
+        Self {
+            title,
+            command: render_command(command, account),
+            env,
+            open: true,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        egui::Window::new(tr_args("preview.title", &[("name", &self.title)]))
+            .id(egui::Id::new("Launch Preview"))
+            .default_size([600.0, 400.0])
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                if ui.button(tr("preview.copy")).clicked() {
+                    ui.output_mut(|o| o.copied_text = self.command.clone());
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.label(egui::RichText::new(&self.command).text_style(TextStyle::Monospace));
+
+                    ui.separator();
+                    ui.strong(tr("preview.environment"));
+                    if self.env.is_empty() {
+                        ui.label(tr("preview.no_environment"));
+                    }
+                    for var in &self.env {
+                        ui.label(egui::RichText::new(var).text_style(TextStyle::Monospace));
+                    }
+                });
+            });
+    }
+}
+
+/// The command as one line a shell would run, with the access token redacted
+pub fn render_command(command: &Command, account: &Account) -> String {
+    let redact = |arg: String| {
+        // Offline accounts have a placeholder token, replacing it would mangle other args
+        if account.offline || account.access_token.is_empty() {
+            arg
+        } else {
+            arg.replace(&account.access_token, REDACTED)
+        }
+    };
+
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| quote(redact(arg.to_string_lossy().to_string())))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quotes args with spaces, e.g. paths, so the line can be pasted into a shell
+fn quote(arg: String) -> String {
+    if arg.is_empty() || arg.contains(char::is_whitespace) {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        arg
+    }
+}
@@ -1,41 +1,98 @@
+mod console;
+mod crash;
+mod details;
+mod errors;
+mod i18n;
+mod icons;
 mod instances;
+mod launch_preview;
+mod modpacks;
+mod mods;
+mod multimc;
+mod queue;
+mod settings;
+mod skins;
+mod toasts;
+mod update;
 mod worker_logic;
 mod wrappers;
 
-use std::cell::Cell;
-use std::fs::File;
-use std::io::Write;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use worker_logic::*;
 use wrappers::*;
 
-use std::path::PathBuf;
-use std::rc::Rc;
-use std::sync::atomic::AtomicU64;
-use std::sync::{atomic::Ordering, Arc};
-use std::time::SystemTime;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
+use async_bridge::{Priority, TaskHandle};
 use eframe::egui::panel::TopBottomSide::Bottom;
-use eframe::egui::style::Spacing;
 use eframe::egui::{
-    self, Align, Button, Color32, FontId, Frame, Image, Label, Layout, Margin, Pos2, Rect, Sense,
-    Stroke, Ui, Vec2, Vec2b,
+    self, Button, Color32, Frame, Image, Label, Margin, RichText, Sense, Stroke, TextureHandle,
+    TextureOptions, Ui,
 };
-use eframe::emath::RectTransform;
+use launcher_app::data::AccountError;
+use launcher_app::prepare::Part;
+use launcher_app::{data, jobs, Args, Hooks, Job, LaunchSettings, PendingJobs};
+use launcher_core::account::secrets::KeyringStore;
 use launcher_core::account::types::Account;
-use launcher_core::types::{Latest, Type, Version};
+use launcher_core::assets::GarbageReport;
+use launcher_core::http::{ClientOptions, Proxy};
+use launcher_core::java;
+use launcher_core::libraries::PruneReport;
+use launcher_core::loader::{Loader, LoaderProfile, LoaderVersion};
+use launcher_core::modpack::PackLoader;
+use launcher_core::multimc::MultiMcInstance;
+use launcher_core::news::PatchNotes;
+use launcher_core::progress::DownloadStats;
+use launcher_core::resource_packs;
+use launcher_core::skin_fix;
+use launcher_core::types::{FeatureFlags, Latest, Version};
 use launcher_core::{
-    types::{AssetIndexJson, VersionJson, VersionManifest},
+    types::{VersionJson, VersionManifest},
     AsyncLauncher,
 };
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+use console::Console;
+use crash::CrashDialog;
+use details::{DetailsAction, InstanceDetails, Tab};
+use errors::{Failure, Retry};
+use i18n::{tr, tr_args};
+use icons::{IconAction, IconPicker};
 use instances::*;
+use launch_preview::LaunchPreview;
+use modpacks::{ImportAction, PackImport};
+use mods::{ModAction, ModBrowser};
+use multimc::{CopyState, MultiMcAction, MultiMcImport};
+use queue::{DownloadQueue, QueueEntry};
+use settings::*;
+use skins::{AccountView, SkinAction};
+use toasts::Toasts;
+use update::{UpdateAction, UpdateBanner};
+
+// How many patch notes the news lists, older ones are rarely read
+const NEWS_ENTRIES: usize = 20;
+// Player heads are drawn at twice their 8 pixels
+const AVATAR_SIZE: f32 = 16.0;
+// What the launcher keeps in its own folder, left out when backing up instances that share it
+const SHARED_FILES: [&str; 11] = [
+    "assets",
+    "libraries",
+    "natives",
+    "runtimes",
+    "versions",
+    "instances",
+    "instance_backups",
+    "icons",
+    "avatars",
+    "launcher_data.toml",
+    "patch_notes.json",
+];
 
-// TODO: Store encrypted auth token for reuse: Use Keyring crate
 // TODO: Document existing UI functionality: In-Progress
-// TODO: Redo error handling, fields that can error should hold Result<T, E>
-// UPDATE: We could also add a tag to the error? Not sure. Constant Error checking would suck.
 struct LauncherGui {
     // Async thread pool to handle futures
     rt: async_bridge::Runtime<Message, Response, State>,
@@ -43,27 +100,107 @@ struct LauncherGui {
     rx: async_channel::Receiver<(String, String)>,
     // Reference to the async launcher
     launcher: Arc<AsyncLauncher>,
+    // Used for requests that don't go through the launcher, such as Modrinth
+    client: Client,
     // Minecraft Data
     data: MCData,
     // Data related to the player
     player: PlayerData,
+    // Where game files are stored, the config dir unless overridden in the settings
     launcher_path: Arc<PathBuf>,
+    // Where launcher_data.toml is stored
+    config_dir: PathBuf,
     // Current major java version
     java_version: u32,
     jvm_index: Option<usize>,
-    current_error: Option<Error>,
+    // Major versions of the JVMs that have been checked, keyed by path
+    java_majors: RefCell<HashMap<String, u32>>,
+    java_install: Option<JavaInstall>,
+    // Installed memory in MiB, bounds the memory sliders
+    system_memory: Option<u64>,
+    // Shown in the error banner, oldest first
+    errors: Vec<Failure>,
+    toasts: Toasts,
     // Path to JVM, if changed
     // Flipped once for startup tasks
     launcher_data: LauncherData,
+    // Refresh tokens, keyed by profile id
+    secrets: KeyringStore,
+    // The proxy and certificates the client was built with, changes apply after a restart
+    proxy: Proxy,
+    certificates: Vec<PathBuf>,
+    proxy_password: String,
     // Holds the position of the dots in the loading message
     loading_place: SystemTime,
     data_updated: bool,
+    settings_open: bool,
+    // The last dry run of the asset collection, and whether one is running
+    asset_garbage: Option<GarbageReport>,
+    collecting_assets: bool,
+    unused_libraries: Option<PruneReport>,
+    pruning_libraries: bool,
     adding_account: bool,
+    // Index of the account waiting for removal confirmation
+    removing_account: Option<usize>,
+    account_view: Option<AccountView>,
+    // Player heads keyed by profile id, None while downloading or for accounts without a skin
+    avatars: HashMap<String, Option<TextureHandle>>,
+    // Profile id of the account that just logged in, its toast waits for the head
+    logged_in: Option<String>,
+    // Name typed into the offline account window, None while it's closed
+    #[cfg(feature = "offline")]
+    offline_name: Option<String>,
     adding_instance: bool,
+    // Index of the instance `temp_instance` replaces when saved, None when adding a new one
+    editing_instance: Option<usize>,
     temp_instance: InstanceBuilder,
+    icon_picker: Option<IconPicker>,
+    // Json of the version picked in the instance editor, for the Java it needs
+    temp_version_json: Option<Arc<VersionJson>>,
+    // The json download for the version picked in the editor, aborted if another is picked
+    editor_version: Option<TaskHandle>,
+    // Versions of the loader picked in the instance editor
+    loader_versions: Option<LoaderVersions>,
+    // Lists loader betas in the instance editor too
+    unstable_loaders: bool,
+    // Index of the instance waiting for deletion confirmation, and whether to remove its folder
+    deleting_instance: Option<(usize, bool)>,
     instances: Vec<EguiInstance>,
-    current_instance: Option<usize>,
-    quick_playing: bool,
+    // Instances and quick play versions being downloaded before they launch
+    queue: DownloadQueue,
+    // What pending_jobs.toml has, it's only written when the jobs change
+    saved_jobs: Vec<Job>,
+    mod_browser: Option<ModBrowser>,
+    details: Option<InstanceDetails>,
+    launch_preview: Option<LaunchPreview>,
+    instance_search: String,
+    pack_import: Option<PackImport>,
+    multimc_import: Option<MultiMcImport>,
+    // Text typed into the quick play and instance version pickers
+    version_search: String,
+    instance_version_search: String,
+    // Shows the progress in its own OS window instead of inside the launcher
+    progress_detached: bool,
+    // One per launched game, pushed to while the instances are drawn
+    consoles: RefCell<Vec<Console>>,
+    crashes: Vec<CrashDialog>,
+    // Version id restored from the last run, selected once the manifest loads
+    restore_version: Option<String>,
+    // Patch notes, fetched once the news is shown
+    news: Option<PatchNotes>,
+    news_loading: bool,
+    // Kept after being dismissed, a staged update still has to be applied on exit
+    update: Option<UpdateBanner>,
+}
+
+/// What eframe stores between runs, next to the window geometry and egui's own memory
+#[derive(Deserialize, Serialize, Default)]
+#[serde(default)]
+struct UiState {
+    // Quick play version id
+    version: Option<String>,
+    // Profile id of the selected account
+    account: Option<String>,
 }
 
 #[derive(Default)]
@@ -84,29 +221,6 @@ struct MCData {
     selected_version: usize,
     // Version JSON, read only
     version_json: Option<Arc<VersionJson>>,
-    // Asset Index, read only
-    asset_index: Option<Arc<AssetIndexJson>>,
-    // Classpath for the MC jar, also doubles as verifying
-    // That libraries are completely loaded before launch
-    class_path: Option<String>,
-    // The jar path, stored separate because futures are not ordered
-    // This is None if the version has changed
-    jar_path: Option<String>,
-    // Total and finished libraries, divide as floats
-    // and multiply by 100 to get progress as percentage
-    total_libraries: Arc<AtomicU64>,
-    finished_libraries: Arc<AtomicU64>,
-    // Total and finished assets, divide as floats
-    // and multiply by 100 to get progress as percentage
-    total_assets: Arc<AtomicU64>,
-    finished_assets: Arc<AtomicU64>,
-    // Total progress downloading the MC jar
-    total_jar: Arc<AtomicU64>,
-    finished_jar: Arc<AtomicU64>,
-    // Whether all assets are loaded
-    assets: bool,
-    // If the launcher is attempting to launch
-    launching: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -141,37 +255,105 @@ impl VersionManifestArc {
         // If the latest release does not exist in the meta, things have probably gone wrong lol
         unreachable!()
     }
-}
 
-#[derive(Default, Deserialize, Serialize)]
-struct LauncherData {
-    jvms: Vec<Rc<Jvm>>,
-    accounts: Vec<AccRefreshPair>,
-    instances: Vec<Rc<Instance>>,
-}
+    /// Versions the filter shows whose id contains `query`, with indexes into `versions`.
+    /// Ids starting with the query come first, both groups keep the manifest order
+    pub fn search(&self, filter: VersionFilter, query: &str) -> Vec<(usize, &Arc<Version>)> {
+        let query = query.trim().to_lowercase();
+
+        let mut prefix = vec![];
+        let mut substring = vec![];
+
+        for (idx, version) in self.versions.iter().enumerate() {
+            if !filter.shows(version.version_type) {
+                continue;
+            }
+
+            let id = version.id.to_lowercase();
+            if id.starts_with(&query) {
+                prefix.push((idx, version));
+            } else if id.contains(&query) {
+                substring.push((idx, version));
+            }
+        }
 
-#[derive(Deserialize, Serialize)]
-struct AccRefreshPair {
-    account: Account,
-    refresh_token: Arc<str>,
+        prefix.append(&mut substring);
+        prefix
+    }
 }
 
+type LauncherData = launcher_app::LauncherData<Settings>;
+
 struct EguiInstance {
-    i_instance: Rc<Instance>,
+    i_instance: Arc<Instance>,
     image: Option<Image<'static>>,
-    version_json: Cell<Option<Arc<VersionJson>>>,
-    launching: Cell<bool>,
-    prepared: Cell<bool>,
+}
+
+impl EguiInstance {
+    fn new(instance: Arc<Instance>) -> Self {
+        let image = instance
+            .image
+            .as_ref()
+            .map(|image| Image::from_uri(format!("file://{}", image.to_string_lossy())));
+
+        Self {
+            i_instance: instance,
+            image,
+        }
+    }
+}
+
+struct JavaInstall {
+    // Kept so a failed download can be started again
+    component: String,
+    major: u32,
+    // Started from the instance editor, or the quick play picker
+    for_instance: bool,
+    stats: Arc<DownloadStats>,
+}
+
+struct LoaderVersions {
+    loader: Loader,
+    game_version: String,
+    // None while they're fetched
+    versions: Option<Vec<LoaderVersion>>,
+}
+
+struct InstanceCard {
+    // Play was clicked
+    play: bool,
+    action: Option<InstanceAction>,
+}
+
+enum InstanceAction {
+    Edit(usize),
+    Duplicate(usize),
+    Delete(usize),
+    Mods(usize),
+    // Worlds and resource packs
+    Details(usize),
+    Backup(usize),
+    // Opens the instance's backups to pick one
+    Restore(usize),
+    // Shows the command in a window, or copies it
+    LaunchCommand(usize, bool),
+    OpenFolder(usize),
+    OpenModsFolder(usize),
+    Stop(usize),
+    ShowConsole(usize),
+    // Files dragged in from outside the launcher and dropped on the card
+    DropFiles(usize, Vec<PathBuf>),
 }
 
 #[derive(Default)]
 struct TempInstance {
     name: String,
     image: Option<PathBuf>,
-    jvm: Option<Rc<Jvm>>,
+    jvm: Option<Arc<Jvm>>,
     version: Option<Arc<Version>>,
     path: String,
     mod_loader: Option<Loader>,
+    loader_version: Option<String>,
     jvm_args: String,
     env_args: String,
 }
@@ -185,29 +367,37 @@ impl From<TempInstance> for Instance {
             version: value.version.unwrap(),
             path: PathBuf::from(value.path),
             mod_loader: value.mod_loader,
+            loader_version: value.loader_version,
+            track_latest_loader: false,
             jvm_args: value.jvm_args.split(' ').map(String::from).collect(),
             env_args: value.env_args.split(' ').map(String::from).collect(),
+            mods: vec![],
+            group: None,
+            last_played: None,
+            playtime: 0,
+            memory: None,
+            resolution: None,
+            hooks: Hooks::default(),
+            account: None,
+            legacy_skin_fix: false,
+            history: vec![],
         }
     }
 }
 
 impl LauncherGui {
-    fn new(cc: &eframe::CreationContext) -> Box<Self> {
-        let (config_dir, config) = check_file().unwrap();
+    fn new(cc: &eframe::CreationContext, args: Args) -> Box<Self> {
+        let config_dir = data::config_dir();
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let mut config = LauncherData::load(&data::data_file()).unwrap();
+
+        let secrets = data::secrets();
+        let migrated = config.migrate_tokens(&secrets);
 
         let egui_instances = config
             .instances
             .iter()
-            .map(|instance| EguiInstance {
-                i_instance: instance.clone(),
-                image: instance
-                    .image
-                    .as_ref()
-                    .map(|image| Image::from_uri(format!("file://{}", image.to_string_lossy()))),
-                version_json: Cell::new(None),
-                launching: false.into(),
-                prepared: false.into(),
-            })
+            .map(|instance| EguiInstance::new(instance.clone()))
             .collect();
 
         let rt = tokio::runtime::Builder::new_multi_thread()
@@ -216,8 +406,15 @@ impl LauncherGui {
             .build()
             .expect("Runtime Failed to Build");
 
-        let client = Client::new();
+        let settings = &config.settings;
+        let options = data::client_options(&settings.proxy, &settings.certificates, &secrets);
+        let proxy_password = options.proxy.as_ref().unwrap().password.clone();
+        let client = options.build().unwrap_or_else(|e| {
+            eprintln!("The proxy or certificates can't be used: {e}");
+            ClientOptions::default().build().unwrap()
+        });
         let launcher_core = Arc::new(AsyncLauncher::new(client.clone()));
+        launcher_core.set_concurrency(config.settings.concurrency);
         let (tx, rx) = async_channel::unbounded();
 
         let state = &*Box::leak(Box::new(State {
@@ -226,128 +423,649 @@ impl LauncherGui {
             tx,
         }));
 
-        let rt = async_bridge::Runtime::new(4, state, cc.egui_ctx.clone(), worker_event_loop, rt);
+        let wake = async_bridge::egui_wake(cc.egui_ctx.clone());
+        let rt = async_bridge::Runtime::new(4, state, wake, worker_event_loop, rt);
+
+        let launcher_path = Arc::new(
+            config
+                .settings
+                .data_dir
+                .clone()
+                .unwrap_or_else(|| config_dir.clone()),
+        );
+
+        let default_java_version = java_major_version(config.settings.jvm_path());
 
-        let launcher_path = Arc::new(config_dir);
+        i18n::set_language(config.settings.language);
+        egui_extras::install_image_loaders(&cc.egui_ctx);
 
-        let (_, default_java_version) = get_vendor_major_version("java");
+        // eframe already follows the system theme, unless there is an accent to add
+        if config.settings.theme != Theme::System || config.settings.accent.is_some() {
+            config
+                .settings
+                .apply_theme(&cc.egui_ctx, cc.integration_info.system_theme);
+        }
+        config.settings.apply_scale(&cc.egui_ctx);
 
         send_message(&rt, Contents::Versions, &launcher_path);
 
-        for acc in &config.accounts {
-            send_message(
-                &rt,
-                Contents::Auth(Some(acc.refresh_token.clone())),
-                &launcher_path,
-            );
+        let show_news = config.settings.show_news;
+        if show_news {
+            fetch_news(&rt, &state.client, &launcher_path);
+        }
+
+        update::remove_old_exe();
+        if config.settings.check_updates {
+            rt.future(check_update(state.client.clone()));
+        }
+
+        let ui_state: UiState = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        let account = ui_state.account.and_then(|id| {
+            config
+                .accounts
+                .iter()
+                .position(|acc| acc.account.profile.id == id)
+        });
+
+        // Offline accounts have nothing to refresh
+        for acc in config.accounts.iter().filter(|acc| !acc.account.offline) {
+            // Without a token the account stays listed, but has to be logged in again
+            if let Some(token) = acc.refresh_token(&secrets) {
+                send_message(&rt, Contents::Auth(Some(token)), &launcher_path);
+            }
         }
 
-        LauncherGui {
+        // The instance from `--launch`, queued like it was played
+        let launch = args.launch.and_then(|name| {
+            let index = config.instance_index(&name);
+            if index.is_none() {
+                eprintln!("There's no instance called {name}");
+            }
+            index
+        });
+
+        // What the client was built with, taken before the config moves into the gui
+        let proxy = config.settings.proxy.clone();
+        let certificates = config.settings.certificates.clone();
+
+        let mut gui = LauncherGui {
             rt,
             rx,
             launcher: launcher_core.clone(),
-            player: Default::default(),
+            client: state.client.clone(),
+            player: PlayerData {
+                account,
+                ..Default::default()
+            },
             data: MCData {
                 // Defaults to usize::MAX to show no version is selected
                 selected_version: usize::MAX,
                 ..Default::default()
             },
             launcher_path,
+            config_dir,
             java_version: default_java_version,
-            current_error: None,
+            errors: Vec::new(),
+            toasts: Toasts::default(),
             jvm_index: None,
+            java_majors: RefCell::new(HashMap::new()),
+            java_install: None,
+            system_memory: launcher_core::memory::total_memory(),
             launcher_data: config,
+            secrets,
+            proxy,
+            certificates,
+            proxy_password,
             loading_place: SystemTime::now(),
-            data_updated: false,
+            // Rewrites the data file without the migrated plaintext tokens
+            data_updated: migrated,
+            settings_open: false,
+            asset_garbage: None,
+            collecting_assets: false,
+            unused_libraries: None,
+            pruning_libraries: false,
             adding_account: false,
+            removing_account: None,
+            account_view: None,
+            avatars: HashMap::new(),
+            logged_in: None,
+            #[cfg(feature = "offline")]
+            offline_name: None,
             adding_instance: false,
+            editing_instance: None,
             temp_instance: InstanceBuilder::default(),
+            icon_picker: None,
+            temp_version_json: None,
+            editor_version: None,
+            loader_versions: None,
+            unstable_loaders: false,
+            deleting_instance: None,
             instances: egui_instances,
-            current_instance: None,
-            quick_playing: false,
+            queue: DownloadQueue::default(),
+            saved_jobs: vec![],
+            mod_browser: None,
+            details: None,
+            launch_preview: None,
+            instance_search: String::new(),
+            pack_import: None,
+            multimc_import: None,
+            version_search: String::new(),
+            instance_version_search: String::new(),
+            progress_detached: false,
+            consoles: RefCell::new(vec![]),
+            crashes: vec![],
+            restore_version: ui_state.version,
+            news: None,
+            news_loading: show_news,
+            update: None,
+        };
+        gui.restore_jobs();
+        if let Some(index) = launch.filter(|index| !gui.queue.contains(Some(*index))) {
+            gui.play(index);
         }
-        .into()
+        gui.into()
     }
 
-    fn current_tag<'a>(&'a self, versions: &'a VersionManifestArc) -> &'a Arc<Version> {
-        if let Some(instance) = &self.current_instance {
-            &self.instances[*instance].i_instance.version
-        } else {
-            &versions.versions[self.data.selected_version]
+    fn update_state(&mut self, ctx: &egui::Context) -> Result<(), Failure> {
+        if let Some(panicked) = self.rt.try_recv_panic() {
+            return Err(Error::Panicked(panicked).into());
         }
-    }
 
-    fn update_state(&mut self, _: &egui::Context) -> Result<(), Error> {
         let event = self.rt.try_recv();
         if let Ok(message) = event {
             match message {
-                Response::Versions(manifest) => self.data.versions = Some(manifest?.into()),
-                Response::Version(json) => {
-                    let arc: Arc<VersionJson> = json?.into();
-                    for instances in &mut self.instances {
-                        if instances.i_instance.version.id == arc.id {
-                            instances.version_json.set(Some(arc.clone()));
-                        }
+                Response::Versions(manifest) => {
+                    let versions: VersionManifestArc = manifest
+                        .map_err(|e| Failure::retry(e, Retry::Versions))?
+                        .into();
+
+                    let restored = self.restore_version.take().and_then(|id| {
+                        versions
+                            .versions
+                            .iter()
+                            .position(|version| version.id == id)
+                    });
+                    if let Some(index) = restored {
+                        self.data.selected_version = index;
+                        self.rt.future(get_version(
+                            self.launcher.clone(),
+                            versions.versions[index].clone(),
+                            self.launcher_path.clone(),
+                        ));
                     }
-                    self.data.version_json = Some(arc.clone())
+
+                    self.data.versions = Some(versions);
                 }
-                Response::Auth(res) => {
-                    let (acc, refresh) = res?;
-                    let into = AccRefreshPair {
-                        account: acc,
-                        refresh_token: refresh.into(),
+                Response::Version(version, json) => {
+                    let arc: Arc<VersionJson> = json
+                        .map_err(|e| Failure::retry(e, Retry::Version(version)))?
+                        .into();
+                    self.data.version_json = Some(arc)
+                }
+                Response::EditorVersion(json) => {
+                    let json = json?;
+                    // The editor could have moved on to another version
+                    if self
+                        .temp_instance
+                        .version()
+                        .as_ref()
+                        .is_some_and(|v| v.id == json.id)
+                    {
+                        self.temp_version_json = Some(Arc::from(json));
+                    }
+                }
+                Response::LaunchPreview { index, copy, json } => {
+                    let json = json?;
+                    let Some(instance) = self.launcher_data.instances.get(index).cloned() else {
+                        return Ok(());
                     };
-                    for acc in &mut self.launcher_data.accounts {
-                        if acc.account.profile.id == into.account.profile.id {
-                            *acc = into;
+                    let acc = self.launch_account(Some(&*instance)).map_err(Error::from)?;
+                    let account = &self.launcher_data.accounts[acc].account;
+
+                    // Where the downloads would put the libraries and the jar
+                    let libraries = launcher_core::class_path(
+                        json.libraries(),
+                        &self.launcher_path.join("libraries"),
+                    );
+                    let id = json.id();
+                    let jar = self
+                        .launcher_path
+                        .join("versions")
+                        .join(id)
+                        .join(format!("{id}.jar"));
+                    let class_path = format!("{libraries}{}", jar.display());
+
+                    let instance = instance.as_ref();
+                    let settings = self.launch_settings(Some(instance));
+                    let command = self.launch_command(
+                        &json,
+                        Some(instance),
+                        &settings,
+                        account,
+                        &class_path,
+                    )?;
+                    if copy {
+                        let text = launch_preview::render_command(&command, account);
+                        ctx.output_mut(|o| o.copied_text = text);
+                        self.toasts.push(tr("instance.command_copied").to_string());
+                    } else {
+                        self.launch_preview =
+                            Some(LaunchPreview::new(instance.name.clone(), &command, account));
+                    }
+                }
+                Response::Java(result) => {
+                    let Some(install) = self.java_install.take() else {
+                        return Ok(());
+                    };
+                    let runtime = result.map_err(|e| {
+                        let retry = Retry::InstallJava {
+                            component: install.component,
+                            major: install.major,
+                            for_instance: install.for_instance,
+                        };
+                        Failure::retry(e, retry)
+                    })?;
+                    let path = runtime.java.display().to_string();
+                    self.java_majors
+                        .get_mut()
+                        .insert(path.clone(), install.major);
+
+                    // Installing the same runtime again reuses its entry
+                    let index = match self.launcher_data.jvms.iter().position(|j| j.path == path) {
+                        Some(index) => index,
+                        None => {
+                            self.launcher_data.jvms.push(Arc::new(Jvm {
+                                path,
+                                name: format!("Mojang {}", runtime.version),
+                            }));
                             self.data_updated = true;
-                            return Ok(());
+                            self.launcher_data.jvms.len() - 1
                         }
+                    };
+
+                    // The editor could have been closed while the runtime downloaded
+                    if install.for_instance && self.adding_instance {
+                        self.temp_instance.jvm = self.launcher_data.jvms[index].clone();
+                    } else if !install.for_instance {
+                        self.jvm_index = Some(index);
+                        self.java_version = install.major;
+                    }
+                }
+                Response::TimedOut(retry, timed_out) => {
+                    if matches!(retry, Retry::News) {
+                        self.news_loading = false;
+                    }
+                    return Err(Failure::retry(Error::TimedOut(timed_out), retry));
+                }
+                Response::News(result) => {
+                    self.news_loading = false;
+                    self.news = Some(result.map_err(|e| Failure::retry(e, Retry::News))?);
+                }
+                Response::Update(UpdateResponse::Checked(result)) => {
+                    // Not worth a banner of its own, the check runs again next launch
+                    match result {
+                        Ok(release) => self.update = release.map(UpdateBanner::new),
+                        Err(e) => eprintln!("Checking for updates failed: {e}"),
+                    }
+                }
+                Response::Update(UpdateResponse::Staged(result)) => {
+                    if let Some(banner) = &mut self.update {
+                        banner.staging = false;
+                        banner.staged = Some(result?);
+                    }
+                }
+                Response::Auth(res) => {
+                    let (acc, refresh) = res.map_err(|e| {
+                        // Lets the login be started again
+                        self.adding_account = false;
+                        Failure::retry(e, Retry::Login)
+                    })?;
+                    // Refreshes at startup go through here too
+                    if self.adding_account {
+                        self.logged_in = Some(acc.profile.id.clone());
                     }
-                    self.launcher_data.accounts.push(into);
+                    // The skin could have been changed elsewhere since
+                    self.avatars.remove(&acc.profile.id);
+                    self.launcher_data.set_account(acc, refresh, &self.secrets);
                     self.adding_account = false;
                     self.data_updated = true;
                 }
-                Response::Tagged(response, tag) => {
-                    if let Some(versions) = &self.data.versions {
-                        match response {
-                            TaggedResponse::Libraries(result) => {
-                                let path = result?;
-                                if self.current_tag(versions) == &tag {
-                                    self.data.class_path = Some(path);
-                                }
-                            }
-                            TaggedResponse::AssetIndex(res) => {
-                                let json = res?;
-                                if self.current_tag(versions) == &tag {
-                                    let index = Arc::new(json);
-
-                                    let future = get_assets(
-                                        self.launcher.clone(),
-                                        index.clone(),
-                                        self.launcher_path.clone(),
-                                        self.data.total_assets.clone(),
-                                        self.data.finished_assets.clone(),
-                                        tag.clone(),
-                                    );
-
-                                    self.rt.future(future);
-
-                                    self.data.asset_index = Some(index);
-                                }
+                Response::Skin(SkinResponse::Preview(url, variant, result)) => {
+                    let images = result?;
+                    if let Some(view) = &mut self.account_view {
+                        view.set_preview(ctx, (url, variant), images);
+                    }
+                }
+                Response::Skin(SkinResponse::Changed(result)) => {
+                    if let Some(view) = &mut self.account_view {
+                        view.busy = false;
+                    }
+                    let profile = result?;
+                    self.avatars.remove(&profile.id);
+                    // The account could have been removed while the change was sent
+                    for acc in &mut self.launcher_data.accounts {
+                        if acc.account.profile.id == profile.id {
+                            acc.account.profile = profile;
+                            self.data_updated = true;
+                            break;
+                        }
+                    }
+                }
+                Response::Skin(SkinResponse::Avatar(id, result)) => {
+                    let avatar = match result {
+                        Ok(head) => head.map(|head| {
+                            ctx.load_texture(format!("Avatar {id}"), head, TextureOptions::NEAREST)
+                        }),
+                        // Not worth a banner, the name is still shown
+                        Err(e) => {
+                            eprintln!("Downloading the avatar failed: {e}");
+                            None
+                        }
+                    };
+
+                    if self.logged_in.as_ref() == Some(&id) {
+                        self.logged_in = None;
+                        let name = self
+                            .launcher_data
+                            .accounts
+                            .iter()
+                            .find(|acc| acc.account.profile.id == id)
+                            .map(|acc| acc.account.profile.name.as_str())
+                            .unwrap_or_default();
+                        let message = tr_args("accounts.logged_in", &[("name", &name)]);
+                        match &avatar {
+                            Some(avatar) => self.toasts.push_with_image(message, avatar.clone()),
+                            None => self.toasts.push(message),
+                        }
+                    }
+                    self.avatars.insert(id, avatar);
+                }
+                Response::Mods(ModResponse::Search(result)) => {
+                    if let Some(browser) = &mut self.mod_browser {
+                        browser.searching = false;
+                        browser.results =
+                            Some(result.map_err(|e| Failure::retry(e, Retry::ModSearch))?);
+                    }
+                }
+                Response::Mods(ModResponse::Installed {
+                    mods_dir,
+                    project_id,
+                    result,
+                }) => {
+                    if let Some(browser) = &mut self.mod_browser {
+                        browser.installing.remove(&project_id);
+                    }
+                    let installed = result?;
+
+                    let index =
+                        self.launcher_data.instances.iter().position(|instance| {
+                            instance.mods_dir(&self.launcher_path) == *mods_dir
+                        });
+
+                    // The instance was deleted while the mod downloaded
+                    if let Some(index) = index {
+                        let mut instance = Instance::clone(&self.launcher_data.instances[index]);
+                        if let Some(old) = instance
+                            .mods
+                            .iter()
+                            .position(|m| m.project_id == installed.project_id)
+                        {
+                            let old = instance.mods.remove(old);
+                            if old.file_name != installed.file_name {
+                                std::fs::remove_file(mods_dir.join(&old.file_name))?;
                             }
-                            TaggedResponse::Asset(result) => {
-                                result?;
-                                if self.current_tag(versions) == &tag {
-                                    self.data.assets = true;
-                                }
+                        }
+                        instance.mods.push(installed);
+                        self.replace_instance(index, Arc::new(instance));
+                    }
+                }
+                Response::Pack(PackResponse::Read(pack, result)) => {
+                    // The import could have been cancelled, or replaced by another pack
+                    let Some(import) = &mut self.pack_import else {
+                        return Ok(());
+                    };
+                    if import.pack != pack {
+                        return Ok(());
+                    }
+
+                    match result {
+                        Ok(info) => {
+                            // A resumed install keeps the name it was given
+                            if !import.resume {
+                                import.name = info.name.clone();
                             }
-                            TaggedResponse::Jar(res) => {
-                                let jar = res?;
-                                if self.current_tag(versions) == &tag {
-                                    self.data.jar_path = Some(jar);
-                                }
+                            import.info = Some(Arc::new(info));
+                        }
+                        Err(e) => {
+                            // Nothing to show if the pack can't be read
+                            self.pack_import = None;
+                            return Err(Failure::retry(e, Retry::ImportPack(pack)));
+                        }
+                    }
+                }
+                Response::Pack(PackResponse::Installed(pack, result)) => {
+                    match &self.pack_import {
+                        Some(import) if import.pack == pack => {}
+                        _ => return Ok(()),
+                    }
+
+                    if let Some(import) = self.pack_import.take() {
+                        result.map_err(|e| Failure::retry(e, Retry::ImportPack(pack)))?;
+                        self.add_pack_instance(&import)?;
+                    }
+                }
+                Response::MultiMc(MultiMcResponse::Found(root, result)) => {
+                    let Some(import) = &mut self.multimc_import else {
+                        return Ok(());
+                    };
+                    // Another folder was picked while this one was read
+                    if import.root.as_ref() != Some(&root) {
+                        return Ok(());
+                    }
+                    match result {
+                        Ok(instances) => import.set_instances(instances),
+                        Err(e) => {
+                            import.reading = false;
+                            return Err(e.into());
+                        }
+                    }
+                }
+                Response::MultiMc(MultiMcResponse::Copied {
+                    instance,
+                    destination,
+                    result,
+                }) => {
+                    // The wizard could have been closed while the copy ran
+                    let found = self.multimc_import.as_mut().and_then(|import| {
+                        import
+                            .instances
+                            .iter_mut()
+                            .find(|found| Arc::ptr_eq(&found.instance, &instance))
+                    });
+                    if let Some(found) = found {
+                        found.state = if result.is_ok() {
+                            CopyState::Done
+                        } else {
+                            CopyState::Failed
+                        };
+                    }
+
+                    result?;
+                    self.add_multimc_instance(&instance, destination)?;
+                }
+                Response::Loader(LoaderResponse::Versions {
+                    loader,
+                    game_version,
+                    result,
+                }) => {
+                    // The editor could have moved on to another loader or game version
+                    let Some(listed) = &mut self.loader_versions else {
+                        return Ok(());
+                    };
+                    if listed.loader != loader || listed.game_version != game_version {
+                        return Ok(());
+                    }
+                    match result {
+                        Ok(versions) => listed.versions = Some(versions),
+                        Err(e) => {
+                            listed.versions = Some(Vec::new());
+                            return Err(Failure::retry(e, Retry::LoaderVersions));
+                        }
+                    }
+                }
+                Response::Loader(LoaderResponse::Installed {
+                    loader,
+                    game_version,
+                    loader_version,
+                    result,
+                }) => {
+                    let name = format!("{} {loader_version}", loader.name());
+                    if let Err(e) = result {
+                        let retry = Retry::InstallLoader {
+                            loader,
+                            game_version,
+                            loader_version,
+                        };
+                        return Err(Failure::retry(e, retry));
+                    }
+                    self.toasts
+                        .push(tr_args("loader.installed", &[("loader", &name)]));
+                }
+                Response::Saves(SavesResponse::BackedUp(world, result)) => {
+                    if let Some(details) = &mut self.details {
+                        details.busy.remove(&world);
+                    }
+                    result?;
+
+                    let name = world.file_name().unwrap_or_default().to_string_lossy();
+                    self.toasts
+                        .push(tr_args("details.backed_up", &[("world", &name)]));
+                    if let Some(details) = &mut self.details {
+                        details.refresh()?;
+                    }
+                }
+                Response::Saves(SavesResponse::AutoBackedUp(result)) => {
+                    let made = result?;
+                    if made.is_empty() {
+                        return Ok(());
+                    }
+
+                    let count = made.len().to_string();
+                    self.toasts
+                        .push(tr_args("details.auto_backed_up", &[("count", &count)]));
+                    if let Some(details) = &mut self.details {
+                        details.refresh()?;
+                    }
+                }
+                Response::Saves(SavesResponse::Restored(backup, result)) => {
+                    if let Some(details) = &mut self.details {
+                        details.busy.remove(&backup);
+                    }
+                    result?;
+
+                    self.toasts.push(tr("details.restored").to_string());
+                    if let Some(details) = &mut self.details {
+                        details.refresh()?;
+                    }
+                }
+                Response::Archive(ArchiveResponse::Created(name, result)) => {
+                    result?;
+                    self.toasts
+                        .push(tr_args("instance.backed_up", &[("name", &name)]));
+                    if let Some(details) = &mut self.details {
+                        details.refresh()?;
+                    }
+                }
+                Response::Archive(ArchiveResponse::Restored(archive, result)) => {
+                    if let Some(details) = &mut self.details {
+                        details.busy.remove(&archive);
+                    }
+                    result?;
+
+                    self.toasts.push(tr("instance.restored").to_string());
+                    if let Some(details) = &mut self.details {
+                        details.refresh()?;
+                    }
+                }
+                Response::Assets(dry_run, result) => {
+                    self.collecting_assets = false;
+                    let report = result?;
+                    if dry_run {
+                        self.asset_garbage = Some(report);
+                    } else {
+                        self.asset_garbage = None;
+                        let size = format_bytes(report.bytes as f64);
+                        self.toasts.push(tr_args(
+                            "settings.assets_removed",
+                            &[("count", &report.objects), ("size", &size)],
+                        ));
+                    }
+                }
+                Response::Libraries(dry_run, result) => {
+                    self.pruning_libraries = false;
+                    let report = result?;
+                    if dry_run {
+                        self.unused_libraries = Some(report);
+                    } else {
+                        self.unused_libraries = None;
+                        let size = format_bytes(report.bytes as f64);
+                        self.toasts.push(tr_args(
+                            "settings.libraries_removed",
+                            &[("count", &report.files), ("size", &size)],
+                        ));
+                    }
+                }
+                Response::Tagged(response, id) => {
+                    // Cancelled entries still get the responses of their downloads
+                    let Some(entry) = self.queue.get_mut(id) else {
+                        return Ok(());
+                    };
+                    match response {
+                        TaggedResponse::Version(result) => {
+                            let json = entry.finish(Part::Version, result)?;
+                            entry.prepare.json = Some(json.into());
+                        }
+                        TaggedResponse::Libraries(result) => {
+                            let libraries = entry.finish(Part::Libraries, result)?;
+                            for skipped in &libraries.skipped_natives {
+                                self.toasts
+                                    .push(tr_args("queue.skipped_native", &[("entry", skipped)]));
                             }
+                            entry.prepare.class_path = Some(libraries.class_path);
+                        }
+                        TaggedResponse::AssetIndex(result) => {
+                            let index = entry.finish(Part::AssetIndex, result)?;
+                            entry.prepare.asset_index = Some(Arc::new(index));
+                        }
+                        TaggedResponse::Asset(result) => {
+                            entry.finish(Part::Assets, result)?;
+                            entry.prepare.assets = true;
+                        }
+                        TaggedResponse::Jar(result) => {
+                            entry.prepare.jar_path = Some(entry.finish(Part::Jar, result)?);
+                        }
+                        TaggedResponse::Loader(result) => {
+                            // The installed build launches if the meta server can't be reached
+                            entry.prepare.loader_update = false;
+                            let instance = entry.instance;
+                            let updated = entry.prepare.finish(Part::Loader, result)?;
+                            let (Some(version), Some(index)) = (updated, instance) else {
+                                return Ok(());
+                            };
+                            let now = SystemTime::now()
+                                .duration_since(SystemTime::UNIX_EPOCH)
+                                .unwrap_or_default()
+                                .as_secs();
+                            self.toasts.push(tr_args(
+                                "loader.updated",
+                                &[("instance", &entry.name), ("version", &version)],
+                            ));
+                            self.update_instance(index, |instance| {
+                                instance.update_loader(version, now)
+                            });
                         }
                     }
                 }
@@ -362,152 +1080,1923 @@ impl LauncherGui {
         Ok(())
     }
 
-    fn prepare_launch(&self, json: &Arc<VersionJson>, manifest: &VersionManifestArc) {
-        let libraries = json.libraries().clone();
-        let index = json.asset_index().clone();
-        let tag = self.current_tag(manifest);
+    /// Starts the downloads of the entries whose turn it is, and launches the ones that are done
+    fn update_queue(&mut self) {
+        for (id, part) in self.queue.start() {
+            let Some(entry) = self.queue.get(id) else {
+                continue;
+            };
+            let launcher = self.launcher.clone();
+            let path = self.launcher_path.clone();
+            match (part, &entry.prepare.json, &entry.prepare.asset_index) {
+                (Part::Version, ..) => {
+                    let version = entry.version.clone();
+                    self.rt
+                        .future(get_queued_version(launcher, version, path, id));
+                }
+                (Part::Libraries, Some(json), _) => {
+                    let libraries = json.libraries().clone();
+                    let stats = entry.library_stats.clone();
+                    self.rt.future_with_priority(
+                        get_libraries(launcher, libraries, path, stats, id),
+                        Priority::Low,
+                    );
+                }
+                (Part::AssetIndex, Some(json), _) => {
+                    let index = json.asset_index().clone();
+                    self.rt.future(get_asset_index(launcher, index, id, path));
+                }
+                (Part::Jar, Some(json), _) => {
+                    let stats = entry.jar_stats.clone();
+                    self.rt.future_with_priority(
+                        get_jar(launcher, json.clone(), path, stats, id),
+                        Priority::Low,
+                    );
+                }
+                (Part::Loader, ..) => {
+                    let instance = entry
+                        .instance
+                        .map(|index| &self.instances[index].i_instance);
+                    let Some((loader, installed)) = instance.and_then(|i| i.loader()) else {
+                        continue;
+                    };
+                    let game_version = entry.version.id.clone();
+                    self.rt.future(update_loader(
+                        self.client.clone(),
+                        loader,
+                        game_version,
+                        installed.to_string(),
+                        path,
+                        id,
+                    ));
+                }
+                (Part::Assets, _, Some(index)) => {
+                    let stats = entry.asset_stats.clone();
+                    self.rt.future_with_priority(
+                        get_assets(launcher, index.clone(), path, stats, id),
+                        Priority::Low,
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        // Ready entries wait in the queue while there's no account to launch with
+        let (data, instances) = (&self.launcher_data, &self.instances);
+        let selected = self.player.account;
+        let ready = self.queue.take_ready(|instance| {
+            let pinned = instance.and_then(|index| instances[index].i_instance.account.as_deref());
+            data.launch_account(pinned, selected) != Err(AccountError::NoAccount)
+        });
+        for entry in ready {
+            if let Err(e) = self.launch(&entry) {
+                self.errors.push(e.into());
+            }
+        }
+    }
+
+    fn launch(&self, entry: &QueueEntry) -> Result<(), Error> {
+        let (Some(json), Some(class_path)) =
+            (&entry.prepare.json, entry.prepare.launch_class_path())
+        else {
+            return Ok(());
+        };
+        let instance = entry
+            .instance
+            .map(|index| &*self.instances[index].i_instance);
 
-        let future = get_asset_index(
-            self.launcher.clone(),
-            index,
-            tag.clone(),
-            self.launcher_path.clone(),
+        let acc = self.launch_account(instance)?;
+        let account = &self.launcher_data.accounts[acc].account;
+        let settings = self.launch_settings(instance);
+        let mut command = self.launch_command(json, instance, &settings, account, &class_path)?;
+
+        settings.hooks.run_pre_launch()?;
+        let child = command
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()?;
+
+        let console = Console::new(entry.name.clone(), child, entry.instance, settings.hooks);
+        self.consoles.borrow_mut().push(console);
+
+        Ok(())
+    }
+
+    /// The account the instance is pinned to, or the selected one
+    fn launch_account(&self, instance: Option<&Instance>) -> Result<usize, AccountError> {
+        let pinned = instance.and_then(|instance| instance.account.as_deref());
+        self.launcher_data
+            .launch_account(pinned, self.player.account)
+    }
+
+    /// The settings with the instance's overrides, versions use the JVM picked next to them
+    fn launch_settings(&self, instance: Option<&Instance>) -> LaunchSettings {
+        let mut defaults = self.launcher_data.settings.launch_defaults();
+        if let (None, Some(jvm)) = (instance, self.jvm_index) {
+            defaults.jvm = Some(self.launcher_data.jvms[jvm].clone());
+        }
+        defaults.resolve(instance)
+    }
+
+    /// The command that starts the game, also shown by the launch command preview.
+    /// `class_path` has the vanilla libraries and the jar, the loader's are added here
+    fn launch_command(
+        &self,
+        json: &VersionJson,
+        instance: Option<&Instance>,
+        settings: &LaunchSettings,
+        account: &Account,
+        class_path: &str,
+    ) -> Result<std::process::Command, Error> {
+        let profile = instance
+            .map(|instance| loader_profile(&self.launcher_path, instance))
+            .transpose()?
+            .flatten();
+        let loader_class_path = profile
+            .as_ref()
+            .map(|profile| {
+                launcher_core::loader::class_path(profile, &self.launcher_path.join("libraries"))
+            })
+            .unwrap_or_default();
+
+        let mut command = launcher_core::launch_command(
+            &settings.jvm,
+            json,
+            &self.launcher_path,
+            &self.launcher_path.join("assets"),
+            account,
+            CLIENT_ID,
+            "0",
+            "Synth Launcher",
+            "0.1.0",
+            &format!("{}{}", loader_class_path, class_path),
+            &settings.jvm_args,
+            profile.as_ref(),
+            &FeatureFlags::default(),
         );
-        self.rt.future(future);
-        let future = get_libraries(
-            self.launcher.clone(),
-            libraries,
+        command.args(&settings.game_args);
+        command.envs(settings.env.iter().map(|(key, value)| (key, value)));
+
+        Ok(command)
+    }
+
+    fn fetch_editor_version(&mut self, version: Arc<Version>) {
+        self.temp_version_json = None;
+        if let Some(previous) = self.editor_version.take() {
+            previous.abort();
+        }
+        let future = get_editor_version(self.launcher.clone(), version, self.launcher_path.clone());
+        self.editor_version = Some(self.rt.future(future));
+    }
+
+    /// Lists the versions of the loader picked in the instance editor when the loader or game
+    /// version changes, and keeps the picked loader version one of them
+    fn update_editor_loader(&mut self) {
+        let (Some(loader), Some(version)) =
+            (self.temp_instance.mod_loader, self.temp_instance.version())
+        else {
+            return;
+        };
+        let game_version = version.id.clone();
+
+        let listed = self
+            .loader_versions
+            .as_ref()
+            .filter(|listed| listed.loader == loader && listed.game_version == game_version);
+        let Some(listed) = listed else {
+            self.loader_versions = Some(LoaderVersions {
+                loader,
+                game_version: game_version.clone(),
+                versions: None,
+            });
+            let future = get_loader_versions(self.client.clone(), loader, game_version);
+            self.rt.future(future);
+            return;
+        };
+
+        let Some(versions) = &listed.versions else {
+            return;
+        };
+        let picked = &mut self.temp_instance.loader_version;
+        let known = picked
+            .as_ref()
+            .is_some_and(|picked| versions.iter().any(|version| &version.version == picked));
+        if !known {
+            // The newest stable build, the list is newest first
+            *picked = versions
+                .iter()
+                .find(|version| version.is_stable())
+                .or(versions.first())
+                .map(|version| version.version.clone());
+        }
+    }
+
+    fn install_instance_loader(&mut self, instance: &Instance) {
+        if let (Some(loader), Some(loader_version)) =
+            (instance.mod_loader, &instance.loader_version)
+        {
+            let game_version = instance.version.id.clone();
+            self.install_loader(loader, game_version, loader_version.clone());
+        }
+    }
+
+    /// Downloads the loader's profile and libraries, files that are already there are kept
+    fn install_loader(&mut self, loader: Loader, game_version: String, loader_version: String) {
+        let name = format!("{} {loader_version}", loader.name());
+        self.toasts
+            .push(tr_args("loader.installing", &[("loader", &name)]));
+
+        let future = install_loader(
+            self.client.clone(),
+            loader,
+            game_version,
+            loader_version,
             self.launcher_path.clone(),
-            self.data.total_libraries.clone(),
-            self.data.finished_libraries.clone(),
-            tag.clone(),
         );
         self.rt.future(future);
-        let future = get_jar(
+    }
+
+    /// Runs the JVM once to find its major version, the result is kept for later calls
+    fn java_major(&self, path: &str) -> u32 {
+        *self
+            .java_majors
+            .borrow_mut()
+            .entry(path.to_string())
+            .or_insert_with(|| java_major_version(path))
+    }
+
+    fn start_java_install(&mut self, component: String, major: u32, for_instance: bool) {
+        let install = JavaInstall {
+            component: component.clone(),
+            major,
+            for_instance,
+            stats: Arc::new(DownloadStats::new()),
+        };
+
+        self.rt.future(install_java(
             self.launcher.clone(),
-            json.clone(),
+            self.client.clone(),
+            component,
             self.launcher_path.clone(),
-            self.data.total_jar.clone(),
-            self.data.finished_jar.clone(),
-            tag.clone(),
-        );
-        self.rt.future(future);
+            install.stats.clone(),
+        ));
+        self.java_install = Some(install);
     }
 
-    fn maybe_launch(&self, json: &Arc<VersionJson>, jvm: Option<&Jvm>, current: bool) -> bool {
-        if let (Some(class_path), Some(acc), Some(jar_path)) = (
-            &self.data.class_path,
-            self.player.account,
-            &self.data.jar_path,
-        ) {
-            if self.data.assets && self.data.launching {
-                let jvm = if let Some(jvm) = jvm {
-                    jvm.path.as_str()
-                } else if let Some(jvm) = self.jvm_index {
-                    &self.launcher_data.jvms[jvm].path
-                } else {
-                    "java"
-                };
+    fn progress_window(&mut self, ctx: &egui::Context) {
+        ctx.request_repaint();
 
-                launcher_core::launch_game(
-                    jvm,
-                    json,
-                    &self.launcher_path,
-                    &self.launcher_path.join("assets"),
-                    &self.launcher_data.accounts[acc].account,
-                    CLIENT_ID,
-                    "0",
-                    "Synth Launcher",
-                    "0.1.0",
-                    &format!("{}{}", class_path, jar_path),
-                );
-                !current
-            } else {
-                current
+        if !self.progress_detached {
+            egui::Window::new(tr("progress.title"))
+                .auto_sized()
+                .show(ctx, |ui| self.progress_contents(ui));
+            return;
+        }
+
+        let builder = egui::ViewportBuilder::default()
+            .with_title(tr("progress.title"))
+            .with_inner_size([500.0, 120.0]);
+        let viewport = egui::ViewportId::from_hash_of("Progress");
+
+        ctx.show_viewport_immediate(viewport, builder, |ctx, class| {
+            // Backends without multiple windows draw the viewport inside the main one
+            if class == egui::ViewportClass::Embedded {
+                egui::Window::new(tr("progress.title"))
+                    .auto_sized()
+                    .show(ctx, |ui| self.progress_contents(ui));
+                return;
             }
+
+            egui::CentralPanel::default().show(ctx, |ui| self.progress_contents(ui));
+
+            // Closing the OS window puts the progress back into the launcher
+            if ctx.input(|i| i.viewport().close_requested()) {
+                self.progress_detached = false;
+            }
+        });
+    }
+
+    fn progress_contents(&mut self, ui: &mut Ui) {
+        self.queue.contents(ui);
+
+        let detach = if self.progress_detached {
+            tr("viewport.attach")
         } else {
-            current
+            tr("viewport.detach")
+        };
+        if ui.button(detach).clicked() {
+            self.progress_detached = !self.progress_detached;
+        }
+    }
+
+    /// Starts downloading the heads of accounts that don't have one yet
+    fn fetch_avatars(&mut self) {
+        for acc in &self.launcher_data.accounts {
+            let profile = &acc.account.profile;
+            // Offline accounts have no skin to show
+            if acc.account.offline || self.avatars.contains_key(&profile.id) {
+                continue;
+            }
+
+            self.avatars.insert(profile.id.clone(), None);
+            let cache_dir = Arc::new(self.launcher_path.join("avatars"));
+            self.rt
+                .future(avatar(self.client.clone(), profile.clone(), cache_dir));
         }
     }
 
-    fn progress_window(&self, ctx: &egui::Context) {
-        egui::Window::new("Progress").auto_sized().show(ctx, |ui| {
-            let percentage = |finished, total| (finished as f64 / total as f64) * 100.0;
+    fn account_picker(&mut self, ui: &mut Ui) {
+        self.fetch_avatars();
+
+        let frame = Frame::canvas(ui.style())
+            .inner_margin(Margin::ZERO)
+            .stroke(Stroke::NONE);
+
+        egui::TopBottomPanel::new(Bottom, "Bottom Panel")
+            .frame(frame)
+            .show_separator_line(false)
+            .show_inside(ui, |ui| {
+                ui.horizontal(|ui| {
+                    #[cfg(not(feature = "offline"))]
+                    {
+                        let button = Button::new("➕").small();
+
+                        if ui.add_enabled(!self.adding_account, button).clicked() {
+                            self.add_microsoft_account();
+                        }
+                    }
+
+                    #[cfg(feature = "offline")]
+                    ui.add_enabled_ui(!self.adding_account, |ui| {
+                        ui.menu_button("➕", |ui| {
+                            if ui.button(tr("accounts.add_microsoft")).clicked() {
+                                self.add_microsoft_account();
+                                ui.close_menu();
+                            }
+                            if ui.button(tr("accounts.add_offline")).clicked() {
+                                self.offline_name = Some(String::new());
+                                ui.close_menu();
+                            }
+                        });
+                    });
+
+                    if let Some(acc_idx) = &mut self.player.account {
+                        let profile = &self.launcher_data.accounts[*acc_idx].account.profile;
+                        avatar_image(ui, &self.avatars, &profile.id);
+
+                        let picker = egui::ComboBox::from_id_source("Account Picker")
+                            .width(ui.available_width() * 0.80)
+                            .selected_text(&profile.name)
+                            .show_ui(ui, |ui| {
+                                for (idx, acc) in self.launcher_data.accounts.iter().enumerate() {
+                                    let profile = &acc.account.profile;
+                                    ui.horizontal(|ui| {
+                                        avatar_image(ui, &self.avatars, &profile.id);
+                                        ui.selectable_value(acc_idx, idx, &profile.name);
+                                    });
+                                }
+                            });
+
+                        if picker.response.has_focus() {
+                            let accounts: Vec<_> = (0..self.launcher_data.accounts.len()).collect();
+                            step_selection(ui, &accounts, acc_idx);
+                        }
+                    } else if self.launcher_data.accounts.is_empty() {
+                        ui.label(tr("accounts.none"));
+                    } else {
+                        self.player.account = Some(0)
+                    };
+
+                    let button = Button::new("👤").small();
+                    // Offline accounts have no skins or capes to show
+                    let online = self
+                        .player
+                        .account
+                        .is_some_and(|idx| !self.launcher_data.accounts[idx].account.offline);
+
+                    if ui.add_enabled(online, button).clicked() {
+                        self.account_view = self.player.account.map(AccountView::new);
+                    }
+
+                    let button = Button::new("➖").small();
+                    let enabled = !self.adding_account && self.player.account.is_some();
+
+                    if ui.add_enabled(enabled, button).clicked() {
+                        self.removing_account = self.player.account;
+                    }
+                });
+            });
+    }
+
+    fn add_microsoft_account(&mut self) {
+        send_message(&self.rt, Contents::Auth(None), &self.launcher_path);
+        self.adding_account = true;
+    }
+
+    #[cfg(feature = "offline")]
+    fn offline_account_window(&mut self, ctx: &egui::Context) {
+        use launcher_app::AccRefreshPair;
+        use launcher_core::account::offline;
+
+        let Some(name) = &mut self.offline_name else {
+            return;
+        };
+
+        let mut open = true;
+        let mut add = false;
+
+        egui::Window::new(tr("accounts.offline_title"))
+            .auto_sized()
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(tr("accounts.offline_hint"));
+
+                let edit = ui.text_edit_singleline(name);
+                let entered = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let valid = offline::is_valid_name(name);
+                if !valid && !name.is_empty() {
+                    ui.colored_label(ui.visuals().error_fg_color, tr("accounts.offline_invalid"));
+                }
+
+                let button = ui.add_enabled(valid, Button::new(tr("accounts.add")));
+                add = valid && (entered || button.clicked());
+            });
+
+        if add {
+            let account = offline::offline_account(name);
+            let accounts = &mut self.launcher_data.accounts;
+            // The same name always gets the same id, adding it twice just selects it
+            let index = match accounts
+                .iter()
+                .position(|acc| acc.account.profile.id == account.profile.id)
+            {
+                Some(index) => index,
+                None => {
+                    accounts.push(AccRefreshPair::new(account));
+                    self.data_updated = true;
+                    accounts.len() - 1
+                }
+            };
+            self.player.account = Some(index);
+            self.offline_name = None;
+        } else if !open {
+            self.offline_name = None;
+        }
+    }
+
+    fn settings_window(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        if !self.settings_open {
+            return;
+        }
+
+        let settings = &mut self.launcher_data.settings;
+        let metrics = self.rt.metrics();
+        let mut open = true;
+        let mut changed = false;
+        let mut fetch_news = false;
+        // Whether it's a dry run
+        let mut collect_assets = None;
+        let mut prune = None;
+
+        egui::Window::new(tr("settings.title"))
+            .auto_sized()
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.default_jvm"));
+
+                    let selected_text = settings
+                        .default_jvm
+                        .as_ref()
+                        .map_or(tr("settings.java_on_path"), |jvm| jvm.name.as_str());
+
+                    egui::ComboBox::from_id_source("Default Java Selector")
+                        .wrap(true)
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            if ui.button(tr("settings.java_on_path")).clicked() {
+                                settings.default_jvm = None;
+                                changed = true;
+                            }
+
+                            for jvm in &self.launcher_data.jvms {
+                                if ui.button(jvm.name.as_str()).clicked() {
+                                    settings.default_jvm = Some(jvm.clone());
+                                    changed = true;
+                                }
+                            }
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.jvm_args"));
+                    changed |= ui.text_edit_singleline(&mut settings.jvm_args).changed();
+                });
+
+                changed |= memory_sliders(
+                    ui,
+                    &mut settings.min_memory,
+                    &mut settings.memory,
+                    self.system_memory,
+                );
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.concurrency"));
+                    let concurrency =
+                        egui::DragValue::new(&mut settings.concurrency).clamp_range(1..=64);
+                    if ui.add(concurrency).changed() {
+                        self.launcher.set_concurrency(settings.concurrency);
+                        changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.theme"));
+                    let before = settings.theme;
+                    ui.radio_value(
+                        &mut settings.theme,
+                        Theme::System,
+                        tr("settings.theme_system"),
+                    );
+                    ui.radio_value(
+                        &mut settings.theme,
+                        Theme::Light,
+                        tr("settings.theme_light"),
+                    );
+                    ui.radio_value(&mut settings.theme, Theme::Dark, tr("settings.theme_dark"));
+                    if settings.theme != before {
+                        settings.apply_theme(ctx, frame.info().system_theme);
+                        changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.accent"));
+                    let mut custom = settings.accent.is_some();
+                    let mut accent_changed = ui
+                        .checkbox(&mut custom, tr("settings.accent_custom"))
+                        .changed();
+                    if accent_changed {
+                        settings.accent = custom.then_some([0x5a, 0x9f, 0xd6]);
+                    }
+
+                    if let Some(accent) = &mut settings.accent {
+                        accent_changed |= ui.color_edit_button_srgb(accent).changed();
+                    }
+
+                    if accent_changed {
+                        settings.apply_theme(ctx, frame.info().system_theme);
+                        changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.ui_scale"));
+                    let slider = egui::Slider::new(&mut settings.ui_scale, 0.5..=3.0)
+                        .step_by(0.1)
+                        .custom_formatter(|n, _| format!("{:.0}%", n * 100.0));
+                    let slider = ui.add(slider);
+                    // Zooming while dragging moves the slider out from under the pointer
+                    if slider.drag_released() || (slider.changed() && !slider.dragged()) {
+                        settings.apply_scale(ctx);
+                        changed = true;
+                    }
+                });
+
+                if ui
+                    .checkbox(&mut settings.large_targets, tr("settings.large_targets"))
+                    .changed()
+                {
+                    settings.apply_scale(ctx);
+                    changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.language"));
+                    egui::ComboBox::from_id_source("Language Selector")
+                        .selected_text(settings.language.name())
+                        .show_ui(ui, |ui| {
+                            for language in i18n::Language::ALL {
+                                let button = ui.selectable_value(
+                                    &mut settings.language,
+                                    language,
+                                    language.name(),
+                                );
+                                if button.changed() {
+                                    i18n::set_language(language);
+                                    changed = true;
+                                }
+                            }
+                        });
+                });
+
+                changed |= ui
+                    .checkbox(&mut settings.check_updates, tr("settings.check_updates"))
+                    .changed();
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.backups_kept"));
+                    let kept = egui::DragValue::new(&mut settings.backups_kept)
+                        .clamp_range(0..=100)
+                        .custom_formatter(|n, _| {
+                            if n == 0.0 {
+                                tr("settings.keep_all").to_string()
+                            } else {
+                                n.to_string()
+                            }
+                        });
+                    changed |= ui.add(kept).changed();
+                });
+                changed |= ui
+                    .checkbox(
+                        &mut settings.backup_before_update,
+                        tr("settings.backup_before_update"),
+                    )
+                    .changed();
+
+                if ui
+                    .checkbox(&mut settings.show_news, tr("settings.show_news"))
+                    .changed()
+                {
+                    fetch_news = settings.show_news && self.news.is_none();
+                    changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.data_dir"));
+                    let text = settings
+                        .data_dir
+                        .as_ref()
+                        .map_or_else(|| self.config_dir.display(), |dir| dir.display());
+                    ui.label(text.to_string());
+
+                    if ui.button(tr("settings.change")).clicked() {
+                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                            settings.data_dir = Some(path);
+                            changed = true;
+                        }
+                    }
+
+                    if ui
+                        .add_enabled(
+                            settings.data_dir.is_some(),
+                            Button::new(tr("settings.reset")),
+                        )
+                        .clicked()
+                    {
+                        settings.data_dir = None;
+                        changed = true;
+                    }
+                });
+
+                let data_dir = settings.data_dir.as_ref().unwrap_or(&self.config_dir);
+                if data_dir != self.launcher_path.as_ref() {
+                    ui.label(tr("settings.data_dir_restart"));
+                }
+
+                ui.collapsing(tr("settings.proxy"), |ui| {
+                    egui::Grid::new("Proxy Grid").num_columns(2).show(ui, |ui| {
+                        let proxy = &mut settings.proxy;
+                        ui.label(tr("settings.proxy_url"));
+                        let url = egui::TextEdit::singleline(&mut proxy.url)
+                            .hint_text("socks5://127.0.0.1:1080");
+                        changed |= ui.add(url).changed();
+                        ui.end_row();
+
+                        ui.label(tr("settings.proxy_username"));
+                        changed |= ui.text_edit_singleline(&mut proxy.username).changed();
+                        ui.end_row();
+
+                        ui.label(tr("settings.proxy_password"));
+                        let password =
+                            egui::TextEdit::singleline(&mut self.proxy_password).password(true);
+                        // Stored once it's typed out, not on every key
+                        if ui.add(password).lost_focus() {
+                            let password = &self.proxy_password;
+                            if let Err(e) = data::set_proxy_password(&self.secrets, password) {
+                                eprintln!("{e}");
+                            }
+                        }
+                        ui.end_row();
+
+                        ui.label(tr("settings.proxy_bypass"));
+                        let bypass = egui::TextEdit::singleline(&mut proxy.bypass)
+                            .hint_text("localhost,.example.com");
+                        changed |= ui.add(bypass).changed();
+                        ui.end_row();
+                    });
+
+                    if settings.proxy != self.proxy {
+                        ui.label(tr("settings.proxy_restart"));
+                    }
+                });
+
+                ui.collapsing(tr("settings.skin_fix"), |ui| {
+                    ui.label(tr("settings.skin_fix_hint"));
+                    let skin_fix = &mut settings.skin_fix;
+                    ui.horizontal(|ui| {
+                        ui.label(tr("settings.skin_fix_proxy"));
+                        let proxy =
+                            egui::TextEdit::singleline(&mut skin_fix.proxy).hint_text("host:port");
+                        changed |= ui.add(proxy).changed();
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(tr("settings.skin_fix_agent"));
+                        if let Some(agent) = &skin_fix.agent {
+                            ui.label(agent.display().to_string());
+                        }
+                        if ui.button(tr("settings.change")).clicked() {
+                            let picked = rfd::FileDialog::new()
+                                .add_filter("jar", &["jar"])
+                                .pick_file();
+                            if let Some(path) = picked {
+                                skin_fix.agent = Some(path);
+                                changed = true;
+                            }
+                        }
+                        let reset = Button::new(tr("settings.reset"));
+                        if ui.add_enabled(skin_fix.agent.is_some(), reset).clicked() {
+                            skin_fix.agent = None;
+                            changed = true;
+                        }
+                    });
+                });
+
+                ui.collapsing(tr("settings.certificates"), |ui| {
+                    let mut removed = None;
+                    for (idx, path) in settings.certificates.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(path.display().to_string());
+                            if ui.button(tr("common.remove")).clicked() {
+                                removed = Some(idx);
+                            }
+                        });
+                    }
+                    if let Some(idx) = removed {
+                        settings.certificates.remove(idx);
+                        changed = true;
+                    }
+
+                    if ui.button(tr("settings.certificates_add")).clicked() {
+                        let picked = rfd::FileDialog::new()
+                            .add_filter(tr("settings.certificates"), &["pem", "crt", "cer", "der"])
+                            .pick_file();
+                        if let Some(path) = picked {
+                            settings.certificates.push(path);
+                            changed = true;
+                        }
+                    }
+
+                    if settings.certificates != self.certificates {
+                        ui.label(tr("settings.certificates_restart"));
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.assets"));
+                    let enabled = !self.collecting_assets;
+                    if ui
+                        .add_enabled(enabled, Button::new(tr("settings.assets_check")))
+                        .clicked()
+                    {
+                        collect_assets = Some(true);
+                    }
+
+                    let Some(report) = &self.asset_garbage else {
+                        return;
+                    };
+                    ui.label(tr_args(
+                        "settings.assets_unused",
+                        &[
+                            ("count", &report.objects),
+                            ("size", &format_bytes(report.bytes as f64)),
+                        ],
+                    ));
+                    let remove = Button::new(tr("settings.assets_remove"));
+                    if ui
+                        .add_enabled(enabled && report.objects > 0, remove)
+                        .clicked()
+                    {
+                        collect_assets = Some(false);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings.libraries"));
+                    let enabled = !self.pruning_libraries;
+                    if ui
+                        .add_enabled(enabled, Button::new(tr("settings.libraries_check")))
+                        .clicked()
+                    {
+                        prune = Some(true);
+                    }
+
+                    let Some(report) = &self.unused_libraries else {
+                        return;
+                    };
+                    ui.label(tr_args(
+                        "settings.libraries_unused",
+                        &[
+                            ("count", &report.files),
+                            ("size", &format_bytes(report.bytes as f64)),
+                        ],
+                    ));
+                    let remove = Button::new(tr("settings.libraries_remove"));
+                    if ui
+                        .add_enabled(enabled && report.files > 0, remove)
+                        .clicked()
+                    {
+                        prune = Some(false);
+                    }
+                });
+
+                ui.collapsing(tr("diagnostics.title"), |ui| {
+                    ui.label(tr_args(
+                        "diagnostics.tasks",
+                        &[
+                            ("active", &metrics.active_tasks),
+                            ("waiting", &metrics.waiting_tasks),
+                            ("completed", &metrics.completed),
+                        ],
+                    ));
+                    ui.label(tr_args(
+                        "diagnostics.responses",
+                        &[
+                            ("waiting", &metrics.waiting_responses),
+                            ("dropped", &metrics.dropped),
+                        ],
+                    ));
+
+                    #[cfg(feature = "durations")]
+                    if let Some(slowest) = metrics.durations.iter().max_by_key(|task| task.took) {
+                        let total: Duration = metrics.durations.iter().map(|task| task.took).sum();
+                        let average = total / metrics.durations.len() as u32;
+                        ui.label(tr_args(
+                            "diagnostics.durations",
+                            &[
+                                ("average", &format!("{average:.2?}")),
+                                ("slowest", &format!("{:.2?}", slowest.took)),
+                            ],
+                        ));
+                    }
+                });
+            });
+
+        self.settings_open = open;
+        self.data_updated |= changed;
+        if fetch_news {
+            self.fetch_news();
+        }
+        if let Some(dry_run) = collect_assets {
+            self.collecting_assets = true;
+            let assets_dir = self.launcher_path.join("assets");
+            self.rt.future(collect_asset_garbage(assets_dir, dry_run));
+        }
+        if let Some(dry_run) = prune {
+            self.pruning_libraries = true;
+            let directory = self.launcher_path.to_path_buf();
+            self.rt.future(prune_libraries(directory, dry_run));
+        }
+    }
+
+    fn mod_browser_window(&mut self, ctx: &egui::Context) -> Result<(), Error> {
+        let Some(browser) = &mut self.mod_browser else {
+            return Ok(());
+        };
+
+        let instance = self.launcher_data.instances[browser.instance].clone();
+        let action = browser.show(ctx, &instance);
+
+        if !browser.open {
+            self.mod_browser = None;
+            return Ok(());
+        }
+
+        // The browser tells vanilla instances apart, so the loader is always set here
+        let Some(loader) = instance.mod_loader else {
+            return Ok(());
+        };
+
+        match action {
+            Some(ModAction::Search(query)) => {
+                browser.searching = true;
+                self.rt.future(search_mods(
+                    self.client.clone(),
+                    query,
+                    loader.modrinth_name(),
+                    instance.version.id.clone(),
+                ));
+            }
+            Some(ModAction::Install(project)) => {
+                browser.installing.insert(project.project_id.clone());
+                self.rt.future(install_mod(
+                    self.client.clone(),
+                    project,
+                    loader.modrinth_name(),
+                    instance.version.id.clone(),
+                    browser.mods_dir.clone(),
+                ));
+            }
+            Some(ModAction::Uninstall(idx)) => {
+                let index = browser.instance;
+                let mut instance = Instance::clone(&instance);
+                let removed = instance.mods.remove(idx);
+
+                let path = browser.mods_dir.join(&removed.file_name);
+                // Already deleted by hand is fine
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+
+                self.replace_instance(index, Arc::new(instance));
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn details_window(&mut self, ctx: &egui::Context) -> Result<(), Error> {
+        let Some(details) = &mut self.details else {
+            return Ok(());
+        };
+
+        let index = details.instance;
+        let running = self
+            .consoles
+            .borrow()
+            .iter()
+            .any(|console| console.instance == Some(index) && console.running());
+        let action = details.show(ctx, &self.instances[index].i_instance, running);
+
+        if !details.open {
+            // Backups that already started still finish
+            self.details = None;
+            return Ok(());
+        }
+
+        match action {
+            Some(DetailsAction::Backup(world)) => {
+                details.busy.insert(world.clone());
+                let backups_dir = details.backups_dir.clone();
+                self.rt.future(backup_world(world, backups_dir));
+            }
+            Some(DetailsAction::Restore(backup)) => {
+                details.busy.insert(backup.clone());
+                let saves_dir = details.saves_dir.clone();
+                self.rt.future(restore_backup(backup, saves_dir));
+            }
+            Some(DetailsAction::SetEnabled(idx, enabled)) => {
+                let pack = resource_packs::set_enabled(&details.packs[idx], enabled)?;
+                details.packs[idx] = pack;
+            }
+            Some(DetailsAction::Remove(idx)) => {
+                resource_packs::remove_pack(&details.packs[idx])?;
+                details.packs.remove(idx);
+            }
+            Some(DetailsAction::Archive) => self.backup_instance(index),
+            Some(DetailsAction::RestoreArchive(archive)) => {
+                details.busy.insert(archive.clone());
+                let directory = details.directory.clone();
+                self.rt.future(restore_archive(archive, directory));
+            }
+            Some(DetailsAction::Refresh) => details.refresh()?,
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn open_details(&mut self, index: usize, tab: Tab) {
+        let instance = &self.instances[index].i_instance;
+        let mut details = InstanceDetails::new(index, instance, &self.launcher_path);
+        details.tab = tab;
+        if let Err(e) = details.refresh() {
+            self.errors.push(e.into());
+        }
+        self.details = Some(details);
+    }
+
+    /// Zips the whole instance, removing the oldest backups past the number kept in the settings
+    fn backup_instance(&mut self, index: usize) {
+        let instance = &self.instances[index].i_instance;
+        // Instances in the launcher's folder share it with every version and library
+        let skip: &[&str] = if instance.path.as_os_str().is_empty() {
+            &SHARED_FILES
+        } else {
+            &[]
+        };
+
+        let future = archive_instance(
+            instance.name.clone(),
+            instance.directory(&self.launcher_path),
+            instance.archives_dir(&self.launcher_path),
+            skip,
+            self.launcher_data.settings.backups_kept,
+        );
+        self.rt.future(future);
+        self.toasts
+            .push(tr_args("instance.backing_up", &[("name", &instance.name)]));
+    }
+
+    fn pack_import_window(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|file| file.path.clone())
+                .find(|path| modpacks::is_pack(path))
+        });
+
+        // Dropping a pack while another is installing is ignored
+        if let Some(path) = dropped {
+            if !self
+                .pack_import
+                .as_ref()
+                .is_some_and(|import| import.installing)
+            {
+                self.start_pack_import(path);
+            }
+        }
+
+        let Some(import) = &mut self.pack_import else {
+            return;
+        };
+
+        match import.show(ctx) {
+            Some(ImportAction::Install) => {
+                let Some(info) = import.info.clone() else {
+                    return;
+                };
+                import.installing = true;
+                self.rt.future(install_pack(
+                    self.launcher.clone(),
+                    self.client.clone(),
+                    import.pack.clone(),
+                    info,
+                    import.directory(&self.launcher_path),
+                    import.stats.clone(),
+                ));
+            }
+            Some(ImportAction::Cancel) => self.pack_import = None,
+            None => {}
+        }
+    }
+
+    /// Queues the downloads and the modpack install the last session didn't finish
+    fn restore_jobs(&mut self) {
+        let file = self.launcher_path.join(jobs::FILE);
+        let pending = match PendingJobs::load(&file) {
+            Ok(pending) => pending,
+            Err(e) => {
+                eprintln!("Couldn't read {}: {e}", file.display());
+                return;
+            }
+        };
+
+        for job in pending.jobs.iter().cloned() {
+            match job {
+                Job::Prepare {
+                    instance,
+                    name,
+                    version,
+                    paused,
+                } => {
+                    let index = match instance {
+                        // The instance could have been removed since
+                        Some(instance) => match self.launcher_data.instance_index(&instance) {
+                            Some(index) => Some(index),
+                            None => continue,
+                        },
+                        None => None,
+                    };
+                    self.queue.restore(index, name, version, paused);
+                }
+                Job::Modpack { pack, name } => {
+                    if self.pack_import.is_none() && pack.exists() {
+                        let import = PackImport::resume(pack, name);
+                        self.rt.future(read_pack(import.pack.clone()));
+                        self.pack_import = Some(import);
+                    }
+                }
+            }
+        }
+        self.saved_jobs = pending.jobs;
+    }
+
+    /// Writes the queue and the modpack install to pending_jobs.toml when they changed
+    fn save_jobs(&mut self) {
+        let mut jobs = self
+            .queue
+            .jobs(|index| self.instances[index].i_instance.name.clone());
+        let installing = self
+            .pack_import
+            .as_ref()
+            .filter(|import| import.installing || import.resume);
+        if let Some(import) = installing {
+            jobs.push(Job::Modpack {
+                pack: import.pack.to_path_buf(),
+                name: import.name.clone(),
+            });
+        }
+        if jobs == self.saved_jobs {
+            return;
+        }
+
+        let file = self.launcher_path.join(jobs::FILE);
+        let pending = PendingJobs { jobs };
+        if let Err(e) = pending.save(&file) {
+            eprintln!("Couldn't save {}: {e}", file.display());
+        }
+        self.saved_jobs = pending.jobs;
+    }
+
+    fn start_pack_import(&mut self, path: PathBuf) {
+        let import = PackImport::new(path);
+        self.rt.future(read_pack(import.pack.clone()));
+        self.pack_import = Some(import);
+    }
+
+    /// Starts the operation a failure came from again
+    fn retry(&mut self, retry: Retry) {
+        match retry {
+            Retry::Versions => send_message(&self.rt, Contents::Versions, &self.launcher_path),
+            Retry::Version(version) => {
+                let future =
+                    get_version(self.launcher.clone(), version, self.launcher_path.clone());
+                self.rt.future(future);
+            }
+            // Nothing to retry once the entry is cancelled
+            Retry::Prepare(id) => self.queue.retry(id),
+            Retry::Login => {
+                if !self.adding_account {
+                    self.add_microsoft_account();
+                }
+            }
+            Retry::ModSearch => {
+                if let Some(browser) = &mut self.mod_browser {
+                    browser.search_again();
+                }
+            }
+            Retry::ImportPack(pack) => self.start_pack_import(pack.to_path_buf()),
+            Retry::News => self.fetch_news(),
+            // Fetched again the next time the editor is drawn
+            Retry::LoaderVersions => self.loader_versions = None,
+            Retry::InstallLoader {
+                loader,
+                game_version,
+                loader_version,
+            } => self.install_loader(loader, game_version, loader_version),
+            Retry::InstallJava {
+                component,
+                major,
+                for_instance,
+            } => {
+                if self.java_install.is_none() {
+                    self.start_java_install(component, major, for_instance);
+                }
+            }
+        }
+    }
+
+    fn fetch_news(&mut self) {
+        if !self.news_loading {
+            self.news_loading = true;
+            fetch_news(&self.rt, &self.client, &self.launcher_path);
+        }
+    }
+
+    fn news_contents(&self, ui: &mut Ui) {
+        ui.heading(tr("news.title"));
+        ui.separator();
+
+        let Some(news) = &self.news else {
+            if self.news_loading {
+                ui.spinner();
+            }
+            return;
+        };
+
+        egui::ScrollArea::vertical()
+            .id_source("News")
+            .show(ui, |ui| {
+                for note in news.entries.iter().take(NEWS_ENTRIES) {
+                    ui.label(RichText::new(&note.title).strong());
+                    // Only the day of the RFC 3339 date
+                    let date = note.date.get(..10).unwrap_or(&note.date);
+                    ui.label(RichText::new(format!("{} · {date}", note.version)).weak());
+                    ui.label(&note.short_text);
+                    ui.separator();
+                }
+            });
+    }
+
+    fn icon_picker_window(&mut self, ctx: &egui::Context) {
+        let Some(picker) = &mut self.icon_picker else {
+            return;
+        };
+
+        // The editor was closed while the picker was open
+        let action = if self.adding_instance {
+            picker.show(ctx)
+        } else {
+            picker.open = false;
+            None
+        };
+
+        if !picker.open {
+            self.icon_picker = None;
+        }
+
+        if let Some(action) = action {
+            match icons::store(&self.launcher_path, action) {
+                Ok(path) => {
+                    *self.temp_instance.image_mut() =
+                        path.map(|path| path.to_string_lossy().to_string());
+                }
+                Err(e) => self.errors.push(e.into()),
+            }
+        }
+    }
+
+    fn add_pack_instance(&mut self, import: &PackImport) -> Result<(), Error> {
+        let Some(info) = &import.info else {
+            return Ok(());
+        };
+
+        let version = self.manifest_version(&info.game_version)?;
+        let (mod_loader, loader_version) = launchable_loader(&info.loader).unzip();
+
+        let directory = import.directory(&self.launcher_path);
+        // Packs that ship a pack.png in their overrides get it as their icon
+        let pack_png = directory.join("pack.png");
+        let image = if pack_png.is_file() {
+            icons::store(&self.launcher_path, IconAction::File(pack_png))
+                .ok()
+                .flatten()
+        } else {
+            None
+        };
+
+        let instance = Instance {
+            name: import.name.clone(),
+            image,
+            jvm: self
+                .launcher_data
+                .settings
+                .default_jvm
+                .clone()
+                .unwrap_or_default(),
+            version,
+            path: directory,
+            mod_loader,
+            loader_version,
+            track_latest_loader: false,
+            jvm_args: vec![],
+            env_args: vec![],
+            mods: vec![],
+            group: None,
+            last_played: None,
+            playtime: 0,
+            memory: None,
+            resolution: None,
+            hooks: Hooks::default(),
+            account: None,
+            legacy_skin_fix: false,
+            history: vec![],
+        };
+
+        self.install_instance_loader(&instance);
+        self.push_instance(Arc::new(instance));
+        Ok(())
+    }
+
+    fn add_multimc_instance(
+        &mut self,
+        imported: &MultiMcInstance,
+        directory: PathBuf,
+    ) -> Result<(), Error> {
+        let version = self.manifest_version(&imported.game_version)?;
+        let (mod_loader, loader_version) = launchable_loader(&imported.loader).unzip();
+
+        let instance = Instance {
+            name: imported.name.clone(),
+            image: None,
+            jvm: self
+                .launcher_data
+                .settings
+                .default_jvm
+                .clone()
+                .unwrap_or_default(),
+            version,
+            path: directory,
+            mod_loader,
+            loader_version,
+            track_latest_loader: false,
+            jvm_args: imported.jvm_args.clone(),
+            env_args: vec![],
+            mods: vec![],
+            group: imported.group.clone(),
+            last_played: None,
+            playtime: 0,
+            memory: imported.memory.map(|(min, max)| Memory { min, max }),
+            resolution: None,
+            hooks: Hooks::default(),
+            account: None,
+            legacy_skin_fix: false,
+            history: vec![],
+        };
+
+        self.install_instance_loader(&instance);
+        self.push_instance(Arc::new(instance));
+        Ok(())
+    }
+
+    fn multimc_import_window(&mut self, ctx: &egui::Context) {
+        let Some(import) = &mut self.multimc_import else {
+            return;
+        };
+
+        let action = import.show(ctx);
+        if !import.open {
+            // Copies that already started still finish and add their instance
+            self.multimc_import = None;
+            return;
+        }
+
+        match action {
+            Some(MultiMcAction::Read(root)) => {
+                import.root = Some(root.clone());
+                import.reading = true;
+                import.instances.clear();
+                self.rt.future(find_multimc_instances(root));
+            }
+            Some(MultiMcAction::Import) => {
+                for found in &mut import.instances {
+                    if !found.selected || !matches!(found.state, CopyState::Waiting) {
+                        continue;
+                    }
+
+                    let destination =
+                        modpacks::instance_directory(&self.launcher_path, &found.instance.name);
+                    let instance = found.instance.clone();
+                    let progress = self.rt.spawn_with_progress(|progress| {
+                        copy_multimc_instance(instance, destination, progress)
+                    });
+                    found.state = CopyState::Copying(progress);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// The manifest entry for a version id, instances can't be created without one
+    fn manifest_version(&self, id: &str) -> Result<Arc<Version>, Error> {
+        let version = self
+            .data
+            .versions
+            .as_ref()
+            .and_then(|versions| versions.versions.iter().find(|version| version.id == id));
+        match version {
+            Some(version) => Ok(version.clone()),
+            None => {
+                let message = format!("Minecraft {id} is not in the manifest");
+                Err(tokio::io::Error::new(tokio::io::ErrorKind::NotFound, message).into())
+            }
+        }
+    }
+
+    /// Indexes of the instances matching the search, sorted and grouped, ungrouped instances first
+    fn instance_groups(&self) -> Vec<(Option<String>, Vec<usize>)> {
+        let search = self.instance_search.trim().to_lowercase();
+
+        let mut indexes: Vec<usize> = (0..self.instances.len())
+            .filter(|&idx| {
+                let name = self.instances[idx].i_instance.name.to_lowercase();
+                search.is_empty() || name.contains(&search)
+            })
+            .collect();
+
+        let instance = |idx: usize| &self.instances[idx].i_instance;
+        match self.launcher_data.settings.instance_sort {
+            InstanceSort::Name => {
+                indexes.sort_by_cached_key(|&idx| instance(idx).name.to_lowercase())
+            }
+            // Most recent first, never played last
+            InstanceSort::LastPlayed => {
+                indexes.sort_by_key(|&idx| std::cmp::Reverse(instance(idx).last_played))
+            }
+            // Newest first, release times are RFC 3339 so they sort as strings
+            InstanceSort::Version => indexes.sort_by(|&a, &b| {
+                let a = &instance(a).version.release_time;
+                let b = &instance(b).version.release_time;
+                b.cmp(a)
+            }),
+        }
+
+        let mut groups: BTreeMap<Option<String>, Vec<usize>> = BTreeMap::new();
+        for idx in indexes {
+            let group = instance(idx).group.clone();
+            groups.entry(group).or_default().push(idx);
+        }
+
+        groups.into_iter().collect()
+    }
+
+    fn instance_card(&self, ui: &mut Ui, idx: usize) -> InstanceCard {
+        let instances = &self.instances[idx];
+        let mut card = InstanceCard {
+            play: false,
+            action: None,
+        };
+
+        let running = self.is_running(idx);
+        // Only one copy of an instance runs at a time
+        let has_account = self.launch_account(Some(&*instances.i_instance)).is_ok();
+        let playable = has_account && !running && !self.queue.contains(Some(idx));
+
+        let response = Frame::group(ui.style())
+            .show(ui, |ui| {
+                ui.set_width(130.0);
+                ui.vertical(|ui| {
+                    if let Some(image) = &instances.image {
+                        ui.add(image.clone());
+                    }
+                    let label = Label::new(&instances.i_instance.name).truncate(true);
+                    ui.add(label);
+                    ui.label(&instances.i_instance.version.id);
+                    ui.label(&instances.i_instance.jvm.name);
+                    instance_playtime(ui, &instances.i_instance);
+
+                    if running {
+                        ui.colored_label(Color32::LIGHT_GREEN, tr("instance.running"));
+                        ui.horizontal(|ui| {
+                            if ui.button(tr("instance.stop")).clicked() {
+                                card.action = Some(InstanceAction::Stop(idx));
+                            }
+                            if ui.button(tr("instance.console")).clicked() {
+                                card.action = Some(InstanceAction::ShowConsole(idx));
+                            }
+                        });
+                    }
+
+                    let button = Button::new(tr("common.play"));
+                    if ui.add_enabled(playable, button).clicked() {
+                        card.play = true;
+                    }
+                });
+            })
+            .response;
+
+        let over = ui
+            .input(|i| i.pointer.latest_pos())
+            .is_some_and(|pos| response.rect.contains(pos));
+        if over {
+            let (hovering, dropped) = ui.input(|i| {
+                let dropped: Vec<_> = i
+                    .raw
+                    .dropped_files
+                    .iter()
+                    .filter_map(|file| file.path.clone())
+                    .collect();
+                (!i.raw.hovered_files.is_empty(), dropped)
+            });
+
+            if hovering {
+                let visuals = ui.visuals();
+                ui.painter().rect_stroke(
+                    response.rect,
+                    visuals.widgets.noninteractive.rounding,
+                    visuals.selection.stroke,
+                );
+            }
+            if !dropped.is_empty() {
+                card.action = Some(InstanceAction::DropFiles(idx, dropped));
+            }
+        }
+
+        let response = response.interact(Sense::click());
+        // Tab stops on the card after its buttons, Enter plays it
+        if response.has_focus() {
+            let visuals = ui.visuals();
+            ui.painter().rect_stroke(
+                response.rect,
+                visuals.widgets.noninteractive.rounding,
+                visuals.selection.stroke,
+            );
+            if playable && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                card.play = true;
+            }
+        }
+
+        response.context_menu(|ui| {
+            let mut item = |ui: &mut Ui, text: &str, action: InstanceAction| {
+                if ui.button(text).clicked() {
+                    card.action = Some(action);
+                    ui.close_menu();
+                }
+            };
+
+            item(
+                ui,
+                tr("instance.open_folder"),
+                InstanceAction::OpenFolder(idx),
+            );
+            item(
+                ui,
+                tr("instance.open_mods_folder"),
+                InstanceAction::OpenModsFolder(idx),
+            );
+            ui.separator();
+            item(ui, tr("instance.edit"), InstanceAction::Edit(idx));
+            item(ui, tr("instance.duplicate"), InstanceAction::Duplicate(idx));
+            item(ui, tr("instance.mods"), InstanceAction::Mods(idx));
+            item(ui, tr("instance.details"), InstanceAction::Details(idx));
+            ui.separator();
+            item(ui, tr("instance.backup"), InstanceAction::Backup(idx));
+            item(ui, tr("instance.restore"), InstanceAction::Restore(idx));
+            ui.separator();
+
+            // The command has the account's name and token in it
+            let has_account = self.launch_account(Some(&*self.instances[idx].i_instance));
+            ui.add_enabled_ui(has_account.is_ok(), |ui| {
+                item(
+                    ui,
+                    tr("instance.launch_command"),
+                    InstanceAction::LaunchCommand(idx, false),
+                );
+                item(
+                    ui,
+                    tr("instance.copy_launch_command"),
+                    InstanceAction::LaunchCommand(idx, true),
+                );
+            });
+            ui.separator();
+            item(ui, tr("common.delete"), InstanceAction::Delete(idx));
+        });
+
+        card
+    }
+
+    fn play(&mut self, idx: usize) {
+        let instance = &self.instances[idx].i_instance;
+        let (name, version) = (instance.name.clone(), instance.version.clone());
+        let loader_update = instance.track_latest_loader && instance.loader().is_some();
+        let id = self.queue.push(Some(idx), name, version, None);
+        if let Some(entry) = self.queue.get_mut(id) {
+            entry.prepare.loader_update = loader_update;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.update_instance(idx, |instance| instance.last_played = Some(now));
+    }
+
+    fn is_running(&self, index: usize) -> bool {
+        self.consoles
+            .borrow()
+            .iter()
+            .any(|console| console.instance == Some(index) && console.running())
+    }
+
+    /// Changes an instance without reloading its icon like [`Self::replace_instance`] does
+    fn update_instance(&mut self, index: usize, f: impl FnOnce(&mut Instance)) {
+        let mut instance = Instance::clone(&self.launcher_data.instances[index]);
+        f(&mut instance);
+
+        let instance = Arc::new(instance);
+        self.launcher_data.instances[index] = instance.clone();
+        self.instances[index].i_instance = instance;
+        self.data_updated = true;
+    }
+
+    fn push_instance(&mut self, instance: Arc<Instance>) {
+        self.launcher_data.instances.push(instance.clone());
+        self.instances.push(EguiInstance::new(instance));
+        self.data_updated = true;
+    }
+
+    fn replace_instance(&mut self, index: usize, instance: Arc<Instance>) {
+        self.launcher_data.instances[index] = instance.clone();
+        self.instances[index] = EguiInstance::new(instance);
+        self.data_updated = true;
+    }
+
+    fn delete_instance_window(&mut self, ctx: &egui::Context) {
+        let Some((index, remove_folder)) = &mut self.deleting_instance else {
+            return;
+        };
+        let index = *index;
+
+        let mut confirmed = None;
+
+        egui::Window::new(tr("instance.delete_title"))
+            .auto_sized()
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let instance = &self.instances[index].i_instance;
+                ui.label(tr_args(
+                    "instance.delete_confirm",
+                    &[("name", &instance.name)],
+                ));
+                ui.checkbox(
+                    remove_folder,
+                    tr_args(
+                        "instance.delete_folder",
+                        &[("path", &instance.path.display())],
+                    ),
+                );
+
+                ui.horizontal(|ui| {
+                    if ui.button(tr("common.delete")).clicked() {
+                        confirmed = Some(true);
+                    }
+
+                    if ui.button(tr("common.cancel")).clicked() {
+                        confirmed = Some(false);
+                    }
+                });
+            });
+
+        match confirmed {
+            Some(true) => {
+                let remove_folder = *remove_folder;
+                self.deleting_instance = None;
+                if let Err(e) = self.delete_instance(index, remove_folder) {
+                    self.errors.push(e.into());
+                }
+            }
+            Some(false) => self.deleting_instance = None,
+            None => {}
+        }
+    }
+
+    /// Copies jars into the mods folder and zips into the resource packs folder,
+    /// mrpacks go to the pack importer as a new instance
+    fn drop_files(&mut self, index: usize, files: Vec<PathBuf>) {
+        let instance = self.instances[index].i_instance.clone();
+        let mut mods = 0;
+        let mut resource_packs = 0;
+        let mut skipped = 0;
+
+        for file in files {
+            let extension = file
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(str::to_ascii_lowercase);
+            let (dir, count) = match extension.as_deref() {
+                Some("jar") => (instance.mods_dir(&self.launcher_path), &mut mods),
+                Some("zip") => (
+                    instance.resource_packs_dir(&self.launcher_path),
+                    &mut resource_packs,
+                ),
+                Some("mrpack") => {
+                    if !self
+                        .pack_import
+                        .as_ref()
+                        .is_some_and(|import| import.installing)
+                    {
+                        self.start_pack_import(file);
+                    }
+                    continue;
+                }
+                _ => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let Some(name) = file.file_name() else {
+                continue;
+            };
+
+            let copied =
+                std::fs::create_dir_all(&dir).and_then(|()| std::fs::copy(&file, dir.join(name)));
+            match copied {
+                Ok(_) => *count += 1,
+                Err(e) => self.errors.push(e.into()),
+            }
+        }
+
+        let name = &instance.name;
+        if mods > 0 {
+            let message = tr_args("drop.mods", &[("count", &mods), ("name", name)]);
+            self.toasts.push(message);
+        }
+        if resource_packs > 0 {
+            let message = tr_args(
+                "drop.resource_packs",
+                &[("count", &resource_packs), ("name", name)],
+            );
+            self.toasts.push(message);
+        }
+        if skipped > 0 {
+            let message = tr_args("drop.skipped", &[("count", &skipped)]);
+            self.toasts.push(message);
+        }
+    }
+
+    fn delete_instance(&mut self, index: usize, remove_folder: bool) -> Result<(), Error> {
+        let instance = self.launcher_data.instances.remove(index);
+        self.instances.remove(index);
+
+        if let Some(browser) = &mut self.mod_browser {
+            if browser.instance == index {
+                self.mod_browser = None;
+            } else if browser.instance > index {
+                browser.instance -= 1;
+            }
+        }
+
+        if let Some(details) = &mut self.details {
+            if details.instance == index {
+                self.details = None;
+            } else if details.instance > index {
+                details.instance -= 1;
+            }
+        }
+
+        // The game keeps running, it just isn't tied to an instance anymore
+        for console in self.consoles.get_mut() {
+            console.instance = match console.instance {
+                Some(current) if current == index => None,
+                Some(current) if current > index => Some(current - 1),
+                current => current,
+            };
+        }
+
+        self.queue.instance_removed(index);
+
+        if self.editing_instance == Some(index) {
+            self.adding_instance = false;
+            self.editing_instance = None;
+        }
+
+        self.data_updated = true;
+
+        // Instances without a folder set share the launcher directory, which is never removed
+        if remove_folder && !instance.path.as_os_str().is_empty() && instance.path.exists() {
+            std::fs::remove_dir_all(&instance.path)?;
+        }
+
+        Ok(())
+    }
+
+    fn remove_account_window(&mut self, ctx: &egui::Context) {
+        let Some(index) = self.removing_account else {
+            return;
+        };
+
+        egui::Window::new(tr("accounts.remove_title"))
+            .auto_sized()
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let name = &self.launcher_data.accounts[index].account.profile.name;
+                ui.label(tr_args("accounts.remove_confirm", &[("name", name)]));
+
+                ui.horizontal(|ui| {
+                    if ui.button(tr("common.remove")).clicked() {
+                        self.remove_account(index);
+                        self.removing_account = None;
+                    }
+
+                    if ui.button(tr("common.cancel")).clicked() {
+                        self.removing_account = None;
+                    }
+                });
+            });
+    }
+
+    fn account_view_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.account_view else {
+            return;
+        };
 
-            let maybe_total = self.data.total_libraries.load(Ordering::Relaxed);
-            let finished = self.data.finished_libraries.load(Ordering::Relaxed);
+        let account = &self.launcher_data.accounts[view.account].account;
+        let action = view.show(ctx, &account.profile);
 
-            // Ensure we're not dividing by 0
-            let total = if maybe_total == 0 { 1 } else { maybe_total };
-            let string = format!("Library Progress: {:.2} %", percentage(finished, total));
-            ui.label(string);
+        if !view.open {
+            self.account_view = None;
+            return;
+        }
 
-            let maybe_total = self.data.total_assets.load(Ordering::Relaxed);
-            let finished = self.data.finished_assets.load(Ordering::Relaxed);
+        let client = self.client.clone();
+        let token = account.access_token.clone();
 
-            // Ensure we're not dividing by 0
-            let total = if maybe_total == 0 { 1 } else { maybe_total };
-            let string = format!("Asset Progress: {:.2} %", percentage(finished, total));
-            ui.label(string);
+        match action {
+            Some(SkinAction::Preview(url, variant)) => {
+                self.rt.future(skin_preview(client, url, variant));
+            }
+            Some(SkinAction::Upload(path, variant)) => {
+                view.busy = true;
+                self.rt.future(upload_skin(client, token, path, variant));
+            }
+            Some(SkinAction::ChangeVariant(url, variant)) => {
+                view.busy = true;
+                self.rt.future(change_skin(client, token, url, variant));
+            }
+            Some(SkinAction::Reset) => {
+                view.busy = true;
+                self.rt.future(reset_skin(client, token));
+            }
+            Some(SkinAction::Cape(cape_id)) => {
+                view.busy = true;
+                self.rt.future(set_cape(client, token, cape_id));
+            }
+            None => {}
+        }
+    }
 
-            if self.data.jar_path.is_none() {
-                let maybe_total = self.data.total_jar.load(Ordering::Relaxed);
-                let finished = self.data.finished_jar.load(Ordering::Relaxed);
+    fn remove_account(&mut self, index: usize) {
+        self.launcher_data.remove_account(index, &self.secrets);
 
-                // Ensure we're not dividing by 0
-                let total = if maybe_total == 0 { 1 } else { maybe_total };
-                let string = format!("Jar Progress: {:.2} %", percentage(finished, total));
-                ui.label(string);
-            } else {
-                ui.label("Jar Progress: 100.00%");
+        self.player.account = match self.player.account {
+            Some(current) if current == index => None,
+            // Accounts after the removed one shift down by one
+            Some(current) if current > index => Some(current - 1),
+            current => current,
+        };
+
+        if let Some(view) = &mut self.account_view {
+            match view.account {
+                current if current == index => self.account_view = None,
+                current if current > index => view.account -= 1,
+                _ => {}
             }
+        }
 
-            ctx.request_repaint();
-        });
+        self.data_updated = true;
     }
+}
 
-    fn account_picker(&mut self, ui: &mut Ui) {
-        let frame = Frame::canvas(ui.style())
-            .inner_margin(Margin::ZERO)
-            .stroke(Stroke::NONE);
+/// The runtime component and major version the game needs, versions before 1.17 don't say and use 8
+fn required_java(json: &VersionJson) -> (String, u32) {
+    match &json.java_version {
+        Some(java) => (java.component.clone(), java.major_version as u32),
+        None => (java::component_for_major(8).to_string(), 8),
+    }
+}
 
-        egui::TopBottomPanel::new(Bottom, "Bottom Panel")
-            .frame(frame)
-            .show_separator_line(false)
-            .show_inside(ui, |ui| {
-                ui.horizontal(|ui| {
-                    let button = Button::new("➕").small();
+/// Shows the progress while a runtime downloads, returns whether installing was clicked otherwise
+fn java_install_button(ui: &mut Ui, major: u32, install: &Option<JavaInstall>) -> bool {
+    let Some(install) = install else {
+        return ui
+            .button(tr_args("java.install", &[("major", &major)]))
+            .clicked();
+    };
+
+    let snapshot = install.stats.snapshot();
+    ui.add(
+        egui::ProgressBar::new(snapshot.fraction())
+            .desired_width(150.0)
+            .text(tr_args("java.progress", &[("major", &install.major)])),
+    );
+    ui.ctx().request_repaint();
+    false
+}
 
-                    if ui.add_enabled(!self.adding_account, button).clicked() {
-                        self.rt.send_with_message(Message {
-                            path: self.launcher_path.clone(),
-                            contents: Contents::Auth(None),
-                        });
-                        self.adding_account = true;
-                    }
+fn progress_row(ui: &mut Ui, label: &str, stats: &DownloadStats) {
+    let snapshot = stats.snapshot();
 
-                    if let Some(acc_idx) = &mut self.player.account {
-                        let name = &self.launcher_data.accounts[*acc_idx].account.profile.name;
+    let mut text = format!(
+        "{} / {}",
+        format_bytes(snapshot.finished as f64),
+        format_bytes(snapshot.total as f64)
+    );
+    if snapshot.bytes_per_second > 0.0 {
+        text.push_str(&format!(", {}/s", format_bytes(snapshot.bytes_per_second)));
+    }
+    if let Some(eta) = snapshot.eta {
+        let secs = eta.as_secs();
+        text.push_str(&format!(", {}:{:02} left", secs / 60, secs % 60));
+    }
 
-                        egui::ComboBox::from_id_source("Account Picker")
-                            .width(ui.available_width() * 0.80)
-                            .selected_text(name)
-                            .show_index(ui, acc_idx, self.launcher_data.accounts.len(), |idx| {
-                                &self.launcher_data.accounts[idx].account.profile.name
-                            });
-                    } else if self.launcher_data.accounts.is_empty() {
-                        ui.label("No Accounts");
-                    } else {
-                        self.player.account = Some(0)
-                    };
+    ui.label(label);
+    ui.add(
+        egui::ProgressBar::new(snapshot.fraction())
+            .desired_width(300.0)
+            .text(text),
+    );
+    ui.end_row();
+}
 
-                    let button = Button::new("➖").small();
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
 
-                    if ui.add_enabled(!self.adding_account, button).clicked() {
-                        todo!()
-                    }
-                });
-            });
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{value:.0} {}", UNITS[unit])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
     }
 }
 
@@ -519,44 +3008,111 @@ fn send_message<R, M>(
     R: Send,
     M: Clone + Send + Sync,
 {
-    rt.send_with_message(Message {
-        path: launcher_path.clone(),
-        contents,
-    });
+    // Launching can be waiting on a token refresh
+    let priority = match contents {
+        Contents::Auth(_) => Priority::High,
+        _ => Priority::Normal,
+    };
+    rt.send_with_priority(
+        Message {
+            path: launcher_path.clone(),
+            contents,
+        },
+        priority,
+    );
+}
+
+fn fetch_news(
+    rt: &async_bridge::Runtime<Message, Response, State>,
+    client: &Client,
+    path: &Arc<PathBuf>,
+) {
+    let (client, path) = (client.clone(), path.clone());
+    rt.future_with_timeout(
+        NEWS_TIMEOUT,
+        move || get_news(client.clone(), path.clone()),
+        |timed_out| Response::TimedOut(Retry::News, timed_out),
+    );
 }
 
 impl eframe::App for LauncherGui {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        if let Err(e) = self.update_state(ctx) {
-            dbg!("{e}");
-            self.current_error = Some(e);
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Downloads and installs can be halfway through writing a file
+        if !self.rt.shutdown(Duration::from_secs(5)) {
+            eprintln!("Some tasks didn't finish before exiting");
         }
 
-        if let Some(error) = &self.current_error {
-            egui::Window::new("Help").auto_sized().show(ctx, |ui| {
-                ui.label(error.to_string());
-            });
+        let staged = self
+            .update
+            .as_ref()
+            .and_then(|banner| banner.staged.as_ref());
+        if let Some(staged) = staged {
+            if let Err(e) = update::replace_exe(staged) {
+                eprintln!("Installing the update failed: {e}");
+            }
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let version = self
+            .data
+            .versions
+            .as_ref()
+            .and_then(|versions| versions.versions.get(self.data.selected_version))
+            .map(|version| version.id.clone())
+            // The manifest hasn't loaded yet, keep what was restored
+            .or_else(|| self.restore_version.clone());
+        let account = self
+            .player
+            .account
+            .map(|idx| self.launcher_data.accounts[idx].account.profile.id.clone());
+
+        eframe::set_value(storage, eframe::APP_KEY, &UiState { version, account });
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if let Err(failure) = self.update_state(ctx) {
+            eprintln!("{}", failure.error);
+            self.errors.push(failure);
+        }
+
+        if let Some(retry) = errors::banner(ctx, &mut self.errors) {
+            self.retry(retry);
+        }
+
+        if let Some(banner) = self.update.as_mut().filter(|banner| banner.open) {
+            if let Some(UpdateAction::Stage(asset)) = banner.show(ctx) {
+                banner.staging = true;
+                self.rt.future(stage_update(self.client.clone(), asset));
+            }
         }
 
         if self.adding_account {
-            egui::Window::new("Login").auto_sized().show(ctx, |ui| {
-                if let (Some(url), Some(code)) = (&self.player.url, &self.player.code) {
-                    let hyper = egui::Hyperlink::from_label_and_url("Click here to login", url);
-                    let label = Label::new(code).sense(Sense::click());
-                    let label = ui.add(label).on_hover_ui(|ui| {
-                        ui.label("Copy this token into the site below!");
-                    });
+            egui::Window::new(tr("login.title"))
+                .auto_sized()
+                .show(ctx, |ui| {
+                    if let (Some(url), Some(code)) = (&self.player.url, &self.player.code) {
+                        let hyper = egui::Hyperlink::from_label_and_url(tr("login.link"), url);
+                        let label = Label::new(code).sense(Sense::click());
+                        let label = ui.add(label).on_hover_ui(|ui| {
+                            ui.label(tr("login.copy_hint"));
+                        });
 
-                    if label.clicked() {
-                        ctx.copy_text(code.to_string());
+                        if label.clicked() {
+                            ctx.copy_text(code.to_string());
+                        }
+                        ui.add(hyper);
+                    } else {
+                        ui.label(tr("login.loading"));
                     }
-                    ui.add(hyper);
-                } else {
-                    ui.label("Loading code and url, please wait...");
-                }
-            });
+                });
         }
 
+        self.remove_account_window(ctx);
+        self.account_view_window(ctx);
+        #[cfg(feature = "offline")]
+        self.offline_account_window(ctx);
+
         let size = ctx.input(|i| i.screen_rect());
         let width = size.width();
         // let height = size.height();
@@ -565,9 +3121,10 @@ impl eframe::App for LauncherGui {
             style.spacing.indent = 0.0;
         });
 
+        // egui remembers the width once it's resized
         egui::SidePanel::left("General Panel")
-            .exact_width(width * 0.20)
-            .resizable(false)
+            .default_width(width * 0.20)
+            .resizable(true)
             .show(ctx, |ui| {
                 if let Some(versions) = self.data.versions.take() {
                     self.account_picker(ui);
@@ -576,26 +3133,42 @@ impl eframe::App for LauncherGui {
                     let text = if *index != usize::MAX {
                         &versions.versions[*index].id
                     } else {
-                        "None"
+                        tr("common.none")
                     };
                     let mut changed = false;
-
-                    egui::ComboBox::from_id_source("VersionSelect")
+                    let filter = &mut self.launcher_data.settings.version_filter;
+                    self.data_updated |= filter.toggles(ui);
+                    let filter = *filter;
+
+                    let search = egui::TextEdit::singleline(&mut self.version_search)
+                        .hint_text(tr("common.search_versions"))
+                        .desired_width(ui.available_width());
+                    let search = ui.add(search);
+                    // Enter in the search box plays the picked version
+                    let play_pressed =
+                        search.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                    let picker = egui::ComboBox::from_id_source("VersionSelect")
                         .width(ui.available_width())
                         .selected_text(text)
                         .show_ui(ui, |ui| {
-                            versions
-                                .versions
-                                .iter()
-                                .enumerate()
-                                .filter(|(_, v)| v.version_type == Type::Release)
-                                .for_each(|(idx, val)| {
-                                    if ui.selectable_value(index, idx, &val.id).clicked() {
-                                        changed = true;
-                                    }
-                                });
+                            for (idx, val) in versions.search(filter, &self.version_search) {
+                                if ui.selectable_value(index, idx, &val.id).clicked() {
+                                    changed = true;
+                                }
+                            }
                         });
 
+                    // The arrow keys go through the search results from the box or the picker
+                    if search.has_focus() || picker.response.has_focus() {
+                        let matches: Vec<_> = versions
+                            .search(filter, &self.version_search)
+                            .into_iter()
+                            .map(|(idx, _)| idx)
+                            .collect();
+                        changed |= step_selection(ui, &matches, index);
+                    }
+
                     if changed {
                         let version = &versions.versions[*index];
                         let launcher = self.launcher.clone();
@@ -605,9 +3178,6 @@ impl eframe::App for LauncherGui {
                         if let Some(json) = &self.data.version_json {
                             if version.id != json.id() {
                                 self.data.version_json = None;
-                                self.data.class_path = None;
-                                self.data.jar_path = None;
-                                self.data.assets = false;
                                 self.rt.future(get_version(launcher, version, path));
                             }
                         } else {
@@ -618,71 +3188,110 @@ impl eframe::App for LauncherGui {
                     let selected_text = if let Some(jvm_index) = self.jvm_index {
                         &self.launcher_data.jvms[jvm_index].name
                     } else {
-                        "Default"
+                        tr("common.default")
                     };
 
                     egui::ComboBox::from_id_source("Java Selector")
                         .wrap(true)
                         .selected_text(selected_text)
                         .show_ui(ui, |ui| {
-                            if ui.button("Default").clicked() {
+                            if ui.button(tr("common.default")).clicked() {
                                 self.jvm_index = None;
-                                let (_vendor, version) = get_vendor_major_version("java");
-                                self.java_version = version;
+                                let default = self.launcher_data.settings.jvm_path();
+                                self.java_version = java_major_version(default);
                             }
 
                             for (index, jvm) in self.launcher_data.jvms.iter().enumerate() {
                                 if ui.button(jvm.name.as_str()).clicked() {
                                     self.jvm_index = Some(index);
-                                    let (_vendor, version) = get_vendor_major_version(&jvm.path);
-                                    self.java_version = version;
+                                    self.java_version = java_major_version(&jvm.path);
                                 }
                             }
                         });
 
                     if self.java_version != u32::MAX {
-                        ui.label(format!("Java Version: {}", self.java_version));
+                        ui.label(tr_args("java.version", &[("version", &self.java_version)]));
                     } else {
-                        ui.label("No Java Version");
+                        ui.label(tr("java.none"));
                     };
 
-                    if ui.button("Add Java Version").clicked() {
+                    let missing_java = self
+                        .data
+                        .version_json
+                        .as_deref()
+                        .map(required_java)
+                        .filter(|(_, major)| *major != self.java_version);
+                    if let Some((component, major)) = missing_java {
+                        if java_install_button(ui, major, &self.java_install) {
+                            self.start_java_install(component, major, false);
+                        }
+                    }
+
+                    if ui.button(tr("java.add")).clicked() {
                         if let Some(path) = rfd::FileDialog::new().pick_file() {
-                            let path = path.display().to_string();
-                            let (vendor, version) = get_vendor_major_version(&path);
-                            self.launcher_data.jvms.push(Rc::new(Jvm {
-                                path,
-                                name: format!("{vendor} {version}"),
-                            }));
-                            self.data_updated = true;
+                            match Jvm::detect(path.display().to_string()) {
+                                Ok(jvm) => {
+                                    self.launcher_data.jvms.push(Arc::new(jvm));
+                                    self.data_updated = true;
+                                }
+                                Err(e) => self.errors.push(Error::from(e).into()),
+                            }
                         }
                     }
 
-                    let button = Button::new("Play");
+                    let button = Button::new(tr("common.play"));
 
                     if let Some(version_json) = &self.data.version_json {
-                        let enabled = !self.data.launching && self.player.account.is_some();
-                        let enabled = ui.add_enabled(enabled, button);
-
-                        if enabled.clicked() {
-                            self.prepare_launch(version_json, &versions);
-                            self.data.launching = true;
-                            self.quick_playing = true;
+                        let enabled = self.player.account.is_some() && !self.queue.contains(None);
+                        let clicked = ui.add_enabled(enabled, button).clicked();
+
+                        if clicked || (enabled && play_pressed) {
+                            let version = versions.versions[self.data.selected_version].clone();
+                            let name = tr_args("common.minecraft", &[("version", &version.id)]);
+                            self.queue
+                                .push(None, name, version, Some(version_json.clone()));
                         }
                     } else {
                         ui.add_enabled(false, button);
                     }
 
-                    let button = Button::new("Add Instance");
+                    if ui
+                        .add_enabled(!self.settings_open, Button::new(tr("settings.title")))
+                        .clicked()
+                    {
+                        self.settings_open = true;
+                    }
+
+                    let button = Button::new(tr("main.import_modpack"));
+                    if ui.add_enabled(self.pack_import.is_none(), button).clicked() {
+                        let pick = rfd::FileDialog::new()
+                            .add_filter("Modpack", &["mrpack", "zip"])
+                            .pick_file();
+                        if let Some(path) = pick {
+                            self.start_pack_import(path);
+                        }
+                    }
+
+                    let button = Button::new(tr("main.import_multimc"));
+                    if ui
+                        .add_enabled(self.multimc_import.is_none(), button)
+                        .clicked()
+                    {
+                        self.multimc_import = Some(MultiMcImport::default());
+                    }
+
+                    let button = Button::new(tr("main.add_instance"));
 
                     if ui.add_enabled(!self.adding_instance, button).clicked() {
                         self.adding_instance = true;
+                        self.editing_instance = None;
                         self.temp_instance = Default::default();
+                        self.temp_version_json = None;
                     }
 
                     self.data.versions = Some(versions);
                 } else {
-                    let mut loading = "Loading".to_string();
+                    let mut loading = tr("main.loading").to_string();
                     let elapsed = self.loading_place.elapsed().unwrap();
                     for _ in 0..elapsed.as_secs() {
                         loading.push('.');
@@ -697,16 +3306,67 @@ impl eframe::App for LauncherGui {
             });
 
         if self.adding_instance {
-            egui::Window::new("Adding Instance").show(ctx, |ui| {
+            let title = if self.editing_instance.is_some() {
+                tr("editor.editing")
+            } else {
+                tr("editor.adding")
+            };
+
+            let mut saved = None;
+            let mut picked_version = None;
+            let mut install_java = false;
+            let mut choose_icon = false;
+
+            // Only offered once the version's json says which Java it needs
+            let missing_java = self
+                .temp_version_json
+                .as_deref()
+                .map(required_java)
+                .filter(|(_, major)| self.java_major(&self.temp_instance.jvm().path) != *major);
+
+            egui::Window::new(title).show(ctx, |ui| {
                 let tmp = &mut self.temp_instance;
 
                 ui.horizontal(|ui| {
-                    ui.label("Name: ");
+                    ui.label(tr("editor.name"));
                     ui.text_edit_singleline(tmp.name_mut());
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("JVM: ");
+                    ui.label(tr("editor.group"));
+                    ui.text_edit_singleline(tmp.group_mut());
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("editor.account"));
+                    let accounts = &self.launcher_data.accounts;
+                    let selected_text = match &tmp.account {
+                        Some(id) => accounts
+                            .iter()
+                            .find(|acc| acc.account.profile.id == *id)
+                            .map_or(tr("editor.account_removed"), |acc| {
+                                acc.account.profile.name.as_str()
+                            }),
+                        None => tr("editor.account_selected"),
+                    };
+                    egui::ComboBox::from_id_source("Account Select")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut tmp.account,
+                                None,
+                                tr("editor.account_selected"),
+                            );
+                            for acc in accounts {
+                                let profile = &acc.account.profile;
+                                let id = Some(profile.id.clone());
+                                ui.selectable_value(&mut tmp.account, id, profile.name.as_str());
+                            }
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("editor.jvm"));
 
                     let selected_text = tmp.jvm().name.as_str();
 
@@ -714,7 +3374,7 @@ impl eframe::App for LauncherGui {
                         .wrap(true)
                         .selected_text(selected_text)
                         .show_ui(ui, |ui| {
-                            if ui.button("Default").clicked() {
+                            if ui.button(tr("common.default")).clicked() {
                                 tmp.jvm = Default::default();
                             }
 
@@ -724,40 +3384,56 @@ impl eframe::App for LauncherGui {
                                 }
                             }
                         });
+
+                    if let Some((_, major)) = &missing_java {
+                        install_java = java_install_button(ui, *major, &self.java_install);
+                    }
                 });
 
                 ui.horizontal(|ui| {
-                    let label = Label::new("Select Icon Path").sense(Sense::click());
-                    if ui.add(label).clicked() {
-                        if let Some(path) = rfd::FileDialog::new().pick_file() {
-                            tmp.image = Some(path.to_string_lossy().to_string());
-                        }
+                    ui.label(tr("editor.icon"));
+                    if let Some(image) = tmp.image() {
+                        let image = Image::from_uri(format!("file://{image}"))
+                            .fit_to_exact_size([24.0, 24.0].into());
+                        ui.add(image);
+                    }
+                    if ui.button(tr("editor.choose_icon")).clicked() {
+                        choose_icon = true;
                     }
                 });
 
+                let filter = &mut self.launcher_data.settings.version_filter;
+                self.data_updated |= filter.toggles(ui);
+                let filter = *filter;
+
                 ui.horizontal(|ui| {
                     if let Some(versions) = &self.data.versions {
+                        let search = egui::TextEdit::singleline(&mut self.instance_version_search)
+                            .hint_text(tr("common.search_versions"));
+                        ui.add(search);
+
                         let selected_text = if let Some(v) = tmp.version() {
                             v.id.as_str()
                         } else {
-                            "None"
+                            tr("common.none")
                         };
 
                         egui::ComboBox::from_id_source("VersionSelect")
                             .selected_text(selected_text)
                             .show_ui(ui, |ui| {
-                                let iter = versions.versions.iter();
-                                iter.for_each(|version| {
+                                let query = &self.instance_version_search;
+                                for (_, version) in versions.search(filter, query) {
                                     if ui.button(&version.id).clicked() {
                                         tmp.version = Some(version.clone());
+                                        picked_version = Some(version.clone());
                                     };
-                                })
+                                }
                             });
                     }
                 });
 
                 ui.horizontal(|ui| {
-                    if ui.button("Select Path").clicked() {
+                    if ui.button(tr("editor.select_path")).clicked() {
                         if let Some(path) = rfd::FileDialog::new().pick_folder() {
                             tmp.path = path.to_string_lossy().to_string();
                         }
@@ -767,204 +3443,535 @@ impl eframe::App for LauncherGui {
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Jvm Args: ");
+                    ui.label(tr("settings.jvm_args"));
                     ui.text_edit_singleline(tmp.jvm_args_mut());
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Env Args: ");
+                    ui.label(tr("editor.env_args"));
                     ui.text_edit_singleline(tmp.env_args_mut());
                 });
 
-                ui.horizontal(|ui| {
-                    ui.radio_value(tmp.mod_loader_mut(), None, "Vanilla");
-                    ui.radio_value(tmp.mod_loader_mut(), Some(Loader::Fabric), "Fabric");
-                });
+                let mut own_memory = tmp.memory.is_some();
+                if ui
+                    .checkbox(&mut own_memory, tr("editor.override_memory"))
+                    .changed()
+                {
+                    tmp.memory = own_memory.then(|| self.launcher_data.settings.heap());
+                }
+                if let Some(memory) = &mut tmp.memory {
+                    memory_sliders(ui, &mut memory.min, &mut memory.max, self.system_memory);
+                }
 
-                if ui.button("Add").clicked() {
-                    let tmp = std::mem::take(tmp);
-                    let instance: Rc<Instance> = Rc::new(tmp.build());
+                ui.horizontal(|ui| {
+                    ui.label(tr("editor.loader"));
 
-                    self.launcher_data.instances.push(instance.clone());
+                    let selected_text = tmp
+                        .mod_loader()
+                        .map_or(tr("editor.vanilla"), |loader| loader.name());
+                    egui::ComboBox::from_id_source("Loader Select")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(tmp.mod_loader_mut(), None, tr("editor.vanilla"));
+                            for loader in Loader::ALL {
+                                ui.selectable_value(
+                                    tmp.mod_loader_mut(),
+                                    Some(loader),
+                                    loader.name(),
+                                );
+                            }
+                        });
 
-                    let image = instance.image.as_ref().map(|image_path| {
-                        Image::from_uri(format!("file://{}", image_path.to_string_lossy()))
+                    let Some(loader) = tmp.mod_loader else {
+                        return;
+                    };
+                    let listed = self.loader_versions.as_ref().filter(|listed| {
+                        listed.loader == loader
+                            && tmp
+                                .version()
+                                .as_ref()
+                                .is_some_and(|v| v.id == listed.game_version)
                     });
+                    match listed.and_then(|listed| listed.versions.as_ref()) {
+                        Some(versions) if versions.is_empty() => {
+                            ui.label(tr("editor.no_loader_versions"));
+                        }
+                        Some(versions) => {
+                            let selected_text =
+                                tmp.loader_version.as_deref().unwrap_or(tr("common.none"));
+                            egui::ComboBox::from_id_source("Loader Version Select")
+                                .selected_text(selected_text)
+                                .show_ui(ui, |ui| {
+                                    let shown = versions.iter().filter(|version| {
+                                        self.unstable_loaders || version.is_stable()
+                                    });
+                                    for version in shown {
+                                        let picked = Some(version.version.clone());
+                                        ui.selectable_value(
+                                            &mut tmp.loader_version,
+                                            picked,
+                                            version.version.as_str(),
+                                        );
+                                    }
+                                });
+                            ui.checkbox(&mut self.unstable_loaders, tr("editor.unstable_loaders"));
+                            ui.checkbox(
+                                &mut tmp.track_latest_loader,
+                                tr("editor.track_latest_loader"),
+                            );
+                        }
+                        None if tmp.version().is_some() => {
+                            ui.spinner();
+                        }
+                        None => {}
+                    }
+                });
 
-                    let egui_i = EguiInstance {
-                        i_instance: instance.clone(),
-                        image,
-                        version_json: Cell::new(None),
-                        launching: false.into(),
-                        prepared: false.into(),
+                // Newer versions have working skins, the fix would do nothing
+                if tmp.version().as_deref().is_some_and(skin_fix::needed) {
+                    ui.checkbox(&mut tmp.legacy_skin_fix, tr("editor.legacy_skin_fix"));
+                }
+
+                ui.horizontal(|ui| {
+                    let confirm = if self.editing_instance.is_some() {
+                        tr("editor.save")
+                    } else {
+                        tr("editor.add")
                     };
 
-                    self.instances.push(egui_i);
-                    self.adding_instance = false;
-                    self.data_updated = true;
-                }
+                    let button = Button::new(confirm);
+                    // Loaders can't be installed without a version
+                    let ready = tmp.version().is_some()
+                        && (tmp.mod_loader().is_none() || tmp.loader_version.is_some());
+                    if ui.add_enabled(ready, button).clicked() {
+                        saved = Some(Arc::new(std::mem::take(tmp).build()));
+                        self.adding_instance = false;
+                    }
+
+                    if ui.button(tr("common.cancel")).clicked() {
+                        self.adding_instance = false;
+                        self.editing_instance = None;
+                    }
+                });
             });
+
+            self.update_editor_loader();
+
+            if let Some(instance) = saved {
+                self.install_instance_loader(&instance);
+                match self.editing_instance.take() {
+                    Some(index) => {
+                        let old = &self.instances[index].i_instance;
+                        let updated = old.version.id != instance.version.id
+                            || old.mod_loader != instance.mod_loader
+                            || old.loader_version != instance.loader_version;
+                        // The files only change at the next launch, the backup starts before that
+                        if updated && self.launcher_data.settings.backup_before_update {
+                            self.backup_instance(index);
+                        }
+                        self.replace_instance(index, instance);
+                    }
+                    None => self.push_instance(instance),
+                }
+            }
+
+            if let Some(version) = picked_version {
+                self.fetch_editor_version(version);
+            }
+
+            if let (true, Some((component, major))) = (install_java, missing_java) {
+                self.start_java_install(component, major, true);
+            }
+
+            if choose_icon {
+                let path = self.temp_instance.path();
+                let instance_dir = if path.is_empty() {
+                    self.launcher_path.to_path_buf()
+                } else {
+                    PathBuf::from(path)
+                };
+                self.icon_picker = Some(IconPicker::new(&instance_dir));
+            }
         }
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::new(Vec2b { x: false, y: true }).show(ui, |ui| {
-                let (response, _painter) = ui.allocate_painter(
-                    Vec2::new(ui.available_width(), ui.available_height()),
-                    Sense::hover(),
-                );
-                let to_screen = RectTransform::from_to(
-                    Rect::from_min_size(Pos2::ZERO, response.rect.size()),
-                    response.rect,
-                );
+        self.icon_picker_window(ctx);
 
-                let mut max_idx = 0;
-                let mut row = 0;
+        let mut instance_action = None;
+        let mut played = None;
 
-                let len = ui.fonts(|fonts| fonts.glyph_width(&FontId::default(), 'W') * 10.0);
+        let show_news = self.launcher_data.settings.show_news;
+        // Without instances the news takes the central panel instead
+        if show_news && !self.instances.is_empty() {
+            egui::SidePanel::right("News Panel")
+                .default_width(width * 0.25)
+                .resizable(true)
+                .show(ctx, |ui| self.news_contents(ui));
+        }
 
-                ui.style_mut().spacing = Spacing::default();
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if show_news && self.instances.is_empty() {
+                self.news_contents(ui);
+                return;
+            }
 
-                for (idx, instances) in self.instances.iter().enumerate() {
-                    if ui.available_width() - len * (idx - max_idx) as f32 <= len {
-                        row += 1;
-                        max_idx = idx;
-                    }
+            ui.horizontal(|ui| {
+                let search = egui::TextEdit::singleline(&mut self.instance_search)
+                    .hint_text(tr("instance.search"));
+                ui.add(search);
+
+                let sort = &mut self.launcher_data.settings.instance_sort;
+                let before = *sort;
+                egui::ComboBox::from_id_source("Instance Sort")
+                    .selected_text(sort.name())
+                    .show_ui(ui, |ui| {
+                        for option in [
+                            InstanceSort::Name,
+                            InstanceSort::LastPlayed,
+                            InstanceSort::Version,
+                        ] {
+                            ui.selectable_value(sort, option, option.name());
+                        }
+                    });
+                self.data_updated |= *sort != before;
+            });
 
-                    let mut clicked = false;
+            ui.separator();
 
-                    ui.put(
-                        Rect {
-                            min: to_screen.transform_pos(Pos2 {
-                                x: 10.0 + len * (idx - max_idx) as f32,
-                                y: 0.0 + (row * 100) as f32,
-                            }),
-                            max: to_screen.transform_pos(Pos2 {
-                                x: 150.0 + len * (idx - max_idx) as f32,
-                                y: 100.0 + (row * 100) as f32,
-                            }),
-                        },
-                        |ui: &mut Ui| {
-                            ui.horizontal(|ui| {
-                                ui.add_space(10.0);
-                                ui.vertical(|ui| {
-                                    ui.style_mut().visuals.window_fill = Color32::WHITE;
-
-                                    if let Some(image) = &instances.image {
-                                        ui.add(image.clone());
-                                    }
-                                    let label =
-                                        Label::new(&instances.i_instance.name).truncate(true);
-                                    ui.add(label);
-                                    ui.label(&instances.i_instance.version.id);
-                                    ui.label(&instances.i_instance.jvm.name);
-
-                                    let button = Button::new("Play");
-
-                                    if let Some(manifest) = &self.data.versions {
-                                        let enabled =
-                                            !self.data.launching && self.player.account.is_some();
-
-                                        let res = ui.add_enabled(enabled, button);
-
-                                        if res.clicked() {
-                                            let launcher = self.launcher.clone();
-                                            let version = instances.i_instance.version.clone();
-                                            let path = self.launcher_path.clone();
-                                            self.rt.future(get_version(launcher, version, path));
-                                            instances.launching.replace(true);
-                                            instances.prepared.replace(false);
-                                            clicked = true
-                                        }
-
-                                        if let Some(json) = instances.version_json.take() {
-                                            if instances.launching.get()
-                                                && !instances.prepared.get()
-                                            {
-                                                self.prepare_launch(&json, manifest);
-                                                instances.prepared.replace(true);
-                                            } else {
-                                                let maybe_launched = self.maybe_launch(
-                                                    &json,
-                                                    Some(&instances.i_instance.jvm),
-                                                    true,
-                                                );
-
-                                                instances.launching.replace(maybe_launched);
-                                            }
-
-                                            instances.version_json.set(Some(json));
-                                        }
-                                    } else {
-                                        ui.add_enabled(false, button);
-                                    }
-                                });
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let groups = self.instance_groups();
+                let grouped = groups.iter().any(|(group, _)| group.is_some());
 
-                                ui.with_layout(Layout::right_to_left(Align::BOTTOM), |ui| {
-                                    ui.separator();
-                                });
-                            })
-                            .response
-                        },
-                    );
+                for (group, indexes) in groups {
+                    let cards = |ui: &mut Ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            for idx in indexes {
+                                let card = self.instance_card(ui, idx);
+                                if card.play {
+                                    played = Some(idx);
+                                }
+                                if card.action.is_some() {
+                                    instance_action = card.action;
+                                }
+                            }
+                        });
+                    };
 
-                    if clicked {
-                        self.current_instance = Some(idx);
-                        self.data.launching = true;
+                    match group {
+                        Some(group) => {
+                            egui::CollapsingHeader::new(group)
+                                .default_open(true)
+                                .show(ui, cards);
+                        }
+                        None if grouped => {
+                            egui::CollapsingHeader::new(tr("instance.ungrouped"))
+                                .default_open(true)
+                                .show(ui, cards);
+                        }
+                        None => cards(ui),
                     }
                 }
             });
         });
 
-        if self.data.launching {
-            if let Some(json) = &self.data.version_json {
-                if self.quick_playing {
-                    self.data.launching = self.maybe_launch(json, None, self.data.launching);
-                    self.quick_playing = self.data.launching;
+        if let Some(idx) = played {
+            self.play(idx);
+        }
+
+        match instance_action {
+            Some(InstanceAction::Edit(index)) => {
+                self.temp_instance = InstanceBuilder::from(&*self.instances[index].i_instance);
+                self.editing_instance = Some(index);
+                self.adding_instance = true;
+                let version = self.instances[index].i_instance.version.clone();
+                self.fetch_editor_version(version);
+            }
+            Some(InstanceAction::Duplicate(index)) => {
+                let mut copy = Instance::clone(&self.instances[index].i_instance);
+                copy.name = format!("{} (Copy)", copy.name);
+                self.push_instance(Arc::new(copy));
+            }
+            Some(InstanceAction::Delete(index)) => self.deleting_instance = Some((index, false)),
+            Some(InstanceAction::Mods(index)) => {
+                let instance = &self.instances[index].i_instance;
+                let mods_dir = instance.mods_dir(&self.launcher_path);
+                self.mod_browser = Some(ModBrowser::new(index, mods_dir));
+            }
+            Some(InstanceAction::LaunchCommand(index, copy)) => {
+                let future = get_preview_version(
+                    self.launcher.clone(),
+                    self.instances[index].i_instance.version.clone(),
+                    self.launcher_path.clone(),
+                    index,
+                    copy,
+                );
+                self.rt.future(future);
+            }
+            Some(InstanceAction::OpenFolder(index)) => {
+                let folder = self.instances[index]
+                    .i_instance
+                    .directory(&self.launcher_path);
+                if let Err(e) = open_folder(&folder) {
+                    self.errors.push(e.into());
+                }
+            }
+            Some(InstanceAction::OpenModsFolder(index)) => {
+                let mods_dir = self.instances[index]
+                    .i_instance
+                    .mods_dir(&self.launcher_path);
+                if let Err(e) = open_folder(&mods_dir) {
+                    self.errors.push(e.into());
+                }
+            }
+            Some(InstanceAction::Details(index)) => self.open_details(index, Tab::Worlds),
+            Some(InstanceAction::Backup(index)) => self.backup_instance(index),
+            Some(InstanceAction::Restore(index)) => self.open_details(index, Tab::Archives),
+            Some(InstanceAction::Stop(index)) => {
+                for console in self.consoles.get_mut() {
+                    if console.instance == Some(index) {
+                        if let Err(e) = console.kill() {
+                            self.errors.push(e.into());
+                        }
+                    }
+                }
+            }
+            Some(InstanceAction::ShowConsole(index)) => {
+                for console in self.consoles.get_mut() {
+                    if console.instance == Some(index) && console.running() {
+                        console.open = true;
+                    }
                 }
             }
+            Some(InstanceAction::DropFiles(index, files)) => {
+                // Keeps the pack importer from also picking up a dropped zip
+                ctx.input_mut(|i| i.raw.dropped_files.clear());
+                self.drop_files(index, files);
+            }
+            None => {}
+        }
+
+        self.pack_import_window(ctx);
+        self.multimc_import_window(ctx);
+
+        if let Err(e) = self.mod_browser_window(ctx) {
+            self.errors.push(e.into());
+        }
+        if let Err(e) = self.details_window(ctx) {
+            self.errors.push(e.into());
+        }
+
+        if let Some(preview) = &mut self.launch_preview {
+            preview.show(ctx);
+            if !preview.open {
+                self.launch_preview = None;
+            }
+        }
+
+        self.delete_instance_window(ctx);
+        self.settings_window(ctx, frame);
+
+        self.update_queue();
+        if !self.queue.is_empty() {
             self.progress_window(ctx);
         }
 
+        let mut exited = Vec::new();
+        let consoles = self.consoles.get_mut();
+        for console in consoles.iter_mut() {
+            let Some(status) = console.show(ctx) else {
+                continue;
+            };
+
+            if let Some(index) = console.instance {
+                exited.push((index, console.started));
+            }
+
+            if console.hooks().backup_worlds.is_some() {
+                let game_dir = match console.instance {
+                    Some(index) => self.instances[index]
+                        .i_instance
+                        .directory(&self.launcher_path),
+                    None => self.launcher_path.to_path_buf(),
+                };
+                let hooks = console.hooks().clone();
+                self.rt.future(backup_played_worlds(game_dir, hooks));
+            }
+
+            if !status.success() && !console.stopped() {
+                match CrashDialog::new(status, &self.launcher_path, console) {
+                    Ok(crash) => self.crashes.push(crash),
+                    Err(e) => self.errors.push(Error::from(e).into()),
+                }
+            }
+        }
+        // Closed consoles are kept while the game runs so it can still be stopped
+        consoles.retain(|console| console.open || console.running());
+
+        for (index, started) in exited {
+            let now = SystemTime::now();
+            let played = now.duration_since(started).unwrap_or_default().as_secs();
+            let now = now
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            self.update_instance(index, |instance| {
+                instance.playtime += played;
+                instance.last_played = Some(now);
+            });
+        }
+
+        for crash in &mut self.crashes {
+            crash.show(ctx);
+        }
+        self.crashes.retain(|crash| crash.open);
+
+        self.toasts.show(ctx);
+
         if self.data_updated {
-            let bytes = toml::to_string_pretty(&self.launcher_data).unwrap();
-            std::fs::write(
-                self.launcher_path.join("launcher_data.toml"),
-                bytes.as_bytes(),
-            )
-            .unwrap();
+            let file = self.config_dir.join("launcher_data.toml");
+            self.launcher_data.save(&file).unwrap();
             self.data_updated = false;
         }
+        self.save_jobs();
+    }
+}
+
+/// The player's head, or the space it takes so names stay lined up
+fn avatar_image(ui: &mut Ui, avatars: &HashMap<String, Option<TextureHandle>>, profile_id: &str) {
+    let size = egui::vec2(AVATAR_SIZE, AVATAR_SIZE);
+    match avatars.get(profile_id) {
+        Some(Some(texture)) => {
+            ui.add(Image::new(texture).fit_to_exact_size(size));
+        }
+        _ => {
+            ui.allocate_space(size);
+        }
     }
 }
 
-fn check_file() -> Result<(PathBuf, LauncherData), Error> {
-    let app_dir = platform_dirs::AppDirs::new(Some("synth_launcher"), false).unwrap();
+/// Total playtime and how long ago the instance was last played, under its card
+/// Moves to the previous or next option on the up and down arrows, for pickers with keyboard
+/// focus. Returns whether the selection changed
+fn step_selection(ui: &Ui, options: &[usize], selected: &mut usize) -> bool {
+    let step = ui.input(|i| {
+        i.key_pressed(egui::Key::ArrowDown) as isize - i.key_pressed(egui::Key::ArrowUp) as isize
+    });
+    if step == 0 || options.is_empty() {
+        return false;
+    }
 
-    let launcher_data: LauncherData;
-    let launcher_data_file = app_dir.config_dir.join("launcher_data.toml");
+    let next = match options.iter().position(|option| option == selected) {
+        Some(pos) => (pos as isize + step).clamp(0, options.len() as isize - 1) as usize,
+        None => 0,
+    };
+    let changed = options[next] != *selected;
+    *selected = options[next];
+    changed
+}
 
-    if !app_dir.config_dir.try_exists()? {
-        std::fs::create_dir(&app_dir.config_dir)?;
+fn instance_playtime(ui: &mut Ui, instance: &Instance) {
+    let Some(last_played) = instance.last_played else {
+        ui.weak(tr("instance.never_played"));
+        return;
+    };
+
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let ago = now.saturating_sub(last_played);
+
+    ui.weak(tr_args(
+        "instance.playtime",
+        &[("time", &format_duration(instance.playtime))],
+    ));
+    if ago < 60 {
+        ui.weak(tr("instance.played_now"));
+    } else {
+        ui.weak(tr_args(
+            "instance.last_played",
+            &[("time", &format_duration(ago))],
+        ));
     }
+}
 
-    if launcher_data_file.exists() {
-        let buffer = std::fs::read_to_string(&launcher_data_file)?;
-        launcher_data = toml::from_str(&buffer)?;
+/// Rounds down to the largest unit that fits, days keep their hours
+fn format_duration(secs: u64) -> String {
+    let minutes = secs / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+    if days > 0 {
+        tr_args("time.days", &[("days", &days), ("hours", &(hours % 24))])
+    } else if hours > 0 {
+        tr_args(
+            "time.hours",
+            &[("hours", &hours), ("minutes", &(minutes % 60))],
+        )
     } else {
-        launcher_data = LauncherData::default();
-        let mut file = File::create(&launcher_data_file)?;
-        let string = toml::to_string(&launcher_data)?;
-        file.write_all(string.as_bytes())?
+        tr_args("time.minutes", &[("minutes", &minutes)])
+    }
+}
+
+/// Fabric and Quilt are installed with the instance, other loaders import as vanilla with their files
+fn launchable_loader(loader: &Option<(PackLoader, String)>) -> Option<(Loader, String)> {
+    match loader {
+        Some((PackLoader::Fabric, version)) => Some((Loader::Fabric, version.clone())),
+        Some((PackLoader::Quilt, version)) => Some((Loader::Quilt, version.clone())),
+        _ => None,
     }
+}
+
+/// Shows the folder in the system's file manager, creating it first if the game hasn't yet
+fn open_folder(path: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(path)?;
+
+    #[cfg(windows)]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(target_os = "linux")]
+    let program = "xdg-open";
 
-    Ok((app_dir.config_dir, launcher_data))
+    std::process::Command::new(program).arg(path).spawn()?;
+    Ok(())
+}
+
+/// The profile of the instance's loader, None for vanilla instances and ones made before
+/// loaders were installed
+fn loader_profile(
+    launcher_path: &Path,
+    instance: &Instance,
+) -> Result<Option<LoaderProfile>, Error> {
+    let (Some(loader), Some(loader_version)) = (instance.mod_loader, &instance.loader_version)
+    else {
+        return Ok(None);
+    };
+
+    let profile = launcher_core::loader::read_profile(
+        launcher_path,
+        loader,
+        &instance.version.id,
+        loader_version,
+    );
+    match profile {
+        Ok(profile) => Ok(Some(profile)),
+        Err(_) => {
+            let name = format!("{} {loader_version}", loader.name());
+            let message = tr_args("loader.not_installed", &[("loader", &name)]);
+            Err(tokio::io::Error::new(tokio::io::ErrorKind::NotFound, message).into())
+        }
+    }
 }
 
 fn main() {
+    let args = Args::from_env();
+    if args.headless || args.daemon {
+        eprintln!("Only gtk_fe can run without a window, opening the launcher instead");
+    }
+
     eframe::run_native(
         "Test App",
         eframe::NativeOptions::default(),
-        Box::new(|cc| LauncherGui::new(cc)),
+        Box::new(|cc| LauncherGui::new(cc, args)),
     )
     .unwrap();
 }
@@ -977,6 +3984,11 @@ enum Error {
     TomlDE(toml::de::Error),
     TomlSER(toml::ser::Error),
     Profile(launcher_core::account::types::ProfileError),
+    TimedOut(async_bridge::TimedOut),
+    Panicked(async_bridge::Panicked),
+    Jvm(launcher_core::jvm::ProbeError),
+    RateLimited(launcher_core::http::RateLimited),
+    Account(launcher_app::data::AccountError),
 }
 
 impl From<reqwest::Error> for Error {
@@ -1009,6 +4021,18 @@ impl From<toml::ser::Error> for Error {
     }
 }
 
+impl From<launcher_core::jvm::ProbeError> for Error {
+    fn from(value: launcher_core::jvm::ProbeError) -> Self {
+        Error::Jvm(value)
+    }
+}
+
+impl From<launcher_app::data::AccountError> for Error {
+    fn from(value: launcher_app::data::AccountError) -> Self {
+        Error::Account(value)
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str: &dyn std::fmt::Display = match self {
@@ -1018,6 +4042,11 @@ impl std::fmt::Display for Error {
             Error::TomlDE(err) => err,
             Error::TomlSER(err) => err,
             Error::Profile(err) => err,
+            Error::TimedOut(err) => err,
+            Error::Panicked(err) => err,
+            Error::Jvm(err) => err,
+            Error::RateLimited(err) => err,
+            Error::Account(err) => err,
         };
         write!(f, "{}", str)
     }
@@ -1030,6 +4059,7 @@ impl From<launcher_core::Error> for Error {
             launcher_core::Error::Tokio(e) => Error::Tokio(e),
             launcher_core::Error::SerdeJson(e) => Error::SerdeJson(e),
             launcher_core::Error::ProfileError(e) => Error::Profile(e),
+            launcher_core::Error::RateLimited(e) => Error::RateLimited(e),
         }
     }
 }
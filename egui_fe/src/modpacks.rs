@@ -0,0 +1,148 @@
+use crate::i18n::{tr, tr_args};
+use eframe::egui::{self, Button};
+use launcher_core::modpack::{PackFormat, PackInfo, PackLoader};
+use launcher_core::progress::DownloadStats;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A modpack picked for import, read in the background before it can be installed
+pub struct PackImport {
+    pub pack: Arc<PathBuf>,
+    // None until the pack has been read
+    pub info: Option<Arc<PackInfo>>,
+    pub name: String,
+    pub stats: Arc<DownloadStats>,
+    pub installing: bool,
+    // Installs as soon as it's read, for an install the last session didn't finish
+    pub resume: bool,
+}
+
+pub enum ImportAction {
+    Install,
+    Cancel,
+}
+
+impl PackImport {
+    pub fn new(pack: PathBuf) -> Self {
+        Self {
+            pack: Arc::new(pack),
+            info: None,
+            name: String::new(),
+            stats: Arc::new(DownloadStats::new()),
+            installing: false,
+            resume: false,
+        }
+    }
+
+    /// Continues an install from the last session into the instance it was named
+    pub fn resume(pack: PathBuf, name: String) -> Self {
+        Self {
+            name,
+            resume: true,
+            ..Self::new(pack)
+        }
+    }
+
+    /// Where the instance is installed
+    pub fn directory(&self, launcher_path: &Path) -> PathBuf {
+        instance_directory(launcher_path, &self.name)
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<ImportAction> {
+        let mut action = None;
+
+        egui::Window::new(tr("modpack.title"))
+            .auto_sized()
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let Some(info) = &self.info else {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(tr_args(
+                            "modpack.reading",
+                            &[("path", &self.pack.display())],
+                        ));
+                    });
+                    return;
+                };
+
+                let format = match info.format {
+                    PackFormat::Modrinth => "Modrinth",
+                    PackFormat::CurseForge => "CurseForge",
+                };
+                ui.label(format!("{} {} ({format})", info.name, info.version));
+                ui.label(tr_args(
+                    "common.minecraft",
+                    &[("version", &info.game_version)],
+                ));
+
+                let loader = match &info.loader {
+                    Some((loader, version)) => format!("{} {version}", loader_name(*loader)),
+                    None => tr("editor.vanilla").into(),
+                };
+                ui.label(tr_args("modpack.loader", &[("loader", &loader)]));
+                ui.label(tr_args("modpack.files", &[("count", &info.files.len())]));
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("modpack.instance_name"));
+                    ui.add_enabled(!self.installing, egui::TextEdit::singleline(&mut self.name));
+                });
+
+                if self.installing {
+                    let snapshot = self.stats.snapshot();
+                    ui.add(egui::ProgressBar::new(snapshot.fraction()).show_percentage());
+                    ctx.request_repaint();
+                    return;
+                }
+                if std::mem::take(&mut self.resume) {
+                    action = Some(ImportAction::Install);
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    let button = Button::new(tr("modpack.import"));
+                    if ui.add_enabled(!self.name.is_empty(), button).clicked() {
+                        action = Some(ImportAction::Install);
+                    }
+
+                    if ui.button(tr("common.cancel")).clicked() {
+                        action = Some(ImportAction::Cancel);
+                    }
+                });
+            });
+
+        action
+    }
+}
+
+/// A folder named after the instance in `instances`, for instances the launcher creates itself
+pub fn instance_directory(launcher_path: &Path, name: &str) -> PathBuf {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    launcher_path.join("instances").join(name)
+}
+
+pub fn loader_name(loader: PackLoader) -> &'static str {
+    match loader {
+        PackLoader::Fabric => "Fabric",
+        PackLoader::Quilt => "Quilt",
+        PackLoader::Forge => "Forge",
+        PackLoader::NeoForge => "NeoForge",
+    }
+}
+
+/// Whether a dropped or picked file looks like a modpack
+pub fn is_pack(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("mrpack" | "zip")
+    )
+}
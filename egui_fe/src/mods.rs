@@ -0,0 +1,138 @@
+use crate::i18n::{tr, tr_args};
+use crate::instances::Instance;
+use eframe::egui::{self, Button, RichText};
+use launcher_core::modrinth::types::{SearchHit, SearchResponse};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Searches Modrinth and manages the mods of one instance
+pub struct ModBrowser {
+    pub instance: usize,
+    pub mods_dir: Arc<PathBuf>,
+    query: String,
+    pub results: Option<SearchResponse>,
+    pub searching: bool,
+    // Set to send the current query again on the next frame
+    search_again: bool,
+    // Project ids currently downloading
+    pub installing: HashSet<String>,
+    pub open: bool,
+}
+
+pub enum ModAction {
+    Search(String),
+    Install(SearchHit),
+    // Index into the instance's installed mods
+    Uninstall(usize),
+}
+
+impl ModBrowser {
+    pub fn new(instance: usize, mods_dir: PathBuf) -> Self {
+        Self {
+            instance,
+            mods_dir: Arc::new(mods_dir),
+            query: String::new(),
+            results: None,
+            searching: false,
+            search_again: false,
+            installing: HashSet::new(),
+            open: true,
+        }
+    }
+
+    /// Repeats the last search, e.g. after it failed
+    pub fn search_again(&mut self) {
+        self.search_again = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, instance: &Instance) -> Option<ModAction> {
+        let mut action = None;
+        if std::mem::take(&mut self.search_again) && !self.searching {
+            action = Some(ModAction::Search(self.query.clone()));
+        }
+
+        egui::Window::new(tr_args("mods.title", &[("name", &instance.name)]))
+            .id(egui::Id::new("Mod Browser"))
+            .default_size([500.0, 500.0])
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                if instance.mod_loader.is_none() {
+                    ui.label(tr("mods.vanilla"));
+                    return;
+                }
+
+                egui::CollapsingHeader::new(tr_args(
+                    "mods.installed",
+                    &[("count", &instance.mods.len())],
+                ))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for (idx, installed) in instance.mods.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(&installed.title).strong());
+                            ui.label(&installed.version_number);
+                            if ui.small_button(tr("common.remove")).clicked() {
+                                action = Some(ModAction::Uninstall(idx));
+                            }
+                        });
+                    }
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    let search = ui.text_edit_singleline(&mut self.query);
+                    let entered =
+                        search.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let button = ui.add_enabled(!self.searching, Button::new(tr("mods.search")));
+
+                    if (entered || button.clicked()) && !self.searching {
+                        action = Some(ModAction::Search(self.query.clone()));
+                    }
+                });
+
+                if self.searching {
+                    ui.spinner();
+                }
+
+                let Some(results) = &self.results else {
+                    return;
+                };
+
+                ui.label(tr_args("mods.results", &[("count", &results.total_hits)]));
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink(false)
+                    .show(ui, |ui| {
+                        for hit in &results.hits {
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(&hit.title).strong());
+                                ui.label(tr_args("mods.by", &[("author", &hit.author)]));
+
+                                let installed =
+                                    instance.mods.iter().any(|m| m.project_id == hit.project_id);
+
+                                if self.installing.contains(&hit.project_id) {
+                                    ui.spinner();
+                                } else {
+                                    let text = if installed {
+                                        tr("mods.update")
+                                    } else {
+                                        tr("mods.install")
+                                    };
+                                    if ui.button(text).clicked() {
+                                        action = Some(ModAction::Install(hit.clone()));
+                                    }
+                                }
+                            });
+                            ui.label(&hit.description);
+                            ui.label(tr_args("mods.downloads", &[("count", &hit.downloads)]));
+                            ui.separator();
+                        }
+                    });
+            });
+
+        action
+    }
+}
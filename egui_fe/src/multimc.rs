@@ -0,0 +1,151 @@
+use crate::i18n::{tr, tr_args};
+use crate::modpacks::loader_name;
+use async_bridge::ProgressHandle;
+use eframe::egui::{self, Button, Color32};
+use launcher_core::multimc::MultiMcInstance;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Lists the instances of a MultiMC or Prism folder and copies the picked ones over
+pub struct MultiMcImport {
+    pub root: Option<PathBuf>,
+    pub reading: bool,
+    pub instances: Vec<Found>,
+    pub open: bool,
+}
+
+pub struct Found {
+    pub instance: Arc<MultiMcInstance>,
+    pub selected: bool,
+    pub state: CopyState,
+}
+
+pub enum CopyState {
+    Waiting,
+    // The fraction copied
+    Copying(ProgressHandle<f32>),
+    Done,
+    Failed,
+}
+
+pub enum MultiMcAction {
+    Read(PathBuf),
+    // Copies every selected instance that hasn't been imported yet
+    Import,
+}
+
+impl Default for MultiMcImport {
+    fn default() -> Self {
+        Self {
+            root: None,
+            reading: false,
+            instances: Vec::new(),
+            open: true,
+        }
+    }
+}
+
+impl MultiMcImport {
+    pub fn set_instances(&mut self, instances: Vec<MultiMcInstance>) {
+        self.reading = false;
+        self.instances = instances
+            .into_iter()
+            .map(|instance| Found {
+                instance: Arc::new(instance),
+                selected: true,
+                state: CopyState::Waiting,
+            })
+            .collect();
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<MultiMcAction> {
+        let mut action = None;
+        let copying = self
+            .instances
+            .iter()
+            .any(|found| matches!(found.state, CopyState::Copying(_)));
+
+        egui::Window::new(tr("multimc.title"))
+            .default_size([400.0, 400.0])
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let button = Button::new(tr("multimc.choose"));
+                    if ui.add_enabled(!copying, button).clicked() {
+                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                            action = Some(MultiMcAction::Read(path));
+                        }
+                    }
+                    match &self.root {
+                        Some(root) => ui.label(root.display().to_string()),
+                        None => ui.label(tr("multimc.hint")),
+                    };
+                });
+
+                if self.reading {
+                    ui.spinner();
+                    return;
+                }
+                if self.root.is_some() && self.instances.is_empty() {
+                    ui.label(tr("multimc.none"));
+                    return;
+                }
+
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for found in &mut self.instances {
+                            ui.horizontal(|ui| {
+                                let waiting = matches!(found.state, CopyState::Waiting);
+                                ui.add_enabled(
+                                    waiting,
+                                    egui::Checkbox::new(&mut found.selected, &found.instance.name),
+                                );
+
+                                let instance = &found.instance;
+                                let loader = match &instance.loader {
+                                    Some((loader, version)) => {
+                                        format!("{} {version}", loader_name(*loader))
+                                    }
+                                    None => tr("editor.vanilla").into(),
+                                };
+                                ui.weak(format!("{} · {loader}", instance.game_version));
+
+                                match &found.state {
+                                    CopyState::Waiting => {}
+                                    CopyState::Copying(progress) => {
+                                        let fraction = progress.latest().unwrap_or_default();
+                                        let bar = egui::ProgressBar::new(fraction)
+                                            .desired_width(100.0)
+                                            .show_percentage();
+                                        ui.add(bar);
+                                    }
+                                    CopyState::Done => {
+                                        ui.colored_label(Color32::LIGHT_GREEN, "✔");
+                                    }
+                                    CopyState::Failed => {
+                                        ui.colored_label(ui.visuals().error_fg_color, "✖");
+                                    }
+                                }
+                            });
+                        }
+                    });
+
+                ui.separator();
+
+                let count = self
+                    .instances
+                    .iter()
+                    .filter(|found| found.selected && matches!(found.state, CopyState::Waiting))
+                    .count();
+                let button = Button::new(tr_args("multimc.import", &[("count", &count)]));
+                if ui.add_enabled(count > 0, button).clicked() {
+                    action = Some(MultiMcAction::Import);
+                }
+            });
+
+        action
+    }
+}
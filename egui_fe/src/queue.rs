@@ -0,0 +1,261 @@
+use crate::errors::{Failure, Retry};
+use crate::i18n::tr;
+use crate::progress_row;
+use eframe::egui::{self, Button, RichText};
+use launcher_app::prepare::{Part, Prepare};
+use launcher_app::Job;
+use launcher_core::progress::DownloadStats;
+use launcher_core::types::{Version, VersionJson};
+use std::sync::Arc;
+
+/// How many entries download at once, the rest wait for their turn
+const MAX_ACTIVE: usize = 2;
+
+/// An instance, or the quick play version, being prepared to launch
+pub struct QueueEntry {
+    // Tags the responses of its downloads, entries move around while they run
+    pub id: u64,
+    // None for quick play
+    pub instance: Option<usize>,
+    pub name: String,
+    pub version: Arc<Version>,
+    pub prepare: Prepare,
+    pub library_stats: Arc<DownloadStats>,
+    pub asset_stats: Arc<DownloadStats>,
+    pub jar_stats: Arc<DownloadStats>,
+    // Running parts still finish, but nothing new starts and it doesn't launch
+    paused: bool,
+    // Nothing new starts until it's retried
+    failed: bool,
+}
+
+impl QueueEntry {
+    /// Takes the result of a part, a failed part can be started again with [`Retry::Prepare`]
+    pub fn finish<T>(
+        &mut self,
+        part: Part,
+        result: Result<T, launcher_core::Error>,
+    ) -> Result<T, Failure> {
+        self.prepare.finish(part, result).map_err(|e| {
+            self.failed = true;
+            Failure::retry(e, Retry::Prepare(self.id))
+        })
+    }
+
+    fn waiting_for_turn(&self) -> bool {
+        !self.paused && !self.failed && !self.prepare.ready()
+    }
+}
+
+/// Instances waiting to launch, downloaded in order a few at a time
+#[derive(Default)]
+pub struct DownloadQueue {
+    entries: Vec<QueueEntry>,
+    next_id: u64,
+}
+
+impl DownloadQueue {
+    /// Quick play passes its json, it's already downloaded to pick the Java version.
+    /// Returns the entry's id
+    pub fn push(
+        &mut self,
+        instance: Option<usize>,
+        name: String,
+        version: Arc<Version>,
+        json: Option<Arc<VersionJson>>,
+    ) -> u64 {
+        self.push_paused(instance, name, version, json, false)
+    }
+
+    /// An entry from the jobs of the last session
+    pub fn restore(
+        &mut self,
+        instance: Option<usize>,
+        name: String,
+        version: Version,
+        paused: bool,
+    ) {
+        self.push_paused(instance, name, Arc::new(version), None, paused);
+    }
+
+    fn push_paused(
+        &mut self,
+        instance: Option<usize>,
+        name: String,
+        version: Arc<Version>,
+        json: Option<Arc<VersionJson>>,
+        paused: bool,
+    ) -> u64 {
+        let id = self.next_id;
+        self.entries.push(QueueEntry {
+            id,
+            instance,
+            name,
+            version,
+            prepare: Prepare::new(json),
+            library_stats: Arc::new(DownloadStats::new()),
+            asset_stats: Arc::new(DownloadStats::new()),
+            jar_stats: Arc::new(DownloadStats::new()),
+            paused,
+            failed: false,
+        });
+        self.next_id += 1;
+        id
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entries to continue after a restart, failed ones are tried again
+    pub fn jobs(&self, instance_name: impl Fn(usize) -> String) -> Vec<Job> {
+        self.entries
+            .iter()
+            .map(|entry| Job::Prepare {
+                instance: entry.instance.map(&instance_name),
+                name: entry.name.clone(),
+                version: (*entry.version).clone(),
+                paused: entry.paused,
+            })
+            .collect()
+    }
+
+    /// Whether the instance, or quick play for None, is queued
+    pub fn contains(&self, instance: Option<usize>) -> bool {
+        self.entries.iter().any(|entry| entry.instance == instance)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&QueueEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// None once the entry is cancelled, its downloads still send their responses
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut QueueEntry> {
+        self.entries.iter_mut().find(|entry| entry.id == id)
+    }
+
+    /// The parts to start for the first few entries, they're counted as running until
+    /// [`QueueEntry::finish`] is called with their result
+    pub fn start(&mut self) -> Vec<(u64, Part)> {
+        let mut started = Vec::new();
+        let active = self
+            .entries
+            .iter_mut()
+            .filter(|entry| entry.waiting_for_turn())
+            .take(MAX_ACTIVE);
+        for entry in active {
+            for part in entry.prepare.start() {
+                started.push((entry.id, part));
+            }
+        }
+        started
+    }
+
+    /// Removes the entries that are done downloading and not paused, and whose instance, or
+    /// quick play for None, `launchable` accepts
+    pub fn take_ready(&mut self, launchable: impl Fn(Option<usize>) -> bool) -> Vec<QueueEntry> {
+        let mut ready = Vec::new();
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            let entry = &self.entries[idx];
+            if entry.prepare.ready() && !entry.paused && launchable(entry.instance) {
+                ready.push(self.entries.remove(idx));
+            } else {
+                idx += 1;
+            }
+        }
+        ready
+    }
+
+    pub fn retry(&mut self, id: u64) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.failed = false;
+        }
+    }
+
+    /// Drops the removed instance's entry and moves the later indexes down
+    pub fn instance_removed(&mut self, index: usize) {
+        self.entries.retain(|entry| entry.instance != Some(index));
+        for entry in &mut self.entries {
+            entry.instance = match entry.instance {
+                Some(current) if current > index => Some(current - 1),
+                current => current,
+            };
+        }
+    }
+
+    pub fn contents(&mut self, ui: &mut egui::Ui) {
+        let active: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.waiting_for_turn())
+            .take(MAX_ACTIVE)
+            .map(|entry| entry.id)
+            .collect();
+
+        let mut moved = None;
+        let mut cancelled = None;
+        let count = self.entries.len();
+        for (idx, entry) in self.entries.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&entry.name).strong());
+                let status = if entry.failed {
+                    tr("queue.failed")
+                } else if entry.paused {
+                    tr("queue.paused")
+                } else if entry.prepare.ready() {
+                    tr("queue.ready")
+                } else if active.contains(&entry.id) {
+                    tr("queue.downloading")
+                } else {
+                    tr("queue.waiting")
+                };
+                ui.weak(status);
+
+                if ui.add_enabled(idx > 0, Button::new("⏶")).clicked() {
+                    moved = Some((idx, idx - 1));
+                }
+                if ui.add_enabled(idx + 1 < count, Button::new("⏷")).clicked() {
+                    moved = Some((idx, idx + 1));
+                }
+                if entry.failed {
+                    if ui.button(tr("error.retry")).clicked() {
+                        entry.failed = false;
+                    }
+                } else {
+                    let text = if entry.paused {
+                        tr("queue.resume")
+                    } else {
+                        tr("queue.pause")
+                    };
+                    if ui.button(text).clicked() {
+                        entry.paused = !entry.paused;
+                    }
+                }
+                if ui.button(tr("common.cancel")).clicked() {
+                    cancelled = Some(idx);
+                }
+            });
+
+            // Entries that haven't started have nothing to show
+            if entry.prepare.started() {
+                egui::Grid::new(("Progress Grid", entry.id))
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        progress_row(ui, tr("progress.libraries"), &entry.library_stats);
+                        progress_row(ui, tr("progress.assets"), &entry.asset_stats);
+                        progress_row(ui, tr("progress.jar"), &entry.jar_stats);
+                    });
+            }
+            ui.separator();
+        }
+
+        if let Some((from, to)) = moved {
+            self.entries.swap(from, to);
+        }
+        // Downloads that are running finish on their own, the files are reused by the next launch
+        if let Some(idx) = cancelled {
+            self.entries.remove(idx);
+        }
+    }
+}
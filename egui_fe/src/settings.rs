@@ -0,0 +1,279 @@
+use crate::i18n::{tr, tr_args, Language};
+use crate::instances::{Jvm, Memory};
+use eframe::egui::{self, Color32, Visuals};
+use launcher_app::{Hooks, LaunchDefaults, Resolution};
+use launcher_core::http::Proxy;
+use launcher_core::skin_fix::SkinFix;
+use launcher_core::types::Type;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    // JVM used when no other JVM is picked, None uses `java` from the PATH
+    pub default_jvm: Option<Arc<Jvm>>,
+    // Space separated, passed to every launch before the instance args
+    pub jvm_args: String,
+    // Maximum heap in MiB, 0 lets the JVM decide
+    pub memory: u32,
+    // Minimum heap in MiB, 0 lets the JVM decide
+    pub min_memory: u32,
+    // Window size for instances that don't set their own, None lets the game decide
+    pub resolution: Option<Resolution>,
+    // Commands run around every launch, instances can replace each one
+    pub hooks: Hooks,
+    // How many files are downloaded at once
+    pub concurrency: usize,
+    // Which version types the version pickers list
+    pub version_filter: VersionFilter,
+    pub instance_sort: InstanceSort,
+    pub theme: Theme,
+    pub language: Language,
+    // Used for selections, links and pressed buttons, None keeps egui's blue
+    pub accent: Option<[u8; 3]>,
+    // Where versions, assets and libraries are stored, defaults to the config directory
+    pub data_dir: Option<PathBuf>,
+    // Shows Mojang's patch notes next to the instances
+    pub show_news: bool,
+    // Looks for a newer launcher release at startup
+    pub check_updates: bool,
+    // Instance backups kept per instance, older ones are removed, 0 keeps all of them
+    pub backups_kept: usize,
+    // Backs up an instance before its game or loader version is changed
+    pub backup_before_update: bool,
+    // Zoom on top of the system's scale
+    pub ui_scale: f32,
+    // Bigger buttons with more space between them, for touch screens
+    pub large_targets: bool,
+    // Every request goes through it once its url is set, the password is in the secret store
+    pub proxy: Proxy,
+    // Trusted on top of the system's roots, for proxies that intercept TLS
+    pub certificates: Vec<PathBuf>,
+    // Gives versions before 1.8 their skins back, for instances that turn it on
+    pub skin_fix: SkinFix,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            default_jvm: None,
+            jvm_args: String::new(),
+            memory: 0,
+            min_memory: 0,
+            resolution: None,
+            hooks: Hooks::default(),
+            concurrency: launcher_core::DEFAULT_CONCURRENCY,
+            version_filter: VersionFilter::default(),
+            instance_sort: InstanceSort::default(),
+            theme: Theme::System,
+            language: Language::default(),
+            accent: None,
+            data_dir: None,
+            show_news: true,
+            check_updates: false,
+            backups_kept: 5,
+            backup_before_update: false,
+            ui_scale: 1.0,
+            large_targets: false,
+            proxy: Proxy::default(),
+            certificates: vec![],
+            skin_fix: SkinFix::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn jvm_path(&self) -> &str {
+        self.default_jvm
+            .as_ref()
+            .map_or("java", |jvm| jvm.path.as_str())
+    }
+
+    /// Arguments every launch starts with, instance arguments are appended after these
+    pub fn launch_args(&self) -> Vec<String> {
+        self.jvm_args
+            .split(' ')
+            .filter(|arg| !arg.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Sets the theme and accent, `system` is the theme eframe detected
+    pub fn apply_theme(&self, ctx: &egui::Context, system: Option<eframe::Theme>) {
+        self.theme.apply(ctx, system, self.accent);
+    }
+
+    /// Sets the zoom and how big clickable things are
+    pub fn apply_scale(&self, ctx: &egui::Context) {
+        ctx.set_zoom_factor(self.ui_scale);
+
+        let default = egui::style::Spacing::default();
+        ctx.style_mut(|style| {
+            let spacing = &mut style.spacing;
+            if self.large_targets {
+                spacing.button_padding = egui::vec2(10.0, 6.0);
+                spacing.item_spacing = egui::vec2(10.0, 8.0);
+                spacing.interact_size.y = 32.0;
+            } else {
+                spacing.button_padding = default.button_padding;
+                spacing.item_spacing = default.item_spacing;
+                spacing.interact_size = default.interact_size;
+            }
+        });
+    }
+
+    /// Heap sizes for instances that don't set their own
+    pub fn heap(&self) -> Memory {
+        Memory {
+            min: self.min_memory,
+            max: self.memory,
+        }
+    }
+
+    /// What launches start from before an instance's overrides
+    pub fn launch_defaults(&self) -> LaunchDefaults {
+        LaunchDefaults {
+            jvm: self.default_jvm.clone(),
+            memory: self.heap(),
+            jvm_args: self.launch_args(),
+            resolution: self.resolution,
+            hooks: self.hooks.clone(),
+            skin_fix: self.skin_fix.clone(),
+        }
+    }
+}
+
+/// Min and max heap sliders, bounded by the installed memory. Returns whether either changed
+pub fn memory_sliders(ui: &mut egui::Ui, min: &mut u32, max: &mut u32, total: Option<u64>) -> bool {
+    // Without knowing the installed memory, 16 GiB is plenty for any modpack
+    let total = total.unwrap_or(16384).min(u32::MAX as u64) as u32;
+    // Steps of 256 MiB, at least 1 GiB
+    let upper = (total / 256 * 256).max(1024);
+
+    let format = |n: f64, _: std::ops::RangeInclusive<usize>| {
+        if n == 0.0 {
+            tr("memory.jvm_default").to_string()
+        } else {
+            format!("{n} MiB")
+        }
+    };
+
+    let max_changed = ui
+        .horizontal(|ui| {
+            ui.label(tr("memory.max"));
+            let slider = egui::Slider::new(max, 0..=upper)
+                .step_by(256.0)
+                .custom_formatter(format);
+            ui.add(slider).changed()
+        })
+        .inner;
+
+    // The minimum can't be above the maximum
+    let min_upper = if *max == 0 { upper } else { *max };
+    let min_changed = ui
+        .horizontal(|ui| {
+            ui.label(tr("memory.min"));
+            let slider = egui::Slider::new(min, 0..=min_upper)
+                .step_by(256.0)
+                .custom_formatter(format);
+            ui.add(slider).changed()
+        })
+        .inner;
+
+    if *max != 0 && *min > *max {
+        *min = *max;
+    }
+
+    // Half of the installed memory is the most that leaves room for the system
+    if *max > total / 2 {
+        ui.colored_label(
+            egui::Color32::GOLD,
+            tr_args("memory.too_much", &[("total", &total)]),
+        );
+    }
+
+    max_changed || min_changed
+}
+
+/// Releases are always shown
+#[derive(Deserialize, Serialize, Default, Clone, Copy)]
+#[serde(default)]
+pub struct VersionFilter {
+    pub snapshots: bool,
+    pub betas: bool,
+    pub alphas: bool,
+}
+
+impl VersionFilter {
+    pub fn shows(&self, version_type: Type) -> bool {
+        match version_type {
+            Type::Release => true,
+            Type::Snapshot => self.snapshots,
+            Type::OldBeta => self.betas,
+            Type::OldAlpha => self.alphas,
+        }
+    }
+
+    /// Returns whether any toggle changed
+    pub fn toggles(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.horizontal(|ui| {
+            let snapshots = ui.toggle_value(&mut self.snapshots, tr("filter.snapshots"));
+            let betas = ui.toggle_value(&mut self.betas, tr("filter.betas"));
+            let alphas = ui.toggle_value(&mut self.alphas, tr("filter.alphas"));
+            snapshots.changed() || betas.changed() || alphas.changed()
+        })
+        .inner
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum InstanceSort {
+    #[default]
+    Name,
+    LastPlayed,
+    Version,
+}
+
+impl InstanceSort {
+    pub fn name(self) -> &'static str {
+        match self {
+            InstanceSort::Name => tr("sort.name"),
+            InstanceSort::LastPlayed => tr("sort.last_played"),
+            InstanceSort::Version => tr("sort.version"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+pub enum Theme {
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// `System` follows whatever eframe detected, falling back to dark
+    pub fn apply(
+        self,
+        ctx: &egui::Context,
+        system: Option<eframe::Theme>,
+        accent: Option<[u8; 3]>,
+    ) {
+        let mut visuals = match (self, system) {
+            (Theme::Light, _) | (Theme::System, Some(eframe::Theme::Light)) => Visuals::light(),
+            _ => Visuals::dark(),
+        };
+
+        if let Some([r, g, b]) = accent {
+            let accent = Color32::from_rgb(r, g, b);
+            visuals.selection.bg_fill = accent;
+            visuals.hyperlink_color = accent;
+            visuals.widgets.hovered.bg_stroke.color = accent;
+            visuals.widgets.active.bg_fill = accent;
+        }
+
+        ctx.set_visuals(visuals);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::i18n::{tr, tr_args};
+use eframe::egui::{self, Button, Color32, ColorImage, RichText, TextureHandle, TextureOptions};
+use image::RgbaImage;
+use launcher_core::account::skins::SkinVariant;
+use launcher_core::account::types::Profile;
+use std::path::PathBuf;
+
+/// Each side of the preview is a 16x32 pixel image, scaled up when drawn
+const PREVIEW_SIZE: [usize; 2] = [16, 32];
+const PREVIEW_SCALE: f32 = 6.0;
+
+/// Shows the skin and capes of one account, and sends changes to them
+pub struct AccountView {
+    pub account: usize,
+    // Skin the preview was made from, None before the first download
+    preview_of: Option<(String, SkinVariant)>,
+    preview: Option<[TextureHandle; 2]>,
+    pub loading: bool,
+    // Set while a change is being sent, the response carries the updated profile
+    pub busy: bool,
+    variant: SkinVariant,
+    pub open: bool,
+}
+
+pub enum SkinAction {
+    // Url of the skin to turn into a preview, and the arms it uses
+    Preview(String, SkinVariant),
+    Upload(PathBuf, SkinVariant),
+    ChangeVariant(String, SkinVariant),
+    Reset,
+    Cape(Option<String>),
+}
+
+impl AccountView {
+    pub fn new(account: usize) -> Self {
+        Self {
+            account,
+            preview_of: None,
+            preview: None,
+            loading: false,
+            busy: false,
+            variant: SkinVariant::Classic,
+            open: true,
+        }
+    }
+
+    pub fn set_preview(
+        &mut self,
+        ctx: &egui::Context,
+        skin: (String, SkinVariant),
+        images: [ColorImage; 2],
+    ) {
+        // The skin was changed again before this preview finished
+        if self.preview_of.as_ref() != Some(&skin) {
+            return;
+        }
+
+        let [front, back] = images;
+        self.preview = Some([
+            ctx.load_texture("Skin Front", front, TextureOptions::NEAREST),
+            ctx.load_texture("Skin Back", back, TextureOptions::NEAREST),
+        ]);
+        self.loading = false;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, profile: &Profile) -> Option<SkinAction> {
+        let mut action = None;
+
+        let active = profile.active_skin();
+        if let Some(skin) = active {
+            let variant = SkinVariant::parse(&skin.variant);
+            // The skin changed since the preview was made, or it was never made
+            if self.preview_of.as_ref() != Some(&(skin.url.clone(), variant)) {
+                self.preview_of = Some((skin.url.clone(), variant));
+                self.variant = variant;
+                self.loading = true;
+                action = Some(SkinAction::Preview(skin.url.clone(), variant));
+            }
+        } else {
+            self.preview_of = None;
+            self.preview = None;
+        }
+
+        egui::Window::new(tr_args("skins.title", &[("name", &profile.name)]))
+            .id(egui::Id::new("Account View"))
+            .auto_sized()
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| match &self.preview {
+                    Some(textures) => {
+                        for texture in textures {
+                            let size = texture.size_vec2() * PREVIEW_SCALE;
+                            ui.image((texture.id(), size));
+                        }
+                    }
+                    None if self.loading => {
+                        ui.spinner();
+                    }
+                    None => {
+                        ui.label(tr("skins.default"));
+                    }
+                });
+
+                ui.separator();
+
+                ui.add_enabled_ui(!self.busy, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("skins.arms"));
+                        let before = self.variant;
+                        ui.radio_value(
+                            &mut self.variant,
+                            SkinVariant::Classic,
+                            tr("skins.classic"),
+                        );
+                        ui.radio_value(&mut self.variant, SkinVariant::Slim, tr("skins.slim"));
+
+                        // Only a worn skin has a variant to switch
+                        if self.variant != before {
+                            if let Some(skin) = active {
+                                action =
+                                    Some(SkinAction::ChangeVariant(skin.url.clone(), self.variant));
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("skins.upload")).clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("PNG", &["png"])
+                                .pick_file()
+                            {
+                                action = Some(SkinAction::Upload(path, self.variant));
+                            }
+                        }
+
+                        if ui
+                            .add_enabled(active.is_some(), Button::new(tr("skins.reset")))
+                            .clicked()
+                        {
+                            action = Some(SkinAction::Reset);
+                        }
+
+                        if self.busy {
+                            ui.spinner();
+                        }
+                    });
+
+                    ui.separator();
+
+                    egui::CollapsingHeader::new(tr_args(
+                        "skins.capes",
+                        &[("count", &profile.capes.len())],
+                    ))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        if profile.capes.is_empty() {
+                            ui.label(tr("skins.no_capes"));
+                            return;
+                        }
+
+                        for cape in &profile.capes {
+                            ui.horizontal(|ui| {
+                                if cape.state == "ACTIVE" {
+                                    ui.label(RichText::new(&cape.alias).strong());
+                                    ui.colored_label(Color32::LIGHT_GREEN, tr("skins.shown"));
+                                    if ui.small_button(tr("skins.hide")).clicked() {
+                                        action = Some(SkinAction::Cape(None));
+                                    }
+                                } else {
+                                    ui.label(&cape.alias);
+                                    if ui.small_button(tr("skins.show")).clicked() {
+                                        action = Some(SkinAction::Cape(Some(cape.id.clone())));
+                                    }
+                                }
+                            });
+                        }
+                    });
+                });
+            });
+
+        action
+    }
+}
+
+/// Builds the front and back previews of a skin texture
+pub fn skin_previews(skin: &RgbaImage, variant: SkinVariant) -> [ColorImage; 2] {
+    // 64x32 skins are from before 1.8, they have no second layer besides the hat,
+    // and the left arm and leg are the right ones mirrored
+    let legacy = skin.height() == 32;
+    let arm = match variant {
+        SkinVariant::Classic => 4,
+        SkinVariant::Slim => 3,
+    };
+    // Slim arms are drawn against the body, leaving a pixel of space on the outside
+    let gap = 4 - arm;
+
+    let mut front = ColorImage::new(PREVIEW_SIZE, Color32::TRANSPARENT);
+    let mut back = ColorImage::new(PREVIEW_SIZE, Color32::TRANSPARENT);
+
+    // Head, hat, body and right leg are the same in both formats
+    let parts = [
+        // (source x, source y, width, height, destination x, destination y)
+        (8, 8, 8, 8, 4, 0),
+        (40, 8, 8, 8, 4, 0),
+        (20, 20, 8, 12, 4, 8),
+        (44, 20, arm, 12, gap, 8),
+        (4, 20, 4, 12, 4, 20),
+    ];
+    for (x, y, w, h, dx, dy) in parts {
+        blit(&mut front, skin, (x, y, w, h), (dx, dy), false);
+    }
+
+    let parts = [
+        (24, 8, 8, 8, 4, 0),
+        (56, 8, 8, 8, 4, 0),
+        (32, 20, 8, 12, 4, 8),
+        (48 + arm, 20, arm, 12, 12, 8),
+        (12, 20, 4, 12, 8, 20),
+    ];
+    for (x, y, w, h, dx, dy) in parts {
+        blit(&mut back, skin, (x, y, w, h), (dx, dy), false);
+    }
+
+    if legacy {
+        // Seen from the other side, the mirrored right limbs are the left ones
+        blit(&mut front, skin, (44, 20, arm, 12), (12, 8), true);
+        blit(&mut front, skin, (4, 20, 4, 12), (8, 20), true);
+        blit(&mut back, skin, (48 + arm, 20, arm, 12), (gap, 8), true);
+        blit(&mut back, skin, (12, 20, 4, 12), (4, 20), true);
+        return [front, back];
+    }
+
+    let parts = [
+        (36, 52, arm, 12, 12, 8),
+        (20, 52, 4, 12, 8, 20),
+        // Second layer
+        (20, 36, 8, 12, 4, 8),
+        (44, 36, arm, 12, gap, 8),
+        (52, 52, arm, 12, 12, 8),
+        (4, 36, 4, 12, 4, 20),
+        (4, 52, 4, 12, 8, 20),
+    ];
+    for (x, y, w, h, dx, dy) in parts {
+        blit(&mut front, skin, (x, y, w, h), (dx, dy), false);
+    }
+
+    let parts = [
+        (40 + arm, 52, arm, 12, gap, 8),
+        (28, 52, 4, 12, 4, 20),
+        (32, 36, 8, 12, 4, 8),
+        (48 + arm, 36, arm, 12, 12, 8),
+        (56 + arm, 52, arm, 12, gap, 8),
+        (12, 36, 4, 12, 8, 20),
+        (12, 52, 4, 12, 4, 20),
+    ];
+    for (x, y, w, h, dx, dy) in parts {
+        blit(&mut back, skin, (x, y, w, h), (dx, dy), false);
+    }
+
+    [front, back]
+}
+
+/// The face with the hat over it, 8x8 pixels
+pub fn head(skin: &RgbaImage) -> ColorImage {
+    let mut head = ColorImage::new([8, 8], Color32::TRANSPARENT);
+    blit(&mut head, skin, (8, 8, 8, 8), (0, 0), false);
+    blit(&mut head, skin, (40, 8, 8, 8), (0, 0), false);
+    head
+}
+
+/// Copies a part of the skin, transparent pixels keep what is already drawn
+fn blit(
+    target: &mut ColorImage,
+    skin: &RgbaImage,
+    (x, y, w, h): (u32, u32, u32, u32),
+    (dx, dy): (u32, u32),
+    mirror: bool,
+) {
+    for row in 0..h {
+        for col in 0..w {
+            let Some(pixel) = skin.get_pixel_checked(x + col, y + row) else {
+                continue;
+            };
+            let [r, g, b, a] = pixel.0;
+            if a == 0 {
+                continue;
+            }
+
+            let col = if mirror { w - 1 - col } else { col };
+            target[((dx + col) as usize, (dy + row) as usize)] =
+                Color32::from_rgba_unmultiplied(r, g, b, a);
+        }
+    }
+}
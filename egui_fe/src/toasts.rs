@@ -0,0 +1,52 @@
+use eframe::egui::{self, Align2, Frame, Image, TextureHandle};
+use std::time::{Duration, Instant};
+
+/// How long a toast stays on screen
+const LIFETIME: Duration = Duration::from_secs(4);
+const IMAGE_SIZE: f32 = 16.0;
+
+/// Short confirmations stacked in the bottom right corner, each goes away after a few seconds
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<(String, Option<TextureHandle>, Instant)>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, message: String) {
+        self.toasts.push((message, None, Instant::now()));
+    }
+
+    /// Shows a small image in front of the message, e.g. the player's head
+    pub fn push_with_image(&mut self, message: String, image: TextureHandle) {
+        self.toasts.push((message, Some(image), Instant::now()));
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.toasts
+            .retain(|(_, _, shown)| shown.elapsed() < LIFETIME);
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        egui::Area::new("Toasts")
+            .anchor(Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                for (message, image, _) in &self.toasts {
+                    Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            if let Some(image) = image {
+                                let size = egui::vec2(IMAGE_SIZE, IMAGE_SIZE);
+                                ui.add(Image::new(image).fit_to_exact_size(size));
+                            }
+                            ui.label(message);
+                        });
+                    });
+                }
+            });
+
+        // Repaint when the oldest one runs out, nothing else might cause a frame
+        let (_, _, oldest) = &self.toasts[0];
+        ctx.request_repaint_after(LIFETIME.saturating_sub(oldest.elapsed()));
+    }
+}
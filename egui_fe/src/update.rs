@@ -0,0 +1,191 @@
+use crate::i18n::{tr, tr_args};
+use eframe::egui::{self, Button};
+use launcher_core::http::USER_AGENT;
+use launcher_core::Error;
+use reqwest::Client;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/AlsoSylv/synth_launcher/releases/latest";
+/// The version of the running launcher, compared against release tags
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Release {
+    pub tag_name: String,
+    // The changelog, in markdown
+    #[serde(default)]
+    pub body: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// The launcher binary built for this platform, if the release has one
+    pub fn asset(&self) -> Option<&Asset> {
+        let os = match std::env::consts::OS {
+            "macos" => "mac",
+            os => os,
+        };
+        self.assets.iter().find(|asset| {
+            let name = asset.name.to_ascii_lowercase();
+            name.contains(os) && (cfg!(not(windows)) || name.ends_with(".exe"))
+        })
+    }
+}
+
+/// The latest release, None if the running launcher is already on it
+pub async fn check(client: &Client) -> Result<Option<Release>, Error> {
+    let release: Release = client
+        .get(LATEST_RELEASE_URL)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(is_newer(&release.tag_name, CURRENT_VERSION).then_some(release))
+}
+
+/// Downloads the new binary next to the running one, it replaces it in [`replace_exe`]
+pub async fn stage(client: &Client, asset: &Asset) -> Result<PathBuf, Error> {
+    let bytes = client
+        .get(&asset.browser_download_url)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let staged = sibling(&std::env::current_exe()?, "new");
+    tokio::fs::write(&staged, &bytes).await?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o755);
+        tokio::fs::set_permissions(&staged, permissions).await?;
+    }
+
+    Ok(staged)
+}
+
+/// Swaps the staged binary in for the running one. The running binary is moved aside
+/// first, Windows allows renaming it but not overwriting it
+pub fn replace_exe(staged: &Path) -> std::io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let old = sibling(&exe, "old");
+    std::fs::rename(&exe, &old)?;
+    if let Err(e) = std::fs::rename(staged, &exe) {
+        // Put the running binary back, the update is tried again next time
+        std::fs::rename(&old, &exe)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Removes the binary left behind by the last update
+pub fn remove_old_exe() {
+    if let Ok(exe) = std::env::current_exe() {
+        let _ = std::fs::remove_file(sibling(&exe, "old"));
+    }
+}
+
+fn sibling(exe: &Path, suffix: &str) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    exe.with_file_name(name)
+}
+
+/// Compares dotted versions number by number, a leading `v` on the tag is ignored
+fn is_newer(tag: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parse(tag) > parse(current)
+}
+
+/// Shown across the top of the launcher while an update is available
+pub struct UpdateBanner {
+    pub release: Release,
+    // Set while the new binary downloads
+    pub staging: bool,
+    // Where the new binary was downloaded to, it replaces the running one on exit
+    pub staged: Option<PathBuf>,
+    pub open: bool,
+}
+
+pub enum UpdateAction {
+    Stage(Asset),
+}
+
+impl UpdateBanner {
+    pub fn new(release: Release) -> Self {
+        Self {
+            release,
+            staging: false,
+            staged: None,
+            open: true,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<UpdateAction> {
+        let mut action = None;
+
+        egui::TopBottomPanel::top("Update Banner").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong(tr_args(
+                    "update.available",
+                    &[
+                        ("version", &self.release.tag_name),
+                        ("current", &CURRENT_VERSION),
+                    ],
+                ));
+                ui.hyperlink_to(tr("update.download"), &self.release.html_url);
+
+                if let Some(asset) = self.release.asset() {
+                    if self.staged.is_some() {
+                        ui.label(tr("update.staged"));
+                    } else if ui
+                        .add_enabled(!self.staging, Button::new(tr("update.install")))
+                        .clicked()
+                    {
+                        action = Some(UpdateAction::Stage(asset.clone()));
+                    }
+                    if self.staging {
+                        ui.spinner();
+                    }
+                }
+
+                if ui.small_button(tr("error.dismiss")).clicked() {
+                    self.open = false;
+                }
+            });
+
+            if !self.release.body.is_empty() {
+                egui::CollapsingHeader::new(tr("update.changelog")).show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .show(ui, |ui| ui.label(&self.release.body));
+                });
+            }
+        });
+
+        action
+    }
+}
@@ -1,17 +1,27 @@
+use crate::errors::Retry;
+use crate::instances::InstalledMod;
+use crate::update::Release;
+use async_bridge::TimedOut;
 use async_channel::Sender;
-use launcher_core::account::auth::{
-    authorization_token_response, device_response, minecraft_ownership_response,
-    minecraft_profile_response, minecraft_response, refresh_token_response, xbox_response,
-    xbox_security_token_response,
-};
-use launcher_core::account::types::Account;
+use eframe::egui::ColorImage;
+use launcher_app::auth;
+use launcher_core::account::skins::SkinVariant;
+use launcher_core::account::types::{Account, Profile};
+use launcher_core::assets::GarbageReport;
+use launcher_core::java::InstalledRuntime;
+use launcher_core::libraries::PruneReport;
+use launcher_core::loader::{Loader, LoaderVersion};
+use launcher_core::modpack::PackInfo;
+use launcher_core::modrinth::types::SearchResponse;
+use launcher_core::multimc::MultiMcInstance;
+use launcher_core::news::PatchNotes;
 use launcher_core::types::{AssetIndexJson, Version, VersionJson, VersionManifest};
-use launcher_core::Error;
+use launcher_core::{Error, Libraries};
 use reqwest::Client;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-pub const CLIENT_ID: &str = "04bc8538-fc3c-4490-9e61-a2b3f4cbcf5c";
+pub use launcher_app::CLIENT_ID;
 
 pub struct Message {
     pub path: Arc<PathBuf>,
@@ -19,21 +29,122 @@ pub struct Message {
 }
 pub enum Contents {
     Versions,
-    Auth(Option<Arc<str>>),
+    Auth(Option<String>),
 }
 
 pub enum Response {
     Versions(Result<VersionManifest, Error>),
-    Version(Result<Box<VersionJson>, Error>),
-    Tagged(TaggedResponse, Arc<Version>),
+    // The version is sent back so a failed download can be retried
+    Version(Arc<Version>, Result<Box<VersionJson>, Error>),
+    // The version picked in the instance editor, only used to tell which Java it needs
+    EditorVersion(Result<Box<VersionJson>, Error>),
+    // The json of the instance whose launch command is previewed
+    LaunchPreview {
+        index: usize,
+        // Copied to the clipboard instead of shown in a window
+        copy: bool,
+        json: Result<Box<VersionJson>, Error>,
+    },
+    // Tagged with the id of the download queue entry
+    Tagged(TaggedResponse, u64),
     Auth(Result<(Account, String), Error>),
+    Mods(ModResponse),
+    Pack(PackResponse),
+    Skin(SkinResponse),
+    Java(Result<InstalledRuntime, Error>),
+    News(Result<PatchNotes, Error>),
+    // Every attempt of a request with a time limit ran out of time
+    TimedOut(Retry, TimedOut),
+    Update(UpdateResponse),
+    MultiMc(MultiMcResponse),
+    Saves(SavesResponse),
+    Archive(ArchiveResponse),
+    Loader(LoaderResponse),
+    // Whether it was a dry run, and the unused assets it found
+    Assets(bool, Result<GarbageReport, Error>),
+    // Whether it was a dry run, and the unused libraries and natives it found
+    Libraries(bool, Result<PruneReport, Error>),
+}
+
+pub enum SkinResponse {
+    // Front and back of the skin with the given url and arms
+    Preview(String, SkinVariant, Result<[ColorImage; 2], Error>),
+    // The profile after a skin or cape change
+    Changed(Result<Profile, Error>),
+    // The head of the profile with the id, None for profiles on a default skin
+    Avatar(String, Result<Option<ColorImage>, Error>),
+}
+
+pub enum UpdateResponse {
+    // None when the launcher is up to date
+    Checked(Result<Option<Release>, Error>),
+    // Where the new binary was downloaded to
+    Staged(Result<PathBuf, Error>),
+}
+
+pub enum SavesResponse {
+    // The world's folder, and the backup made of it
+    BackedUp(PathBuf, Result<PathBuf, Error>),
+    Restored(PathBuf, Result<(), Error>),
+    // The backups made after the game exited
+    AutoBackedUp(Result<Vec<PathBuf>, Error>),
+}
+
+pub enum ArchiveResponse {
+    // The name of the instance that was backed up
+    Created(String, Result<PathBuf, Error>),
+    // The backup that was restored
+    Restored(PathBuf, Result<(), Error>),
+}
+
+pub enum MultiMcResponse {
+    // The folder that was read, and the instances in it
+    Found(PathBuf, Result<Vec<MultiMcInstance>, Error>),
+    Copied {
+        instance: Arc<MultiMcInstance>,
+        destination: PathBuf,
+        result: Result<(), Error>,
+    },
+}
+
+pub enum LoaderResponse {
+    // Loader versions for the game version picked in the instance editor
+    Versions {
+        loader: Loader,
+        game_version: String,
+        result: Result<Vec<LoaderVersion>, Error>,
+    },
+    Installed {
+        loader: Loader,
+        game_version: String,
+        loader_version: String,
+        result: Result<(), Error>,
+    },
+}
+
+pub enum PackResponse {
+    Read(Arc<PathBuf>, Result<PackInfo, Error>),
+    Installed(Arc<PathBuf>, Result<(), Error>),
+}
+
+pub enum ModResponse {
+    Search(Result<SearchResponse, Error>),
+    Installed {
+        // Identifies the instance, indexes can change while the download runs
+        mods_dir: Arc<PathBuf>,
+        project_id: String,
+        result: Result<InstalledMod, Error>,
+    },
 }
 
 pub enum TaggedResponse {
-    Libraries(Result<String, Error>),
+    Version(Result<Box<VersionJson>, Error>),
+    Libraries(Result<Libraries, Error>),
     AssetIndex(Result<AssetIndexJson, Error>),
     Asset(Result<(), Error>),
     Jar(Result<String, Error>),
+    // The newer loader version that was installed, if there was one
+    Loader(Result<Option<String>, Error>),
 }
 
 #[derive(Clone)]
@@ -53,72 +164,9 @@ pub async fn worker_event_loop(message: Message, state: &State) -> Response {
             Response::Versions(versions)
         }
         Contents::Auth(string) => {
-            let result =
-                auth_or_refresh(&state.client, &state.tx, string.as_deref(), CLIENT_ID).await;
+            let on_code = |url, code| state.tx.try_send((url, code)).unwrap();
+            let result = auth::log_in(&state.client, CLIENT_ID, string.as_deref(), on_code).await;
             Response::Auth(result)
         }
     }
 }
-
-async fn auth_or_refresh(
-    client: &Client,
-    tx: &Sender<(String, String)>,
-    refresh_token: Option<&str>,
-    client_id: &str,
-) -> Result<(Account, String), Error> {
-    let auth_res = if let Some(token) = refresh_token {
-        refresh_token_response(client, token, client_id)
-            .await?
-            .into()
-    } else {
-        // https://wiki.vg/Microsoft_Authentication_Scheme
-
-        let device_response = device_response(client, client_id).await?;
-
-        let code = device_response.user_code;
-        let ms_url = device_response.verification_uri;
-
-        tx.send((ms_url, code)).await.unwrap();
-
-        loop {
-            let device_code = &device_response.device_code;
-            let auth_hook = authorization_token_response(client, device_code, client_id).await;
-            if let Ok(t) = auth_hook {
-                break t;
-            }
-        }
-    };
-
-    let xbox_response = xbox_response(client, &auth_res.access_token).await?;
-
-    let xbox_secure_token_res = xbox_security_token_response(client, &xbox_response.token).await?;
-
-    let claims = &xbox_secure_token_res.display_claims;
-    let token = &xbox_secure_token_res.token;
-    let mc_res = minecraft_response(claims, token, client).await?;
-
-    let ownership_check = minecraft_ownership_response(&mc_res.access_token, client).await?;
-
-    if ownership_check.items.is_empty() {
-        todo!("Is this worth checking?")
-    }
-
-    let profile = minecraft_profile_response(&mc_res.access_token, client).await?;
-
-    use std::time::{Duration, SystemTime};
-
-    let expires_in = Duration::from_secs(auth_res.expires_in);
-    let system_time = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap();
-    let combined_duration = system_time + expires_in;
-
-    let account = Account {
-        active: true,
-        expiry: combined_duration.as_secs(),
-        access_token: mc_res.access_token,
-        profile,
-    };
-
-    Ok((account, auth_res.refresh_token))
-}
@@ -1,14 +1,36 @@
-use crate::worker_logic::{Response, TaggedResponse};
+use crate::instances::InstalledMod;
+use crate::skins::{head, skin_previews};
+use crate::update::{self, Asset};
+use crate::worker_logic::{
+    ArchiveResponse, LoaderResponse, ModResponse, MultiMcResponse, PackResponse, Response,
+    SavesResponse, SkinResponse, TaggedResponse, UpdateResponse,
+};
+use async_bridge::{ProgressSender, Timeout};
+use launcher_app::Hooks;
+use launcher_core::account::skins::{self, SkinVariant};
+use launcher_core::account::types::Profile;
+use launcher_core::archives::{self, CompressionLevel};
+use launcher_core::assets;
+use launcher_core::java;
+use launcher_core::libraries;
+use launcher_core::loader::{self, Loader};
+use launcher_core::modpack::{self, PackInfo};
+use launcher_core::modrinth::{api, types::SearchHit};
+use launcher_core::multimc::{self, MultiMcInstance};
+use launcher_core::news;
+use launcher_core::progress::DownloadStats;
+use launcher_core::saves;
 use launcher_core::types::{AssetIndex, AssetIndexJson, Library, Version, VersionJson};
 use launcher_core::AsyncLauncher;
-use std::path::PathBuf;
-use std::sync::atomic::AtomicU64;
+use reqwest::Client;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub async fn get_asset_index(
     launcher_core: Arc<AsyncLauncher>,
     asset_index: Arc<AssetIndex>,
-    tag: Arc<Version>,
+    tag: u64,
     path: Arc<PathBuf>,
 ) -> Response {
     let index = launcher_core
@@ -25,24 +47,64 @@ pub async fn get_version(
     let json = launcher_core
         .get_version_json(&version, &path.join("versions"))
         .await;
-    Response::Version(json.map(Box::new))
+    Response::Version(version, json.map(Box::new))
+}
+
+pub async fn get_queued_version(
+    launcher_core: Arc<AsyncLauncher>,
+    version: Arc<Version>,
+    path: Arc<PathBuf>,
+    tag: u64,
+) -> Response {
+    let json = launcher_core
+        .get_version_json(&version, &path.join("versions"))
+        .await;
+    Response::Tagged(TaggedResponse::Version(json.map(Box::new)), tag)
+}
+
+pub async fn get_editor_version(
+    launcher_core: Arc<AsyncLauncher>,
+    version: Arc<Version>,
+    path: Arc<PathBuf>,
+) -> Response {
+    let json = launcher_core
+        .get_version_json(&version, &path.join("versions"))
+        .await;
+    Response::EditorVersion(json.map(Box::new))
+}
+
+pub async fn get_preview_version(
+    launcher_core: Arc<AsyncLauncher>,
+    version: Arc<Version>,
+    path: Arc<PathBuf>,
+    index: usize,
+    copy: bool,
+) -> Response {
+    let json = launcher_core
+        .get_version_json(&version, &path.join("versions"))
+        .await;
+    Response::LaunchPreview {
+        index,
+        copy,
+        json: json.map(Box::new),
+    }
 }
 
 pub async fn get_libraries(
     launcher_core: Arc<AsyncLauncher>,
     libs: Arc<[Library]>,
     path: Arc<PathBuf>,
-    total: Arc<AtomicU64>,
-    finished: Arc<AtomicU64>,
-    tag: Arc<Version>,
+    stats: Arc<DownloadStats>,
+    tag: u64,
 ) -> Response {
+    stats.reset();
     let path = launcher_core
         .download_libraries_and_get_path(
             &libs,
             &path.join("libraries"),
             &path.join("natives"),
-            &total,
-            &finished,
+            stats.total(),
+            stats.finished(),
         )
         .await;
     Response::Tagged(TaggedResponse::Libraries(path), tag)
@@ -52,12 +114,17 @@ pub async fn get_jar(
     launcher_core: Arc<AsyncLauncher>,
     json: Arc<VersionJson>,
     path: Arc<PathBuf>,
-    total: Arc<AtomicU64>,
-    finished: Arc<AtomicU64>,
-    tag: Arc<Version>,
+    stats: Arc<DownloadStats>,
+    tag: u64,
 ) -> Response {
+    stats.reset();
     let result = launcher_core
-        .download_jar(&json, &path.join("versions"), &total, &finished)
+        .download_jar(
+            &json,
+            &path.join("versions"),
+            stats.total(),
+            stats.finished(),
+        )
         .await;
     Response::Tagged(TaggedResponse::Jar(result), tag)
 }
@@ -66,44 +133,311 @@ pub async fn get_assets(
     launcher_core: Arc<AsyncLauncher>,
     index: Arc<AssetIndexJson>,
     path: Arc<PathBuf>,
-    total: Arc<AtomicU64>,
-    finished: Arc<AtomicU64>,
-    tag: Arc<Version>,
+    stats: Arc<DownloadStats>,
+    tag: u64,
 ) -> Response {
+    stats.reset();
     let result = launcher_core
-        .download_and_store_asset_index(&index, &path.join("assets"), &total, &finished)
+        .download_and_store_asset_index(
+            &index,
+            &path.join("assets"),
+            stats.total(),
+            stats.finished(),
+        )
         .await;
     Response::Tagged(TaggedResponse::Asset(result), tag)
 }
 
-/// Compiled Java byte-code to check for the current Java Version
-const CHECKER_CLASS: &[u8] = include_bytes!("VersionPrinter.class");
+pub async fn search_mods(
+    client: Client,
+    query: String,
+    loader: &'static str,
+    game_version: String,
+) -> Response {
+    let result = api::search_mods(&client, &query, loader, &game_version, 0).await;
+    Response::Mods(ModResponse::Search(result))
+}
+
+pub async fn install_mod(
+    client: Client,
+    project: SearchHit,
+    loader: &'static str,
+    game_version: String,
+    mods_dir: Arc<PathBuf>,
+) -> Response {
+    let result = async {
+        let versions =
+            api::project_versions(&client, &project.project_id, loader, &game_version).await?;
+
+        // Versions come newest first
+        let Some((version, file)) = versions
+            .iter()
+            .find_map(|version| Some((version, version.primary_file()?)))
+        else {
+            let message = format!("{} has no files for {game_version}", project.title);
+            return Err(tokio::io::Error::new(tokio::io::ErrorKind::NotFound, message).into());
+        };
+
+        let path = api::download_file(&client, file, &mods_dir).await?;
+
+        Ok::<_, launcher_core::Error>(InstalledMod {
+            project_id: project.project_id.clone(),
+            version_id: version.id.clone(),
+            title: project.title.clone(),
+            version_number: version.version_number.clone(),
+            file_name: path.file_name().unwrap().to_string_lossy().to_string(),
+        })
+    }
+    .await;
+
+    Response::Mods(ModResponse::Installed {
+        mods_dir,
+        project_id: project.project_id,
+        result,
+    })
+}
+
+pub async fn read_pack(pack: Arc<PathBuf>) -> Response {
+    let result = modpack::read_pack(&pack).await;
+    Response::Pack(PackResponse::Read(pack, result))
+}
+
+pub async fn install_pack(
+    launcher_core: Arc<AsyncLauncher>,
+    client: Client,
+    pack: Arc<PathBuf>,
+    info: Arc<PackInfo>,
+    directory: PathBuf,
+    stats: Arc<DownloadStats>,
+) -> Response {
+    let concurrency = launcher_core.concurrency();
+    let result =
+        modpack::install_pack(&client, &pack, &info, &directory, concurrency, &stats).await;
+    Response::Pack(PackResponse::Installed(pack, result))
+}
+
+pub async fn skin_preview(client: Client, url: String, variant: SkinVariant) -> Response {
+    let result = async {
+        let png = skins::texture(&client, &url).await?;
+        Ok::<_, launcher_core::Error>(skin_previews(&decode_skin(&png)?, variant))
+    }
+    .await;
+
+    Response::Skin(SkinResponse::Preview(url, variant, result))
+}
+
+pub async fn avatar(client: Client, profile: Profile, cache_dir: Arc<PathBuf>) -> Response {
+    let result = async {
+        let Some(png) = skins::avatar_texture(&client, &profile, &cache_dir).await? else {
+            return Ok(None);
+        };
+        Ok::<_, launcher_core::Error>(Some(head(&decode_skin(&png)?)))
+    }
+    .await;
+
+    Response::Skin(SkinResponse::Avatar(profile.id, result))
+}
+
+fn decode_skin(png: &[u8]) -> Result<image::RgbaImage, launcher_core::Error> {
+    let skin = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+        .map_err(|e| tokio::io::Error::new(tokio::io::ErrorKind::InvalidData, e))?;
+    Ok(skin.to_rgba8())
+}
+
+pub async fn upload_skin(
+    client: Client,
+    access_token: String,
+    path: PathBuf,
+    variant: SkinVariant,
+) -> Response {
+    let result = async {
+        let png = tokio::fs::read(&path).await?;
+        skins::upload_skin(&client, &access_token, png, variant).await
+    }
+    .await;
+
+    Response::Skin(SkinResponse::Changed(result))
+}
+
+pub async fn change_skin(
+    client: Client,
+    access_token: String,
+    url: String,
+    variant: SkinVariant,
+) -> Response {
+    let result = skins::change_skin(&client, &access_token, &url, variant).await;
+    Response::Skin(SkinResponse::Changed(result))
+}
+
+pub async fn reset_skin(client: Client, access_token: String) -> Response {
+    let result = skins::reset_skin(&client, &access_token).await;
+    Response::Skin(SkinResponse::Changed(result))
+}
+
+pub async fn set_cape(client: Client, access_token: String, cape_id: Option<String>) -> Response {
+    let result = skins::set_cape(&client, &access_token, cape_id.as_deref()).await;
+    Response::Skin(SkinResponse::Changed(result))
+}
+
+pub async fn install_java(
+    launcher_core: Arc<AsyncLauncher>,
+    client: Client,
+    component: String,
+    path: Arc<PathBuf>,
+    stats: Arc<DownloadStats>,
+) -> Response {
+    let concurrency = launcher_core.concurrency();
+    let result = java::install_runtime(
+        &client,
+        &component,
+        &path.join("runtimes"),
+        concurrency,
+        &stats,
+    )
+    .await;
+    Response::Java(result)
+}
+
+/// The news is only a side panel, so a slow server isn't waited on for long
+pub const NEWS_TIMEOUT: Timeout =
+    Timeout::new(Duration::from_secs(15)).with_retries(2, Duration::from_secs(2));
+
+pub async fn get_news(client: Client, path: Arc<PathBuf>) -> Response {
+    let result = news::patch_notes(&client, &path.join("patch_notes.json")).await;
+    Response::News(result)
+}
+
+pub async fn find_multimc_instances(root: PathBuf) -> Response {
+    let result = multimc::find_instances(&root).await;
+    Response::MultiMc(MultiMcResponse::Found(root, result))
+}
+
+pub async fn copy_multimc_instance(
+    instance: Arc<MultiMcInstance>,
+    destination: PathBuf,
+    progress: ProgressSender<f32>,
+) -> Response {
+    let stats = DownloadStats::new();
+    let copy = multimc::copy_instance(&instance, &destination, &stats);
+    let result = progress.watch(copy, || stats.snapshot().fraction()).await;
+    Response::MultiMc(MultiMcResponse::Copied {
+        instance,
+        destination,
+        result,
+    })
+}
+
+pub async fn backup_world(world: PathBuf, backups_dir: PathBuf) -> Response {
+    let result = saves::backup_world(&world, &backups_dir, CompressionLevel::default()).await;
+    Response::Saves(SavesResponse::BackedUp(world, result))
+}
 
-pub fn get_vendor_major_version(jvm: &str) -> (String, u32) {
-    let tmp = std::env::temp_dir();
-    let checker_class_file = tmp.join("VersionPrinter.class");
-    std::fs::write(checker_class_file, CHECKER_CLASS).unwrap();
-    let io = std::process::Command::new(jvm)
-        .env_clear()
-        .current_dir(tmp)
-        .args(["-DFile.Encoding=UTF-8", "VersionPrinter"])
-        .output()
-        .unwrap();
+/// Backs up the worlds played in the game directory once the game exits
+pub async fn backup_played_worlds(game_dir: PathBuf, hooks: Hooks) -> Response {
+    let result = hooks.run_backup_worlds(&game_dir).await;
+    Response::Saves(SavesResponse::AutoBackedUp(result))
+}
 
-    let string = String::from_utf8_lossy(&io.stdout);
+pub async fn restore_backup(backup: PathBuf, saves_dir: PathBuf) -> Response {
+    let result = saves::restore_backup(&backup, &saves_dir).await;
+    Response::Saves(SavesResponse::Restored(backup, result))
+}
 
-    let (version, name) = unsafe { string.split_once('\n').unwrap_unchecked() };
+/// Removes the oldest backups past `keep` once the new one is made
+pub async fn archive_instance(
+    name: String,
+    directory: PathBuf,
+    archives_dir: PathBuf,
+    skip: &'static [&'static str],
+    keep: usize,
+) -> Response {
+    let result = async {
+        let archive = archives::archive_instance(&directory, &archives_dir, skip).await?;
+        archives::prune_archives(&archives_dir, keep)?;
+        Ok::<_, launcher_core::Error>(archive)
+    }
+    .await;
+    Response::Archive(ArchiveResponse::Created(name, result))
+}
 
-    let mut split = version.split('.');
-    let next = split.next().unwrap();
-    let version = if next == "1" {
-        split.next().unwrap()
-    } else {
-        next
-    };
+pub async fn restore_archive(archive: PathBuf, directory: PathBuf) -> Response {
+    let result = archives::restore_archive(&archive, &directory).await;
+    Response::Archive(ArchiveResponse::Restored(archive, result))
+}
+
+/// Looks for assets no version uses, removing them unless it's a dry run
+pub async fn collect_asset_garbage(assets_dir: PathBuf, dry_run: bool) -> Response {
+    let result = tokio::task::spawn_blocking(move || assets::collect_garbage(&assets_dir, dry_run))
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e).into()));
+    Response::Assets(dry_run, result)
+}
 
-    let name = name.to_string();
-    let version = version.parse().unwrap_or(0);
+/// Looks for libraries and natives no version uses, removing them unless it's a dry run
+pub async fn prune_libraries(directory: PathBuf, dry_run: bool) -> Response {
+    Response::Libraries(dry_run, libraries::prune(&directory, dry_run).await)
+}
+
+pub async fn check_update(client: Client) -> Response {
+    Response::Update(UpdateResponse::Checked(update::check(&client).await))
+}
+
+pub async fn stage_update(client: Client, asset: Asset) -> Response {
+    Response::Update(UpdateResponse::Staged(update::stage(&client, &asset).await))
+}
+
+pub async fn get_loader_versions(client: Client, loader: Loader, game_version: String) -> Response {
+    let result = loader::loader_versions(&client, loader, &game_version).await;
+    Response::Loader(LoaderResponse::Versions {
+        loader,
+        game_version,
+        result,
+    })
+}
+
+pub async fn install_loader(
+    client: Client,
+    loader: Loader,
+    game_version: String,
+    loader_version: String,
+    path: Arc<PathBuf>,
+) -> Response {
+    let result = loader::install(&client, loader, &game_version, &loader_version, &path).await;
+    Response::Loader(LoaderResponse::Installed {
+        loader,
+        game_version,
+        loader_version,
+        result: result.map(|_| ()),
+    })
+}
+
+pub async fn update_loader(
+    client: Client,
+    loader: Loader,
+    game_version: String,
+    installed: String,
+    path: Arc<PathBuf>,
+    tag: u64,
+) -> Response {
+    let result = async {
+        let update = loader::stable_update(&client, loader, &game_version, &installed).await?;
+        let Some(version) = update else {
+            return Ok(None);
+        };
+        loader::install(&client, loader, &game_version, &version, &path).await?;
+        Ok(Some(version))
+    }
+    .await;
+    Response::Tagged(TaggedResponse::Loader(result), tag)
+}
 
-    (name, version)
+/// The JVM's major version, `u32::MAX` if it doesn't run
+pub fn java_major_version(jvm: &str) -> u32 {
+    match launcher_core::jvm::probe(Path::new(jvm)) {
+        Ok(version) => version.major,
+        Err(e) => {
+            eprintln!("{e}");
+            u32::MAX
+        }
+    }
 }
@@ -0,0 +1,160 @@
+//! The endpoints without async, for code that doesn't run a tokio runtime
+
+use crate::types::{Full, Game, Installer, Loader, LoaderEntry, Profile};
+use crate::{server_jar_url, Error, BASE};
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+fn get_json<T: DeserializeOwned>(client: &Client, url: String) -> Result<T, Error> {
+    let response = client.get(url).send()?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Http(status));
+    }
+    let buf = response.bytes()?;
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+pub fn full(client: &Client) -> Result<Full, Error> {
+    get_json(client, format!("{BASE}/v2/versions"))
+}
+
+pub fn game_versions(client: &Client) -> Result<Vec<Game>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/game"))
+}
+
+pub fn game_yarn_versions(client: &Client) -> Result<Vec<Game>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/game/yarn"))
+}
+
+pub fn game_intermediary_versions(client: &Client) -> Result<Vec<Game>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/game/intermediary"))
+}
+
+pub fn intermediary_versions(client: &Client) -> Result<Vec<Loader>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/intermediary"))
+}
+
+pub fn intermediary_versions_for_game_version(
+    client: &Client,
+    game_version: &str,
+) -> Result<Vec<Loader>, Error> {
+    get_json(
+        client,
+        format!("{BASE}/v2/versions/intermediary/{game_version}"),
+    )
+}
+
+pub fn loader_versions(client: &Client) -> Result<Vec<Loader>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/loader"))
+}
+
+/// The loader versions that support the game version, newest first.
+/// Game versions the meta server doesn't know are [`Error::NotFound`]
+pub fn loader_versions_for_game_version(
+    client: &Client,
+    game_version: &str,
+) -> Result<Vec<LoaderEntry>, Error> {
+    let url = format!("{BASE}/v2/versions/loader/{game_version}");
+    let entries: Vec<LoaderEntry> =
+        get_json(client, url).map_err(|e| e.or_not_found(game_version, None))?;
+    if entries.is_empty() {
+        return Err(Error::NotFound {
+            game_version: game_version.to_string(),
+            loader_version: None,
+        });
+    }
+    Ok(entries)
+}
+
+pub fn installer_versions(client: &Client) -> Result<Vec<Installer>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/installer"))
+}
+
+pub fn yarn_versions_for_game_version(
+    client: &Client,
+    game_version: &str,
+) -> Result<Vec<Loader>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/yarn/{game_version}"))
+}
+
+pub fn loader_profile_json(
+    client: &Client,
+    game_version: &str,
+    loader_version: &str,
+) -> Result<Profile, Error> {
+    let url = format!("{BASE}/v2/versions/loader/{game_version}/{loader_version}/profile/json");
+    get_json(client, url).map_err(|e| e.or_not_found(game_version, Some(loader_version)))
+}
+
+/// Downloads the server launcher jar to `file`, it's started with `java -jar` in the
+/// server's folder
+pub fn download_server_jar(
+    client: &Client,
+    game_version: &str,
+    loader_version: &str,
+    installer_version: &str,
+    file: &Path,
+) -> Result<(), Error> {
+    let url = server_jar_url(game_version, loader_version, installer_version);
+    let response = client.get(url).send()?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Http(status).or_not_found(game_version, Some(loader_version)));
+    }
+    let buf = response.bytes()?;
+
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(file, &buf)?;
+    Ok(())
+}
+
+pub mod quilt {
+    use super::get_json;
+    use crate::quilt::{Installer, Loader, LoaderEntry, BASE};
+    use crate::types::{Game, Profile};
+    use crate::Error;
+    use reqwest::blocking::Client;
+
+    pub fn game_versions(client: &Client) -> Result<Vec<Game>, Error> {
+        get_json(client, format!("{BASE}/v3/versions/game"))
+    }
+
+    pub fn loader_versions(client: &Client) -> Result<Vec<Loader>, Error> {
+        get_json(client, format!("{BASE}/v3/versions/loader"))
+    }
+
+    /// The loader versions that support the game version, newest first.
+    /// Game versions the meta server doesn't know are [`Error::NotFound`]
+    pub fn loader_versions_for_game_version(
+        client: &Client,
+        game_version: &str,
+    ) -> Result<Vec<LoaderEntry>, Error> {
+        let url = format!("{BASE}/v3/versions/loader/{game_version}");
+        let entries: Vec<LoaderEntry> =
+            get_json(client, url).map_err(|e| e.or_not_found(game_version, None))?;
+        if entries.is_empty() {
+            return Err(Error::NotFound {
+                game_version: game_version.to_string(),
+                loader_version: None,
+            });
+        }
+        Ok(entries)
+    }
+
+    pub fn installer_versions(client: &Client) -> Result<Vec<Installer>, Error> {
+        get_json(client, format!("{BASE}/v3/versions/installer"))
+    }
+
+    pub fn loader_profile_json(
+        client: &Client,
+        game_version: &str,
+        loader_version: &str,
+    ) -> Result<Profile, Error> {
+        let url = format!("{BASE}/v3/versions/loader/{game_version}/{loader_version}/profile/json");
+        get_json(client, url).map_err(|e| e.or_not_found(game_version, Some(loader_version)))
+    }
+}
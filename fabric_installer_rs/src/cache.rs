@@ -0,0 +1,243 @@
+use crate::types::{Game, Installer, Loader, LoaderEntry};
+use crate::{Error, BASE};
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a list is used without asking the meta server, if the cache isn't given one
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Keeps meta responses in a folder, so the lists are there offline and aren't fetched every
+/// time a dropdown opens. Lists older than the ttl are checked with their etag, and used
+/// as they are if the server can't be reached
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    pub async fn game_versions(&self, client: &reqwest::Client) -> Result<Vec<Game>, Error> {
+        self.get(client, &format!("{BASE}/v2/versions/game")).await
+    }
+
+    pub async fn loader_versions(&self, client: &reqwest::Client) -> Result<Vec<Loader>, Error> {
+        self.get(client, &format!("{BASE}/v2/versions/loader"))
+            .await
+    }
+
+    pub async fn loader_versions_for_game_version(
+        &self,
+        client: &reqwest::Client,
+        game_version: &str,
+    ) -> Result<Vec<LoaderEntry>, Error> {
+        let url = format!("{BASE}/v2/versions/loader/{game_version}");
+        self.get(client, &url)
+            .await
+            .map_err(|e| e.or_not_found(game_version, None))
+    }
+
+    pub async fn installer_versions(
+        &self,
+        client: &reqwest::Client,
+    ) -> Result<Vec<Installer>, Error> {
+        self.get(client, &format!("{BASE}/v2/versions/installer"))
+            .await
+    }
+
+    /// Any json endpoint, like [`crate::quilt`]'s
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+    ) -> Result<T, Error> {
+        let key = url
+            .trim_start_matches("https://")
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        let body_file = self.dir.join(format!("{key}.json"));
+        let etag_file = self.dir.join(format!("{key}.etag"));
+
+        let cached = tokio::fs::read(&body_file).await.ok();
+        if let Some(cached) = &cached {
+            if self.is_fresh(&body_file).await {
+                if let Ok(value) = serde_json::from_slice(cached) {
+                    return Ok(value);
+                }
+            }
+        }
+
+        let mut request = client.get(url);
+        if cached.is_some() {
+            if let Ok(etag) = tokio::fs::read_to_string(&etag_file).await {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+        }
+
+        let response = match request.send().await {
+            Ok(response) if response.status().is_client_error() => {
+                return Err(Error::Http(response.status()));
+            }
+            Ok(response) if !response.status().is_server_error() => response,
+            // Offline or the server is down, an old list is better than none
+            failed => {
+                let stale = cached.and_then(|cached| serde_json::from_slice(&cached).ok());
+                return match (stale, failed) {
+                    (Some(stale), _) => Ok(stale),
+                    (None, Ok(response)) => Err(Error::Http(response.status())),
+                    (None, Err(e)) => Err(e.into()),
+                };
+            }
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                // Written again so it counts as fresh for another ttl
+                store(&body_file, &cached).await;
+                return Ok(serde_json::from_slice(&cached)?);
+            }
+        }
+
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string);
+        let buf = response.bytes().await?;
+        let value = serde_json::from_slice(&buf)?;
+
+        store(&body_file, &buf).await;
+        if let Some(etag) = etag {
+            store(&etag_file, etag.as_bytes()).await;
+        }
+        Ok(value)
+    }
+
+    async fn is_fresh(&self, file: &Path) -> bool {
+        let Ok(metadata) = tokio::fs::metadata(file).await else {
+            return false;
+        };
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.elapsed().ok());
+        age.is_some_and(|age| age < self.ttl)
+    }
+}
+
+// The cache is only a shortcut, failing to write it doesn't fail the request
+async fn store(file: &Path, contents: &[u8]) {
+    if let Some(parent) = file.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    if let Err(e) = tokio::fs::write(file, contents).await {
+        eprintln!("Couldn't cache {}: {e}", file.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cache;
+    use crate::Error;
+    use reqwest::StatusCode;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const OK: &str =
+        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 3\r\nConnection: close\r\n\r\n[1]";
+    const OK_AGAIN: &str = "HTTP/1.1 200 OK\r\nContent-Length: 3\r\nConnection: close\r\n\r\n[2]";
+    const NOT_MODIFIED: &str = "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n";
+    const UNAVAILABLE: &str =
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const NOT_FOUND: &str =
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+    /// Answers each connection with the next response, keeping the requests it got
+    async fn serve(responses: Vec<&'static str>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/versions", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = stream.read(&mut buf).await.unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let request = String::from_utf8(request).unwrap().to_lowercase();
+                received.lock().unwrap().push(request);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_fresh_cache() {
+        let dir = cache_dir("synth_fabric_cache_fresh");
+        let cache = Cache::new(dir.clone(), Duration::from_secs(60 * 60));
+        let client = reqwest::Client::new();
+        let (url, requests) = serve(vec![OK, OK_AGAIN]).await;
+
+        let first: Vec<u32> = cache.get(&client, &url).await.unwrap();
+        let second: Vec<u32> = cache.get(&client, &url).await.unwrap();
+        assert_eq!((first, second), (vec![1], vec![1]));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_etag_revalidation() {
+        let dir = cache_dir("synth_fabric_cache_etag");
+        let cache = Cache::new(dir.clone(), Duration::ZERO);
+        let client = reqwest::Client::new();
+        let (url, requests) = serve(vec![OK, NOT_MODIFIED]).await;
+
+        let first: Vec<u32> = cache.get(&client, &url).await.unwrap();
+        let second: Vec<u32> = cache.get(&client, &url).await.unwrap();
+        assert_eq!((first, second), (vec![1], vec![1]));
+        let requests = requests.lock().unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stale_cache() {
+        let dir = cache_dir("synth_fabric_cache_stale");
+        let cache = Cache::new(dir.clone(), Duration::ZERO);
+        let client = reqwest::Client::new();
+        let (url, _) = serve(vec![OK, UNAVAILABLE, NOT_FOUND]).await;
+
+        let first: Vec<u32> = cache.get(&client, &url).await.unwrap();
+        assert_eq!(first, vec![1]);
+        // The server being down falls back to the old list, a bad request doesn't
+        let stale: Vec<u32> = cache.get(&client, &url).await.unwrap();
+        assert_eq!(stale, vec![1]);
+        let missing = cache.get::<Vec<u32>>(&client, &url).await;
+        assert!(matches!(missing, Err(Error::Http(StatusCode::NOT_FOUND))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+use reqwest::StatusCode;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum Error {
+    /// The meta server answered, but not with a success
+    Http(StatusCode),
+    /// The meta server couldn't be reached, or the response was cut off
+    Network(reqwest::Error),
+    /// The response isn't what the types expect
+    Decode(serde_json::Error),
+    /// A download couldn't be written
+    Io(std::io::Error),
+    /// The library with that name didn't match its hash
+    Checksum(String),
+    /// The meta server doesn't know the game version, or the loader version for it
+    NotFound {
+        game_version: String,
+        loader_version: Option<String>,
+    },
+}
+
+impl Error {
+    /// The meta server answers unknown versions with a 400 or a 404
+    pub(crate) fn or_not_found(self, game_version: &str, loader_version: Option<&str>) -> Self {
+        match self {
+            Error::Http(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => Error::NotFound {
+                game_version: game_version.to_string(),
+                loader_version: loader_version.map(str::to_string),
+            },
+            e => e,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Error::Network(value)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Error::Decode(value)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Http(status) => write!(f, "The meta server answered with {status}"),
+            Error::Network(err) => err.fmt(f),
+            Error::Decode(err) => err.fmt(f),
+            Error::Io(err) => err.fmt(f),
+            Error::Checksum(name) => write!(f, "{name} failed its checksum"),
+            Error::NotFound {
+                game_version,
+                loader_version: Some(loader_version),
+            } => write!(f, "There is no loader {loader_version} for {game_version}"),
+            Error::NotFound { game_version, .. } => {
+                write!(f, "There is no loader for {game_version}")
+            }
+        }
+    }
+}
+
+// launcher_core has no variant for the server's answer, so those become io errors
+// like the ones for failed checksums
+impl From<Error> for launcher_core::Error {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::Network(err) => launcher_core::Error::Reqwest(err),
+            Error::Decode(err) => launcher_core::Error::SerdeJson(err),
+            Error::Io(err) => launcher_core::Error::Tokio(err),
+            e @ Error::Http(_) => std::io::Error::other(e.to_string()).into(),
+            e @ Error::Checksum(_) => {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()).into()
+            }
+            e @ Error::NotFound { .. } => {
+                std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string()).into()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use reqwest::StatusCode;
+    use std::io::ErrorKind;
+
+    #[tokio::test]
+    async fn test_from() {
+        let io = Error::from(std::io::Error::new(ErrorKind::PermissionDenied, "denied"));
+        assert!(matches!(&io, Error::Io(e) if e.kind() == ErrorKind::PermissionDenied));
+        let decode = Error::from(serde_json::from_str::<u32>("x").unwrap_err());
+        assert!(matches!(decode, Error::Decode(_)));
+        // An invalid url fails before anything is sent
+        let network = Error::from(
+            reqwest::Client::new()
+                .get("not a url")
+                .send()
+                .await
+                .unwrap_err(),
+        );
+        assert!(matches!(network, Error::Network(_)));
+
+        assert!(matches!(
+            launcher_core::Error::from(io),
+            launcher_core::Error::Tokio(e) if e.kind() == ErrorKind::PermissionDenied
+        ));
+        assert!(matches!(
+            launcher_core::Error::from(decode),
+            launcher_core::Error::SerdeJson(_)
+        ));
+        assert!(matches!(
+            launcher_core::Error::from(network),
+            launcher_core::Error::Reqwest(_)
+        ));
+    }
+
+    #[test]
+    fn test_into_io_errors() {
+        let kind = |error: Error| match launcher_core::Error::from(error) {
+            launcher_core::Error::Tokio(e) => (e.kind(), e.to_string()),
+            e => panic!("{e}"),
+        };
+        assert_eq!(
+            kind(Error::Http(StatusCode::INTERNAL_SERVER_ERROR)),
+            (
+                ErrorKind::Other,
+                "The meta server answered with 500 Internal Server Error".into()
+            )
+        );
+        assert_eq!(
+            kind(Error::Checksum("net.fabricmc:fabric-loader:0.15.7".into())),
+            (
+                ErrorKind::InvalidData,
+                "net.fabricmc:fabric-loader:0.15.7 failed its checksum".into()
+            )
+        );
+        let not_found = Error::Http(StatusCode::NOT_FOUND).or_not_found("1.20.4", Some("0.1.0"));
+        assert_eq!(
+            kind(not_found),
+            (
+                ErrorKind::NotFound,
+                "There is no loader 0.1.0 for 1.20.4".into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_or_not_found() {
+        let not_found = Error::Http(StatusCode::BAD_REQUEST).or_not_found("1.20.4", None);
+        assert!(matches!(
+            not_found,
+            Error::NotFound { game_version, loader_version: None } if game_version == "1.20.4"
+        ));
+        // Only unknown versions are turned into NotFound
+        let down = Error::Http(StatusCode::BAD_GATEWAY).or_not_found("1.20.4", None);
+        assert!(matches!(down, Error::Http(StatusCode::BAD_GATEWAY)));
+    }
+}
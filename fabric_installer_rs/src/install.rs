@@ -0,0 +1,94 @@
+use crate::types::{Profile, ProfileLibrary};
+use crate::Error;
+use futures::{StreamExt, TryStreamExt};
+use launcher_core::checksum;
+use launcher_core::progress::DownloadStats;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+
+/// Downloads the loader's libraries, intermediary included, into the launcher's libraries
+/// folder. Jars that are already there with the right hash are kept. The sizes are added
+/// to `stats`, which isn't reset first so it can be shared with the vanilla download
+pub async fn install_loader_libraries(
+    client: &reqwest::Client,
+    profile: &Profile,
+    libraries_dir: &Path,
+    concurrency: usize,
+    stats: &DownloadStats,
+) -> Result<(), Error> {
+    let libraries = profile.libraries.iter().filter_map(|library| {
+        let path = library.path()?;
+        let url = library.download_url()?;
+        let size = library.size.unwrap_or_default();
+        stats.total().fetch_add(size, Ordering::Relaxed);
+        Some(Ok::<_, Error>((library, libraries_dir.join(path), url)))
+    });
+
+    futures::stream::iter(libraries)
+        .try_for_each_concurrent(concurrency, |(library, file, url)| async move {
+            if tokio::fs::try_exists(&file).await? {
+                let buf = tokio::fs::read(&file).await?;
+                if matches(library, &buf) {
+                    stats
+                        .finished()
+                        .fetch_add(buf.len() as u64, Ordering::Relaxed);
+                    return Ok(());
+                }
+            }
+
+            let response = client.get(url).send().await?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(Error::Http(status));
+            }
+
+            let mut buf = Vec::new();
+            let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk?;
+                stats
+                    .finished()
+                    .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                buf.extend_from_slice(&chunk);
+            }
+
+            if !matches(library, &buf) {
+                return Err(Error::Checksum(library.name.clone()));
+            }
+            if let Some(parent) = file.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&file, &buf).await?;
+            Ok(())
+        })
+        .await
+}
+
+// Libraries without a hash can't be checked, any jar counts
+fn matches(library: &ProfileLibrary, buf: &[u8]) -> bool {
+    let Some(sha1) = &library.sha1 else {
+        return true;
+    };
+    checksum::sha1(buf) == *sha1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+    use crate::types::ProfileLibrary;
+    use launcher_core::checksum;
+
+    #[test]
+    fn test_matches() {
+        let mut library = ProfileLibrary {
+            name: "net.fabricmc:sponge-mixin:0.12.5".into(),
+            url: "https://maven.fabricmc.net/".into(),
+            sha1: None,
+            size: None,
+        };
+        assert!(matches(&library, b"anything"));
+        library.sha1 = Some(checksum::sha1(b"mixin"));
+        assert!(matches(&library, b"mixin"));
+        assert!(!matches(&library, b"mixin, but different"));
+    }
+}
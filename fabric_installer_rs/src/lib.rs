@@ -1,63 +1,236 @@
-use crate::types::{Game, Loader};
+use crate::types::{Game, Installer, Loader, LoaderEntry, Profile};
+use launcher_core::types::{
+    Action, Artifact, GameElement, JvmClass, Library, Rule, Value, VersionJson,
+};
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
 use types::Full;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+mod error;
+mod install;
+pub mod quilt;
 pub mod types;
 
+pub use error::Error;
+pub use install::install_loader_libraries;
+
 pub const BASE: &str = "https://meta.fabricmc.net";
+/// Where the mappings are, the meta server only lists them
+pub const MAVEN: &str = "https://maven.fabricmc.net";
 
-pub async fn full(client: &reqwest::Client) -> reqwest::Result<Full> {
-    client
-        .get(format!("{BASE}/v2/versions"))
-        .send()
-        .await?
-        .json()
-        .await
+pub(crate) async fn get_json<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: String,
+) -> Result<T, Error> {
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Http(status));
+    }
+    let buf = response.bytes().await?;
+    Ok(serde_json::from_slice(&buf)?)
 }
 
-pub async fn game_versions(client: &reqwest::Client) -> reqwest::Result<Vec<Game>> {
-    client
-        .get(format!("{BASE}/v2/versions/game"))
-        .send()
-        .await?
-        .json()
-        .await
+pub async fn full(client: &reqwest::Client) -> Result<Full, Error> {
+    get_json(client, format!("{BASE}/v2/versions")).await
 }
 
-pub async fn game_yarn_versions(client: &reqwest::Client) -> reqwest::Result<Vec<Game>> {
-    client
-        .get(format!("{BASE}/v2/versions/game/yarn"))
-        .send()
-        .await?
-        .json()
-        .await
+pub async fn game_versions(client: &reqwest::Client) -> Result<Vec<Game>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/game")).await
+}
+
+pub async fn game_yarn_versions(client: &reqwest::Client) -> Result<Vec<Game>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/game/yarn")).await
 }
 
-pub async fn game_intermediary_versions(client: &reqwest::Client) -> reqwest::Result<Vec<Game>> {
-    client
-        .get(format!("{BASE}/v2/versions/game/intermediary"))
-        .send()
-        .await?
-        .json()
+pub async fn game_intermediary_versions(client: &reqwest::Client) -> Result<Vec<Game>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/game/intermediary")).await
+}
+
+pub async fn intermediary_versions(client: &reqwest::Client) -> Result<Vec<Loader>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/intermediary")).await
+}
+
+pub async fn intermediary_versions_for_game_version(
+    client: &reqwest::Client,
+    game_version: &str,
+) -> Result<Vec<Loader>, Error> {
+    get_json(
+        client,
+        format!("{BASE}/v2/versions/intermediary/{game_version}"),
+    )
+    .await
+}
+
+pub async fn loader_versions(client: &reqwest::Client) -> Result<Vec<Loader>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/loader")).await
+}
+
+/// The loader versions that support the game version, newest first.
+/// Game versions the meta server doesn't know are [`Error::NotFound`]
+pub async fn loader_versions_for_game_version(
+    client: &reqwest::Client,
+    game_version: &str,
+) -> Result<Vec<LoaderEntry>, Error> {
+    let url = format!("{BASE}/v2/versions/loader/{game_version}");
+    let entries: Vec<LoaderEntry> = get_json(client, url)
         .await
+        .map_err(|e| e.or_not_found(game_version, None))?;
+    if entries.is_empty() {
+        return Err(Error::NotFound {
+            game_version: game_version.to_string(),
+            loader_version: None,
+        });
+    }
+    Ok(entries)
+}
+
+pub async fn installer_versions(client: &reqwest::Client) -> Result<Vec<Installer>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/installer")).await
+}
+
+pub async fn yarn_versions_for_game_version(
+    client: &reqwest::Client,
+    game_version: &str,
+) -> Result<Vec<Loader>, Error> {
+    get_json(client, format!("{BASE}/v2/versions/yarn/{game_version}")).await
 }
 
-pub async fn intermediary_versions(client: &reqwest::Client) -> reqwest::Result<Vec<Loader>> {
-    client
-        .get(format!("{BASE}/v2/versions/intermediary"))
-        .send()
-        .await?
-        .json()
+pub async fn loader_profile_json(
+    client: &reqwest::Client,
+    game_version: &str,
+    loader_version: &str,
+) -> Result<Profile, Error> {
+    let url = format!("{BASE}/v2/versions/loader/{game_version}/{loader_version}/profile/json");
+    get_json(client, url)
         .await
+        .map_err(|e| e.or_not_found(game_version, Some(loader_version)))
 }
 
-pub async fn intermediary_versions_for_game_version(
+/// Where the server launcher jar for the loader is, it downloads the server and the
+/// libraries the first time it runs
+pub fn server_jar_url(game_version: &str, loader_version: &str, installer_version: &str) -> String {
+    format!(
+        "{BASE}/v2/versions/loader/{game_version}/{loader_version}/{installer_version}/server/jar"
+    )
+}
+
+/// Downloads the server launcher jar to `file`, it's started with `java -jar` in the
+/// server's folder
+pub async fn download_server_jar(
     client: &reqwest::Client,
     game_version: &str,
-) -> reqwest::Result<Vec<Loader>> {
-    client
-        .get(format!("{BASE}/v2/versions/intermediary/{game_version}"))
-        .send()
-        .await?
-        .json()
+    loader_version: &str,
+    installer_version: &str,
+    file: &Path,
+) -> Result<(), Error> {
+    let url = server_jar_url(game_version, loader_version, installer_version);
+    download(client, url, file)
         .await
+        .map_err(|e| e.or_not_found(game_version, Some(loader_version)))
+}
+
+/// Downloads the newest yarn mappings for the game version into `directory`, and returns
+/// the jar. Nothing uses them yet, they're for setting up a development instance
+pub async fn download_yarn(
+    client: &reqwest::Client,
+    game_version: &str,
+    directory: &Path,
+) -> Result<PathBuf, Error> {
+    let versions = yarn_versions_for_game_version(client, game_version).await?;
+    download_mappings(client, versions.first(), game_version, directory).await
+}
+
+/// Downloads the intermediary mappings for the game version into `directory`, and returns
+/// the jar
+pub async fn download_intermediary(
+    client: &reqwest::Client,
+    game_version: &str,
+    directory: &Path,
+) -> Result<PathBuf, Error> {
+    let versions = intermediary_versions_for_game_version(client, game_version).await?;
+    download_mappings(client, versions.first(), game_version, directory).await
+}
+
+async fn download_mappings(
+    client: &reqwest::Client,
+    mappings: Option<&Loader>,
+    game_version: &str,
+    directory: &Path,
+) -> Result<PathBuf, Error> {
+    let not_found = || Error::NotFound {
+        game_version: game_version.to_string(),
+        loader_version: None,
+    };
+    let path = mappings
+        .and_then(Loader::mappings_path)
+        .ok_or_else(not_found)?;
+    let file = directory.join(path.rsplit('/').next().unwrap_or(&path));
+    download(client, format!("{MAVEN}/{path}"), &file).await?;
+    Ok(file)
+}
+
+async fn download(client: &reqwest::Client, url: String, file: &Path) -> Result<(), Error> {
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Http(status));
+    }
+    let buf = response.bytes().await?;
+
+    if let Some(parent) = file.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(file, &buf).await?;
+    Ok(())
+}
+
+/// Layers the profile over the vanilla version it inherits from, so the launcher can start
+/// it like any other version. The loader's libraries go first, ahead of the vanilla ones
+pub fn version_json(profile: Profile, vanilla: VersionJson) -> VersionJson {
+    let loader_libraries = profile.libraries.into_iter().filter_map(|library| {
+        let path = library.path()?;
+        let url = library.download_url()?;
+        Some(Library {
+            // Without a hash the launcher can't tell the jar is complete, and fetches it again
+            downloads: Some(Artifact {
+                sha1: library.sha1.unwrap_or_default(),
+                size: library.size.unwrap_or_default(),
+                url,
+                path,
+            }),
+            name: library.name,
+            rule: Rule {
+                action: Action::Allow,
+                os: None,
+            },
+        })
+    });
+    let libraries = loader_libraries
+        .chain(vanilla.libraries.iter().cloned())
+        .collect();
+
+    let mut arguments = vanilla.arguments;
+    arguments
+        .game
+        .extend(profile.arguments.game.into_iter().map(GameElement::String));
+    arguments
+        .jvm
+        .extend(profile.arguments.jvm.into_iter().map(|value| JvmClass {
+            rules: None,
+            value: Value::String(value),
+        }));
+
+    VersionJson {
+        arguments,
+        id: profile.id,
+        main_class: profile.main_class,
+        release_time: profile.release_time,
+        time: profile.time,
+        libraries,
+        ..vanilla
+    }
 }
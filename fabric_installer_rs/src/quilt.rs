@@ -0,0 +1,80 @@
+//! The same endpoints for Quilt, its meta server mirrors Fabric's. Quilt doesn't mark
+//! stable builds, and its profiles are converted with [`crate::version_json`]
+
+use crate::types::{Game, Profile};
+use crate::{get_json, Error};
+use serde::{Deserialize, Serialize};
+
+pub const BASE: &str = "https://meta.quiltmc.org";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Loader {
+    pub separator: String,
+    pub build: i64,
+    pub maven: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mappings {
+    pub maven: String,
+    pub version: String,
+}
+
+/// A loader version that works with the game version it was asked for, with the
+/// mappings for it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoaderEntry {
+    pub loader: Loader,
+    pub hashed: Mappings,
+    pub intermediary: Mappings,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Installer {
+    pub url: Option<String>,
+    pub maven: String,
+    pub version: String,
+}
+
+pub async fn game_versions(client: &reqwest::Client) -> Result<Vec<Game>, Error> {
+    get_json(client, format!("{BASE}/v3/versions/game")).await
+}
+
+pub async fn loader_versions(client: &reqwest::Client) -> Result<Vec<Loader>, Error> {
+    get_json(client, format!("{BASE}/v3/versions/loader")).await
+}
+
+/// The loader versions that support the game version, newest first.
+/// Game versions the meta server doesn't know are [`Error::NotFound`]
+pub async fn loader_versions_for_game_version(
+    client: &reqwest::Client,
+    game_version: &str,
+) -> Result<Vec<LoaderEntry>, Error> {
+    let url = format!("{BASE}/v3/versions/loader/{game_version}");
+    let entries: Vec<LoaderEntry> = get_json(client, url)
+        .await
+        .map_err(|e| e.or_not_found(game_version, None))?;
+    if entries.is_empty() {
+        return Err(Error::NotFound {
+            game_version: game_version.to_string(),
+            loader_version: None,
+        });
+    }
+    Ok(entries)
+}
+
+pub async fn installer_versions(client: &reqwest::Client) -> Result<Vec<Installer>, Error> {
+    get_json(client, format!("{BASE}/v3/versions/installer")).await
+}
+
+pub async fn loader_profile_json(
+    client: &reqwest::Client,
+    game_version: &str,
+    loader_version: &str,
+) -> Result<Profile, Error> {
+    let url = format!("{BASE}/v3/versions/loader/{game_version}/{loader_version}/profile/json");
+    get_json(client, url)
+        .await
+        .map_err(|e| e.or_not_found(game_version, Some(loader_version)))
+}
@@ -33,3 +33,124 @@ pub struct Loader {
     pub stable: bool,
     pub game_version: Option<String>,
 }
+
+impl Loader {
+    /// For yarn and intermediary, where the jar with the tiny v2 mappings is in the maven
+    pub fn mappings_path(&self) -> Option<String> {
+        maven_path(&format!("{}:v2", self.maven))
+    }
+}
+
+/// A loader version that works with the game version it was asked for, with the
+/// intermediary mappings for it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoaderEntry {
+    pub loader: Loader,
+    pub intermediary: Installer,
+}
+
+/// The launch profile for one loader and game version, layered over the vanilla version json
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub inherits_from: String,
+    pub release_time: String,
+    pub time: String,
+    pub main_class: String,
+    #[serde(default)]
+    pub arguments: ProfileArguments,
+    pub libraries: Vec<ProfileLibrary>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProfileArguments {
+    #[serde(default)]
+    pub game: Vec<String>,
+    #[serde(default)]
+    pub jvm: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileLibrary {
+    /// Maven coordinates, `group:artifact:version`
+    pub name: String,
+    /// The maven repository the library is in
+    pub url: String,
+    // Older loader versions don't list these
+    pub sha1: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl ProfileLibrary {
+    /// Where the jar is in the repository, and in the libraries folder
+    pub fn path(&self) -> Option<String> {
+        maven_path(&self.name)
+    }
+
+    pub fn download_url(&self) -> Option<String> {
+        let path = self.path()?;
+        Some(format!("{}/{path}", self.url.trim_end_matches('/')))
+    }
+}
+
+/// Where the jar for maven coordinates, `group:artifact:version[:classifier]`,
+/// is in a repository
+pub fn maven_path(name: &str) -> Option<String> {
+    let mut parts = name.split(':');
+    let (group, artifact, version) = (parts.next()?, parts.next()?, parts.next()?);
+    let classifier = parts.next().map(|c| format!("-{c}")).unwrap_or_default();
+    Some(format!(
+        "{}/{artifact}/{version}/{artifact}-{version}{classifier}.jar",
+        group.replace('.', "/")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{maven_path, Loader, ProfileLibrary};
+
+    #[test]
+    fn test_maven_path() {
+        assert_eq!(
+            maven_path("net.fabricmc:fabric-loader:0.15.7").unwrap(),
+            "net/fabricmc/fabric-loader/0.15.7/fabric-loader-0.15.7.jar"
+        );
+        assert_eq!(
+            maven_path("net.fabricmc:yarn:1.20.4+build.3:v2").unwrap(),
+            "net/fabricmc/yarn/1.20.4+build.3/yarn-1.20.4+build.3-v2.jar"
+        );
+        assert_eq!(maven_path("net.fabricmc:fabric-loader"), None);
+    }
+
+    #[test]
+    fn test_library_url() {
+        let library = ProfileLibrary {
+            name: "net.fabricmc:intermediary:1.20.4".into(),
+            url: "https://maven.fabricmc.net/".into(),
+            sha1: None,
+            size: None,
+        };
+        assert_eq!(
+            library.path().unwrap(),
+            "net/fabricmc/intermediary/1.20.4/intermediary-1.20.4.jar"
+        );
+        assert_eq!(
+            library.download_url().unwrap(),
+            "https://maven.fabricmc.net/net/fabricmc/intermediary/1.20.4/intermediary-1.20.4.jar"
+        );
+
+        let yarn = Loader {
+            separator: "+build.".into(),
+            build: 3,
+            maven: "net.fabricmc:yarn:1.20.4+build.3".into(),
+            version: "1.20.4+build.3".into(),
+            stable: true,
+            game_version: Some("1.20.4".into()),
+        };
+        assert_eq!(
+            yarn.mappings_path().unwrap(),
+            "net/fabricmc/yarn/1.20.4+build.3/yarn-1.20.4+build.3-v2.jar"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::types::{Builds, Promotions};
+use reqwest::StatusCode;
+use std::fmt::{Display, Formatter};
+
+pub mod types;
+
+pub const FORGE_PROMOTIONS: &str =
+    "https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json";
+pub const FORGE_MAVEN: &str = "https://maven.minecraftforge.net/net/minecraftforge/forge";
+pub const NEOFORGE_MAVEN: &str = "https://maven.neoforged.net/releases/net/neoforged/neoforge";
+
+/// The loaders published to a maven repository instead of a meta server
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Loader {
+    Forge,
+    NeoForge,
+}
+
+impl Loader {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Loader::Forge => "Forge",
+            Loader::NeoForge => "NeoForge",
+        }
+    }
+
+    fn maven_url(&self) -> &'static str {
+        match self {
+            Loader::Forge => FORGE_MAVEN,
+            Loader::NeoForge => NEOFORGE_MAVEN,
+        }
+    }
+
+    /// The game version a build is for, and the build without it.
+    /// Forge builds start with it, `1.20.1-47.2.0`, NeoForge ones are numbered after it,
+    /// `20.4.80-beta` is for 1.20.4 and `21.0.10` for 1.21
+    pub fn split_version<'a>(&self, version: &'a str) -> Option<(String, &'a str)> {
+        match self {
+            Loader::Forge => {
+                let (game, build) = version.split_once('-')?;
+                Some((game.to_string(), build))
+            }
+            Loader::NeoForge => {
+                let mut parts = version.split('.');
+                let (major, minor) = (parts.next()?, parts.next()?);
+                let game = match minor {
+                    "0" => format!("1.{major}"),
+                    _ => format!("1.{major}.{minor}"),
+                };
+                Some((game, version))
+            }
+        }
+    }
+}
+
+pub async fn forge_promotions(client: &reqwest::Client) -> Result<Promotions, Error> {
+    let buf = get(client, FORGE_PROMOTIONS).await?;
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+/// Every build in the loader's maven-metadata.xml, oldest first like the file lists them
+pub async fn maven_versions(
+    client: &reqwest::Client,
+    loader: Loader,
+) -> Result<Vec<String>, Error> {
+    let url = format!("{}/maven-metadata.xml", loader.maven_url());
+    let buf = get(client, &url).await?;
+    let xml = String::from_utf8_lossy(&buf);
+    Ok(parse_versions(&xml))
+}
+
+/// The builds for the game version. NeoForge has no promotions, its newest build is the
+/// latest and its newest one that isn't a beta the recommended one
+pub async fn builds(
+    client: &reqwest::Client,
+    loader: Loader,
+    game_version: &str,
+) -> Result<Builds, Error> {
+    let versions = maven_versions(client, loader).await?;
+    let promotions = match loader {
+        Loader::Forge => Some(forge_promotions(client).await?),
+        Loader::NeoForge => None,
+    };
+    Ok(builds_for(
+        loader,
+        &versions,
+        promotions.as_ref(),
+        game_version,
+    ))
+}
+
+fn builds_for(
+    loader: Loader,
+    versions: &[String],
+    promotions: Option<&Promotions>,
+    game_version: &str,
+) -> Builds {
+    let all: Vec<String> = versions
+        .iter()
+        .rev()
+        .filter_map(|version| loader.split_version(version))
+        .filter(|(game, _)| game == game_version)
+        .map(|(_, build)| build.to_string())
+        .collect();
+
+    let (latest, recommended) = match promotions {
+        Some(promotions) => (
+            promotions.latest(game_version).map(str::to_string),
+            promotions.recommended(game_version).map(str::to_string),
+        ),
+        None => (
+            all.first().cloned(),
+            all.iter().find(|build| !build.contains("beta")).cloned(),
+        ),
+    };
+
+    Builds {
+        latest,
+        recommended,
+        all,
+    }
+}
+
+// maven-metadata.xml is flat enough that the versions can be picked out without an xml parser
+fn parse_versions(xml: &str) -> Vec<String> {
+    xml.split("<version>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</version>"))
+        .map(|(version, _)| version.trim().to_string())
+        .collect()
+}
+
+async fn get(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, Error> {
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Http(status));
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The server answered, but not with a success
+    Http(StatusCode),
+    Network(reqwest::Error),
+    Decode(serde_json::Error),
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Error::Network(value)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Error::Decode(value)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Http(status) => write!(f, "The maven server answered with {status}"),
+            Error::Network(err) => err.fmt(f),
+            Error::Decode(err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builds_for, parse_versions, Loader};
+    use crate::types::Promotions;
+
+    const METADATA: &str = "<metadata>
+  <groupId>net.neoforged</groupId>
+  <versioning>
+    <latest>20.4.81</latest>
+    <versions>
+      <version>20.2.86</version>
+      <version>20.4.80-beta</version>
+      <version>20.4.81</version>
+      <version>20.4.82-beta</version>
+      <version>21.0.10</version>
+    </versions>
+  </versioning>
+</metadata>";
+
+    #[test]
+    fn test_neoforge_builds() {
+        let versions = parse_versions(METADATA);
+        assert_eq!(versions.len(), 5);
+
+        let builds = builds_for(Loader::NeoForge, &versions, None, "1.20.4");
+        assert_eq!(builds.all, ["20.4.82-beta", "20.4.81", "20.4.80-beta"]);
+        assert_eq!(builds.latest.as_deref(), Some("20.4.82-beta"));
+        assert_eq!(builds.recommended.as_deref(), Some("20.4.81"));
+
+        let builds = builds_for(Loader::NeoForge, &versions, None, "1.21");
+        assert_eq!(builds.all, ["21.0.10"]);
+    }
+
+    #[test]
+    fn test_forge_builds() {
+        let versions = ["1.20.1-47.1.0", "1.20.1-47.2.0", "1.20.2-48.0.1"].map(String::from);
+        let promotions: Promotions = serde_json::from_str(
+            r#"{"homepage": "https://files.minecraftforge.net/", "promos": {
+                "1.20.1-latest": "47.2.0", "1.20.1-recommended": "47.1.0"}}"#,
+        )
+        .unwrap();
+
+        let builds = builds_for(Loader::Forge, &versions, Some(&promotions), "1.20.1");
+        assert_eq!(builds.all, ["47.2.0", "47.1.0"]);
+        assert_eq!(builds.latest.as_deref(), Some("47.2.0"));
+        assert_eq!(builds.recommended.as_deref(), Some("47.1.0"));
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// promotions_slim.json, Forge's picks for each game version
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Promotions {
+    pub homepage: String,
+    /// Keyed by `{game_version}-latest` and `{game_version}-recommended`
+    pub promos: HashMap<String, String>,
+}
+
+impl Promotions {
+    pub fn latest(&self, game_version: &str) -> Option<&str> {
+        self.promos
+            .get(&format!("{game_version}-latest"))
+            .map(String::as_str)
+    }
+
+    pub fn recommended(&self, game_version: &str) -> Option<&str> {
+        self.promos
+            .get(&format!("{game_version}-recommended"))
+            .map(String::as_str)
+    }
+}
+
+/// The loader builds for one game version, what a loader dropdown shows
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Builds {
+    pub latest: Option<String>,
+    pub recommended: Option<String>,
+    /// Newest first
+    pub all: Vec<String>,
+}
@@ -0,0 +1,72 @@
+use gtk4 as gtk;
+use gtk4::prelude::{BoxExt, ButtonExt, WidgetExt};
+use gtk4::{Label, Orientation};
+use launcher_app::AccRefreshPair;
+
+/// What a button in the account list asks for, by the account's index
+pub enum Action {
+    Use(usize),
+    Refresh(usize),
+}
+
+fn state(pair: &AccRefreshPair) -> &'static str {
+    if pair.account.offline {
+        "Offline"
+    } else if pair.needs_refresh() {
+        "Expired"
+    } else {
+        "Signed in"
+    }
+}
+
+/// A saved account with whether its token is still valid. Offline accounts can't be refreshed
+pub fn row(
+    pair: &AccRefreshPair,
+    playing: bool,
+    on_use: impl Fn() + 'static,
+    on_refresh: impl Fn() + 'static,
+) -> gtk::Box {
+    let name = Label::builder()
+        .label(&pair.account.profile.name)
+        .xalign(0.0)
+        .build();
+    name.add_css_class("heading");
+
+    let state = Label::builder().label(state(pair)).xalign(0.0).build();
+    state.add_css_class("dim-label");
+
+    let labels = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .hexpand(true)
+        .build();
+    labels.append(&name);
+    labels.append(&state);
+
+    let use_account = gtk::Button::builder()
+        .label(if playing { "In use" } else { "Use" })
+        .sensitive(!playing)
+        .build();
+    use_account.connect_clicked(move |_| on_use());
+
+    let refresh = gtk::Button::builder()
+        .label("Refresh")
+        .visible(!pair.account.offline)
+        .build();
+    refresh.connect_clicked(move |refresh| {
+        refresh.set_sensitive(false);
+        on_refresh();
+    });
+
+    let row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .margin_top(6)
+        .margin_bottom(6)
+        .margin_start(6)
+        .margin_end(6)
+        .build();
+    row.append(&labels);
+    row.append(&use_account);
+    row.append(&refresh);
+    row
+}
@@ -0,0 +1,233 @@
+use crate::{download, download_and_launch, headless, launcher, launcher_path, runtime};
+use crate::{Launch, LauncherData};
+use async_channel::Sender;
+use gtk4::glib::ExitCode;
+use launcher_core::progress::DownloadStats;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+
+/// How often subscribers are sent the download progress
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Tokio has no unix sockets on Windows, so the daemon listens on loopback there
+#[cfg(windows)]
+pub const ADDRESS: &str = "127.0.0.1:25585";
+
+// The error codes JSON-RPC defines, and the one for operations that failed
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const FAILED: i64 = -32000;
+
+type RpcError = (i64, String);
+
+#[derive(Deserialize)]
+struct Request {
+    // Requests without one are notifications, they aren't answered
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct Daemon {
+    data: Mutex<LauncherData>,
+    // Libraries, assets and the jar of whatever is downloading
+    stats: Arc<[DownloadStats; 3]>,
+}
+
+impl Daemon {
+    /// The instance named by the `instance` param, and where its files go
+    async fn instance(&self, params: &Value) -> Result<(Launch, PathBuf), RpcError> {
+        let name = params
+            .get("instance")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "The instance's name is missing".to_string()))?;
+        let data = self.data.lock().await;
+        let index = data
+            .instance_index(name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("There's no instance called {name}")))?;
+        let launch = Launch::instance(&data.instances[index], &data.settings.launch_defaults());
+        Ok((launch, launcher_path(&data)))
+    }
+}
+
+/// The socket frontends connect to, in the config folder
+#[cfg(unix)]
+pub fn socket_path() -> PathBuf {
+    launcher_app::data::config_dir().join("daemon.sock")
+}
+
+/// Serves JSON-RPC 2.0 requests, one per line, until the process is stopped.
+/// The methods are `versions`, `instances`, `prepare` and `launch` with an `instance`,
+/// and `subscribe`, after which `progress` notifications are sent
+pub fn run(data: LauncherData) -> ExitCode {
+    let daemon = Arc::new(Daemon {
+        data: Mutex::new(data),
+        stats: Arc::default(),
+    });
+
+    match runtime().block_on(listen(daemon)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn listen(daemon: Arc<Daemon>) -> std::io::Result<()> {
+    let path = socket_path();
+    // Left behind if the last daemon didn't exit cleanly
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    println!("Listening on {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(serve(daemon.clone(), stream));
+    }
+}
+
+#[cfg(windows)]
+async fn listen(daemon: Arc<Daemon>) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(ADDRESS).await?;
+    println!("Listening on {ADDRESS}");
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(serve(daemon.clone(), stream));
+    }
+}
+
+async fn serve<S>(daemon: Arc<Daemon>, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (read, mut write) = tokio::io::split(stream);
+    let (tx, rx) = async_channel::unbounded::<Value>();
+
+    // Responses and notifications go through one writer, so their lines don't interleave
+    tokio::spawn(async move {
+        while let Ok(message) = rx.recv().await {
+            let line = format!("{message}\n");
+            if write.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let _ = tx
+                    .send(error(Value::Null, (PARSE_ERROR, e.to_string())))
+                    .await;
+                continue;
+            }
+        };
+
+        // A launch can take minutes, the requests after it are answered in the meantime
+        let (daemon, tx) = (daemon.clone(), tx.clone());
+        tokio::spawn(async move {
+            let result = handle(&daemon, &request.method, &request.params, &tx).await;
+            let Some(id) = request.id else {
+                return;
+            };
+            let response = match result {
+                Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                Err(e) => error(id, e),
+            };
+            let _ = tx.send(response).await;
+        });
+    }
+}
+
+async fn handle(
+    daemon: &Daemon,
+    method: &str,
+    params: &Value,
+    tx: &Sender<Value>,
+) -> Result<Value, RpcError> {
+    let failed = |e: launcher_core::Error| (FAILED, e.to_string());
+
+    match method {
+        "versions" => {
+            let directory = launcher_path(&*daemon.data.lock().await);
+            let manifest = launcher()
+                .get_version_manifest(&directory)
+                .await
+                .map_err(failed)?;
+            Ok(json!(manifest.versions))
+        }
+        "instances" => {
+            let data = daemon.data.lock().await;
+            let names: Vec<_> = data.instances.iter().map(|i| &i.name).collect();
+            Ok(json!(names))
+        }
+        "prepare" => {
+            let (launch, directory) = daemon.instance(params).await?;
+            download(&launch, &directory, &daemon.stats)
+                .await
+                .map_err(failed)?;
+            Ok(Value::Bool(true))
+        }
+        "launch" => {
+            let (launch, directory) = daemon.instance(params).await?;
+            let pinned = launch.account.as_deref();
+            let account = headless::account(&mut *daemon.data.lock().await, pinned)
+                .await
+                .map_err(|e| (FAILED, e))?;
+            let pid = download_and_launch(launch, account, &directory, &daemon.stats)
+                .await
+                .map_err(failed)?;
+            Ok(json!({"pid": pid}))
+        }
+        "subscribe" => {
+            let (stats, tx) = (daemon.stats.clone(), tx.clone());
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(PROGRESS_INTERVAL).await;
+                    let [libraries, assets, jar] = &*stats;
+                    let params = json!({
+                        "libraries": progress(libraries),
+                        "assets": progress(assets),
+                        "jar": progress(jar),
+                    });
+                    let notification =
+                        json!({"jsonrpc": "2.0", "method": "progress", "params": params});
+                    // The connection is gone
+                    if tx.send(notification).await.is_err() {
+                        break;
+                    }
+                }
+            });
+            Ok(Value::Bool(true))
+        }
+        _ => Err((
+            METHOD_NOT_FOUND,
+            format!("There's no method called {method}"),
+        )),
+    }
+}
+
+fn progress(stats: &DownloadStats) -> Value {
+    let snapshot = stats.snapshot();
+    json!({
+        "finished": snapshot.finished,
+        "total": snapshot.total,
+        "bytesPerSecond": snapshot.bytes_per_second,
+    })
+}
+
+fn error(id: Value, (code, message): RpcError) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
@@ -0,0 +1,57 @@
+use gtk4 as gtk;
+use gtk4::prelude::{BoxExt, WidgetExt};
+use gtk4::{Label, Orientation};
+use launcher_core::types::{Type, Version};
+
+/// The version picked in the sidebar, above the instances
+pub struct VersionDetails {
+    content: gtk::Box,
+    name: Label,
+    info: Label,
+}
+
+impl VersionDetails {
+    pub fn new() -> Self {
+        let name = Label::builder()
+            .label("Pick a version to play")
+            .xalign(0.0)
+            .wrap(true)
+            .build();
+        name.add_css_class("title-1");
+
+        let info = Label::builder().xalign(0.0).visible(false).build();
+        info.add_css_class("dim-label");
+
+        let content = gtk::Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(6)
+            .build();
+        content.append(&name);
+        content.append(&info);
+
+        Self {
+            content,
+            name,
+            info,
+        }
+    }
+
+    pub fn widget(&self) -> &gtk::Box {
+        &self.content
+    }
+
+    pub fn show(&self, version: &Version) {
+        let kind = match version.version_type {
+            Type::Release => "Release",
+            Type::Snapshot => "Snapshot",
+            Type::OldBeta => "Beta",
+            Type::OldAlpha => "Alpha",
+        };
+        // The date without the time, `2023-06-07T09:35:21+00:00`
+        let released = version.release_time.split('T').next().unwrap_or_default();
+
+        self.name.set_text(&version.id);
+        self.info.set_text(&format!("{kind}, released {released}"));
+        self.info.set_visible(true);
+    }
+}
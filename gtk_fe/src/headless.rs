@@ -0,0 +1,82 @@
+use crate::progress::{self, LABELS};
+use crate::{client, download_and_launch, launcher_path, runtime, Launch, LauncherData};
+use gtk4::glib::ExitCode;
+use launcher_app::{auth, data, CLIENT_ID};
+use launcher_core::account::types::Account;
+use launcher_core::progress::DownloadStats;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The account the instance is pinned to, otherwise the first saved one, refreshed if its
+/// token has run out
+pub async fn account(data: &mut LauncherData, pinned: Option<&str>) -> Result<Account, String> {
+    let index = data
+        .launch_account(pinned, Some(0))
+        .map_err(|e| e.to_string())?;
+    let pair = &data.accounts[index];
+    if !pair.needs_refresh() {
+        return Ok(pair.account.clone());
+    }
+
+    let name = &pair.account.profile.name;
+    let refresh_token = pair
+        .refresh_token(&data::secrets())
+        .ok_or_else(|| format!("Sign in again as {name}"))?;
+    let (account, refresh_token) = auth::refresh(client(), CLIENT_ID, &refresh_token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    data.set_account(account.clone(), refresh_token, &data::secrets());
+    if let Err(e) = data.save(&data::data_file()) {
+        eprintln!("{e}");
+    }
+    Ok(account)
+}
+
+/// Launches the instance without a window, printing the download progress until it starts
+pub fn launch(data: &mut LauncherData, name: &str) -> ExitCode {
+    let Some(index) = data.instance_index(name) else {
+        eprintln!("There's no instance called {name}");
+        return ExitCode::FAILURE;
+    };
+    let launch = Launch::instance(&data.instances[index], &data.settings.launch_defaults());
+    let account = match runtime().block_on(account(data, launch.account.as_deref())) {
+        Ok(account) => account,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let directory = launcher_path(data);
+    let stats: Arc<[DownloadStats; 3]> = Arc::default();
+    let task = runtime().spawn({
+        let stats = stats.clone();
+        async move { download_and_launch(launch, account, &directory, &stats).await }
+    });
+
+    while !task.is_finished() {
+        std::thread::sleep(Duration::from_millis(500));
+        let lines: Vec<_> = LABELS
+            .iter()
+            .zip(stats.iter())
+            .map(|(label, stats)| format!("{label}: {}", progress::text(&stats.snapshot())))
+            .collect();
+        println!("{}", lines.join("  "));
+    }
+
+    match runtime().block_on(task) {
+        Ok(Ok(_)) => {
+            println!("Launched {name}");
+            ExitCode::SUCCESS
+        }
+        Ok(Err(e)) => {
+            eprintln!("Launching {name} failed: {e}");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
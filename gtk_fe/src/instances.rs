@@ -0,0 +1,239 @@
+use crate::{client, runtime};
+use gtk4 as gtk;
+use gtk4::glib;
+use gtk4::prelude::{BoxExt, ButtonExt, EditableExt, GridExt, GtkWindowExt, IsA, WidgetExt};
+use gtk4::{DropDown, Entry, Label, Orientation, Spinner};
+use launcher_app::{Instance, Jvm};
+use launcher_core::loader::{self, Loader};
+use launcher_core::types::Version;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Icon, name and version, with a button to play it
+pub fn row(instance: &Instance, on_play: impl Fn() + 'static) -> gtk::Box {
+    let icon = match &instance.image {
+        Some(path) => gtk::Image::from_file(path),
+        None => gtk::Image::from_icon_name("applications-games"),
+    };
+    icon.set_pixel_size(32);
+
+    let name = Label::builder().label(&instance.name).xalign(0.0).build();
+    name.add_css_class("heading");
+
+    let version = match instance.mod_loader {
+        Some(loader) => format!("{} {}", instance.version.id, loader.name()),
+        None => instance.version.id.clone(),
+    };
+    let version = Label::builder().label(version).xalign(0.0).build();
+    version.add_css_class("dim-label");
+
+    let labels = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .hexpand(true)
+        .build();
+    labels.append(&name);
+    labels.append(&version);
+
+    let play = gtk::Button::builder().label("Play").build();
+    play.connect_clicked(move |_| on_play());
+
+    let row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .margin_top(6)
+        .margin_bottom(6)
+        .margin_start(6)
+        .margin_end(6)
+        .build();
+    row.append(&icon);
+    row.append(&labels);
+    row.append(&play);
+    row
+}
+
+/// The fields of the egui frontend's instance editor. Picking a loader uses its newest
+/// version for the game version, which is looked up before `on_create` is called
+pub fn dialog(
+    parent: &impl IsA<gtk::Window>,
+    versions: &[Version],
+    jvms: &[Arc<Jvm>],
+    on_create: impl Fn(Instance) + 'static,
+) {
+    let name = Entry::new();
+    let group = Entry::builder().placeholder_text("None").build();
+
+    let jvms: Vec<Arc<Jvm>> = std::iter::once(Arc::default())
+        .chain(jvms.iter().cloned())
+        .collect();
+    let jvm_names: Vec<&str> = jvms.iter().map(|jvm| jvm.name.as_str()).collect();
+    let jvm = DropDown::from_strings(&jvm_names);
+
+    let image = Entry::builder().placeholder_text("Path to a PNG").build();
+
+    let versions = versions.to_vec();
+    let ids: Vec<&str> = versions.iter().map(|version| version.id.as_str()).collect();
+    let version = DropDown::from_strings(&ids);
+
+    let loaders: Vec<&str> = std::iter::once("None")
+        .chain(Loader::ALL.iter().map(Loader::name))
+        .collect();
+    let loader = DropDown::from_strings(&loaders);
+
+    let path = Entry::builder()
+        .placeholder_text("The launcher's folder")
+        .build();
+    let jvm_args = Entry::new();
+    let env_args = Entry::builder().placeholder_text("KEY=value").build();
+
+    let grid = gtk::Grid::builder()
+        .column_spacing(10)
+        .row_spacing(10)
+        .build();
+    let fields: [(&str, &gtk::Widget); 9] = [
+        ("Name", name.as_ref()),
+        ("Group", group.as_ref()),
+        ("Java", jvm.as_ref()),
+        ("Icon", image.as_ref()),
+        ("Version", version.as_ref()),
+        ("Loader", loader.as_ref()),
+        ("Folder", path.as_ref()),
+        ("JVM arguments", jvm_args.as_ref()),
+        ("Environment", env_args.as_ref()),
+    ];
+    for (row, (label, field)) in fields.into_iter().enumerate() {
+        let label = Label::builder().label(label).xalign(0.0).build();
+        grid.attach(&label, 0, row as i32, 1, 1);
+        grid.attach(field, 1, row as i32, 1, 1);
+    }
+
+    let spinner = Spinner::new();
+    let status = Label::builder().wrap(true).build();
+    let create = gtk::Button::builder().label("Create").build();
+    let cancel = gtk::Button::builder().label("Cancel").build();
+
+    let buttons = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    buttons.append(&spinner);
+    buttons.append(&status);
+    buttons.append(&cancel);
+    buttons.append(&create);
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(10)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    content.append(&grid);
+    content.append(&buttons);
+
+    let window = gtk::Window::builder()
+        .title("New Instance")
+        .modal(true)
+        .transient_for(parent)
+        .child(&content)
+        .build();
+
+    cancel.connect_clicked({
+        let window = window.clone();
+        move |_| window.close()
+    });
+
+    let on_create = std::rc::Rc::new(on_create);
+    create.connect_clicked({
+        let window = window.clone();
+        move |create| {
+            let Some(picked) = versions.get(version.selected() as usize).cloned() else {
+                status.set_text("Pick a version");
+                return;
+            };
+            let instance_name = name.text().trim().to_string();
+            if instance_name.is_empty() {
+                status.set_text("The instance needs a name");
+                return;
+            }
+
+            let split = |entry: &Entry| -> Vec<String> {
+                entry
+                    .text()
+                    .split(' ')
+                    .filter(|arg| !arg.is_empty())
+                    .map(String::from)
+                    .collect()
+            };
+            let text =
+                |entry: &Entry| Some(entry.text().trim().to_string()).filter(|t| !t.is_empty());
+
+            let jvm = jvms[jvm.selected() as usize].clone();
+            let mut instance = Instance::new(instance_name, jvm, Arc::new(picked));
+            instance.image = text(&image).map(PathBuf::from);
+            instance.path = text(&path).map(PathBuf::from).unwrap_or_default();
+            instance.jvm_args = split(&jvm_args);
+            instance.env_args = split(&env_args);
+            instance.group = text(&group);
+
+            // The first entry is no loader
+            let Some(picked_loader) = (loader.selected() as usize)
+                .checked_sub(1)
+                .and_then(|idx| Loader::ALL.get(idx).copied())
+            else {
+                window.close();
+                on_create(instance);
+                return;
+            };
+
+            create.set_sensitive(false);
+            spinner.start();
+            status.set_text(&format!("Finding a {} version", picked_loader.name()));
+
+            let (tx, rx) = async_channel::bounded(1);
+            let game_version = instance.version.id.clone();
+            runtime().spawn(async move {
+                let versions =
+                    loader::loader_versions(client(), picked_loader, &game_version).await;
+                tx.send(versions).await.unwrap();
+            });
+
+            let window = window.clone();
+            let create = create.clone();
+            let spinner = spinner.clone();
+            let status = status.clone();
+            let on_create = on_create.clone();
+            glib::spawn_future_local(async move {
+                let result = rx.recv().await.unwrap();
+                spinner.stop();
+                create.set_sensitive(true);
+
+                let versions = match result {
+                    Ok(versions) => versions,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        status.set_text(&format!("Couldn't list the loader versions: {e}"));
+                        return;
+                    }
+                };
+                // Newest first, stable builds are preferred
+                let newest = versions
+                    .iter()
+                    .find(|version| version.is_stable())
+                    .or(versions.first());
+                let Some(newest) = newest else {
+                    let name = picked_loader.name();
+                    status.set_text(&format!("{name} doesn't support this version"));
+                    return;
+                };
+
+                instance.mod_loader = Some(picked_loader);
+                instance.loader_version = Some(newest.version.clone());
+                window.close();
+                on_create(instance);
+            });
+        }
+    });
+
+    window.present();
+}
@@ -0,0 +1,147 @@
+use crate::{client, runtime};
+use gtk4 as gtk;
+use gtk4::glib;
+use gtk4::prelude::{BoxExt, ButtonExt, GtkWindowExt, IsA, WidgetExt};
+use gtk4::{Label, Orientation, Spinner};
+use launcher_app::{auth, CLIENT_ID};
+use launcher_core::account::types::Account;
+
+enum Login {
+    // The page to open and the code to enter there
+    Code(String, String),
+    // The account and its refresh token
+    Done(Result<(Account, String), launcher_core::Error>),
+}
+
+fn spawn() -> (tokio::task::JoinHandle<()>, async_channel::Receiver<Login>) {
+    let (tx, rx) = async_channel::unbounded();
+    let task = runtime().spawn(async move {
+        // Nobody is listening once the dialog is closed
+        let on_code = |url, code| drop(tx.try_send(Login::Code(url, code)));
+        let result = auth::log_in(client(), CLIENT_ID, None, on_code).await;
+        let _ = tx.send(Login::Done(result)).await;
+    });
+    (task, rx)
+}
+
+/// Gets a new access token for a saved account, without asking the player
+pub fn refresh(
+    refresh_token: String,
+    on_done: impl FnOnce(Result<(Account, String), launcher_core::Error>) + 'static,
+) {
+    let (tx, rx) = async_channel::bounded(1);
+    runtime().spawn(async move {
+        let result = auth::refresh(client(), CLIENT_ID, &refresh_token).await;
+        let _ = tx.send(result).await;
+    });
+    glib::spawn_future_local(async move {
+        if let Ok(result) = rx.recv().await {
+            on_done(result);
+        }
+    });
+}
+
+/// Walks the player through the device code login, `on_login` gets the account and its
+/// refresh token. Closing the dialog stops waiting for the code
+pub fn dialog(parent: &impl IsA<gtk::Window>, on_login: impl Fn(Account, String) + 'static) {
+    let heading = Label::builder()
+        .label("Open the page below and enter the code to sign in")
+        .wrap(true)
+        .build();
+
+    let code = Label::builder().label("…").selectable(true).build();
+    code.add_css_class("title-1");
+
+    let copy = gtk::Button::builder()
+        .label("Copy code")
+        .sensitive(false)
+        .build();
+    let link = gtk::LinkButton::builder()
+        .label("Open in browser")
+        .sensitive(false)
+        .build();
+
+    let spinner = Spinner::builder().spinning(true).build();
+    let status = Label::builder()
+        .label("Requesting a code…")
+        .wrap(true)
+        .build();
+
+    let cancel = gtk::Button::builder().label("Cancel").build();
+
+    let buttons = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    buttons.append(&copy);
+    buttons.append(&link);
+
+    let waiting = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    waiting.append(&spinner);
+    waiting.append(&status);
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(10)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    content.append(&heading);
+    content.append(&code);
+    content.append(&buttons);
+    content.append(&waiting);
+    content.append(&cancel);
+
+    let window = gtk::Window::builder()
+        .title("Sign in with Microsoft")
+        .modal(true)
+        .transient_for(parent)
+        .resizable(false)
+        .child(&content)
+        .build();
+
+    let (task, rx) = spawn();
+    window.connect_close_request(move |_| {
+        task.abort();
+        glib::Propagation::Proceed
+    });
+
+    cancel.connect_clicked({
+        let window = window.clone();
+        move |_| window.close()
+    });
+
+    glib::spawn_future_local({
+        let window = window.clone();
+        async move {
+            while let Ok(message) = rx.recv().await {
+                match message {
+                    Login::Code(url, user_code) => {
+                        code.set_text(&user_code);
+                        link.set_uri(&url);
+                        link.set_sensitive(true);
+                        copy.set_sensitive(true);
+                        copy.connect_clicked(move |copy| copy.clipboard().set_text(&user_code));
+                        status.set_text("Waiting for you to sign in…");
+                    }
+                    Login::Done(Ok((account, refresh_token))) => {
+                        window.close();
+                        on_login(account, refresh_token);
+                    }
+                    Login::Done(Err(e)) => {
+                        eprintln!("{e}");
+                        spinner.stop();
+                        status.set_text(&format!("Sign in failed: {e}"));
+                    }
+                }
+            }
+        }
+    });
+
+    window.present();
+}
@@ -1,133 +1,839 @@
+mod accounts;
+mod daemon;
+mod details;
+mod headless;
+mod instances;
+mod login;
+mod progress;
+mod settings;
+
+use adw::prelude::AdwApplicationWindowExt;
+use details::VersionDetails;
 use gtk4 as gtk;
-use gtk4::glib::property::PropertyGet;
-use gtk4::glib::WeakRef;
+use gtk4::glib;
 use gtk4::prelude::{
-    ApplicationExt, ApplicationExtManual, BoxExt, ButtonExt, GridExt, GtkWindowExt,
-};
-use gtk4::{
-    Application, ApplicationWindow, Grid, Label, ListBox, Orientation, Overflow, PolicyType,
-    ScrolledWindow,
+    ApplicationExt, ApplicationExtManual, BoxExt, ButtonExt, GtkWindowExt, ListBoxRowExt, ToValue,
+    WidgetExt,
 };
-use launcher_core::types::Version;
-use std::path::Path;
+use gtk4::{Label, ListBox, Orientation, PolicyType, ScrolledWindow};
+use launcher_app::data;
+use launcher_app::{Args, Instance, LaunchDefaults, LaunchSettings, CLIENT_ID};
+use launcher_core::account::types::Account;
+use launcher_core::http::{ClientOptions, Proxy};
+use launcher_core::loader::{self, Loader, LoaderProfile};
+use launcher_core::progress::DownloadStats;
+use launcher_core::types::{FeatureFlags, Version, VersionJson};
+use libadwaita as adw;
+use progress::DownloadProgress;
+use settings::Settings;
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+type LauncherData = launcher_app::LauncherData<Settings>;
 
 fn runtime() -> &'static tokio::runtime::Runtime {
     static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
     RUNTIME.get_or_init(|| tokio::runtime::Runtime::new().unwrap())
 }
 
+static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+
+fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(|| ClientOptions::default().build().unwrap())
+}
+
+/// Builds the client with the proxy and certificates from the settings, before anything is
+/// downloaded
+fn init_client(proxy: &Proxy, certificates: &[PathBuf]) {
+    let options = data::client_options(proxy, certificates, &data::secrets());
+    let client = options.build().unwrap_or_else(|e| {
+        eprintln!("The proxy or certificates can't be used: {e}");
+        ClientOptions::default().build().unwrap()
+    });
+    let _ = CLIENT.set(client);
+}
+
 fn launcher() -> &'static launcher_core::AsyncLauncher {
     static LAUNCHER: std::sync::OnceLock<launcher_core::AsyncLauncher> = std::sync::OnceLock::new();
-    LAUNCHER.get_or_init(|| launcher_core::AsyncLauncher::new(reqwest::Client::new()))
+    LAUNCHER.get_or_init(|| launcher_core::AsyncLauncher::new(client().clone()))
+}
+
+/// What to start, an instance or the version picked in the list
+#[derive(Clone)]
+pub struct Launch {
+    // Shown while it downloads
+    pub name: String,
+    pub version: Arc<Version>,
+    pub settings: LaunchSettings,
+    // The loader and its version, installed before launching if it isn't yet
+    pub loader: Option<(Loader, String)>,
+    // Launches the loader's newest stable build instead, if there's a newer one
+    pub track_latest_loader: bool,
+    // Profile id of the account the instance is pinned to, None plays as the selected one
+    pub account: Option<String>,
+}
+
+impl Launch {
+    pub fn instance(instance: &Instance, defaults: &LaunchDefaults) -> Self {
+        Self {
+            name: instance.name.clone(),
+            version: instance.version.clone(),
+            settings: defaults.resolve(Some(instance)),
+            loader: instance
+                .loader()
+                .map(|(loader, version)| (loader, version.to_string())),
+            track_latest_loader: instance.track_latest_loader,
+            account: instance.account.clone(),
+        }
+    }
+
+    pub fn version(version: Arc<Version>, defaults: &LaunchDefaults) -> Self {
+        Self {
+            name: version.id.clone(),
+            version,
+            settings: defaults.resolve(None),
+            loader: None,
+            track_latest_loader: false,
+            account: None,
+        }
+    }
+}
+
+/// Where versions, assets and libraries are stored, the data directory from the settings
+/// if there is one
+fn launcher_path(data: &LauncherData) -> PathBuf {
+    data.settings
+        .data_dir
+        .clone()
+        .unwrap_or_else(data::config_dir)
+}
+
+/// The saved account an instance is pinned to, it has to be refreshed in the accounts list
+/// once its token runs out
+fn pinned_account(data: Option<&LauncherData>, pinned: &str) -> Result<Account, String> {
+    let data = data.ok_or("Accounts aren't saved, so there's none to launch with")?;
+    let index = data
+        .launch_account(Some(pinned), None)
+        .map_err(|e| e.to_string())?;
+    let pair = &data.accounts[index];
+    if pair.needs_refresh() {
+        return Err(format!("Refresh {} first", pair.account.profile.name));
+    }
+    Ok(pair.account.clone())
+}
+
+/// What starting a version needs once its files are downloaded
+struct Prepared {
+    json: VersionJson,
+    class_path: String,
+    profile: Option<LoaderProfile>,
+}
+
+/// Downloads the version's files, and the loader's, into the launcher's folder
+async fn download(
+    launch: &Launch,
+    directory: &Path,
+    // Libraries, assets and the jar
+    stats: &[DownloadStats; 3],
+) -> Result<Prepared, launcher_core::Error> {
+    let launcher = launcher();
+    let assets_dir = directory.join("assets");
+    let [library_stats, asset_stats, jar_stats] = stats;
+
+    let json = launcher
+        .get_version_json(&launch.version, &directory.join("versions"))
+        .await?;
+
+    let libraries = launcher.download_libraries_and_get_path(
+        json.libraries(),
+        &directory.join("libraries"),
+        &directory.join("natives"),
+        library_stats.total(),
+        library_stats.finished(),
+    );
+    let jar = launcher.download_jar(
+        &json,
+        &directory.join("versions"),
+        jar_stats.total(),
+        jar_stats.finished(),
+    );
+    let assets = async {
+        let index = launcher
+            .get_asset_index_json(json.asset_index(), &assets_dir)
+            .await?;
+        launcher
+            .download_and_store_asset_index(
+                &index,
+                &assets_dir,
+                asset_stats.total(),
+                asset_stats.finished(),
+            )
+            .await
+    };
+    let profile = async {
+        match &launch.loader {
+            Some((loader, installed)) => {
+                let id = &launch.version.id;
+                // The installed build launches if the meta server can't be reached
+                let update = if launch.track_latest_loader {
+                    loader::stable_update(client(), *loader, id, installed)
+                        .await
+                        .unwrap_or_else(|e| {
+                            eprintln!("The loader couldn't be checked for updates: {e}");
+                            None
+                        })
+                } else {
+                    None
+                };
+                let loader_version = update.as_deref().unwrap_or(installed);
+                loader::install(client(), *loader, id, loader_version, directory)
+                    .await
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    };
+    let (libraries, jar_path, (), profile) = futures::try_join!(libraries, jar, assets, profile)?;
+    for skipped in &libraries.skipped_natives {
+        eprintln!("A native library was skipped, the game may crash: {skipped}");
+    }
+
+    let loader_class_path = profile
+        .as_ref()
+        .map(|profile| loader::class_path(profile, &directory.join("libraries")))
+        .unwrap_or_default();
+
+    Ok(Prepared {
+        json,
+        class_path: format!("{loader_class_path}{}{jar_path}", libraries.class_path),
+        profile,
+    })
+}
+
+/// Downloads the version's files into the launcher's folder and starts it, returning the
+/// game's process id. The post-exit hook only runs if the launcher is still open by then
+async fn download_and_launch(
+    launch: Launch,
+    account: Account,
+    directory: &Path,
+    stats: &[DownloadStats; 3],
+) -> Result<u32, launcher_core::Error> {
+    let prepared = download(&launch, directory, stats).await?;
+    let settings = launch.settings;
+
+    let mut command = launcher_core::launch_command(
+        &settings.jvm,
+        &prepared.json,
+        directory,
+        &directory.join("assets"),
+        &account,
+        CLIENT_ID,
+        "0",
+        "Synth Launcher",
+        "0.1.0",
+        &prepared.class_path,
+        &settings.jvm_args,
+        prepared.profile.as_ref(),
+        &FeatureFlags::default(),
+    );
+    command.args(&settings.game_args);
+    command.envs(settings.env.iter().map(|(key, value)| (key, value)));
+
+    tokio::task::block_in_place(|| settings.hooks.run_pre_launch())?;
+    let mut child = command.spawn()?;
+    let id = child.id();
+    if settings.hooks.post_exit.is_some() || settings.hooks.backup_worlds.is_some() {
+        let game_dir = directory.to_path_buf();
+        std::thread::spawn(move || {
+            let _ = child.wait();
+            settings.hooks.run_post_exit();
+            if let Err(e) = runtime().block_on(settings.hooks.run_backup_worlds(&game_dir)) {
+                eprintln!("The worlds couldn't be backed up: {e}");
+            }
+        });
+    }
+    Ok(id)
 }
 
 fn main() -> gtk4::glib::ExitCode {
-    let app = Application::builder()
+    let args = Args::from_env();
+    let app = adw::Application::builder()
         .application_id("com.also_sylv.synth_launcher")
         .build();
 
+    // None if the file couldn't be read, so it isn't overwritten
+    let data = match LauncherData::load(&data::data_file()) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            eprintln!("{e}, accounts and instances won't be saved");
+            None
+        }
+    };
+    let launcher_path = data.as_ref().map_or_else(data::config_dir, launcher_path);
+    if let Some(data) = &data {
+        init_client(&data.settings.proxy, &data.settings.certificates);
+    }
+
+    if args.headless {
+        // Parsing makes sure there's an instance to launch
+        let (Some(mut data), Some(name)) = (data, args.launch) else {
+            return glib::ExitCode::FAILURE;
+        };
+        return headless::launch(&mut data, &name);
+    }
+    if args.daemon {
+        let Some(data) = data else {
+            return glib::ExitCode::FAILURE;
+        };
+        return daemon::run(data);
+    }
+
+    // The instance from `--launch`, started once there's an account
+    let pending = args.launch.and_then(|name| {
+        let data = data.as_ref()?;
+        match data.instance_index(&name) {
+            Some(index) => Some(Launch::instance(
+                &data.instances[index],
+                &data.settings.launch_defaults(),
+            )),
+            None => {
+                eprintln!("There's no instance called {name}");
+                None
+            }
+        }
+    });
+    let data = Rc::new(RefCell::new(data));
+
     let (sender, receiver) = async_channel::unbounded();
 
     let sender_ref = sender.clone();
+    let manifest_path = launcher_path.clone();
 
     runtime().spawn(async move {
-        let message = launcher().get_version_manifest(Path::new("./")).await;
+        let message = launcher().get_version_manifest(&manifest_path).await;
 
         sender_ref.send(message).await.unwrap()
     });
 
     app.connect_activate(move |app| {
         let receiver_ref = receiver.clone();
+        let data = data.clone();
+        let launcher_path = launcher_path.clone();
 
-        let mut vec: Vec<Version> = Vec::new();
+        let versions: Rc<RefCell<Vec<Version>>> = Rc::default();
+        // The versions the settings show, in the order of the list
+        let listed: Rc<RefCell<Vec<Version>>> = Rc::default();
+        let account: Rc<RefCell<Option<Account>>> = Rc::default();
+        // Only one launch downloads at a time
+        let launching = Rc::new(Cell::new(false));
+        let pending = Cell::new(pending.clone());
 
         let combo_box = ListBox::builder().build();
-
+        combo_box.add_css_class("navigation-sidebar");
         combo_box.set_placeholder(Some(&Label::new(Some("Loading..."))));
 
-        combo_box.connect_row_selected(|list, row| {
-            println!("Selected! {row:?}");
+        let details = VersionDetails::new();
+
+        let status = Label::builder()
+            .label("Not signed in")
+            .wrap(true)
+            .hexpand(true)
+            .xalign(0.0)
+            .build();
+
+        let progress = Rc::new(DownloadProgress::new());
+
+        let button = gtk::Button::builder()
+            .label("Play")
+            .sensitive(false)
+            .build();
+        button.add_css_class("suggested-action");
+
+        let login = gtk::Button::builder().label("Sign in").build();
+        let open_settings = gtk::Button::builder()
+            .icon_name("emblem-system-symbolic")
+            .tooltip_text("Settings")
+            .sensitive(data.borrow().is_some())
+            .build();
+
+        let instance_list = ListBox::builder().build();
+        instance_list.add_css_class("boxed-list");
+        instance_list.set_placeholder(Some(&Label::new(Some("No instances"))));
+
+        let new_instance = gtk::Button::builder()
+            .label("New Instance")
+            .sensitive(data.borrow().is_some())
+            .build();
+
+        let start: Rc<dyn Fn(Launch)> = Rc::new({
+            let data = data.clone();
+            let status = status.clone();
+            let progress = progress.clone();
+            let button = button.clone();
+            let launcher_path = launcher_path.clone();
+            move |launch: Launch| {
+                let account = match &launch.account {
+                    Some(pinned) => pinned_account(data.borrow().as_ref(), pinned),
+                    None => account.borrow().clone().ok_or("Sign in first".to_string()),
+                };
+                let account = match account {
+                    Ok(account) => account,
+                    Err(e) => {
+                        status.set_text(&e);
+                        return;
+                    }
+                };
+                if launching.replace(true) {
+                    return;
+                }
+                let name = launch.name.clone();
+
+                button.set_sensitive(false);
+                status.set_text(&format!("Downloading {name}"));
+
+                let stats: Arc<[DownloadStats; 3]> = Arc::default();
+                let (tx, rx) = async_channel::bounded(1);
+                runtime().spawn({
+                    let stats = stats.clone();
+                    let directory = launcher_path.clone();
+                    async move {
+                        let result = download_and_launch(launch, account, &directory, &stats).await;
+                        tx.send(result).await.unwrap();
+                    }
+                });
+
+                let timer = progress.watch(stats);
+
+                let button = button.clone();
+                let status = status.clone();
+                let progress = progress.clone();
+                let launching = launching.clone();
+                glib::spawn_future_local(async move {
+                    let result = rx.recv().await.unwrap();
+                    timer.remove();
+                    launching.set(false);
+                    progress.hide();
+                    button.set_sensitive(true);
+
+                    match result {
+                        Ok(_) => status.set_text(&format!("Launched {name}")),
+                        Err(e) => {
+                            eprintln!("{e}");
+                            status.set_text(&format!("Launching {name} failed: {e}"));
+                        }
+                    }
+                });
+            }
+        });
+
+        let account_list = ListBox::builder().build();
+        account_list.add_css_class("boxed-list");
+        account_list.set_placeholder(Some(&Label::new(Some("No accounts"))));
+        let (account_tx, account_rx) = async_channel::unbounded();
+
+        // Lists the saved accounts again, after one is used, added or refreshed
+        let fill_accounts = Rc::new({
+            let account_list = account_list.clone();
+            let account = account.clone();
+            let data = data.clone();
+            move || {
+                while let Some(row) = account_list.row_at_index(0) {
+                    account_list.remove(&row);
+                }
+                let playing = account.borrow().as_ref().map(|acc| acc.profile.id.clone());
+                if let Some(data) = &*data.borrow() {
+                    for (index, pair) in data.accounts.iter().enumerate() {
+                        let use_tx = account_tx.clone();
+                        let refresh_tx = account_tx.clone();
+                        let row = accounts::row(
+                            pair,
+                            playing.as_deref() == Some(pair.account.profile.id.as_str()),
+                            move || drop(use_tx.try_send(accounts::Action::Use(index))),
+                            move || drop(refresh_tx.try_send(accounts::Action::Refresh(index))),
+                        );
+                        account_list.append(&row);
+                    }
+                }
+            }
+        });
+
+        let use_account = Rc::new({
+            let status = status.clone();
+            let button = button.clone();
+            let account = account.clone();
+            let fill_accounts = fill_accounts.clone();
+            let start = start.clone();
+            move |playing: Account| {
+                status.set_text(&format!("Playing as {}", playing.profile.name));
+                *account.borrow_mut() = Some(playing);
+                button.set_sensitive(true);
+                fill_accounts();
+                // `--launch` waits for an account
+                if let Some(launch) = pending.take() {
+                    start(launch);
+                }
+            }
         });
 
-        let weak = WeakRef::new();
-        weak.set(Some(&combo_box));
+        let save_account = Rc::new({
+            let data = data.clone();
+            move |account: Account, refresh_token: String| {
+                if let Some(data) = &mut *data.borrow_mut() {
+                    data.set_account(account, refresh_token, &data::secrets());
+                    if let Err(e) = data.save(&data::data_file()) {
+                        eprintln!("{e}");
+                    }
+                }
+            }
+        });
+
+        let signed_in = Rc::new({
+            let use_account = use_account.clone();
+            let save_account = save_account.clone();
+            move |signed_in: Account, refresh_token: String| {
+                save_account(signed_in.clone(), refresh_token);
+                use_account(signed_in);
+            }
+        });
 
-        let button = gtk::Button::builder().label("Play").build();
+        // Gets a new token for a saved account. It's used afterwards if `then_use` is set,
+        // or if it's the one being played
+        let refresh_account = Rc::new({
+            let data = data.clone();
+            let account = account.clone();
+            let status = status.clone();
+            let use_account = use_account.clone();
+            let fill_accounts = fill_accounts.clone();
+            move |index: usize, then_use: bool| {
+                let Some((saved, refresh_token)) = data
+                    .borrow()
+                    .as_ref()
+                    .and_then(|data| data.accounts.get(index))
+                    .map(|pair| (pair.account.clone(), pair.refresh_token(&data::secrets())))
+                else {
+                    return;
+                };
+                let name = saved.profile.name;
+                let Some(refresh_token) = refresh_token else {
+                    status.set_text(&format!("Sign in again as {name}"));
+                    fill_accounts();
+                    return;
+                };
+
+                status.set_text(&format!("Refreshing {name}"));
+                let account = account.clone();
+                let status = status.clone();
+                let save_account = save_account.clone();
+                let use_account = use_account.clone();
+                let fill_accounts = fill_accounts.clone();
+                login::refresh(refresh_token, move |result| match result {
+                    Ok((refreshed, refresh_token)) => {
+                        save_account(refreshed.clone(), refresh_token);
+                        let playing = account
+                            .borrow()
+                            .as_ref()
+                            .is_some_and(|acc| acc.profile.id == refreshed.profile.id);
+                        if then_use || playing {
+                            use_account(refreshed);
+                        } else {
+                            status.set_text(&format!("Refreshed {name}"));
+                            fill_accounts();
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{e}");
+                        status.set_text(&format!("Sign in again as {name}"));
+                        fill_accounts();
+                    }
+                });
+            }
+        });
+
+        // Saved accounts are used until their token expires, then they're refreshed first
+        let select_account = Rc::new({
+            let data = data.clone();
+            let refresh_account = refresh_account.clone();
+            move |index: usize| {
+                let saved = data
+                    .borrow()
+                    .as_ref()
+                    .and_then(|data| data.accounts.get(index))
+                    .map(|pair| (pair.account.clone(), pair.needs_refresh()));
+                match saved {
+                    Some((saved, false)) => use_account(saved),
+                    Some((_, true)) => refresh_account(index, true),
+                    None => {}
+                }
+            }
+        });
+
+        // Lists the versions again, after they're fetched and when the settings change
+        let fill = Rc::new({
+            let combo_box = combo_box.clone();
+            let versions = versions.clone();
+            let listed = listed.clone();
+            let data = data.clone();
+            move || {
+                while let Some(row) = combo_box.row_at_index(0) {
+                    combo_box.remove(&row);
+                }
+                let filter = data
+                    .borrow()
+                    .as_ref()
+                    .map(|data| data.settings.version_filter)
+                    .unwrap_or_default();
+                let mut listed = listed.borrow_mut();
+                listed.clear();
+                for version in versions.borrow().iter().filter(|v| filter.shows(v)) {
+                    combo_box.append(&Label::new(Some(&version.id)));
+                    listed.push(version.clone());
+                }
+            }
+        });
 
         button.connect_clicked({
+            let combo_box = combo_box.clone();
+            let listed = listed.clone();
+            let data = data.clone();
+            let status = status.clone();
+            let start = start.clone();
             move |_| {
-                let row = weak.get(|list| list.as_ref().unwrap().selected_row());
-                println!("{:?}", row)
+                let Some(row) = combo_box.selected_row() else {
+                    status.set_text("Pick a version first");
+                    return;
+                };
+                let version = Arc::new(listed.borrow()[row.index() as usize].clone());
+                let defaults = data
+                    .borrow()
+                    .as_ref()
+                    .map(|data| data.settings.launch_defaults())
+                    .unwrap_or_default();
+                start(Launch::version(version, &defaults));
             }
         });
 
-        let grid = Grid::builder()
-            .column_spacing(10)
-            .row_spacing(10)
-            .height_request(1)
-            .width_request(1)
-            .hexpand(false)
-            .vexpand(false)
-            .overflow(Overflow::Hidden)
-            .column_homogeneous(false)
-            .row_homogeneous(false)
-            .build();
-        grid.attach(&Label::new(Some("Cell One")), 0, 0, 1, 1);
-        grid.attach(&Label::new(Some("Cell Two")), 1, 0, 1, 1);
-        grid.attach(
-            &Label::new(Some("REALLY LONG CELL NAME BECAUSE I HATE YOU")),
-            0,
-            1,
-            1,
-            1,
-        );
-
-        let hori_box = gtk4::Box::builder()
-            .orientation(Orientation::Horizontal)
+        let add_row = Rc::new({
+            let instance_list = instance_list.clone();
+            let data = data.clone();
+            move |instance: &Instance| {
+                let start = start.clone();
+                let data = data.clone();
+                let launched = instance.clone();
+                // Resolved on click, so changed settings apply without restarting
+                let row = instances::row(instance, move || {
+                    let defaults = data
+                        .borrow()
+                        .as_ref()
+                        .map(|data| data.settings.launch_defaults())
+                        .unwrap_or_default();
+                    start(Launch::instance(&launched, &defaults))
+                });
+                instance_list.append(&row);
+            }
+        });
+        if let Some(data) = &*data.borrow() {
+            for instance in &data.instances {
+                add_row(instance);
+            }
+        }
+
+        let sidebar = adw::ToolbarView::new();
+        sidebar.add_top_bar(&adw::HeaderBar::new());
+        sidebar.set_content(Some(
+            &ScrolledWindow::builder()
+                .hscrollbar_policy(PolicyType::Never)
+                .vexpand(true)
+                .child(&combo_box)
+                .build(),
+        ));
+        let sidebar = adw::NavigationPage::builder()
+            .title("Versions")
+            .child(&sidebar)
             .build();
-        let vert_box = gtk4::Box::builder()
+
+        let body = gtk::Box::builder()
             .orientation(Orientation::Vertical)
+            .spacing(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
             .build();
+        body.append(details.widget());
+        for (title, list, action) in [
+            ("Instances", &instance_list, Some(&new_instance)),
+            ("Accounts", &account_list, None),
+        ] {
+            let heading = gtk::Box::builder()
+                .orientation(Orientation::Horizontal)
+                .spacing(6)
+                .build();
+            let title = Label::builder()
+                .label(title)
+                .hexpand(true)
+                .xalign(0.0)
+                .build();
+            title.add_css_class("heading");
+            heading.append(&title);
+            if let Some(action) = action {
+                heading.append(action);
+            }
+            body.append(&heading);
+            body.append(list);
+        }
+        // Keeps the lists readable on wide windows
+        let body = adw::Clamp::builder().maximum_size(720).child(&body).build();
 
-        let nhori_box = gtk::Box::builder()
+        let launch_bar = gtk::Box::builder()
             .orientation(Orientation::Horizontal)
+            .spacing(10)
             .build();
-
-        let nvert_box = gtk4::Box::builder()
+        launch_bar.append(&status);
+        launch_bar.append(&button);
+        let bottom = gtk::Box::builder()
             .orientation(Orientation::Vertical)
+            .spacing(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
             .build();
+        bottom.append(progress.widget());
+        bottom.append(&launch_bar);
 
-        nhori_box.append(&grid);
-        nvert_box.append(&nhori_box);
+        let header = adw::HeaderBar::new();
+        header.pack_start(&login);
+        header.pack_end(&open_settings);
 
-        let scrolled_window = ScrolledWindow::builder()
-            .hscrollbar_policy(PolicyType::Never)
-            .width_request(100)
-            .height_request(100)
-            .child(&combo_box)
+        let content = adw::ToolbarView::new();
+        content.add_top_bar(&header);
+        content.set_content(Some(
+            &ScrolledWindow::builder()
+                .hscrollbar_policy(PolicyType::Never)
+                .vexpand(true)
+                .child(&body)
+                .build(),
+        ));
+        content.add_bottom_bar(&bottom);
+        let content = adw::NavigationPage::builder()
+            .title("Synth Launcher")
+            .child(&content)
             .build();
 
-        vert_box.append(&scrolled_window);
-        vert_box.append(&button);
-        hori_box.append(&vert_box);
-        hori_box.append(&nvert_box);
+        let split_view = adw::NavigationSplitView::builder()
+            .sidebar(&sidebar)
+            .content(&content)
+            .build();
 
-        let window = ApplicationWindow::builder()
+        let window = adw::ApplicationWindow::builder()
             .application(app)
-            .default_height(500)
-            .default_width(500)
-            .child(&hori_box)
+            .default_height(600)
+            .default_width(900)
+            .width_request(360)
+            .height_request(294)
+            .content(&split_view)
             .build();
 
+        // Narrow windows show the version list and the rest as separate pages
+        let narrow = adw::BreakpointCondition::parse("max-width: 600sp").unwrap();
+        let breakpoint = adw::Breakpoint::new(narrow);
+        breakpoint.add_setter(&split_view, "collapsed", &true.to_value());
+        window.add_breakpoint(breakpoint);
+
+        combo_box.connect_row_selected({
+            let listed = listed.clone();
+            move |_, row| {
+                if let Some(row) = row {
+                    details.show(&listed.borrow()[row.index() as usize]);
+                    split_view.set_show_content(true);
+                }
+            }
+        });
+
+        login.connect_clicked({
+            let window = window.clone();
+            let signed_in = signed_in.clone();
+            move |_| {
+                let signed_in = signed_in.clone();
+                login::dialog(&window, move |account, token| signed_in(account, token));
+            }
+        });
+
+        open_settings.connect_clicked({
+            let window = window.clone();
+            let data = data.clone();
+            let fill = fill.clone();
+            let launcher_path = launcher_path.clone();
+            move |_| {
+                let Some((settings, jvms)) = data
+                    .borrow()
+                    .as_ref()
+                    .map(|data| (data.settings.clone(), data.jvms.clone()))
+                else {
+                    return;
+                };
+                let data = data.clone();
+                let fill = fill.clone();
+                let on_save = move |settings, jvms| {
+                    if let Some(data) = &mut *data.borrow_mut() {
+                        data.settings = settings;
+                        data.jvms = jvms;
+                        if let Err(e) = data.save(&data::data_file()) {
+                            eprintln!("{e}");
+                        }
+                    }
+                    fill();
+                };
+                settings::dialog(&window, &settings, &jvms, &launcher_path, on_save);
+            }
+        });
+
+        new_instance.connect_clicked({
+            let window = window.clone();
+            let listed = listed.clone();
+            move |_| {
+                let jvms = data
+                    .borrow()
+                    .as_ref()
+                    .map(|data| data.jvms.clone())
+                    .unwrap_or_default();
+                let data = data.clone();
+                let add_row = add_row.clone();
+                instances::dialog(&window, &listed.borrow(), &jvms, move |instance| {
+                    add_row(&instance);
+                    if let Some(data) = &mut *data.borrow_mut() {
+                        data.instances.push(Arc::new(instance));
+                        if let Err(e) = data.save(&data::data_file()) {
+                            eprintln!("{e}");
+                        }
+                    }
+                });
+            }
+        });
+
+        fill_accounts();
+        select_account(0);
+
+        glib::spawn_future_local(async move {
+            while let Ok(action) = account_rx.recv().await {
+                match action {
+                    accounts::Action::Use(index) => select_account(index),
+                    accounts::Action::Refresh(index) => refresh_account(index, false),
+                }
+            }
+        });
+
         gtk4::glib::spawn_future_local({
             async move {
                 while let Ok(response) = receiver_ref.recv().await {
-                    if let Ok(list) = response {
-                        vec.extend(list.versions);
-                        for i in &vec {
-                            let label = Label::new(Some(&i.id));
-                            combo_box.append(&label);
+                    match response {
+                        Ok(list) => {
+                            versions.borrow_mut().extend(list.versions);
+                            fill();
                         }
+                        Err(e) => eprintln!("{e}"),
                     }
                 }
             }
@@ -136,5 +842,6 @@ fn main() -> gtk4::glib::ExitCode {
         window.present();
     });
 
-    app.run()
+    // The arguments are already parsed, GTK would reject them
+    app.run_with_args::<&str>(&[])
 }
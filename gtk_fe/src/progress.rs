@@ -0,0 +1,96 @@
+use gtk4 as gtk;
+use gtk4::glib;
+use gtk4::prelude::{GridExt, WidgetExt};
+use gtk4::{Label, ProgressBar};
+use launcher_core::progress::{DownloadStats, Snapshot};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The downloads of a launch, in the order of the stats
+pub const LABELS: [&str; 3] = ["Libraries", "Assets", "Jar"];
+
+/// A bar for each download of a launch, in the same order as the stats
+pub struct DownloadProgress {
+    grid: gtk::Grid,
+    bars: [ProgressBar; 3],
+}
+
+impl DownloadProgress {
+    pub fn new() -> Self {
+        let grid = gtk::Grid::builder()
+            .column_spacing(10)
+            .row_spacing(6)
+            .visible(false)
+            .build();
+
+        let bars = LABELS.map(|_| ProgressBar::builder().show_text(true).hexpand(true).build());
+        for (row, (label, bar)) in LABELS.into_iter().zip(&bars).enumerate() {
+            let label = Label::builder().label(label).xalign(0.0).build();
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(bar, 1, row as i32, 1, 1);
+        }
+
+        Self { grid, bars }
+    }
+
+    pub fn widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    /// Shows the bars and updates them from the stats until the returned source is removed
+    pub fn watch(&self, stats: Arc<[DownloadStats; 3]>) -> glib::SourceId {
+        for bar in &self.bars {
+            bar.set_fraction(0.0);
+            bar.set_text(None);
+        }
+        self.grid.set_visible(true);
+
+        let bars = self.bars.clone();
+        glib::timeout_add_local(Duration::from_millis(100), move || {
+            for (bar, stats) in bars.iter().zip(stats.iter()) {
+                let snapshot = stats.snapshot();
+                bar.set_fraction(snapshot.fraction() as f64);
+                bar.set_text(Some(&text(&snapshot)));
+            }
+            glib::ControlFlow::Continue
+        })
+    }
+
+    pub fn hide(&self) {
+        self.grid.set_visible(false);
+    }
+}
+
+/// Sizes, speed and time left, like the egui frontend's progress rows
+pub fn text(snapshot: &Snapshot) -> String {
+    let mut text = format!(
+        "{} / {}",
+        format_bytes(snapshot.finished as f64),
+        format_bytes(snapshot.total as f64)
+    );
+    if snapshot.bytes_per_second > 0.0 {
+        text.push_str(&format!(", {}/s", format_bytes(snapshot.bytes_per_second)));
+    }
+    if let Some(eta) = snapshot.eta {
+        let secs = eta.as_secs();
+        text.push_str(&format!(", {}:{:02} left", secs / 60, secs % 60));
+    }
+    text
+}
+
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{value:.0} {}", UNITS[unit])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
@@ -0,0 +1,466 @@
+use crate::progress::format_bytes;
+use crate::runtime;
+use gtk4 as gtk;
+use gtk4::prelude::{
+    BoxExt, ButtonExt, CheckButtonExt, EditableExt, FileChooserExt, FileExt, GridExt, GtkWindowExt,
+    IsA, NativeDialogExt, WidgetExt,
+};
+use gtk4::{glib, CheckButton, DropDown, Entry, FileChooserAction, Label, Orientation, StringList};
+use launcher_app::java;
+use launcher_app::{Hooks, Jvm, LaunchDefaults, Memory, Resolution};
+use launcher_core::assets::{self, GarbageReport};
+use launcher_core::http::Proxy;
+use launcher_core::libraries::{self, PruneReport};
+use launcher_core::skin_fix::SkinFix;
+use launcher_core::types::{Type, Version};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The settings shared with the egui frontend that are changed or used here, the rest are
+/// written back as they were read
+#[derive(Deserialize, Serialize, Default, Clone)]
+pub struct Settings {
+    // None uses `java` from the PATH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_jvm: Option<Arc<Jvm>>,
+    // Space separated, passed to every launch before the instance args
+    #[serde(default)]
+    pub jvm_args: String,
+    // Heap sizes in MiB, 0 lets the JVM decide
+    #[serde(default)]
+    pub memory: u32,
+    #[serde(default)]
+    pub min_memory: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+    #[serde(default)]
+    pub hooks: Hooks,
+    // Where versions, assets and libraries are stored, defaults to the config directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub version_filter: VersionFilter,
+    // Only read here, it's set up in the egui frontend
+    #[serde(default)]
+    pub proxy: Proxy,
+    #[serde(default)]
+    pub certificates: Vec<PathBuf>,
+    // Set up in the egui frontend too, instances turn it on
+    #[serde(default)]
+    pub skin_fix: SkinFix,
+    #[serde(flatten)]
+    rest: toml::Table,
+}
+
+impl Settings {
+    /// What launches start from before an instance's overrides
+    pub fn launch_defaults(&self) -> LaunchDefaults {
+        LaunchDefaults {
+            jvm: self.default_jvm.clone(),
+            memory: Memory {
+                min: self.min_memory,
+                max: self.memory,
+            },
+            jvm_args: self
+                .jvm_args
+                .split(' ')
+                .filter(|arg| !arg.is_empty())
+                .map(String::from)
+                .collect(),
+            resolution: self.resolution,
+            hooks: self.hooks.clone(),
+            skin_fix: self.skin_fix.clone(),
+        }
+    }
+}
+
+/// Releases are always shown
+#[derive(Deserialize, Serialize, Default, Clone, Copy)]
+#[serde(default)]
+pub struct VersionFilter {
+    pub snapshots: bool,
+    pub betas: bool,
+    pub alphas: bool,
+}
+
+impl VersionFilter {
+    pub fn shows(&self, version: &Version) -> bool {
+        match version.version_type {
+            Type::Release => true,
+            Type::Snapshot => self.snapshots,
+            Type::OldBeta => self.betas,
+            Type::OldAlpha => self.alphas,
+        }
+    }
+}
+
+/// Picks a file or folder, `on_pick` isn't called if it's cancelled
+fn pick(
+    parent: &impl IsA<gtk::Window>,
+    title: &str,
+    action: FileChooserAction,
+    on_pick: impl Fn(PathBuf) + 'static,
+) {
+    let chooser = gtk::FileChooserNative::new(
+        Some(title),
+        Some(parent),
+        action,
+        Some("_Select"),
+        Some("_Cancel"),
+    );
+    chooser.connect_response(move |chooser, response| {
+        if response == gtk::ResponseType::Accept {
+            if let Some(path) = chooser.file().and_then(|file| file.path()) {
+                on_pick(path);
+            }
+        }
+        chooser.destroy();
+    });
+    chooser.show();
+}
+
+/// Looks for assets no version uses off the main thread, removing them unless it's a dry run
+fn collect_assets(
+    assets_dir: PathBuf,
+    dry_run: bool,
+    on_done: impl FnOnce(Result<GarbageReport, launcher_core::Error>) + 'static,
+) {
+    let (tx, rx) = async_channel::bounded(1);
+    runtime().spawn_blocking(move || {
+        let _ = tx.send_blocking(assets::collect_garbage(&assets_dir, dry_run));
+    });
+    glib::spawn_future_local(async move {
+        if let Ok(result) = rx.recv().await {
+            on_done(result);
+        }
+    });
+}
+
+/// Looks for libraries and natives no version uses on the runtime, removing them unless it's
+/// a dry run
+fn prune_libraries(
+    directory: PathBuf,
+    dry_run: bool,
+    on_done: impl FnOnce(Result<PruneReport, launcher_core::Error>) + 'static,
+) {
+    let (tx, rx) = async_channel::bounded(1);
+    runtime().spawn(async move {
+        let _ = tx.send(libraries::prune(&directory, dry_run).await).await;
+    });
+    glib::spawn_future_local(async move {
+        if let Ok(result) = rx.recv().await {
+            on_done(result);
+        }
+    });
+}
+
+/// The data directory, default Java and snapshot toggle, and cleaning up the assets and
+/// libraries in `directory`. `on_save` gets the changed settings and every Java install,
+/// including ones added here
+pub fn dialog(
+    parent: &impl IsA<gtk::Window>,
+    settings: &Settings,
+    jvms: &[Arc<Jvm>],
+    directory: &Path,
+    on_save: impl Fn(Settings, Vec<Arc<Jvm>>) + 'static,
+) {
+    let assets_dir = directory.join("assets");
+    let data_dir = Entry::builder()
+        .placeholder_text("The launcher's config folder")
+        .hexpand(true)
+        .build();
+    if let Some(dir) = &settings.data_dir {
+        data_dir.set_text(&dir.to_string_lossy());
+    }
+    let browse = gtk::Button::builder().label("Browse…").build();
+    let data_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    data_row.append(&data_dir);
+    data_row.append(&browse);
+
+    let jvms = Rc::new(RefCell::new(jvms.to_vec()));
+    // The first entry is `java` from the PATH
+    let names = StringList::new(&["Default"]);
+    for jvm in jvms.borrow().iter() {
+        names.append(&jvm.name);
+    }
+    let jvm = DropDown::builder().model(&names).hexpand(true).build();
+    let selected = settings.default_jvm.as_ref().and_then(|default| {
+        jvms.borrow()
+            .iter()
+            .position(|jvm| jvm.path == default.path)
+    });
+    if let Some(index) = selected {
+        jvm.set_selected(index as u32 + 1);
+    }
+    let add_jvm = gtk::Button::builder().label("Add…").build();
+    let detect = gtk::Button::builder().label("Detect").build();
+    let jvm_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    jvm_row.append(&jvm);
+    jvm_row.append(&add_jvm);
+    jvm_row.append(&detect);
+
+    let snapshots = CheckButton::builder()
+        .label("Show snapshots")
+        .active(settings.version_filter.snapshots)
+        .build();
+
+    let check_assets = gtk::Button::builder().label("Look for unused").build();
+    let remove_assets = gtk::Button::builder()
+        .label("Remove")
+        .sensitive(false)
+        .build();
+    let assets_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    assets_row.append(&check_assets);
+    assets_row.append(&remove_assets);
+
+    let check_libraries = gtk::Button::builder().label("Look for unused").build();
+    let remove_libraries = gtk::Button::builder()
+        .label("Remove")
+        .sensitive(false)
+        .build();
+    let libraries_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    libraries_row.append(&check_libraries);
+    libraries_row.append(&remove_libraries);
+
+    let grid = gtk::Grid::builder()
+        .column_spacing(10)
+        .row_spacing(10)
+        .build();
+    let fields: [(&str, &gtk::Widget); 5] = [
+        ("Data folder", data_row.as_ref()),
+        ("Java", jvm_row.as_ref()),
+        ("Versions", snapshots.as_ref()),
+        ("Assets", assets_row.as_ref()),
+        ("Libraries", libraries_row.as_ref()),
+    ];
+    for (row, (label, field)) in fields.into_iter().enumerate() {
+        let label = Label::builder().label(label).xalign(0.0).build();
+        grid.attach(&label, 0, row as i32, 1, 1);
+        grid.attach(field, 1, row as i32, 1, 1);
+    }
+
+    let status = Label::builder()
+        .label("A new data folder is used after a restart")
+        .wrap(true)
+        .hexpand(true)
+        .xalign(0.0)
+        .build();
+    status.add_css_class("dim-label");
+    let save = gtk::Button::builder().label("Save").build();
+    let cancel = gtk::Button::builder().label("Cancel").build();
+
+    let buttons = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    buttons.append(&status);
+    buttons.append(&cancel);
+    buttons.append(&save);
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(10)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    content.append(&grid);
+    content.append(&buttons);
+
+    let window = gtk::Window::builder()
+        .title("Settings")
+        .modal(true)
+        .transient_for(parent)
+        .child(&content)
+        .build();
+
+    browse.connect_clicked({
+        let window = window.clone();
+        let data_dir = data_dir.clone();
+        move |_| {
+            let data_dir = data_dir.clone();
+            let on_pick = move |path: PathBuf| data_dir.set_text(&path.to_string_lossy());
+            pick(
+                &window,
+                "Data Folder",
+                FileChooserAction::SelectFolder,
+                on_pick,
+            );
+        }
+    });
+
+    // Adds the install unless it's already listed, and selects it
+    let add = Rc::new({
+        let jvms = jvms.clone();
+        let jvm = jvm.clone();
+        let status = status.clone();
+        move |path: PathBuf| {
+            let path = path.to_string_lossy().to_string();
+            let existing = jvms.borrow().iter().position(|jvm| jvm.path == path);
+            let index = match existing {
+                Some(index) => index,
+                None => match Jvm::detect(path) {
+                    Ok(detected) => {
+                        names.append(&detected.name);
+                        jvms.borrow_mut().push(Arc::new(detected));
+                        jvms.borrow().len() - 1
+                    }
+                    Err(e) => {
+                        eprintln!("{e}");
+                        status.set_text(&format!("That isn't a working Java install: {e}"));
+                        return;
+                    }
+                },
+            };
+            jvm.set_selected(index as u32 + 1);
+        }
+    });
+
+    add_jvm.connect_clicked({
+        let window = window.clone();
+        let add = add.clone();
+        move |_| {
+            let add = add.clone();
+            pick(
+                &window,
+                "Java Binary",
+                FileChooserAction::Open,
+                move |path| add(path),
+            );
+        }
+    });
+
+    detect.connect_clicked({
+        let status = status.clone();
+        move |_| {
+            let found = java::find_installs();
+            if found.is_empty() {
+                status.set_text("No Java installs were found, add one instead");
+                return;
+            }
+            status.set_text(&format!("Found {} Java installs", found.len()));
+            for path in found {
+                add(path);
+            }
+        }
+    });
+
+    check_assets.connect_clicked({
+        let status = status.clone();
+        let remove = remove_assets.clone();
+        let assets_dir = assets_dir.clone();
+        move |button| {
+            button.set_sensitive(false);
+            status.set_text("Looking for unused assets…");
+            let (button, status, remove) = (button.clone(), status.clone(), remove.clone());
+            collect_assets(assets_dir.clone(), true, move |result| {
+                button.set_sensitive(true);
+                match result {
+                    Ok(report) => {
+                        let size = format_bytes(report.bytes as f64);
+                        status.set_text(&format!("{} unused assets, {size}", report.objects));
+                        remove.set_sensitive(report.objects > 0);
+                    }
+                    Err(e) => status.set_text(&format!("Couldn't look through the assets: {e}")),
+                }
+            });
+        }
+    });
+
+    remove_assets.connect_clicked({
+        let status = status.clone();
+        let assets_dir = assets_dir.clone();
+        move |button| {
+            button.set_sensitive(false);
+            let status = status.clone();
+            collect_assets(assets_dir.clone(), false, move |result| match result {
+                Ok(report) => {
+                    let size = format_bytes(report.bytes as f64);
+                    status.set_text(&format!("Removed {} unused assets, {size}", report.objects));
+                }
+                Err(e) => status.set_text(&format!("Removing the assets failed: {e}")),
+            });
+        }
+    });
+
+    check_libraries.connect_clicked({
+        let status = status.clone();
+        let remove = remove_libraries.clone();
+        let directory = directory.to_path_buf();
+        move |button| {
+            button.set_sensitive(false);
+            status.set_text("Looking for unused libraries…");
+            let (button, status, remove) = (button.clone(), status.clone(), remove.clone());
+            prune_libraries(directory.clone(), true, move |result| {
+                button.set_sensitive(true);
+                match result {
+                    Ok(report) => {
+                        let size = format_bytes(report.bytes as f64);
+                        status.set_text(&format!("{} unused libraries, {size}", report.files));
+                        remove.set_sensitive(report.files > 0);
+                    }
+                    Err(e) => status.set_text(&format!("Couldn't look through the libraries: {e}")),
+                }
+            });
+        }
+    });
+
+    remove_libraries.connect_clicked({
+        let status = status.clone();
+        let directory = directory.to_path_buf();
+        move |button| {
+            button.set_sensitive(false);
+            let status = status.clone();
+            prune_libraries(directory.clone(), false, move |result| match result {
+                Ok(report) => {
+                    let size = format_bytes(report.bytes as f64);
+                    status.set_text(&format!(
+                        "Removed {} unused libraries, {size}",
+                        report.files
+                    ));
+                }
+                Err(e) => status.set_text(&format!("Removing the libraries failed: {e}")),
+            });
+        }
+    });
+
+    cancel.connect_clicked({
+        let window = window.clone();
+        move |_| window.close()
+    });
+
+    let settings = settings.clone();
+    save.connect_clicked({
+        let window = window.clone();
+        move |_| {
+            let mut settings = settings.clone();
+            let dir = data_dir.text().trim().to_string();
+            settings.data_dir = Some(PathBuf::from(dir)).filter(|dir| !dir.as_os_str().is_empty());
+            settings.default_jvm = (jvm.selected() as usize)
+                .checked_sub(1)
+                .and_then(|index| jvms.borrow().get(index).cloned());
+            settings.version_filter.snapshots = snapshots.is_active();
+
+            window.close();
+            on_save(settings, jvms.borrow().clone());
+        }
+    });
+
+    window.present();
+}
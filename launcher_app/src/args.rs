@@ -0,0 +1,98 @@
+/// The usage printed for `--help` and for arguments that can't be parsed
+pub const USAGE: &str = "\
+Usage: [--launch <instance> [--headless]] [--daemon]
+
+  --launch <instance>  Start the instance with that name once there is an account
+  --headless           Don't open a window, print the download progress instead
+  --daemon             Don't open a window, take requests from other programs instead
+  -h, --help           Print this message";
+
+/// The command line shared by the frontends, so desktop files and shortcuts can start an
+/// instance directly
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct Args {
+    pub launch: Option<String>,
+    pub headless: bool,
+    pub daemon: bool,
+    pub help: bool,
+}
+
+impl Args {
+    /// The arguments after the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--launch" => {
+                    let name = args.next().ok_or("--launch needs an instance name")?;
+                    parsed.launch = Some(name);
+                }
+                "--headless" => parsed.headless = true,
+                "--daemon" => parsed.daemon = true,
+                "-h" | "--help" => parsed.help = true,
+                _ => match arg.strip_prefix("--launch=") {
+                    Some(name) => parsed.launch = Some(name.to_string()),
+                    None => return Err(format!("Unknown argument {arg}")),
+                },
+            }
+        }
+
+        if parsed.headless && parsed.launch.is_none() {
+            return Err("--headless needs an instance to --launch".into());
+        }
+        if parsed.daemon && parsed.launch.is_some() {
+            return Err("--daemon can't --launch, ask it to launch instead".into());
+        }
+        Ok(parsed)
+    }
+
+    /// Parses the process' arguments, printing the usage and exiting for `--help` or
+    /// if they're wrong
+    pub fn from_env() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(args) if args.help => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            Ok(args) => args,
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(&[]), Ok(Args::default()));
+
+        let args = parse(&["--launch", "Survival", "--headless"]).unwrap();
+        assert_eq!(args.launch.as_deref(), Some("Survival"));
+        assert!(args.headless);
+
+        let args = parse(&["--launch=Modded 1.20"]).unwrap();
+        assert_eq!(args.launch.as_deref(), Some("Modded 1.20"));
+        assert!(!args.headless);
+
+        assert!(parse(&["--daemon"]).unwrap().daemon);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&["--launch"]).is_err());
+        assert!(parse(&["--headless"]).is_err());
+        assert!(parse(&["--fullscreen"]).is_err());
+        assert!(parse(&["--daemon", "--launch", "Survival"]).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use launcher_core::account::auth::{
+    authorization_token_response, device_response, minecraft_profile_response, minecraft_response,
+    refresh_token_response, xbox_response, xbox_security_token_response,
+};
+use launcher_core::account::types::{Account, AuthorizationTokenResponse, DeviceCodeResponse};
+use std::time::{Duration, SystemTime};
+
+// https://wiki.vg/Microsoft_Authentication_Scheme
+
+/// Microsoft's device code login, or a refresh if there is a token. `on_code` gets the page
+/// to open and the code to enter there, as soon as there is one.
+/// Returns the account and its new refresh token
+pub async fn log_in(
+    client: &reqwest::Client,
+    client_id: &str,
+    refresh_token: Option<&str>,
+    on_code: impl FnOnce(String, String),
+) -> Result<(Account, String), launcher_core::Error> {
+    if let Some(token) = refresh_token {
+        return refresh(client, client_id, token).await;
+    }
+
+    let device = device_response(client, client_id).await?;
+    on_code(device.verification_uri.clone(), device.user_code.clone());
+    let token = wait_for_code(client, client_id, &device).await;
+    account(client, token).await
+}
+
+/// Gets a new access token for a saved account, without asking the player
+pub async fn refresh(
+    client: &reqwest::Client,
+    client_id: &str,
+    refresh_token: &str,
+) -> Result<(Account, String), launcher_core::Error> {
+    let token = refresh_token_response(client, refresh_token, client_id).await?;
+    account(client, token).await
+}
+
+/// Polls at the interval Microsoft asks for, the token request fails until the code is entered
+pub async fn wait_for_code(
+    client: &reqwest::Client,
+    client_id: &str,
+    device: &DeviceCodeResponse,
+) -> AuthorizationTokenResponse {
+    loop {
+        tokio::time::sleep(Duration::from_secs(device.interval)).await;
+        let response = authorization_token_response(client, &device.device_code, client_id).await;
+        if let Ok(token) = response {
+            return token;
+        }
+    }
+}
+
+/// Signs in to Xbox Live and Minecraft with a Microsoft token.
+/// Ownership isn't checked, the profile request fails for accounts without the game
+pub async fn account(
+    client: &reqwest::Client,
+    token: AuthorizationTokenResponse,
+) -> Result<(Account, String), launcher_core::Error> {
+    let xbox = xbox_response(client, &token.access_token).await?;
+    let security = xbox_security_token_response(client, &xbox.token).await?;
+    let minecraft = minecraft_response(&security.display_claims, &security.token, client).await?;
+    let profile = minecraft_profile_response(&minecraft.access_token, client).await?;
+
+    let account = Account {
+        active: true,
+        expiry: expiry(token.expires_in),
+        access_token: minecraft.access_token,
+        profile,
+        offline: false,
+    };
+    Ok((account, token.refresh_token))
+}
+
+/// Whether the access token has run out, offline accounts never do
+pub fn expired(account: &Account) -> bool {
+    !account.offline && account.expiry <= expiry(0)
+}
+
+/// Unix time in seconds, `expires_in` seconds from now
+fn expiry(expires_in: u64) -> u64 {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap();
+    (now + Duration::from_secs(expires_in)).as_secs()
+}
@@ -0,0 +1,421 @@
+use crate::instance::{Instance, Jvm};
+use launcher_core::account::secrets::{KeyringStore, SecretError, SecretStore};
+use launcher_core::account::types::Account;
+use launcher_core::http::{ClientOptions, Proxy};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Everything in launcher_data.toml. The settings belong to the frontend, the ones that don't
+/// have any of their own keep them as a table so they're written back untouched
+#[derive(Default, Deserialize, Serialize)]
+#[serde(bound(deserialize = "S: Deserialize<'de> + Default"))]
+pub struct LauncherData<S = toml::Table> {
+    #[serde(default)]
+    pub jvms: Vec<Arc<Jvm>>,
+    #[serde(default)]
+    pub accounts: Vec<AccRefreshPair>,
+    #[serde(default)]
+    pub instances: Vec<Arc<Instance>>,
+    #[serde(default)]
+    pub settings: S,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AccRefreshPair {
+    pub account: Account,
+    // Tokens are kept in the keyring, this is only set if storing it there failed,
+    // or for data files written before the keyring was used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+}
+
+impl AccRefreshPair {
+    /// An account without a refresh token, like an offline one
+    pub fn new(account: Account) -> Self {
+        Self {
+            account,
+            refresh_token: None,
+        }
+    }
+
+    /// Whether the access token has run out and has to be refreshed before a launch
+    pub fn needs_refresh(&self) -> bool {
+        crate::auth::expired(&self.account)
+    }
+
+    /// The token from launcher_data.toml, or from the secret store
+    pub fn refresh_token(&self, secrets: &impl SecretStore) -> Option<String> {
+        if let Some(token) = &self.refresh_token {
+            return Some(token.clone());
+        }
+
+        secrets.get(&self.account.profile.id).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            None
+        })
+    }
+}
+
+impl<S> LauncherData<S> {
+    /// The default data if the file doesn't exist yet, it's only written on save
+    pub fn load(file: &Path) -> Result<Self, Error>
+    where
+        S: for<'de> Deserialize<'de> + Default,
+    {
+        if file.exists() {
+            let buffer = std::fs::read_to_string(file)?;
+            Ok(toml::from_str(&buffer)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, file: &Path) -> Result<(), Error>
+    where
+        S: Serialize,
+    {
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The instance `--launch` names
+    pub fn instance_index(&self, name: &str) -> Option<usize> {
+        self.instances
+            .iter()
+            .position(|instance| instance.name == name)
+    }
+
+    /// Moves plaintext refresh tokens into the secret store, returns whether any were moved
+    pub fn migrate_tokens(&mut self, secrets: &impl SecretStore) -> bool {
+        let mut migrated = false;
+
+        for acc in &mut self.accounts {
+            if let Some(token) = &acc.refresh_token {
+                match secrets.set(&acc.account.profile.id, token) {
+                    Ok(()) => {
+                        acc.refresh_token = None;
+                        migrated = true;
+                    }
+                    // The token stays in plaintext and is tried again next launch
+                    Err(e) => eprintln!("{e}"),
+                }
+            }
+        }
+
+        migrated
+    }
+
+    /// Adds the account, or replaces the one with the same profile. Returns its index
+    pub fn insert_account(&mut self, pair: AccRefreshPair) -> usize {
+        let existing = self
+            .accounts
+            .iter()
+            .position(|acc| acc.account.profile.id == pair.account.profile.id);
+        match existing {
+            Some(index) => {
+                self.accounts[index] = pair;
+                index
+            }
+            None => {
+                self.accounts.push(pair);
+                self.accounts.len() - 1
+            }
+        }
+    }
+
+    /// Stores the refresh token and adds or replaces the account, see [`Self::insert_account`].
+    /// The token goes in launcher_data.toml if the secret store fails
+    pub fn set_account(
+        &mut self,
+        account: Account,
+        refresh_token: String,
+        secrets: &impl SecretStore,
+    ) -> usize {
+        let refresh_token = match secrets.set(&account.profile.id, &refresh_token) {
+            Ok(()) => None,
+            Err(e) => {
+                eprintln!("{e}, falling back to launcher_data.toml");
+                Some(refresh_token)
+            }
+        };
+
+        self.insert_account(AccRefreshPair {
+            account,
+            refresh_token,
+        })
+    }
+
+    /// The account an instance launches with: the one it's pinned to by profile id, see
+    /// [`Instance::account`], otherwise the selected one. A pinned account that was removed is
+    /// an error, instead of launching as someone else
+    pub fn launch_account(
+        &self,
+        pinned: Option<&str>,
+        selected: Option<usize>,
+    ) -> Result<usize, AccountError> {
+        match pinned {
+            Some(id) => self
+                .accounts
+                .iter()
+                .position(|acc| acc.account.profile.id == id)
+                .ok_or_else(|| AccountError::PinnedRemoved(id.to_string())),
+            None => selected
+                .filter(|index| *index < self.accounts.len())
+                .ok_or(AccountError::NoAccount),
+        }
+    }
+
+    /// Removes the account and its token from the secret store
+    pub fn remove_account(&mut self, index: usize, secrets: &impl SecretStore) -> AccRefreshPair {
+        let removed = self.accounts.remove(index);
+        if let Err(e) = secrets.delete(&removed.account.profile.id) {
+            eprintln!("{e}");
+        }
+        removed
+    }
+}
+
+// Shared by every frontend, so accounts added in one work in the others
+pub fn secrets() -> KeyringStore {
+    KeyringStore::new("synth_launcher")
+}
+
+/// What the proxy password is stored under, profile ids never look like this
+const PROXY_SECRET: &str = "proxy";
+
+/// The options every frontend builds its client with, the proxy from the settings gets its
+/// password from the secret store
+pub fn client_options(
+    proxy: &Proxy,
+    certificates: &[PathBuf],
+    secrets: &impl SecretStore,
+) -> ClientOptions {
+    let mut proxy = proxy.clone();
+    if proxy.is_enabled() && !proxy.username.is_empty() {
+        match secrets.get(PROXY_SECRET) {
+            Ok(password) => proxy.password = password.unwrap_or_default(),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+    ClientOptions {
+        proxy: Some(proxy),
+        certificates: certificates.to_vec(),
+        ..Default::default()
+    }
+}
+
+/// An empty password removes it
+pub fn set_proxy_password(secrets: &impl SecretStore, password: &str) -> Result<(), SecretError> {
+    if password.is_empty() {
+        secrets.delete(PROXY_SECRET)
+    } else {
+        secrets.set(PROXY_SECRET, password)
+    }
+}
+
+/// Where launcher_data.toml is, and the default data directory
+pub fn config_dir() -> PathBuf {
+    let app_dir = platform_dirs::AppDirs::new(Some("synth_launcher"), false).unwrap();
+    app_dir.config_dir
+}
+
+pub fn data_file() -> PathBuf {
+    config_dir().join("launcher_data.toml")
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    TomlDE(toml::de::Error),
+    TomlSER(toml::ser::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(value: toml::de::Error) -> Self {
+        Error::TomlDE(value)
+    }
+}
+
+impl From<toml::ser::Error> for Error {
+    fn from(value: toml::ser::Error) -> Self {
+        Error::TomlSER(value)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(err) => err.fmt(f),
+            Error::TomlDE(err) => err.fmt(f),
+            Error::TomlSER(err) => err.fmt(f),
+        }
+    }
+}
+
+/// Why there's no account to launch with, see [`LauncherData::launch_account`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum AccountError {
+    /// The profile id the instance is pinned to
+    PinnedRemoved(String),
+    NoAccount,
+}
+
+impl Display for AccountError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountError::PinnedRemoved(_) => {
+                f.write_str("The instance's account was removed, pick another one in its settings")
+            }
+            AccountError::NoAccount => {
+                f.write_str("There's no account to launch with, sign in first")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{client_options, set_proxy_password, AccRefreshPair, AccountError, LauncherData};
+    use launcher_core::account::offline::offline_account;
+    use launcher_core::account::secrets::{SecretError, SecretStore};
+    use launcher_core::http::Proxy;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryStore(RefCell<HashMap<String, String>>);
+
+    impl SecretStore for MemoryStore {
+        fn get(&self, account: &str) -> Result<Option<String>, SecretError> {
+            Ok(self.0.borrow().get(account).cloned())
+        }
+
+        fn set(&self, account: &str, secret: &str) -> Result<(), SecretError> {
+            self.0.borrow_mut().insert(account.into(), secret.into());
+            Ok(())
+        }
+
+        fn delete(&self, account: &str) -> Result<(), SecretError> {
+            self.0.borrow_mut().remove(account);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_settings_kept() {
+        let text = "[settings]\ndata_dir = \"/games\"\nmemory = 4096\n";
+        let data: LauncherData = toml::from_str(text).unwrap();
+        assert!(data.accounts.is_empty());
+        assert_eq!(data.settings["memory"].as_integer(), Some(4096));
+
+        let written = toml::to_string_pretty(&data).unwrap();
+        let read: LauncherData = toml::from_str(&written).unwrap();
+        assert_eq!(read.settings, data.settings);
+    }
+
+    #[test]
+    fn test_accounts() {
+        let secrets = MemoryStore::default();
+        let mut data = LauncherData::<toml::Table>::default();
+
+        let first = data.set_account(offline_account("Steve"), "a".into(), &secrets);
+        let second = data.insert_account(AccRefreshPair::new(offline_account("Alex")));
+        // The same profile replaces the account instead of adding another
+        let again = data.set_account(offline_account("Steve"), "b".into(), &secrets);
+        assert_eq!((first, second, again), (0, 1, 0));
+        assert_eq!(data.accounts.len(), 2);
+        assert_eq!(
+            data.accounts[0].refresh_token(&secrets).as_deref(),
+            Some("b")
+        );
+        assert_eq!(data.accounts[1].refresh_token(&secrets), None);
+
+        data.remove_account(0, &secrets);
+        assert!(secrets.0.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_launch_account() {
+        let mut data = LauncherData::<toml::Table>::default();
+        data.insert_account(AccRefreshPair::new(offline_account("Steve")));
+        data.insert_account(AccRefreshPair::new(offline_account("Alex")));
+        let alex = data.accounts[1].account.profile.id.clone();
+
+        assert_eq!(data.launch_account(None, Some(0)), Ok(0));
+        assert_eq!(
+            data.launch_account(None, Some(2)),
+            Err(AccountError::NoAccount)
+        );
+        assert_eq!(
+            data.launch_account(None, None),
+            Err(AccountError::NoAccount)
+        );
+
+        // Pinned wins over the selected account, and doesn't need one selected
+        assert_eq!(data.launch_account(Some(&alex), Some(0)), Ok(1));
+        assert_eq!(data.launch_account(Some(&alex), None), Ok(1));
+
+        data.accounts.remove(1);
+        assert_eq!(
+            data.launch_account(Some(&alex), Some(0)),
+            Err(AccountError::PinnedRemoved(alex))
+        );
+    }
+
+    #[test]
+    fn test_migrate_tokens() {
+        let secrets = MemoryStore::default();
+        let mut data = LauncherData::<toml::Table>::default();
+        let mut pair = AccRefreshPair::new(offline_account("Steve"));
+        pair.refresh_token = Some("token".into());
+        data.accounts.push(pair);
+
+        assert!(data.migrate_tokens(&secrets));
+        assert!(!data.migrate_tokens(&secrets));
+        assert_eq!(data.accounts[0].refresh_token, None);
+        assert_eq!(
+            data.accounts[0].refresh_token(&secrets).as_deref(),
+            Some("token")
+        );
+    }
+
+    #[test]
+    fn test_proxy_password() {
+        let secrets = MemoryStore::default();
+        let mut proxy = Proxy {
+            url: "socks5://127.0.0.1:1080".into(),
+            username: "steve".into(),
+            ..Default::default()
+        };
+        set_proxy_password(&secrets, "hunter2").unwrap();
+        let options = client_options(&proxy, &[], &secrets);
+        assert_eq!(options.proxy.unwrap().password, "hunter2");
+
+        // Without a username there's nothing to log in with
+        proxy.username.clear();
+        let options = client_options(&proxy, &[], &secrets);
+        assert_eq!(options.proxy.unwrap().password, "");
+
+        set_proxy_password(&secrets, "").unwrap();
+        assert!(secrets.0.borrow().is_empty());
+
+        // The password never ends up in the settings
+        let written = toml::to_string(&Proxy {
+            password: "hunter2".into(),
+            ..proxy
+        })
+        .unwrap();
+        assert!(!written.contains("hunter2"));
+    }
+}
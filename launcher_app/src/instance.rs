@@ -0,0 +1,269 @@
+use crate::settings::{Hooks, Resolution};
+use launcher_core::loader::Loader;
+use launcher_core::types::Version;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// One entry of `instances` in launcher_data.toml. Every frontend reads and writes it, so
+/// fields added later need a default for files written before them
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Instance {
+    pub name: String,
+    pub image: Option<PathBuf>,
+    // Missing from instances made by the C# frontend, they use `java` from the PATH
+    #[serde(default)]
+    pub jvm: Arc<Jvm>,
+    pub version: Arc<Version>,
+    pub path: PathBuf,
+    pub mod_loader: Option<Loader>,
+    // Set for instances made since loaders are installed, older ones launch as vanilla
+    #[serde(default)]
+    pub loader_version: Option<String>,
+    // Updates the loader to its newest stable build before launching
+    #[serde(default)]
+    pub track_latest_loader: bool,
+    #[serde(default)]
+    pub jvm_args: Vec<String>,
+    #[serde(default)]
+    pub env_args: Vec<String>,
+    #[serde(default)]
+    pub mods: Vec<InstalledMod>,
+    // Instances sharing a group are shown together
+    #[serde(default)]
+    pub group: Option<String>,
+    // Unix time in seconds, set at launch and again when the game exits
+    #[serde(default)]
+    pub last_played: Option<u64>,
+    // Seconds spent in game, across every launch
+    #[serde(default)]
+    pub playtime: u64,
+    // None uses the memory from the settings
+    #[serde(default)]
+    pub memory: Option<Memory>,
+    // None uses the resolution from the settings, or the game's own
+    #[serde(default)]
+    pub resolution: Option<Resolution>,
+    // Each one that's set replaces the one from the settings
+    #[serde(default)]
+    pub hooks: Hooks,
+    // Profile id of the account it always launches with, None uses the selected one
+    #[serde(default)]
+    pub account: Option<String>,
+    // Launches with the skin fix from the settings, if the version is old enough to need it
+    #[serde(default)]
+    pub legacy_skin_fix: bool,
+    // Changes the launcher made on its own, oldest first
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+}
+
+impl Instance {
+    /// A vanilla instance, the rest of the fields are left empty
+    pub fn new(name: String, jvm: Arc<Jvm>, version: Arc<Version>) -> Self {
+        Self {
+            name,
+            image: None,
+            jvm,
+            version,
+            path: PathBuf::new(),
+            mod_loader: None,
+            loader_version: None,
+            track_latest_loader: false,
+            jvm_args: Vec::new(),
+            env_args: Vec::new(),
+            mods: Vec::new(),
+            group: None,
+            last_played: None,
+            playtime: 0,
+            memory: None,
+            resolution: None,
+            hooks: Hooks::default(),
+            account: None,
+            legacy_skin_fix: false,
+            history: Vec::new(),
+        }
+    }
+
+    /// The loader and its version, None for vanilla and for instances made before loaders
+    /// were installed
+    pub fn loader(&self) -> Option<(Loader, &str)> {
+        self.mod_loader.zip(self.loader_version.as_deref())
+    }
+
+    /// Switches to another build of the installed loader, and records it in the history
+    pub fn update_loader(&mut self, version: String, time: u64) {
+        let Some(loader) = self.mod_loader else {
+            return;
+        };
+        let from = self
+            .loader_version
+            .replace(version.clone())
+            .unwrap_or_default();
+        self.history.push(HistoryEntry {
+            time,
+            change: Change::LoaderUpdated {
+                loader,
+                from,
+                to: version,
+            },
+        });
+    }
+
+    /// The game directory, instances without a path share the launcher directory
+    pub fn directory(&self, launcher_path: &Path) -> PathBuf {
+        if self.path.as_os_str().is_empty() {
+            launcher_path.to_path_buf()
+        } else {
+            self.path.clone()
+        }
+    }
+
+    /// Where backups of the whole instance go, kept outside it so they aren't backed up too
+    pub fn archives_dir(&self, launcher_path: &Path) -> PathBuf {
+        let folder = match self.path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => "launcher".to_string(),
+        };
+        launcher_path.join("instance_backups").join(folder)
+    }
+
+    /// Instances without a path share the launcher directory
+    pub fn mods_dir(&self, launcher_path: &Path) -> PathBuf {
+        self.directory(launcher_path).join("mods")
+    }
+
+    pub fn resource_packs_dir(&self, launcher_path: &Path) -> PathBuf {
+        self.directory(launcher_path).join("resourcepacks")
+    }
+
+    pub fn saves_dir(&self, launcher_path: &Path) -> PathBuf {
+        self.directory(launcher_path).join("saves")
+    }
+
+    /// Where world backups go, the same folder the game puts its own in
+    pub fn backups_dir(&self, launcher_path: &Path) -> PathBuf {
+        self.directory(launcher_path).join("backups")
+    }
+}
+
+/// Something the launcher changed about an instance, and when in unix seconds
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub time: u64,
+    pub change: Change,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    /// The loader was updated to its newest stable build, see [`Instance::track_latest_loader`]
+    LoaderUpdated {
+        loader: Loader,
+        from: String,
+        to: String,
+    },
+}
+
+/// A mod installed from Modrinth, the file lives in the instance's mods folder
+#[derive(Deserialize, Serialize, Clone)]
+pub struct InstalledMod {
+    pub project_id: String,
+    pub version_id: String,
+    pub title: String,
+    pub version_number: String,
+    pub file_name: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct Jvm {
+    pub path: String,
+    pub name: String,
+}
+
+impl Default for Jvm {
+    fn default() -> Self {
+        Self {
+            path: "java".into(),
+            name: "Default".into(),
+        }
+    }
+}
+
+/// Heap sizes in MiB, 0 lets the JVM decide
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Memory {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Memory {
+    pub fn args(self) -> Vec<String> {
+        launcher_core::memory::heap_args(self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Change, HistoryEntry, Instance, Jvm};
+    use launcher_core::loader::Loader;
+
+    // What the C# frontend wrote before the frontends shared these types
+    const CS_INSTANCE: &str = r#"
+name = "Survival"
+path = "/games/survival"
+mod_loader = "Fabric"
+jvm_args = ["-Xss4M"]
+env_args = []
+
+[version]
+id = "1.20.1"
+type = "release"
+url = "https://piston-meta.mojang.com/v1/packages/1.20.1.json"
+time = "2023-06-12T13:25:51+00:00"
+releaseTime = "2023-06-12T13:25:51+00:00"
+"#;
+
+    #[test]
+    fn test_cs_instance() {
+        let instance: Instance = toml::from_str(CS_INSTANCE).unwrap();
+        assert_eq!(*instance.jvm, Jvm::default());
+        assert_eq!(instance.mod_loader, Some(Loader::Fabric));
+        // Made before loaders were installed, so it launches as vanilla
+        assert_eq!(instance.loader(), None);
+        assert!(instance.mods.is_empty());
+
+        let written = toml::to_string_pretty(&instance).unwrap();
+        let read: Instance = toml::from_str(&written).unwrap();
+        assert_eq!(read.version, instance.version);
+        assert_eq!(read.jvm_args, ["-Xss4M"]);
+        assert_eq!(read.hooks, instance.hooks);
+    }
+
+    #[test]
+    fn test_update_loader() {
+        let mut instance: Instance = toml::from_str(CS_INSTANCE).unwrap();
+        instance.loader_version = Some("0.15.3".into());
+        instance.update_loader("0.15.6".into(), 1_700_000_000);
+        assert_eq!(instance.loader(), Some((Loader::Fabric, "0.15.6")));
+
+        let written = toml::to_string_pretty(&instance).unwrap();
+        let read: Instance = toml::from_str(&written).unwrap();
+        assert_eq!(
+            read.history,
+            [HistoryEntry {
+                time: 1_700_000_000,
+                change: Change::LoaderUpdated {
+                    loader: Loader::Fabric,
+                    from: "0.15.3".into(),
+                    to: "0.15.6".into(),
+                },
+            }]
+        );
+
+        // Vanilla instances have nothing to update
+        instance.mod_loader = None;
+        instance.update_loader("0.16.0".into(), 1_700_000_001);
+        assert_eq!(instance.history.len(), 1);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::instance::Jvm;
+use launcher_core::jvm::{self, ProbeError};
+use std::path::{Path, PathBuf};
+
+impl Jvm {
+    /// Runs the binary to name it after its vendor and major version
+    pub fn detect(path: String) -> Result<Self, ProbeError> {
+        let version = jvm::probe(Path::new(&path))?;
+        Ok(Self {
+            path,
+            name: version.to_string(),
+        })
+    }
+}
+
+/// Java binaries in JAVA_HOME and the folders Java is usually installed to.
+/// They aren't run, so some may not work
+pub fn find_installs() -> Vec<PathBuf> {
+    let binary = if cfg!(windows) { "java.exe" } else { "java" };
+
+    let mut homes: Vec<PathBuf> = std::env::var_os("JAVA_HOME")
+        .map(PathBuf::from)
+        .into_iter()
+        .collect();
+
+    let parents: &[&str] = if cfg!(windows) {
+        &[
+            "C:\\Program Files\\Java",
+            "C:\\Program Files\\Eclipse Adoptium",
+            "C:\\Program Files\\Microsoft",
+        ]
+    } else if cfg!(target_os = "macos") {
+        &["/Library/Java/JavaVirtualMachines"]
+    } else {
+        &["/usr/lib/jvm", "/usr/lib64/jvm", "/opt/java"]
+    };
+    for parent in parents {
+        let Ok(entries) = std::fs::read_dir(parent) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let home = entry.path();
+            // macOS bundles keep the JDK a few folders down
+            let bundled = home.join("Contents").join("Home");
+            homes.push(if bundled.is_dir() { bundled } else { home });
+        }
+    }
+
+    let mut installs: Vec<PathBuf> = homes
+        .iter()
+        .map(|home| home.join("bin").join(binary))
+        .filter(|path| path.is_file())
+        .collect();
+    // Distributions link the same install under a few names
+    installs.sort_by_key(|path| canonical(path));
+    installs.dedup_by_key(|path| canonical(path));
+    installs
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,111 @@
+use crate::data::Error;
+use launcher_core::types::Version;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where the jobs are kept, in the data directory next to the files they download
+pub const FILE: &str = "pending_jobs.toml";
+
+/// Downloads that were queued or running, so they continue after a restart or a crash
+#[derive(Default, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PendingJobs {
+    #[serde(default)]
+    pub jobs: Vec<Job>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Job {
+    /// An instance by name, or a version for quick play, downloading before it launches
+    Prepare {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        instance: Option<String>,
+        name: String,
+        version: Version,
+        #[serde(default)]
+        paused: bool,
+    },
+    /// A modpack installing into a new instance with that name
+    Modpack { pack: PathBuf, name: String },
+}
+
+impl PendingJobs {
+    /// No jobs if the file doesn't exist
+    pub fn load(file: &Path) -> Result<Self, Error> {
+        if file.exists() {
+            let buffer = std::fs::read_to_string(file)?;
+            Ok(toml::from_str(&buffer)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// The file is removed once there's nothing left to do
+    pub fn save(&self, file: &Path) -> Result<(), Error> {
+        if self.jobs.is_empty() {
+            return match std::fs::remove_file(file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Job, PendingJobs};
+    use launcher_core::types::{Type, Version};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join("synth_jobs_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let file = dir.join(super::FILE);
+
+        let version = Version {
+            id: "1.20.4".into(),
+            version_type: Type::Release,
+            url: "https://piston-meta.mojang.com/v1/packages/1.20.4.json".into(),
+            time: "2023-12-07T12:56:20+00:00".into(),
+            release_time: "2023-12-07T12:56:20+00:00".into(),
+        };
+        let jobs = PendingJobs {
+            jobs: vec![
+                Job::Prepare {
+                    instance: Some("Survival".into()),
+                    name: "Survival".into(),
+                    version: version.clone(),
+                    paused: true,
+                },
+                Job::Prepare {
+                    instance: None,
+                    name: "Minecraft 1.20.4".into(),
+                    version,
+                    paused: false,
+                },
+                Job::Modpack {
+                    pack: PathBuf::from("/home/steve/Downloads/pack.mrpack"),
+                    name: "Pack".into(),
+                },
+            ],
+        };
+
+        assert_eq!(PendingJobs::load(&file).unwrap(), PendingJobs::default());
+        jobs.save(&file).unwrap();
+        assert_eq!(PendingJobs::load(&file).unwrap(), jobs);
+
+        // Nothing left to do removes the file
+        PendingJobs::default().save(&file).unwrap();
+        assert!(!file.exists());
+        PendingJobs::default().save(&file).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
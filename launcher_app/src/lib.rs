@@ -0,0 +1,21 @@
+//! What every frontend needs on top of launcher_core: the instances and accounts saved in
+//! launcher_data.toml, signing in, and the downloads that come before a launch, which are kept
+//! in pending_jobs.toml until they're done
+
+pub mod args;
+pub mod auth;
+pub mod data;
+pub mod instance;
+pub mod java;
+pub mod jobs;
+pub mod prepare;
+pub mod settings;
+
+pub use args::Args;
+pub use data::{AccRefreshPair, LauncherData};
+pub use instance::{InstalledMod, Instance, Jvm, Memory};
+pub use jobs::{Job, PendingJobs};
+pub use settings::{Hooks, LaunchDefaults, LaunchSettings, Resolution};
+
+/// The Azure application the launcher signs in with
+pub const CLIENT_ID: &str = "04bc8538-fc3c-4490-9e61-a2b3f4cbcf5c";
@@ -0,0 +1,154 @@
+use launcher_core::types::{AssetIndexJson, VersionJson};
+use std::sync::Arc;
+
+/// The downloads a version needs before it can launch
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Part {
+    Version,
+    Libraries,
+    AssetIndex,
+    Assets,
+    Jar,
+    /// Updating the loader, for instances that track its newest stable build
+    Loader,
+}
+
+/// Tracks which parts of a launch are downloaded, and which can start next.
+/// The frontend runs the parts [`Prepare::start`] returns, and stores what they return
+#[derive(Default)]
+pub struct Prepare {
+    pub json: Option<Arc<VersionJson>>,
+    pub asset_index: Option<Arc<AssetIndexJson>>,
+    pub class_path: Option<String>,
+    pub jar_path: Option<String>,
+    pub assets: bool,
+    // Set by the frontend when the loader should be checked for an update first
+    pub loader_update: bool,
+    // Parts whose futures haven't returned yet
+    running: Vec<Part>,
+}
+
+impl Prepare {
+    /// The json can be passed if it's already downloaded
+    pub fn new(json: Option<Arc<VersionJson>>) -> Self {
+        Self {
+            json,
+            ..Default::default()
+        }
+    }
+
+    pub fn done(&self, part: Part) -> bool {
+        match part {
+            Part::Version => self.json.is_some(),
+            Part::Libraries => self.class_path.is_some(),
+            Part::AssetIndex => self.asset_index.is_some(),
+            Part::Assets => self.assets,
+            Part::Jar => self.jar_path.is_some(),
+            Part::Loader => !self.loader_update,
+        }
+    }
+
+    /// Everything is downloaded
+    pub fn ready(&self) -> bool {
+        [Part::Libraries, Part::Assets, Part::Jar, Part::Loader]
+            .into_iter()
+            .all(|part| self.done(part))
+    }
+
+    /// Whether anything has started, or is still running
+    pub fn started(&self) -> bool {
+        self.json.is_some() || !self.running.is_empty()
+    }
+
+    /// Parts that can start now, the files need the json and the assets need their index.
+    /// The loader doesn't need anything
+    fn startable(&self) -> Vec<Part> {
+        let parts: &[Part] = if self.json.is_none() {
+            &[Part::Version, Part::Loader]
+        } else if self.asset_index.is_none() {
+            &[Part::Libraries, Part::AssetIndex, Part::Jar, Part::Loader]
+        } else {
+            &[Part::Libraries, Part::Assets, Part::Jar, Part::Loader]
+        };
+
+        parts
+            .iter()
+            .copied()
+            .filter(|part| !self.done(*part) && !self.running.contains(part))
+            .collect()
+    }
+
+    /// The parts to start, they're counted as running until [`Prepare::finish`] is called
+    pub fn start(&mut self) -> Vec<Part> {
+        let parts = self.startable();
+        self.running.extend_from_slice(&parts);
+        parts
+    }
+
+    /// Takes the result of a part, a failed part is started again by the next
+    /// [`Prepare::start`]
+    pub fn finish<T, E>(&mut self, part: Part, result: Result<T, E>) -> Result<T, E> {
+        self.running.retain(|running| *running != part);
+        result
+    }
+
+    /// The libraries and the jar, once both are downloaded
+    pub fn launch_class_path(&self) -> Option<String> {
+        let (class_path, jar_path) = self.class_path.as_ref().zip(self.jar_path.as_ref())?;
+        Some(format!("{class_path}{jar_path}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Part, Prepare};
+
+    #[test]
+    fn test_order() {
+        let mut prepare = Prepare::default();
+        assert_eq!(prepare.start(), [Part::Version]);
+        // Running parts aren't started twice
+        assert!(prepare.start().is_empty());
+
+        let failed: Result<(), ()> = prepare.finish(Part::Version, Err(()));
+        assert!(failed.is_err());
+        assert_eq!(prepare.start(), [Part::Version]);
+        assert!(prepare.started());
+    }
+
+    #[test]
+    fn test_ready() {
+        let mut prepare = Prepare {
+            class_path: Some("a.jar:".into()),
+            assets: true,
+            ..Default::default()
+        };
+        assert!(!prepare.ready());
+        assert_eq!(prepare.launch_class_path(), None);
+
+        prepare.jar_path = Some("client.jar".into());
+        assert!(prepare.ready());
+        assert_eq!(
+            prepare.launch_class_path().as_deref(),
+            Some("a.jar:client.jar")
+        );
+    }
+
+    #[test]
+    fn test_loader_update() {
+        let mut prepare = Prepare {
+            loader_update: true,
+            ..Default::default()
+        };
+        assert_eq!(prepare.start(), [Part::Version, Part::Loader]);
+
+        prepare.class_path = Some("a.jar:".into());
+        prepare.jar_path = Some("client.jar".into());
+        prepare.assets = true;
+        assert!(!prepare.ready());
+
+        let _: Result<(), ()> = prepare.finish(Part::Loader, Ok(()));
+        prepare.loader_update = false;
+        assert!(prepare.ready());
+    }
+}
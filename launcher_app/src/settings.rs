@@ -0,0 +1,296 @@
+use crate::instance::{Instance, Jvm, Memory};
+use launcher_core::saves::{self, BackupPolicy};
+use launcher_core::skin_fix::{self, SkinFix};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+/// The game window's size, passed as `--width` and `--height`
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    pub fn args(self) -> Vec<String> {
+        vec![
+            "--width".into(),
+            self.width.to_string(),
+            "--height".into(),
+            self.height.to_string(),
+        ]
+    }
+}
+
+/// Shell commands run around a launch
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct Hooks {
+    /// The launch waits for it, and doesn't happen if it fails
+    pub pre_launch: Option<String>,
+    /// Started once the game exits, nothing waits for it
+    pub post_exit: Option<String>,
+    /// Backs up the worlds that were played once the game exits, see
+    /// [`Hooks::run_backup_worlds`]
+    pub backup_worlds: Option<BackupPolicy>,
+}
+
+impl Hooks {
+    pub fn run_pre_launch(&self) -> std::io::Result<()> {
+        let Some(hook) = &self.pre_launch else {
+            return Ok(());
+        };
+        let status = shell(hook).status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            let message = format!("The pre-launch command exited with {status}");
+            Err(std::io::Error::other(message))
+        }
+    }
+
+    pub fn run_post_exit(&self) {
+        if let Some(hook) = &self.post_exit {
+            if let Err(e) = shell(hook).spawn() {
+                eprintln!("The post-exit command couldn't start: {e}");
+            }
+        }
+    }
+
+    /// Backs up the worlds in the game directory's saves folder that were played since their
+    /// last backup into its backups folder, and prunes the old ones. Returns the new backups,
+    /// none if `backup_worlds` isn't set
+    pub async fn run_backup_worlds(
+        &self,
+        game_dir: &Path,
+    ) -> Result<Vec<PathBuf>, launcher_core::Error> {
+        let Some(policy) = &self.backup_worlds else {
+            return Ok(Vec::new());
+        };
+        let saves_dir = game_dir.join("saves");
+        saves::backup_played_worlds(&saves_dir, &game_dir.join("backups"), policy).await
+    }
+}
+
+fn shell(command: &str) -> Command {
+    #[cfg(windows)]
+    let mut shell = Command::new("cmd");
+    #[cfg(windows)]
+    shell.arg("/C");
+
+    #[cfg(not(windows))]
+    let mut shell = Command::new("sh");
+    #[cfg(not(windows))]
+    shell.arg("-c");
+
+    shell.arg(command);
+    shell
+}
+
+/// What every launch starts from, the frontends fill it from their settings.
+/// Instances override parts of it, see [`LaunchDefaults::resolve`]
+#[derive(Clone, Default, Debug)]
+pub struct LaunchDefaults {
+    /// None uses `java` from the PATH
+    pub jvm: Option<Arc<Jvm>>,
+    pub memory: Memory,
+    pub jvm_args: Vec<String>,
+    pub resolution: Option<Resolution>,
+    pub hooks: Hooks,
+    /// Only used by instances that turn it on
+    pub skin_fix: SkinFix,
+}
+
+/// The defaults with an instance's overrides applied, what the launch actually uses
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LaunchSettings {
+    pub jvm: String,
+    /// The default arguments, the heap sizes, then the instance's arguments
+    pub jvm_args: Vec<String>,
+    pub game_args: Vec<String>,
+    /// From the instance's `KEY=value` arguments, anything else is left out
+    pub env: Vec<(String, String)>,
+    pub hooks: Hooks,
+}
+
+impl LaunchDefaults {
+    /// The one place that decides what wins. An instance's JVM, memory, resolution and
+    /// each of its hooks replace the defaults when set. The default JVM only counts as
+    /// unset for instances, its arguments are added after the default ones and the skin fix
+    pub fn resolve(&self, instance: Option<&Instance>) -> LaunchSettings {
+        let own_jvm = instance
+            .map(|instance| &instance.jvm)
+            .filter(|jvm| ***jvm != Jvm::default());
+        let jvm = match own_jvm.or(self.jvm.as_ref()) {
+            Some(jvm) => jvm.path.clone(),
+            None => Jvm::default().path,
+        };
+
+        let memory = instance
+            .and_then(|instance| instance.memory)
+            .unwrap_or(self.memory);
+        let mut jvm_args = self.jvm_args.clone();
+        jvm_args.extend(memory.args());
+
+        let resolution = instance
+            .and_then(|instance| instance.resolution)
+            .or(self.resolution);
+        let game_args = resolution.map(Resolution::args).unwrap_or_default();
+
+        let mut hooks = self.hooks.clone();
+        let mut env = Vec::new();
+        if let Some(instance) = instance {
+            if instance.legacy_skin_fix && skin_fix::needed(&instance.version) {
+                jvm_args.extend(self.skin_fix.jvm_args());
+            }
+            jvm_args.extend(instance.jvm_args.iter().cloned());
+            env = instance
+                .env_args
+                .iter()
+                .filter_map(|var| var.split_once('='))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            if instance.hooks.pre_launch.is_some() {
+                hooks.pre_launch = instance.hooks.pre_launch.clone();
+            }
+            if instance.hooks.post_exit.is_some() {
+                hooks.post_exit = instance.hooks.post_exit.clone();
+            }
+            if instance.hooks.backup_worlds.is_some() {
+                hooks.backup_worlds = instance.hooks.backup_worlds;
+            }
+        }
+
+        LaunchSettings {
+            jvm,
+            jvm_args,
+            game_args,
+            env,
+            hooks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hooks, LaunchDefaults, Resolution};
+    use crate::instance::{Instance, Jvm, Memory};
+    use launcher_core::archives::CompressionLevel;
+    use launcher_core::saves::BackupPolicy;
+    use launcher_core::skin_fix::SkinFix;
+    use launcher_core::types::{Type, Version};
+    use std::sync::Arc;
+
+    fn instance() -> Instance {
+        let version = Version {
+            id: "1.20.1".into(),
+            version_type: Type::Release,
+            url: String::new(),
+            time: String::new(),
+            release_time: String::new(),
+        };
+        Instance::new("Survival".into(), Arc::default(), Arc::new(version))
+    }
+
+    #[test]
+    fn test_defaults() {
+        let defaults = LaunchDefaults {
+            jvm: Some(Arc::new(Jvm {
+                path: "/usr/lib/jvm/17/bin/java".into(),
+                name: "17".into(),
+            })),
+            jvm_args: vec!["-Dglobal".into()],
+            resolution: Some(Resolution {
+                width: 1280,
+                height: 720,
+            }),
+            hooks: Hooks {
+                pre_launch: Some("sync".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // An instance that sets nothing launches like a version would
+        let settings = defaults.resolve(Some(&instance()));
+        assert_eq!(settings, defaults.resolve(None));
+        assert_eq!(settings.jvm, "/usr/lib/jvm/17/bin/java");
+        assert_eq!(settings.jvm_args, ["-Dglobal"]);
+        assert_eq!(settings.game_args, ["--width", "1280", "--height", "720"]);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let defaults = LaunchDefaults {
+            jvm_args: vec!["-Dglobal".into()],
+            hooks: Hooks {
+                pre_launch: Some("sync".into()),
+                post_exit: Some("notify-send done".into()),
+                backup_worlds: Some(BackupPolicy::default()),
+            },
+            ..Default::default()
+        };
+        let mut instance = instance();
+        instance.jvm = Arc::new(Jvm {
+            path: "/opt/java/bin/java".into(),
+            name: "Custom".into(),
+        });
+        instance.memory = Some(Memory { min: 0, max: 4096 });
+        instance.jvm_args = vec!["-Dinstance".into()];
+        instance.env_args = vec!["MESA_GL_VERSION_OVERRIDE=4.6".into(), "junk".into()];
+        instance.hooks.post_exit = Some("backup".into());
+        let policy = BackupPolicy {
+            compression: CompressionLevel::Best,
+            ..Default::default()
+        };
+        instance.hooks.backup_worlds = Some(policy);
+
+        let settings = defaults.resolve(Some(&instance));
+        assert_eq!(settings.jvm, "/opt/java/bin/java");
+        assert_eq!(settings.jvm_args, ["-Dglobal", "-Xmx4096M", "-Dinstance"]);
+        assert!(settings.game_args.is_empty());
+        assert_eq!(
+            settings.env,
+            [("MESA_GL_VERSION_OVERRIDE".into(), "4.6".into())]
+        );
+        assert_eq!(settings.hooks.pre_launch.as_deref(), Some("sync"));
+        assert_eq!(settings.hooks.post_exit.as_deref(), Some("backup"));
+        assert_eq!(settings.hooks.backup_worlds, Some(policy));
+    }
+
+    #[test]
+    fn test_skin_fix() {
+        let defaults = LaunchDefaults {
+            skin_fix: SkinFix {
+                proxy: "skins.example.com:11707".into(),
+                agent: None,
+            },
+            ..Default::default()
+        };
+        let mut instance = instance();
+        instance.legacy_skin_fix = true;
+        instance.jvm_args = vec!["-Dinstance".into()];
+        // 1.20.1 has working skins
+        instance.version = Arc::new(Version {
+            release_time: "2023-06-12T13:25:51+00:00".into(),
+            ..(*instance.version).clone()
+        });
+        assert_eq!(defaults.resolve(Some(&instance)).jvm_args, ["-Dinstance"]);
+
+        instance.version = Arc::new(Version {
+            id: "1.7.10".into(),
+            release_time: "2014-05-14T17:29:23+00:00".into(),
+            ..(*instance.version).clone()
+        });
+        assert_eq!(
+            defaults.resolve(Some(&instance)).jvm_args,
+            [
+                "-Dhttp.proxyHost=skins.example.com",
+                "-Dhttp.proxyPort=11707",
+                "-Dinstance"
+            ]
+        );
+    }
+}
@@ -2,6 +2,7 @@ use serde::Serialize;
 use std::collections::HashMap;
 
 use crate::account::types::ProfileResult;
+use crate::http;
 use serde_json::json;
 
 use super::types;
@@ -10,17 +11,14 @@ pub async fn device_response(
     client: &reqwest::Client,
     client_id: &str,
 ) -> Result<types::DeviceCodeResponse, crate::Error> {
-    Ok(client
+    let request = client
         .get("https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode")
         .form(&[
             ("client_id", client_id),
             ("response_type", "code"),
             ("scope", "XboxLive.signin offline_access"),
-        ])
-        .send()
-        .await?
-        .json()
-        .await?)
+        ]);
+    Ok(http::send(request).await?.json().await?)
 }
 
 pub async fn authorization_token_response(
@@ -51,17 +49,14 @@ pub async fn token_response(
     client_id: &str,
     grant_type: &str,
 ) -> Result<types::AuthorizationTokenResponse, crate::Error> {
-    Ok(client
+    let request = client
         .post("https://login.microsoftonline.com/consumers/oauth2/v2.0/token")
         .form(&[
             ("grant_type", grant_type),
             ("client_id", client_id),
             ("device_code", device_code),
-        ])
-        .send()
-        .await?
-        .json()
-        .await?)
+        ]);
+    Ok(http::send(request).await?.json().await?)
 }
 
 #[derive(Serialize)]
@@ -84,7 +79,7 @@ pub async fn xbox_response(
     client: &reqwest::Client,
     access_token: &str,
 ) -> Result<types::XboxLiveAuthenticationResponse, crate::Error> {
-    Ok(client
+    let request = client
         .post("https://user.auth.xboxlive.com/user/authenticate")
         .json(&LiveAuthRequest {
             properties: Properties {
@@ -94,11 +89,8 @@ pub async fn xbox_response(
             },
             relying_party: "http://auth.xboxlive.com",
             token_type: "JWT",
-        })
-        .send()
-        .await?
-        .json()
-        .await?)
+        });
+    Ok(http::send(request).await?.json().await?)
 }
 
 #[derive(Serialize)]
@@ -120,7 +112,7 @@ pub async fn xbox_security_token_response(
     client: &reqwest::Client,
     token: &str,
 ) -> Result<types::XboxLiveAuthenticationResponse, crate::Error> {
-    Ok(client
+    let request = client
         .post("https://xsts.auth.xboxlive.com/xsts/authorize")
         // TODO: Replace with struct
         /*
@@ -140,11 +132,8 @@ pub async fn xbox_security_token_response(
             },
             relying_party: "rp://api.minecraftservices.com/",
             token_type: "JWT",
-        })
-        .send()
-        .await?
-        .json()
-        .await?)
+        });
+    Ok(http::send(request).await?.json().await?)
 }
 
 pub async fn minecraft_response(
@@ -152,25 +141,22 @@ pub async fn minecraft_response(
     token: &str,
     client: &reqwest::Client,
 ) -> Result<types::MinecraftAuthenticationResponse, crate::Error> {
-    Ok(client
+    let request = client
         .post("https://api.minecraftservices.com/authentication/login_with_xbox")
         .json(&json!({
             "identityToken": format!("XBL3.0 x={};{}", &display_claims["xui"][0]["uhs"], token)
-        }))
-        .send()
-        .await?
-        .json()
-        .await?)
+        }));
+    Ok(http::send(request).await?.json().await?)
 }
 
 pub async fn minecraft_profile_response(
     access_token: &str,
     client: &reqwest::Client,
 ) -> Result<types::Profile, crate::Error> {
-    client
+    let request = client
         .get("https://api.minecraftservices.com/minecraft/profile")
-        .bearer_auth(access_token)
-        .send()
+        .bearer_auth(access_token);
+    http::send(request)
         .await?
         .json::<ProfileResult>()
         .await?
@@ -181,11 +167,8 @@ pub async fn minecraft_ownership_response(
     access_token: &str,
     client: &reqwest::Client,
 ) -> Result<types::ProductCheck, crate::Error> {
-    Ok(client
+    let request = client
         .get("https://api.minecraftservices.com/entitlements/mcstore")
-        .bearer_auth(access_token)
-        .send()
-        .await?
-        .json()
-        .await?)
+        .bearer_auth(access_token);
+    Ok(http::send(request).await?.json().await?)
 }
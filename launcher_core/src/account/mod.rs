@@ -1,2 +1,6 @@
 pub mod auth;
+#[cfg(feature = "offline")]
+pub mod offline;
+pub mod secrets;
+pub mod skins;
 pub mod types;
@@ -0,0 +1,62 @@
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+
+use super::types::{Account, Profile};
+
+/// An account that skips the Microsoft login, for LAN worlds and servers in offline mode.
+/// The name is only checked with [`is_valid_name`], Mojang never sees it
+pub fn offline_account(name: &str) -> Account {
+    Account {
+        active: true,
+        // Nothing to refresh
+        expiry: u64::MAX,
+        access_token: "0".to_string(),
+        profile: Profile {
+            id: offline_uuid(name),
+            name: name.to_string(),
+            skins: Vec::new(),
+            capes: Vec::new(),
+            profile_actions: HashMap::new(),
+        },
+        offline: true,
+    }
+}
+
+/// Whether the game accepts the name, 3 to 16 letters, digits or underscores
+pub fn is_valid_name(name: &str) -> bool {
+    (3..=16).contains(&name.len()) && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// A name based UUID without dashes, like profile ids. The same name always gets the same
+/// id, so worlds keep the player's inventory between runs
+fn offline_uuid(name: &str) -> String {
+    let mut bytes: [u8; 20] = Sha1::digest(format!("OfflinePlayer:{name}")).into();
+
+    // Version 5 and the RFC 4122 variant, the rest of the hash is kept
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    bytes[..16].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid_name, offline_uuid};
+
+    #[test]
+    fn test_offline_uuid() {
+        let uuid = offline_uuid("Steve");
+        assert_eq!(uuid.len(), 32);
+        assert_eq!(&uuid[12..13], "5");
+        assert_eq!(uuid, offline_uuid("Steve"));
+        assert_ne!(uuid, offline_uuid("Alex"));
+    }
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("Steve_01"));
+        assert!(!is_valid_name("ab"));
+        assert!(!is_valid_name("seventeen_chars_x"));
+        assert!(!is_valid_name("with space"));
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt::{Display, Formatter};
+
+/// Somewhere to keep refresh tokens that isn't a plaintext config file.
+/// Secrets are keyed by the Minecraft profile id of the account they belong to
+pub trait SecretStore {
+    /// Returns `None` if nothing is stored for the account
+    fn get(&self, account: &str) -> Result<Option<String>, SecretError>;
+    fn set(&self, account: &str, secret: &str) -> Result<(), SecretError>;
+    /// Deleting a secret that doesn't exist is not an error
+    fn delete(&self, account: &str) -> Result<(), SecretError>;
+}
+
+#[derive(Debug)]
+pub struct SecretError(Box<dyn std::error::Error + Send + Sync>);
+
+impl Display for SecretError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret store error: {}", self.0)
+    }
+}
+
+impl std::error::Error for SecretError {}
+
+/// Stores secrets in the OS keyring, the Secret Service on Linux,
+/// the Credential Manager on Windows and the Keychain on macOS
+#[cfg(feature = "keyring")]
+pub struct KeyringStore {
+    service: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringStore {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    fn entry(&self, account: &str) -> Result<keyring::Entry, SecretError> {
+        Ok(keyring::Entry::new(&self.service, account)?)
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl SecretStore for KeyringStore {
+    fn get(&self, account: &str) -> Result<Option<String>, SecretError> {
+        match self.entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), SecretError> {
+        Ok(self.entry(account)?.set_password(secret)?)
+    }
+
+    fn delete(&self, account: &str) -> Result<(), SecretError> {
+        match self.entry(account)?.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl From<keyring::Error> for SecretError {
+    fn from(value: keyring::Error) -> Self {
+        SecretError(Box::new(value))
+    }
+}
@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::account::types::{Profile, ProfileResult};
+use crate::http;
+
+const SKINS_URL: &str = "https://api.minecraftservices.com/minecraft/profile/skins";
+const CAPES_URL: &str = "https://api.minecraftservices.com/minecraft/profile/capes/active";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinVariant {
+    /// Steve's 4 pixel wide arms
+    Classic,
+    /// Alex's 3 pixel wide arms
+    Slim,
+}
+
+impl SkinVariant {
+    /// Profiles report the variant in upper case, requests take either
+    pub fn parse(variant: &str) -> Self {
+        if variant.eq_ignore_ascii_case("slim") {
+            SkinVariant::Slim
+        } else {
+            SkinVariant::Classic
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SkinVariant::Classic => "classic",
+            SkinVariant::Slim => "slim",
+        }
+    }
+}
+
+/// Downloads a skin or cape texture, the urls come from [`Profile::skins`] and [`Profile::capes`]
+pub async fn texture(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, crate::Error> {
+    let request = client.get(url);
+    let bytes = http::send(request)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(bytes.to_vec())
+}
+
+/// The active skin's texture for drawing the player's head, kept in `cache_dir` as
+/// `<profile id>-<texture key>.png` so it's only downloaded again after the skin changes.
+/// None while the profile uses a default skin
+pub async fn avatar_texture(
+    client: &reqwest::Client,
+    profile: &Profile,
+    cache_dir: &Path,
+) -> Result<Option<Vec<u8>>, crate::Error> {
+    let Some(skin) = profile.active_skin() else {
+        return Ok(None);
+    };
+    let file = cache_dir.join(avatar_file_name(&profile.id, &skin.texture_key));
+    if let Ok(png) = tokio::fs::read(&file).await {
+        return Ok(Some(png));
+    }
+
+    let png = texture(client, &skin.url).await?;
+    tokio::fs::create_dir_all(cache_dir).await?;
+    // Skins the profile wore before
+    let prefix = format!("{}-", profile.id);
+    let mut entries = tokio::fs::read_dir(cache_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+    }
+    tokio::fs::write(&file, &png).await?;
+
+    Ok(Some(png))
+}
+
+fn avatar_file_name(profile_id: &str, texture_key: &str) -> String {
+    format!("{profile_id}-{texture_key}.png")
+}
+
+/// Uploads a 64x64 or 64x32 png as the new skin
+pub async fn upload_skin(
+    client: &reqwest::Client,
+    access_token: &str,
+    png: Vec<u8>,
+    variant: SkinVariant,
+) -> Result<Profile, crate::Error> {
+    let file = reqwest::multipart::Part::bytes(png)
+        .file_name("skin.png")
+        .mime_str("image/png")?;
+    let form = reqwest::multipart::Form::new()
+        .text("variant", variant.as_str())
+        .part("file", file);
+
+    let request = client
+        .post(SKINS_URL)
+        .bearer_auth(access_token)
+        .multipart(form);
+    http::send(request)
+        .await?
+        .json::<ProfileResult>()
+        .await?
+        .into()
+}
+
+/// Sets the skin from a url, re-using the active skin's url is how the variant is switched
+pub async fn change_skin(
+    client: &reqwest::Client,
+    access_token: &str,
+    url: &str,
+    variant: SkinVariant,
+) -> Result<Profile, crate::Error> {
+    let request = client
+        .post(SKINS_URL)
+        .bearer_auth(access_token)
+        .json(&json!({
+            "variant": variant.as_str(),
+            "url": url,
+        }));
+    http::send(request)
+        .await?
+        .json::<ProfileResult>()
+        .await?
+        .into()
+}
+
+/// Goes back to one of the default skins
+pub async fn reset_skin(
+    client: &reqwest::Client,
+    access_token: &str,
+) -> Result<Profile, crate::Error> {
+    let request = client
+        .delete(format!("{SKINS_URL}/active"))
+        .bearer_auth(access_token);
+    http::send(request)
+        .await?
+        .json::<ProfileResult>()
+        .await?
+        .into()
+}
+
+/// Shows one of the owned capes, or hides the cape when `cape_id` is None
+pub async fn set_cape(
+    client: &reqwest::Client,
+    access_token: &str,
+    cape_id: Option<&str>,
+) -> Result<Profile, crate::Error> {
+    let request = match cape_id {
+        Some(cape_id) => client.put(CAPES_URL).json(&json!({ "capeId": cape_id })),
+        None => client.delete(CAPES_URL),
+    };
+
+    let request = request.bearer_auth(access_token);
+    http::send(request)
+        .await?
+        .json::<ProfileResult>()
+        .await?
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::avatar_file_name;
+
+    #[test]
+    fn test_avatar_file_name() {
+        assert_eq!(
+            avatar_file_name("069a79f444e94726a5befca90e38aaf5", "1a4af718455d4aab"),
+            "069a79f444e94726a5befca90e38aaf5-1a4af718455d4aab.png"
+        );
+    }
+}
@@ -74,6 +74,17 @@ pub struct Profile {
     pub profile_actions: HashMap<String, String>,
 }
 
+impl Profile {
+    /// The skin currently worn, None while the profile uses a default skin
+    pub fn active_skin(&self) -> Option<&Skin> {
+        self.skins.iter().find(|skin| skin.state == "ACTIVE")
+    }
+
+    pub fn active_cape(&self) -> Option<&Cape> {
+        self.capes.iter().find(|cape| cape.state == "ACTIVE")
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ProfileError {
@@ -134,4 +145,7 @@ pub struct Account {
     pub expiry: u64,
     pub access_token: String,
     pub profile: Profile,
+    // Offline accounts have no Microsoft login behind them, see `offline::offline_account`
+    #[serde(default)]
+    pub offline: bool,
 }
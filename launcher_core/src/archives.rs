@@ -0,0 +1,232 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, DeflateOption, ZipEntryBuilder};
+use serde::{Deserialize, Serialize};
+use tokio::fs::File;
+
+use crate::error::{zip_error, ZipReader};
+use crate::modpack::extract_folder;
+use crate::multimc::list_files;
+use crate::Error;
+
+/// The folder in the archive the instance's files are under
+const ROOT: &str = "instance";
+
+/// How much the files in a zip are compressed, smaller zips take longer to make
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionLevel {
+    /// Not compressed at all
+    Stored,
+    Fast,
+    #[default]
+    Normal,
+    Best,
+}
+
+impl CompressionLevel {
+    fn entry(self, name: String) -> ZipEntryBuilder {
+        let option = match self {
+            CompressionLevel::Stored => {
+                return ZipEntryBuilder::new(name.into(), Compression::Stored)
+            }
+            CompressionLevel::Fast => DeflateOption::Fast,
+            CompressionLevel::Normal => DeflateOption::Normal,
+            CompressionLevel::Best => DeflateOption::Maximum,
+        };
+        ZipEntryBuilder::new(name.into(), Compression::Deflate).deflate_option(option)
+    }
+}
+
+/// A zip of an instance's folder, made by [`archive_instance`]
+#[derive(Debug, Clone)]
+pub struct Archive {
+    pub path: PathBuf,
+    pub created: SystemTime,
+    /// In bytes
+    pub size: u64,
+}
+
+/// Archives in the folder, newest first. Zips that weren't made by [`archive_instance`] are skipped
+pub fn list_archives(archives_dir: &Path) -> Result<Vec<Archive>, Error> {
+    let entries = match std::fs::read_dir(archives_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut archives = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("zip") {
+            continue;
+        }
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let Ok(secs) = stem.parse() else {
+            continue;
+        };
+        archives.push(Archive {
+            created: UNIX_EPOCH + Duration::from_secs(secs),
+            size: entry.metadata()?.len(),
+            path,
+        });
+    }
+
+    archives.sort_by_key(|archive| std::cmp::Reverse(archive.created));
+    Ok(archives)
+}
+
+/// Zips the instance's folder into `archives_dir`, named after the time. Top level files and
+/// folders named in `skip` are left out, e.g. the versions of an instance in the launcher's folder
+pub async fn archive_instance(
+    directory: &Path,
+    archives_dir: &Path,
+    skip: &[&str],
+) -> Result<PathBuf, Error> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    tokio::fs::create_dir_all(archives_dir).await?;
+    let path = archives_dir.join(format!("{secs}.zip"));
+
+    let mut writer = ZipFileWriter::with_tokio(File::create(&path).await?);
+    let mut entries = tokio::fs::read_dir(directory).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if skip.contains(&name.as_str()) || entry.path() == archives_dir {
+            continue;
+        }
+
+        let prefix = format!("{ROOT}/{name}");
+        let level = CompressionLevel::default();
+        if entry.file_type().await?.is_dir() {
+            add_folder(&mut writer, &entry.path(), &prefix, level).await?;
+        } else {
+            add_file(&mut writer, &entry.path(), prefix, level).await?;
+        }
+    }
+    writer.close().await.map_err(zip_error)?;
+
+    Ok(path)
+}
+
+/// Puts the archived files back into the instance's folder. Everything at the top of the
+/// archive replaces what is there now, files that weren't archived are kept.
+/// The archive is extracted next to them first, so a failed restore changes nothing
+pub async fn restore_archive(archive: &Path, directory: &Path) -> Result<(), Error> {
+    let reader = ZipReader::new(archive).await.map_err(zip_error)?;
+
+    let staging = directory.join(".restoring");
+    if tokio::fs::try_exists(&staging).await? {
+        tokio::fs::remove_dir_all(&staging).await?;
+    }
+    extract_folder(&reader, ROOT, &staging).await?;
+
+    let mut entries = tokio::fs::read_dir(&staging).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let target = directory.join(entry.file_name());
+        match tokio::fs::metadata(&target).await {
+            Ok(metadata) if metadata.is_dir() => tokio::fs::remove_dir_all(&target).await?,
+            Ok(_) => tokio::fs::remove_file(&target).await?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        tokio::fs::rename(entry.path(), &target).await?;
+    }
+    tokio::fs::remove_dir(&staging).await?;
+
+    Ok(())
+}
+
+/// Removes all but the newest `keep` archives, 0 keeps every archive
+pub fn prune_archives(archives_dir: &Path, keep: usize) -> Result<(), Error> {
+    if keep == 0 {
+        return Ok(());
+    }
+
+    for archive in list_archives(archives_dir)?.iter().skip(keep) {
+        std::fs::remove_file(&archive.path)?;
+    }
+    Ok(())
+}
+
+/// Adds every file in the folder to the zip, under `prefix`
+pub(crate) async fn add_folder(
+    writer: &mut ZipFileWriter<File>,
+    folder: &Path,
+    prefix: &str,
+    level: CompressionLevel,
+) -> Result<(), Error> {
+    for (relative, _) in list_files(folder).await? {
+        // The running game keeps world locks open, and they mean nothing in a backup
+        if relative
+            .file_name()
+            .is_some_and(|name| name == "session.lock")
+        {
+            continue;
+        }
+
+        // Zip paths always use forward slashes
+        let parts: Vec<_> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        let name = format!("{prefix}/{}", parts.join("/"));
+        add_file(writer, &folder.join(&relative), name, level).await?;
+    }
+
+    Ok(())
+}
+
+async fn add_file(
+    writer: &mut ZipFileWriter<File>,
+    path: &Path,
+    name: String,
+    level: CompressionLevel,
+) -> Result<(), Error> {
+    let data = tokio::fs::read(path).await?;
+    writer
+        .write_entry_whole(level.entry(name), &data)
+        .await
+        .map_err(zip_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{archive_instance, list_archives, restore_archive};
+
+    #[tokio::test]
+    async fn test_archive_round_trip() {
+        let root = std::env::temp_dir().join("synth_archive_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let instance = root.join("instance");
+        let archives = root.join("archives");
+        std::fs::create_dir_all(instance.join("mods")).unwrap();
+        std::fs::create_dir_all(instance.join("versions")).unwrap();
+        std::fs::write(instance.join("options.txt"), "fov:70").unwrap();
+        std::fs::write(instance.join("mods/sodium.jar"), "sodium").unwrap();
+
+        let archive = archive_instance(&instance, &archives, &["versions"])
+            .await
+            .unwrap();
+        assert_eq!(list_archives(&archives).unwrap().len(), 1);
+
+        std::fs::write(instance.join("options.txt"), "fov:110").unwrap();
+        std::fs::write(instance.join("mods/lithium.jar"), "lithium").unwrap();
+        restore_archive(&archive, &instance).await.unwrap();
+
+        let options = std::fs::read_to_string(instance.join("options.txt")).unwrap();
+        assert_eq!(options, "fov:70");
+        // The mods folder is replaced as a whole, the skipped folder is left alone
+        assert!(!instance.join("mods/lithium.jar").exists());
+        assert!(instance.join("mods/sodium.jar").exists());
+        assert!(instance.join("versions").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
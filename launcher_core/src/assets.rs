@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::invalid_data;
+use crate::types::AssetIndexJson;
+use crate::Error;
+
+/// What [`collect_garbage`] found, and removed unless it was a dry run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GarbageReport {
+    /// Asset indexes whose objects are kept
+    pub indexes: usize,
+    /// Objects none of them refer to
+    pub objects: usize,
+    /// The size of those objects
+    pub bytes: u64,
+}
+
+/// Removes objects that no index in `assets_dir/indexes` refers to, versions that are launched
+/// again download theirs back. A dry run only counts them.
+/// Without any index nothing is removed, an empty indexes folder is more likely a mistake
+pub fn collect_garbage(assets_dir: &Path, dry_run: bool) -> Result<GarbageReport, Error> {
+    let (indexes, live) = live_hashes(&assets_dir.join("indexes"))?;
+    let mut report = GarbageReport {
+        indexes,
+        ..Default::default()
+    };
+    let dry_run = dry_run || indexes == 0;
+
+    let prefixes = match std::fs::read_dir(assets_dir.join("objects")) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(report),
+        Err(e) => return Err(e.into()),
+    };
+    for prefix in prefixes {
+        let prefix = prefix?;
+        if !prefix.file_type()?.is_dir() {
+            continue;
+        }
+
+        for entry in std::fs::read_dir(prefix.path())? {
+            let entry = entry?;
+            let name = entry.file_name();
+            // Anything that isn't named after a hash wasn't put there by the launcher
+            let Some(hash) = name.to_str().filter(|name| is_hash(name)) else {
+                continue;
+            };
+            if live.contains(hash) {
+                continue;
+            }
+
+            report.objects += 1;
+            report.bytes += entry.metadata()?.len();
+            if !dry_run {
+                std::fs::remove_file(entry.path())?;
+            }
+        }
+
+        if !dry_run {
+            // Fails while something is left in it, which is fine
+            let _ = std::fs::remove_dir(prefix.path());
+        }
+    }
+
+    Ok(report)
+}
+
+/// How many indexes there are, and every hash they refer to. An index that can't be read
+/// stops the collection, its objects would be removed otherwise
+fn live_hashes(indexes_dir: &Path) -> Result<(usize, HashSet<String>), Error> {
+    let entries = match std::fs::read_dir(indexes_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, HashSet::new())),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut indexes = 0;
+    let mut live = HashSet::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        let buffer = std::fs::read(&path)?;
+        let index: AssetIndexJson = serde_json::from_slice(&buffer)
+            .map_err(|e| invalid_data(format!("{}: {e}", path.display())))?;
+        live.extend(index.objects.into_values().map(|object| object.hash));
+        indexes += 1;
+    }
+
+    Ok((indexes, live))
+}
+
+fn is_hash(name: &str) -> bool {
+    name.len() == 40 && name.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_garbage, GarbageReport};
+
+    const KEPT: &str = "bdf48ef6b5d0d23bbb02e17d04865216179f510a";
+    const UNUSED: &str = "5ff04807c356f1beed0b86ccf659b44b9983e3fa";
+
+    #[test]
+    fn test_collect_garbage() {
+        let root = std::env::temp_dir().join("synth_assets_test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("indexes")).unwrap();
+        std::fs::create_dir_all(root.join("objects/bd")).unwrap();
+        std::fs::create_dir_all(root.join("objects/5f")).unwrap();
+
+        let index = format!(
+            r#"{{"objects": {{"icons/icon_16x16.png": {{"hash": "{KEPT}", "size": 4}}}}}}"#
+        );
+        std::fs::write(root.join("indexes/17.json"), index).unwrap();
+        std::fs::write(root.join("objects/bd").join(KEPT), "kept").unwrap();
+        std::fs::write(root.join("objects/5f").join(UNUSED), "unused").unwrap();
+        std::fs::write(root.join("objects/5f/notes.txt"), "mine").unwrap();
+
+        let expected = GarbageReport {
+            indexes: 1,
+            objects: 1,
+            bytes: 6,
+        };
+        assert_eq!(collect_garbage(&root, true).unwrap(), expected);
+        assert!(root.join("objects/5f").join(UNUSED).exists());
+
+        assert_eq!(collect_garbage(&root, false).unwrap(), expected);
+        assert!(!root.join("objects/5f").join(UNUSED).exists());
+        assert!(root.join("objects/5f/notes.txt").exists());
+        assert!(root.join("objects/bd").join(KEPT).exists());
+
+        // Without an index nothing goes
+        std::fs::remove_file(root.join("indexes/17.json")).unwrap();
+        collect_garbage(&root, false).unwrap();
+        assert!(root.join("objects/bd").join(KEPT).exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
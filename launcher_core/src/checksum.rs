@@ -0,0 +1,154 @@
+use sha1::Digest;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// What a download is checked against. The hashes are hex, like every download site has them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checksum {
+    Sha1(String),
+    Sha256(String),
+    /// There's no hash, only the size can be checked
+    SizeOnly(u64),
+}
+
+impl Checksum {
+    /// The strongest supported hash out of a map like Modrinth's `hashes`, falling back to the
+    /// size. sha512 isn't one of them, Modrinth always lists sha1 next to it
+    pub fn from_hashes(hashes: &HashMap<String, String>, size: Option<u64>) -> Option<Self> {
+        if let Some(hash) = hashes.get("sha256") {
+            Some(Checksum::Sha256(hash.clone()))
+        } else if let Some(hash) = hashes.get("sha1") {
+            Some(Checksum::Sha1(hash.clone()))
+        } else {
+            size.map(Checksum::SizeOnly)
+        }
+    }
+
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        let mut hasher = self.hasher();
+        hasher.update(bytes);
+        hasher.matches()
+    }
+
+    /// For files that are read in chunks
+    pub fn hasher(&self) -> Hasher<'_> {
+        let state = match self {
+            Checksum::Sha1(_) => State::Sha1(sha1::Sha1::new()),
+            Checksum::Sha256(_) => State::Sha256(sha2::Sha256::new()),
+            Checksum::SizeOnly(_) => State::SizeOnly,
+        };
+        Hasher {
+            checksum: self,
+            state,
+            len: 0,
+        }
+    }
+}
+
+impl Display for Checksum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Checksum::Sha1(_) => f.write_str("sha1"),
+            Checksum::Sha256(_) => f.write_str("sha256"),
+            Checksum::SizeOnly(_) => f.write_str("size"),
+        }
+    }
+}
+
+pub struct Hasher<'a> {
+    checksum: &'a Checksum,
+    state: State,
+    len: u64,
+}
+
+enum State {
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+    SizeOnly,
+}
+
+impl Hasher<'_> {
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+        match &mut self.state {
+            State::Sha1(hasher) => hasher.update(bytes),
+            State::Sha256(hasher) => hasher.update(bytes),
+            State::SizeOnly => {}
+        }
+    }
+
+    /// Whether everything passed to [`Hasher::update`] matches the checksum
+    pub fn matches(self) -> bool {
+        let hash = match self.state {
+            State::Sha1(hasher) => format!("{:x}", hasher.finalize()),
+            State::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            State::SizeOnly => {
+                return matches!(self.checksum, Checksum::SizeOnly(size) if *size == self.len)
+            }
+        };
+        match self.checksum {
+            Checksum::Sha1(expected) | Checksum::Sha256(expected) => {
+                expected.eq_ignore_ascii_case(&hash)
+            }
+            Checksum::SizeOnly(_) => false,
+        }
+    }
+}
+
+/// The sha1 of the bytes as lowercase hex
+pub fn sha1(bytes: &[u8]) -> String {
+    format!("{:x}", sha1::Sha1::digest(bytes))
+}
+
+/// The sha256 of the bytes as lowercase hex
+pub fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", sha2::Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sha1, sha256, Checksum};
+    use std::collections::HashMap;
+
+    const SHA1: &str = "a9993e364706816aba3e25717850c26c9cd0d89d";
+    const SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn test_hashes() {
+        assert_eq!(sha1(b"abc"), SHA1);
+        assert_eq!(sha256(b"abc"), SHA256);
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(Checksum::Sha1(SHA1.into()).matches(b"abc"));
+        assert!(Checksum::Sha1(SHA1.to_uppercase()).matches(b"abc"));
+        assert!(!Checksum::Sha1(SHA1.into()).matches(b"abd"));
+        assert!(Checksum::Sha256(SHA256.into()).matches(b"abc"));
+        assert!(Checksum::SizeOnly(3).matches(b"abc"));
+        assert!(!Checksum::SizeOnly(4).matches(b"abc"));
+
+        // Chunks hash the same as the whole
+        let checksum = Checksum::Sha256(SHA256.into());
+        let mut hasher = checksum.hasher();
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        assert!(hasher.matches());
+    }
+
+    #[test]
+    fn test_from_hashes() {
+        let mut hashes = HashMap::new();
+        assert_eq!(Checksum::from_hashes(&hashes, None), None);
+        assert_eq!(
+            Checksum::from_hashes(&hashes, Some(3)),
+            Some(Checksum::SizeOnly(3))
+        );
+        hashes.insert("sha512".to_string(), "ddaf35a1".to_string());
+        hashes.insert("sha1".to_string(), SHA1.to_string());
+        assert_eq!(
+            Checksum::from_hashes(&hashes, Some(3)),
+            Some(Checksum::Sha1(SHA1.into()))
+        );
+    }
+}
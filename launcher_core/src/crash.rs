@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The parts of a Minecraft crash report worth showing before the full file
+#[derive(Debug, Clone, Default)]
+pub struct CrashReport {
+    pub path: PathBuf,
+    /// What the game was doing, e.g. `Rendering overlay`
+    pub description: Option<String>,
+    /// The exception line, e.g. `java.lang.NullPointerException: ...`
+    pub exception: Option<String>,
+    /// The first frames of the exception's stack trace
+    pub stack: Vec<String>,
+    /// Only Forge and NeoForge name suspects, Fabric reports leave this empty
+    pub suspected_mods: Vec<String>,
+}
+
+/// How many stack frames are kept, the rest is in the full report
+const STACK_FRAMES: usize = 8;
+
+impl CrashReport {
+    pub fn parse(path: PathBuf, text: &str) -> Self {
+        let mut report = CrashReport {
+            path,
+            ..Default::default()
+        };
+
+        let mut lines = text.lines().peekable();
+        while let Some(line) = lines.next() {
+            if let Some(description) = line.strip_prefix("Description: ") {
+                report.description = Some(description.trim().to_string());
+
+                // The exception follows the description after a blank line
+                let mut block = lines
+                    .by_ref()
+                    .skip_while(|line| line.trim().is_empty())
+                    .take_while(|line| !line.trim().is_empty());
+                report.exception = block.next().map(|line| line.trim().to_string());
+                report.stack = block
+                    .filter(|line| line.trim_start().starts_with("at "))
+                    .take(STACK_FRAMES)
+                    .map(|line| line.trim().to_string())
+                    .collect();
+            } else if let Some(rest) = line
+                .strip_prefix("Suspected Mods:")
+                .or_else(|| line.strip_prefix("Suspected Mod:"))
+            {
+                let rest = rest.trim();
+                if !rest.is_empty() && rest != "NONE" {
+                    report.suspected_mods.push(rest.to_string());
+                }
+
+                // Each mod is indented once, its details twice
+                while let Some(next) = lines.peek() {
+                    let Some(name) = next.strip_prefix('\t') else {
+                        break;
+                    };
+                    if !name.starts_with('\t') && !name.trim().is_empty() {
+                        report.suspected_mods.push(name.trim().to_string());
+                    }
+                    lines.next();
+                }
+            }
+        }
+
+        report
+    }
+}
+
+/// Finds the newest report in `crash-reports` written after `since`, which is when the game launched
+pub fn find_crash_report(game_dir: &Path, since: SystemTime) -> std::io::Result<Option<PathBuf>> {
+    let dir = game_dir.join("crash-reports");
+    if !dir.exists() {
+        return Ok(None);
+    }
+
+    let mut newest: Option<(SystemTime, PathBuf)> = None;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let modified = entry.metadata()?.modified()?;
+        let is_report = entry.file_name().to_string_lossy().ends_with(".txt");
+
+        if is_report && modified >= since && newest.as_ref().is_none_or(|(n, _)| modified > *n) {
+            newest = Some((modified, entry.path()));
+        }
+    }
+
+    Ok(newest.map(|(_, path)| path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CrashReport;
+
+    #[test]
+    fn test_parse_forge_report() {
+        let text = "---- Minecraft Crash Report ----\n\
+            // Who set us up the TNT?\n\
+            \n\
+            Time: 2024-01-01 12:00:00\n\
+            Description: Rendering overlay\n\
+            \n\
+            java.lang.NullPointerException: Cannot invoke \"Object.toString()\"\n\
+            \tat com.example.Mod.render(Mod.java:10)\n\
+            \tat net.minecraft.client.Minecraft.run(Minecraft.java:20)\n\
+            \n\
+            A detailed walkthrough of the error\n\
+            Suspected Mods: \n\
+            \tExample Mod (examplemod), Version: 1.0\n\
+            \t\tIssue tracker URL: https://example.com\n\
+            Stacktrace:\n";
+
+        let report = CrashReport::parse("crash.txt".into(), text);
+        assert_eq!(report.description.as_deref(), Some("Rendering overlay"));
+        assert_eq!(
+            report.exception.as_deref(),
+            Some("java.lang.NullPointerException: Cannot invoke \"Object.toString()\"")
+        );
+        assert_eq!(report.stack.len(), 2);
+        assert_eq!(
+            report.suspected_mods,
+            ["Example Mod (examplemod), Version: 1.0"]
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! Helpers for the io errors several modules report through [`Error::Tokio`]
+
+use crate::Error;
+
+pub(crate) type ZipReader = async_zip::tokio::read::fs::ZipFileReader;
+
+pub(crate) fn zip_error(e: async_zip::error::ZipError) -> Error {
+    invalid_data(e.to_string())
+}
+
+pub(crate) fn invalid_data(message: impl Into<String>) -> Error {
+    Error::Tokio(tokio::io::Error::new(
+        tokio::io::ErrorKind::InvalidData,
+        message.into(),
+    ))
+}
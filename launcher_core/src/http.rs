@@ -0,0 +1,227 @@
+use crate::Error;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Sent with every request, some mirrors and APIs reject clients without one
+pub const USER_AGENT: &str = concat!("AlsoSylv/synth_launcher/", env!("CARGO_PKG_VERSION"));
+
+/// How the client every frontend downloads with is set up, see [`ClientOptions::build`]
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    pub user_agent: String,
+    /// Idle connections kept open to each host, the downloads mostly go to the same few
+    pub max_idle_per_host: usize,
+    /// HTTP/2 is used when the server offers it, otherwise only HTTP/1.1 is
+    pub http2: bool,
+    pub connect_timeout: Option<Duration>,
+    /// For a whole request, None by default since a jar on a slow connection takes a while
+    pub timeout: Option<Duration>,
+    pub proxy: Option<Proxy>,
+    /// PEM or DER files trusted on top of the system's roots, for proxies that intercept TLS
+    pub certificates: Vec<PathBuf>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            user_agent: USER_AGENT.to_string(),
+            max_idle_per_host: crate::DEFAULT_CONCURRENCY,
+            http2: true,
+            connect_timeout: Some(Duration::from_secs(10)),
+            timeout: None,
+            proxy: None,
+            certificates: vec![],
+        }
+    }
+}
+
+impl ClientOptions {
+    /// Fails if the TLS backend can't be set up, the proxy url can't be parsed or a certificate
+    /// can't be read
+    pub fn build(&self) -> Result<reqwest::Client, Error> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&self.user_agent)
+            .pool_max_idle_per_host(self.max_idle_per_host);
+        if !self.http2 {
+            builder = builder.http1_only();
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy.as_ref().filter(|proxy| proxy.is_enabled()) {
+            builder = builder.proxy(proxy.build()?);
+        }
+        for path in &self.certificates {
+            builder = builder.add_root_certificate(read_certificate(path)?);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+fn read_certificate(path: &Path) -> Result<reqwest::Certificate, Error> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+    let certificate = if bytes.starts_with(b"-----BEGIN") {
+        reqwest::Certificate::from_pem(&bytes)
+    } else {
+        reqwest::Certificate::from_der(&bytes)
+    };
+    Ok(certificate?)
+}
+
+/// How often a rate limited request is tried again before giving up
+const MAX_RETRIES: u32 = 3;
+/// Waits longer than this are reported instead of waited out
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// A request the server turned down with 429 Too Many Requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    /// From the Retry-After header, if the server sent one
+    pub retry_after: Option<Duration>,
+}
+
+impl Display for RateLimited {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.retry_after {
+            Some(after) => write!(
+                f,
+                "Too many requests, try again in {} seconds",
+                after.as_secs().max(1)
+            ),
+            None => f.write_str("Too many requests, try again later"),
+        }
+    }
+}
+
+/// Sends the request, every request to Mojang, Xbox and the meta servers goes through here.
+/// A 429 is waited out and the request sent again if it's idempotent, otherwise, or once it
+/// was tried a few times, it fails with [`Error::RateLimited`]. Other statuses are left to
+/// the caller
+pub async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+    let (client, request) = request.build_split();
+    let mut request = request?;
+    let mut attempt = 0;
+
+    loop {
+        // Bodies that are streamed can't be sent twice
+        let retry = (attempt < MAX_RETRIES && request.method().is_idempotent())
+            .then(|| request.try_clone())
+            .flatten();
+
+        let response = client.execute(request).await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+
+        let retry_after = retry_after(response.headers());
+        let wait = retry_after.unwrap_or(Duration::from_secs(1 << attempt));
+        match retry {
+            Some(next) if wait <= MAX_RETRY_AFTER => {
+                tokio::time::sleep(wait).await;
+                request = next;
+                attempt += 1;
+            }
+            _ => return Err(Error::RateLimited(RateLimited { retry_after })),
+        }
+    }
+}
+
+/// Only the delay in seconds is understood, an HTTP date is treated like no header.
+/// Mojang and Xbox send seconds
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// A proxy every request goes through, except the ones to hosts in `bypass`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Proxy {
+    /// `http://`, `https://` or `socks5://` with the host and port, empty for no proxy
+    pub url: String,
+    /// Empty if the proxy doesn't need credentials
+    pub username: String,
+    /// Not written with the settings, the frontends keep it in the secret store
+    #[serde(skip)]
+    pub password: String,
+    /// Comma separated hosts, domains and IP ranges that are reached directly
+    pub bypass: String,
+}
+
+impl Proxy {
+    pub fn is_enabled(&self) -> bool {
+        !self.url.trim().is_empty()
+    }
+
+    fn build(&self) -> reqwest::Result<reqwest::Proxy> {
+        let mut proxy = reqwest::Proxy::all(self.url.trim())?;
+        if !self.username.is_empty() {
+            proxy = proxy.basic_auth(&self.username, &self.password);
+        }
+        Ok(proxy.no_proxy(reqwest::NoProxy::from_string(&self.bypass)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{retry_after, ClientOptions, Proxy, RateLimited};
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use std::time::Duration;
+
+    #[test]
+    fn test_proxy() {
+        let options = |url: &str| ClientOptions {
+            proxy: Some(Proxy {
+                url: url.into(),
+                username: "steve".into(),
+                password: "hunter2".into(),
+                bypass: "localhost,.example.com".into(),
+            }),
+            ..Default::default()
+        };
+        assert!(options("socks5://127.0.0.1:1080").build().is_ok());
+        assert!(options("http://proxy.example.com:3128").build().is_ok());
+        // An empty url is no proxy at all
+        assert!(options(" ").build().is_ok());
+        assert!(options("not a url").build().is_err());
+    }
+
+    #[test]
+    fn test_missing_certificate() {
+        let options = ClientOptions {
+            certificates: vec!["/nonexistent/corporate.pem".into()],
+            ..Default::default()
+        };
+        let error = options.build().unwrap_err().to_string();
+        assert!(error.contains("corporate.pem"));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static(" 30"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(30)));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), None);
+
+        let limited = RateLimited {
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        assert_eq!(
+            limited.to_string(),
+            "Too many requests, try again in 30 seconds"
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+use futures::{stream, TryStreamExt};
+use serde::Deserialize;
+
+use crate::checksum;
+use crate::error::invalid_data;
+use crate::http;
+use crate::plan::Planned;
+use crate::progress::DownloadStats;
+use crate::Error;
+
+/// Every runtime Mojang hosts, the same list the official launcher uses
+const RUNTIMES_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+#[derive(Deserialize)]
+struct RuntimeEntry {
+    manifest: Download,
+    version: RuntimeVersion,
+}
+
+#[derive(Deserialize)]
+struct RuntimeVersion {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Download {
+    sha1: String,
+    size: u64,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct RuntimeManifest {
+    files: HashMap<String, RuntimeFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum RuntimeFile {
+    Directory,
+    File {
+        #[serde(default)]
+        executable: bool,
+        downloads: FileDownloads,
+    },
+    Link {
+        target: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct FileDownloads {
+    raw: Download,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstalledRuntime {
+    /// The java binary, can be passed to [`crate::launch_game`]
+    pub java: PathBuf,
+    /// The full Java version, e.g. `17.0.8`
+    pub version: String,
+}
+
+/// Mojang's name for this platform, None if there are no runtimes for it
+pub fn platform() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        ("macos", "x86_64") => Some("mac-os"),
+        ("macos", "aarch64") => Some("mac-os-arm64"),
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "aarch64") => Some("windows-arm64"),
+        _ => None,
+    }
+}
+
+/// The component for versions whose json doesn't name one, which is every version before 1.17
+pub fn component_for_major(major: u32) -> &'static str {
+    match major {
+        16 => "java-runtime-alpha",
+        17 => "java-runtime-gamma",
+        21.. => "java-runtime-delta",
+        _ => "jre-legacy",
+    }
+}
+
+/// Downloads a runtime component, e.g. `java-runtime-gamma`, into `directory/component`.
+/// Files that are already there and match their hash are kept
+pub async fn install_runtime(
+    client: &reqwest::Client,
+    component: &str,
+    directory: &Path,
+    concurrency: usize,
+    stats: &DownloadStats,
+) -> Result<InstalledRuntime, Error> {
+    stats.reset();
+    let (entry, manifest) = runtime_manifest(client, component).await?;
+
+    let directory = directory.join(component);
+    tokio::fs::create_dir_all(&directory).await?;
+
+    let total = manifest
+        .files
+        .values()
+        .map(|file| match file {
+            RuntimeFile::File { downloads, .. } => downloads.raw.size,
+            _ => 0,
+        })
+        .sum();
+    stats.total().store(total, Ordering::Relaxed);
+
+    // Directories first, so the files and links have somewhere to go
+    for (path, file) in &manifest.files {
+        if let RuntimeFile::Directory = file {
+            tokio::fs::create_dir_all(directory.join(path)).await?;
+        }
+    }
+
+    stream::iter(manifest.files.iter().map(Ok))
+        .try_for_each_concurrent(concurrency.max(1), |(path, file)| {
+            let path = directory.join(path);
+            async move {
+                let RuntimeFile::File {
+                    executable,
+                    downloads,
+                } = file
+                else {
+                    return Ok(());
+                };
+
+                download_runtime_file(client, &downloads.raw, &path, stats).await?;
+
+                #[cfg(unix)]
+                if *executable {
+                    use std::os::unix::fs::PermissionsExt;
+                    let permissions = std::fs::Permissions::from_mode(0o755);
+                    tokio::fs::set_permissions(&path, permissions).await?;
+                }
+                #[cfg(not(unix))]
+                let _ = executable;
+
+                Ok::<_, Error>(())
+            }
+        })
+        .await?;
+
+    // Only macOS runtimes have links, and they point inside the runtime
+    #[cfg(unix)]
+    for (path, file) in &manifest.files {
+        if let RuntimeFile::Link { target } = file {
+            let path = directory.join(path);
+            if tokio::fs::symlink_metadata(&path).await.is_ok() {
+                tokio::fs::remove_file(&path).await?;
+            }
+            tokio::fs::symlink(target, &path).await?;
+        }
+    }
+
+    Ok(InstalledRuntime {
+        java: java_binary(&directory),
+        version: entry.version.name,
+    })
+}
+
+/// The files of a runtime component that aren't in `directory/component` yet, what
+/// [`install_runtime`] would download
+pub async fn plan_runtime(
+    client: &reqwest::Client,
+    component: &str,
+    directory: &Path,
+) -> Result<Planned, Error> {
+    let (_, manifest) = runtime_manifest(client, component).await?;
+    let directory = directory.join(component);
+
+    let mut planned = Planned::default();
+    for (path, file) in &manifest.files {
+        let RuntimeFile::File { downloads, .. } = file else {
+            continue;
+        };
+        if !runtime_file_matches(&downloads.raw, &directory.join(path)).await? {
+            planned.add(downloads.raw.size);
+        }
+    }
+    Ok(planned)
+}
+
+/// The runtime list and the manifest of the component in it for this platform
+async fn runtime_manifest(
+    client: &reqwest::Client,
+    component: &str,
+) -> Result<(RuntimeEntry, RuntimeManifest), Error> {
+    let Some(platform) = platform() else {
+        return Err(not_found(format!(
+            "There are no Java runtimes for {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )));
+    };
+
+    let request = client.get(RUNTIMES_URL);
+    let mut runtimes: HashMap<String, HashMap<String, Vec<RuntimeEntry>>> = http::send(request)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let Some(entry) = runtimes
+        .remove(platform)
+        .and_then(|mut components| components.remove(component))
+        .and_then(|entries| entries.into_iter().next())
+    else {
+        return Err(not_found(format!(
+            "{component} is not available for {platform}"
+        )));
+    };
+
+    let request = client.get(&entry.manifest.url);
+    let bytes = http::send(request)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    if bytes.len() as u64 != entry.manifest.size || checksum::sha1(&bytes) != entry.manifest.sha1 {
+        return Err(invalid_data(format!(
+            "The {component} manifest does not match its sha1"
+        )));
+    }
+    let manifest: RuntimeManifest = serde_json::from_slice(&bytes)?;
+    Ok((entry, manifest))
+}
+
+async fn download_runtime_file(
+    client: &reqwest::Client,
+    download: &Download,
+    path: &Path,
+    stats: &DownloadStats,
+) -> Result<(), Error> {
+    if runtime_file_matches(download, path).await? {
+        stats.finished().fetch_add(download.size, Ordering::Relaxed);
+        return Ok(());
+    }
+
+    let request = client.get(&download.url);
+    let bytes = http::send(request)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    if checksum::sha1(&bytes) != download.sha1 {
+        return Err(invalid_data(format!(
+            "{} does not match its sha1",
+            download.url
+        )));
+    }
+
+    tokio::fs::write(path, &bytes).await?;
+    stats
+        .finished()
+        .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+
+    Ok(())
+}
+
+async fn runtime_file_matches(download: &Download, path: &Path) -> Result<bool, Error> {
+    match tokio::fs::read(path).await {
+        Ok(buf) => Ok(checksum::sha1(&buf) == download.sha1),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn java_binary(runtime: &Path) -> PathBuf {
+    if cfg!(target_os = "macos") {
+        runtime.join("jre.bundle/Contents/Home/bin/java")
+    } else if cfg!(windows) {
+        runtime.join("bin").join("java.exe")
+    } else {
+        runtime.join("bin/java")
+    }
+}
+
+fn not_found(message: String) -> Error {
+    Error::Tokio(tokio::io::Error::new(
+        tokio::io::ErrorKind::NotFound,
+        message,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{component_for_major, RuntimeFile, RuntimeManifest};
+
+    #[test]
+    fn test_component_for_major() {
+        assert_eq!(component_for_major(8), "jre-legacy");
+        assert_eq!(component_for_major(17), "java-runtime-gamma");
+        assert_eq!(component_for_major(21), "java-runtime-delta");
+    }
+
+    #[test]
+    fn test_parse_runtime_manifest() {
+        let json = r#"{"files": {
+            "bin": {"type": "directory"},
+            "bin/java": {"type": "file", "executable": true, "downloads": {
+                "raw": {"sha1": "abc", "size": 10, "url": "https://example.com/java"}
+            }},
+            "legal/LICENSE": {"type": "link", "target": "../LICENSE"}
+        }}"#;
+
+        let manifest: RuntimeManifest = serde_json::from_str(json).unwrap();
+        assert!(matches!(manifest.files["bin"], RuntimeFile::Directory));
+        assert!(matches!(
+            manifest.files["bin/java"],
+            RuntimeFile::File {
+                executable: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &manifest.files["legal/LICENSE"],
+            RuntimeFile::Link { target } if target == "../LICENSE"
+        ));
+    }
+}
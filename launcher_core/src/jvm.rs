@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Compiled Java byte-code that prints the Java version and vendor.
+/// Source can be found in VersionPrinter.java
+const CHECKER_CLASS: &[u8] = include_bytes!("VersionPrinter.class");
+
+/// More than the checker ever prints, the rest of the output is thrown away
+const MAX_OUTPUT: u64 = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JvmVersion {
+    pub vendor: String,
+    pub major: u32,
+}
+
+impl Display for JvmVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.vendor, self.major)
+    }
+}
+
+#[derive(Debug)]
+pub enum ProbeError {
+    Io(std::io::Error),
+    /// The JVM exited with an error, with the start of what it printed to stderr
+    Failed(ExitStatus, String),
+    /// It ran but didn't print a version, with the start of what it printed instead
+    Unrecognized(String),
+}
+
+impl From<std::io::Error> for ProbeError {
+    fn from(value: std::io::Error) -> Self {
+        ProbeError::Io(value)
+    }
+}
+
+impl Display for ProbeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProbeError::Io(err) => err.fmt(f),
+            ProbeError::Failed(status, stderr) if stderr.is_empty() => {
+                write!(f, "Java exited with {status}")
+            }
+            ProbeError::Failed(status, stderr) => write!(f, "Java exited with {status}: {stderr}"),
+            ProbeError::Unrecognized(stdout) => {
+                write!(f, "Java didn't print its version: {stdout}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+/// Results by path and modification time, so an updated install is run again
+fn cache() -> &'static Mutex<HashMap<(PathBuf, SystemTime), JvmVersion>> {
+    static CACHE: OnceLock<Mutex<HashMap<(PathBuf, SystemTime), JvmVersion>>> = OnceLock::new();
+    CACHE.get_or_init(Mutex::default)
+}
+
+/// None for binaries found through the PATH, they can't be told apart once they change
+fn cache_key(jvm: &Path) -> Option<(PathBuf, SystemTime)> {
+    let modified = std::fs::metadata(jvm)
+        .and_then(|meta| meta.modified())
+        .ok()?;
+    Some((jvm.to_path_buf(), modified))
+}
+
+/// The vendor and major version of the Java binary, it's only run the first time
+pub fn probe(jvm: &Path) -> Result<JvmVersion, ProbeError> {
+    let key = cache_key(jvm);
+    if let Some(version) = key
+        .as_ref()
+        .and_then(|key| cache().lock().unwrap().get(key).cloned())
+    {
+        return Ok(version);
+    }
+
+    let version = run_checker(jvm)?;
+    if let Some(key) = key {
+        cache().lock().unwrap().insert(key, version.clone());
+    }
+    Ok(version)
+}
+
+/// [`probe`] on a blocking thread
+pub async fn probe_async(jvm: PathBuf) -> Result<JvmVersion, ProbeError> {
+    tokio::task::spawn_blocking(move || probe(&jvm))
+        .await
+        .map_err(|e| ProbeError::Io(std::io::Error::other(e)))?
+}
+
+fn run_checker(jvm: &Path) -> Result<JvmVersion, ProbeError> {
+    let tmp = std::env::temp_dir();
+    std::fs::write(tmp.join("VersionPrinter.class"), CHECKER_CLASS)?;
+    let mut child = Command::new(jvm)
+        .env_clear()
+        .current_dir(tmp)
+        .args(["-DFile.Encoding=UTF-8", "VersionPrinter"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let (stdout, stderr) = (child.stdout.take(), child.stderr.take());
+    let (stdout, stderr) = std::thread::scope(|scope| {
+        let stderr = scope.spawn(|| stderr.map(read_limited).unwrap_or(Ok(String::new())));
+        let stdout = stdout.map(read_limited).unwrap_or(Ok(String::new()));
+        (stdout, stderr.join().unwrap())
+    });
+    let status = child.wait()?;
+    let (stdout, stderr) = (stdout?, stderr?);
+
+    if !status.success() {
+        return Err(ProbeError::Failed(status, stderr.trim().to_string()));
+    }
+    parse_version(&stdout).ok_or_else(|| ProbeError::Unrecognized(stdout.trim().to_string()))
+}
+
+/// Up to [`MAX_OUTPUT`] bytes, the rest is dropped so the JVM never blocks on a full pipe
+fn read_limited(mut pipe: impl Read) -> std::io::Result<String> {
+    let mut buffer = Vec::new();
+    pipe.by_ref().take(MAX_OUTPUT).read_to_end(&mut buffer)?;
+    std::io::copy(&mut pipe, &mut std::io::sink())?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+/// `1.8.0_392` is Java 8, newer versions start with the major version
+fn parse_version(output: &str) -> Option<JvmVersion> {
+    let (version, vendor) = output.split_once('\n')?;
+    let mut split = version.split('.');
+    let major = match split.next()? {
+        "1" => split.next()?,
+        major => major,
+    };
+    Some(JvmVersion {
+        vendor: vendor.trim().to_string(),
+        major: major.trim().parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_version, probe, read_limited, JvmVersion, ProbeError, MAX_OUTPUT};
+    use std::path::Path;
+
+    #[test]
+    fn test_parse_version() {
+        let version = |vendor: &str, major| {
+            Some(JvmVersion {
+                vendor: vendor.to_string(),
+                major,
+            })
+        };
+        assert_eq!(parse_version("1.8.0_392\nTemurin"), version("Temurin", 8));
+        assert_eq!(
+            parse_version("17.0.9\nOracle Corporation"),
+            version("Oracle Corporation", 17)
+        );
+        assert_eq!(
+            parse_version("21\nEclipse Adoptium"),
+            version("Eclipse Adoptium", 21)
+        );
+        assert_eq!(parse_version(""), None);
+        assert_eq!(
+            parse_version("Error: Could not find or load main class\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_limited() {
+        let long = vec![b'a'; MAX_OUTPUT as usize * 2];
+        assert_eq!(read_limited(&long[..]).unwrap().len(), MAX_OUTPUT as usize);
+        // Invalid UTF-8 is replaced instead of failing
+        assert_eq!(read_limited(&b"17\n\xff"[..]).unwrap(), "17\n\u{fffd}");
+    }
+
+    #[test]
+    fn test_missing_binary() {
+        let result = probe(Path::new("/nonexistent/bin/java"));
+        assert!(matches!(result, Err(ProbeError::Io(_))));
+    }
+}
@@ -1,8 +1,9 @@
 use std::fmt::Display;
-use std::path::Path;
-use std::sync::atomic::AtomicU64;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
 use crate::account::types::Account;
+use crate::checksum::{sha1, Checksum};
 use crate::types::{OsName, Value};
 use futures::{stream, Stream, StreamExt, TryStreamExt};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -19,11 +20,45 @@ const OS: OsName = OsName::Osx;
 const OS: OsName = OsName::Linux;
 
 pub mod account;
+pub mod archives;
+pub mod assets;
+pub mod checksum;
+pub mod crash;
+mod error;
+pub mod http;
+pub mod java;
+pub mod jvm;
+pub mod libraries;
+pub mod loader;
+pub mod memory;
+pub mod modpack;
+pub mod modrinth;
+pub mod multimc;
+pub mod nbt;
+pub mod news;
+pub mod plan;
+pub mod progress;
+pub mod resource_packs;
+pub mod saves;
+pub mod server_status;
+pub mod skin_fix;
 pub mod types;
 
-#[derive(Clone)]
+/// How many files are downloaded at once, unless changed with [`AsyncLauncher::set_concurrency`]
+pub const DEFAULT_CONCURRENCY: usize = 16;
+
 pub struct AsyncLauncher {
     client: reqwest::Client,
+    concurrency: AtomicUsize,
+}
+
+impl Clone for AsyncLauncher {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            concurrency: AtomicUsize::new(self.concurrency()),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -32,6 +67,7 @@ pub enum Error {
     Tokio(tokio::io::Error),
     SerdeJson(serde_json::Error),
     ProfileError(account::types::ProfileError),
+    RateLimited(http::RateLimited),
 }
 
 impl From<reqwest::Error> for Error {
@@ -59,6 +95,7 @@ impl Display for Error {
             Error::Tokio(err) => err,
             Error::SerdeJson(err) => err,
             Error::ProfileError(err) => err,
+            Error::RateLimited(err) => err,
         };
         write!(f, "{}", str)
     }
@@ -73,9 +110,56 @@ impl serde::Serialize for Error {
     }
 }
 
+/// What [`AsyncLauncher::download_libraries_and_get_path`] downloaded
+#[derive(Debug, Clone, Default)]
+pub struct Libraries {
+    pub class_path: String,
+    /// Native libraries that couldn't be extracted, like `{jar}: {entry}: {reason}`. The game
+    /// may still start without them, it's up to the frontend to warn about it
+    pub skipped_natives: Vec<String>,
+}
+
+/// Which jar [`AsyncLauncher::download_mappings`] gets the mappings for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Client,
+    Server,
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Client => f.write_str("client"),
+            Side::Server => f.write_str("server"),
+        }
+    }
+}
+
 impl AsyncLauncher {
     pub fn new(client: reqwest::Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            concurrency: AtomicUsize::new(DEFAULT_CONCURRENCY),
+        }
+    }
+
+    /// A launcher with its own client, [`AsyncLauncher::client`] shares it with the rest
+    pub fn with_options(options: &http::ClientOptions) -> Result<Self, Error> {
+        Ok(Self::new(options.build()?))
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Sets how many assets or libraries are downloaded at once, applies to downloads started after this call
+    pub fn set_concurrency(&self, concurrency: usize) {
+        self.concurrency
+            .store(concurrency.max(1), Ordering::Relaxed);
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency.load(Ordering::Relaxed)
     }
 
     /// Downloads "version_manifest.json" to the provided directory,
@@ -102,7 +186,7 @@ impl AsyncLauncher {
         let file = directory.join("version_manifest.json");
 
         if tokio::fs::try_exists(&file).await? {
-            let response = self.client.get(VERSION_MANIFEST_URL).send().await?;
+            let response = http::send(self.client.get(VERSION_MANIFEST_URL)).await?;
 
             let buf = tokio::fs::read(&file).await?;
             let mut meta: types::VersionManifest = serde_json::from_slice(&buf)?;
@@ -137,7 +221,7 @@ impl AsyncLauncher {
                 tokio::fs::create_dir_all(directory).await?;
             }
 
-            let response = self.client.get(VERSION_MANIFEST_URL).send().await?;
+            let response = http::send(self.client.get(VERSION_MANIFEST_URL)).await?;
             let bytes = response.bytes().await?;
 
             tokio::fs::write(file, &bytes).await?;
@@ -161,7 +245,7 @@ impl AsyncLauncher {
             return Ok(serde_json::from_slice(&buf)?);
         }
 
-        let response = self.client.get(&version_details.url).send().await?;
+        let response = http::send(self.client.get(&version_details.url)).await?;
         let buf = response.bytes().await?;
 
         if !tokio::fs::try_exists(&directory).await? {
@@ -193,7 +277,7 @@ impl AsyncLauncher {
                 return Ok(val);
             }
         }
-        let response = self.client.get(&asset_index.url).send().await?;
+        let response = http::send(self.client.get(&asset_index.url)).await?;
         let buf = response.bytes().await?;
 
         if !tokio::fs::try_exists(&directory).await? {
@@ -232,58 +316,32 @@ impl AsyncLauncher {
         }
 
         stream::iter(asset_index.objects.values().map(Ok))
-            .try_for_each_concurrent(16, |asset| async {
+            .try_for_each_concurrent(self.concurrency(), |asset| async {
                 let first_two = &asset.hash[0..=1];
                 let dir_path = object_path.join(first_two);
                 let file_path = dir_path.join(&asset.hash);
 
-                // If the file exists, we can verify it
-                let mut file = if file_path.exists() {
-                    let mut file = tokio::fs::OpenOptions::new()
-                        .read(true)
-                        .write(true)
-                        .open(&file_path)
-                        .await?;
-                    // If the lengths don't match, there is no reason to hash
-                    if file.metadata().await?.len() == asset.size {
-                        // Buffer size of 64kb
-                        let mut buf = [0; 64 * 1024];
-                        let mut hasher = sha1_smol::Sha1::new();
-
-                        // Update the hasher with the bytes read, until we've read everything
-                        let mut total_read = 0;
-                        while total_read < asset.size {
-                            let read_bytes = file.read(&mut buf).await?;
-                            total_read += read_bytes as u64;
-                            hasher.update(&buf[..read_bytes]);
-                        }
-
-                        // If they match, we don't need to do any more work
-                        if hasher.digest().to_string() == asset.hash {
-                            finished.fetch_add(asset.size, std::sync::atomic::Ordering::Relaxed);
-                            return Ok(());
-                        }
-                    }
-
-                    file.set_len(0).await?;
-                    file
-                } else {
-                    // Else we make sure the dir it sits in exists, and create a new one
-                    if !dir_path.exists() {
-                        tokio::fs::create_dir_all(dir_path).await?;
-                    }
+                // If the file exists and matches, we don't need to do any more work
+                let checksum = Checksum::Sha1(asset.hash.clone());
+                if file_matches(&file_path, asset.size, &checksum).await? {
+                    finished.fetch_add(asset.size, std::sync::atomic::Ordering::Relaxed);
+                    return Ok(());
+                }
 
-                    tokio::fs::File::create(&file_path).await?
-                };
+                // Else we make sure the dir it sits in exists, and replace the file
+                if !dir_path.exists() {
+                    tokio::fs::create_dir_all(dir_path).await?;
+                }
+                let mut file = tokio::fs::File::create(&file_path).await?;
 
                 // Format the URL according to how the meta holds it
                 let url = format!("{}/{}/{}", ASSET_BASE_URL, first_two, &asset.hash);
-                let response = self.client.get(url).send().await?;
+                let response = http::send(self.client.get(url)).await?;
                 // Create a stream from the response
                 let mut bytes = response.bytes_stream();
 
                 // Write the bytes to the file
-                write_file(&mut file, &mut bytes, finished).await?;
+                write_file(&mut file, &mut bytes, finished, Some(&checksum)).await?;
 
                 Ok(())
             })
@@ -298,8 +356,10 @@ impl AsyncLauncher {
         native_dir: &Path,
         total: &AtomicU64,
         finished: &AtomicU64,
-    ) -> Result<String, Error> {
-        let mut path = String::new();
+    ) -> Result<Libraries, Error> {
+        let path = class_path(libraries, directory);
+        let skipped = std::sync::Mutex::new(Vec::new());
+        let skipped_natives = &skipped;
 
         finished.store(0, std::sync::atomic::Ordering::Relaxed);
         total.store(0, std::sync::atomic::Ordering::Relaxed);
@@ -315,52 +375,41 @@ impl AsyncLauncher {
                 return None;
             }
 
-            let dir = directory.to_str().unwrap();
-            #[cfg(not(windows))]
-            path.extend([dir, "/", &artifact.path, ":"]);
-
-            #[cfg(windows)]
-            path.extend([dir, "/", &artifact.path, ";"]);
-
             total.fetch_add(artifact.size, std::sync::atomic::Ordering::Relaxed);
 
             Some(Ok::<_, Error>((artifact, native)))
         }))
-        .try_for_each_concurrent(16, |(artifact, native)| async move {
-            let mut fetch = true;
-
+        .try_for_each_concurrent(self.concurrency(), |(artifact, native)| async move {
             let path = directory.join(Path::new(&artifact.path));
             let parent = path.parent().unwrap();
+            let checksum = Checksum::Sha1(artifact.sha1.clone());
 
-            if path.exists() {
-                let buf = tokio::fs::read(&path).await?;
-                if sha1(&buf) == artifact.sha1 {
-                    fetch = false;
-                } else {
-                    tokio::fs::remove_file(&path).await?;
-                }
-            }
-
-            if fetch {
+            if file_matches(&path, artifact.size, &checksum).await? {
+                finished.fetch_add(artifact.size, std::sync::atomic::Ordering::Relaxed);
+            } else {
                 tokio::fs::create_dir_all(parent).await?;
 
-                let response = self.client.get(&artifact.url).send().await?;
+                let response = http::send(self.client.get(&artifact.url)).await?;
                 let mut stream = response.bytes_stream();
                 let mut file = tokio::fs::File::create(&path).await?;
-                write_file(&mut file, &mut stream, finished).await?;
-            } else {
-                finished.fetch_add(artifact.size, std::sync::atomic::Ordering::Relaxed);
+                write_file(&mut file, &mut stream, finished, Some(&checksum)).await?;
             }
 
             if native {
-                extract_native(native_dir, &path).await
-            } else {
-                Ok(())
+                let skipped = extract_native(native_dir, &path).await?;
+                let skipped = skipped
+                    .into_iter()
+                    .map(|entry| format!("{}: {entry}", path.display()));
+                skipped_natives.lock().unwrap().extend(skipped);
             }
+            Ok(())
         })
         .await?;
 
-        Ok(path)
+        Ok(Libraries {
+            class_path: path,
+            skipped_natives: skipped.into_inner().unwrap(),
+        })
     }
 
     pub async fn download_jar(
@@ -370,101 +419,241 @@ impl AsyncLauncher {
         total_bytes: &AtomicU64,
         finished_bytes: &AtomicU64,
     ) -> Result<String, Error> {
-        total_bytes.store(
-            version_details.downloads.client.size,
-            std::sync::atomic::Ordering::Relaxed,
-        );
-        finished_bytes.store(0, std::sync::atomic::Ordering::Relaxed);
+        let id = version_details.id();
+        let file = directory.join(id).join(format!("{id}.jar"));
+        let client = &version_details.downloads.client;
+        self.download_artifact(client, &file, total_bytes, finished_bytes)
+            .await?;
 
+        Ok(file.to_str().unwrap().to_string())
+    }
+
+    /// Downloads the version's obfuscation mappings into its folder in `directory`, named like
+    /// `1.20.4-client.txt`. Versions before 1.14.4 have none
+    pub async fn download_mappings(
+        &self,
+        version_details: &types::VersionJson,
+        side: Side,
+        directory: &Path,
+        total_bytes: &AtomicU64,
+        finished_bytes: &AtomicU64,
+    ) -> Result<PathBuf, Error> {
         let id = version_details.id();
-        let url = version_details.url();
-        let folder = directory.join(id);
+        let downloads = &version_details.downloads;
+        let mappings = match side {
+            Side::Client => downloads.client_mappings.as_ref(),
+            Side::Server => downloads.server_mappings.as_ref(),
+        };
+        let mappings =
+            mappings.ok_or_else(|| error::invalid_data(format!("{id} has no {side} mappings")))?;
 
-        let file = folder.join(format!("{id}.jar"));
-        let str = file.to_str().unwrap().to_string();
+        let file = directory.join(id).join(format!("{id}-{side}.txt"));
+        self.download_artifact(mappings, &file, total_bytes, finished_bytes)
+            .await?;
+        Ok(file)
+    }
 
-        if tokio::fs::try_exists(&file).await? {
-            let buf = tokio::fs::read(&file).await?;
-            if sha1(&buf) == version_details.sha1() {
-                finished_bytes.store(
-                    version_details.downloads.client.size,
-                    std::sync::atomic::Ordering::Relaxed,
-                );
-                return Ok(str);
-            }
+    /// Downloads the dedicated server jar into the version's folder in `directory`, named like
+    /// `1.20.4-server.jar`. Some old versions have none
+    pub async fn download_server(
+        &self,
+        version_details: &types::VersionJson,
+        directory: &Path,
+        total_bytes: &AtomicU64,
+        finished_bytes: &AtomicU64,
+    ) -> Result<PathBuf, Error> {
+        let id = version_details.id();
+        let server = version_details
+            .downloads
+            .server
+            .as_ref()
+            .ok_or_else(|| error::invalid_data(format!("{id} has no server jar")))?;
+
+        let file = directory.join(id).join(format!("{id}-server.jar"));
+        self.download_artifact(server, &file, total_bytes, finished_bytes)
+            .await?;
+        Ok(file)
+    }
+
+    /// Downloads one of the version's files unless it's already there and whole, the sha1 is
+    /// checked either way
+    async fn download_artifact(
+        &self,
+        artifact: &types::Jar,
+        file: &Path,
+        total_bytes: &AtomicU64,
+        finished_bytes: &AtomicU64,
+    ) -> Result<(), Error> {
+        total_bytes.store(artifact.size, std::sync::atomic::Ordering::Relaxed);
+        finished_bytes.store(0, std::sync::atomic::Ordering::Relaxed);
+
+        let checksum = Checksum::Sha1(artifact.sha1.clone());
+        if file_matches(file, artifact.size, &checksum).await? {
+            finished_bytes.store(artifact.size, std::sync::atomic::Ordering::Relaxed);
+            return Ok(());
         }
 
+        if let Some(parent) = file.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
         let mut file = tokio::fs::File::create(file).await?;
 
-        let jar = self.client.get(url).send().await?;
-        let len = jar.content_length().unwrap();
-        finished_bytes.store(len, std::sync::atomic::Ordering::Relaxed);
+        let response = http::send(self.client.get(&artifact.url)).await?;
+        if let Some(len) = response.content_length() {
+            total_bytes.store(len, std::sync::atomic::Ordering::Relaxed);
+        }
 
-        let mut stream = jar.bytes_stream();
-        write_file(&mut file, &mut stream, finished_bytes).await?;
+        let mut stream = response.bytes_stream();
+        write_file(&mut file, &mut stream, finished_bytes, Some(&checksum)).await
+    }
+
+    /// What preparing the version would download: the missing or broken assets, libraries,
+    /// jar and Java runtime, with their size. Nothing is written, the asset index is only
+    /// downloaded into memory if it isn't there yet
+    pub async fn plan_downloads(
+        &self,
+        version_json: &types::VersionJson,
+        dirs: &plan::Directories,
+    ) -> Result<plan::DownloadPlan, Error> {
+        plan::plan(self, version_json, dirs).await
+    }
+}
+
+/// The [`Libraries::class_path`] [`AsyncLauncher::download_libraries_and_get_path`] returns,
+/// without downloading anything. The game jar still has to be added to the end
+pub fn class_path(libraries: &[types::Library], directory: &Path) -> String {
+    let mut path = String::new();
+    let dir = directory.to_str().unwrap();
+
+    for library in libraries {
+        let Some(artifact) = &library.downloads else {
+            continue;
+        };
+        if !library.rule.apply() {
+            continue;
+        }
+
+        #[cfg(not(windows))]
+        path.extend([dir, "/", &artifact.path, ":"]);
 
-        Ok(str)
+        #[cfg(windows)]
+        path.extend([dir, "/", &artifact.path, ";"]);
     }
+
+    path
 }
 
+/// Hashes the chunks as they're written, so the file doesn't have to be read back to check it.
+/// A file that doesn't match is left behind, the next attempt replaces it
 async fn write_file<S>(
     file: &mut tokio::fs::File,
     stream: &mut S,
     bytes: &AtomicU64,
+    checksum: Option<&Checksum>,
 ) -> Result<(), Error>
 where
     S: Stream<Item = reqwest::Result<bytes::Bytes>> + Unpin,
 {
+    let mut hasher = checksum.map(Checksum::hasher);
     while let Some(next) = stream.next().await {
         let chunk = next?;
         file.write_all(&chunk).await?;
+        if let Some(hasher) = &mut hasher {
+            hasher.update(&chunk);
+        }
         bytes.fetch_add(chunk.len() as u64, std::sync::atomic::Ordering::Relaxed);
     }
 
+    if let (Some(hasher), Some(checksum)) = (hasher, checksum) {
+        if !hasher.matches() {
+            let message = format!("The download does not match its {checksum}");
+            return Err(error::invalid_data(message));
+        }
+    }
     Ok(())
 }
 
-async fn extract_native(native_dir: &Path, path: &Path) -> Result<(), Error> {
-    if !tokio::fs::try_exists(native_dir).await? {
-        tokio::fs::create_dir_all(native_dir).await?;
+/// Whether a file from an earlier download is whole. It's read in chunks, and not at all if
+/// the size is off
+async fn file_matches(path: &Path, size: u64, checksum: &Checksum) -> Result<bool, Error> {
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if file.metadata().await?.len() != size {
+        return Ok(false);
     }
 
-    let reader = async_zip::tokio::read::fs::ZipFileReader::new(path)
-        .await
-        .unwrap();
-    for (idx, entry) in reader.file().entries().iter().enumerate() {
-        if entry.dir().unwrap() {
-            continue;
+    // Buffer size of 64kb
+    let mut buf = vec![0; 64 * 1024];
+    let mut hasher = checksum.hasher();
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
         }
-        let file_path = entry.filename().as_str().unwrap();
+        hasher.update(&buf[..read]);
+    }
+    Ok(hasher.matches())
+}
 
-        #[cfg(windows)]
-        let ends_with = ".dll";
-        #[cfg(target_os = "linux")]
-        let ends_with = ".so";
-        #[cfg(target_os = "macos")]
-        let ends_with = ".dylib";
+/// The name a native library in a jar is extracted as, None for the rest of the jar
+pub(crate) fn native_file_name(entry: &str) -> Option<&str> {
+    #[cfg(windows)]
+    let ends_with = ".dll";
+    #[cfg(target_os = "linux")]
+    let ends_with = ".so";
+    #[cfg(target_os = "macos")]
+    let ends_with = ".dylib";
+
+    if !entry.ends_with(ends_with) {
+        return None;
+    }
+    entry.split('/').last()
+}
+
+/// Streams the native libraries in the jar into `native_dir`. Entries that can't be read are
+/// skipped and returned with the reason, so one broken file doesn't stop the launch
+pub async fn extract_native(native_dir: &Path, path: &Path) -> Result<Vec<String>, Error> {
+    tokio::fs::create_dir_all(native_dir).await?;
 
-        if !file_path.ends_with(ends_with) {
+    let reader = error::ZipReader::new(path)
+        .await
+        .map_err(error::zip_error)?;
+    let mut skipped = vec![];
+    for (idx, entry) in reader.file().entries().iter().enumerate() {
+        let Some(name) = entry.filename().as_str().ok().and_then(native_file_name) else {
             continue;
-        }
+        };
 
-        let file = file_path.split('/').last().unwrap();
-        let mut entry_reader = reader.reader_without_entry(idx).await.unwrap().compat();
-        let mut buffer = Vec::with_capacity(entry.uncompressed_size() as usize);
-        tokio::io::copy(&mut entry_reader, &mut buffer).await?;
-        tokio::fs::write(native_dir.join(file), &buffer).await?;
+        let target = native_dir.join(name);
+        let mut file = tokio::fs::File::create(&target).await?;
+        if let Err(e) = copy_entry(&reader, idx, &mut file).await {
+            drop(file);
+            tokio::fs::remove_file(&target).await?;
+            skipped.push(format!("{name}: {e}"));
+        }
     }
 
-    Ok(())
+    Ok(skipped)
 }
 
-fn sha1(buf: &[u8]) -> String {
-    let mut sha1 = sha1_smol::Sha1::new();
-    sha1.update(buf);
-    sha1.digest().to_string()
+async fn copy_entry(
+    reader: &error::ZipReader,
+    idx: usize,
+    file: &mut tokio::fs::File,
+) -> Result<(), Error> {
+    let mut entry = reader
+        .reader_without_entry(idx)
+        .await
+        .map_err(error::zip_error)?
+        .compat();
+    tokio::io::copy(&mut entry, file).await?;
+    Ok(())
 }
 
+/// Spawns the game, see [`launch_command`] to configure the process before it starts
 #[allow(clippy::too_many_arguments)]
 pub fn launch_game(
     java_path: &str,
@@ -479,10 +668,54 @@ pub fn launch_game(
     launcher_name: &str,
     launcher_version: &str,
     class_path: &str,
-) {
+    jvm_args: &[String],
+) -> Result<std::process::Child, Error> {
+    let mut process = launch_command(
+        java_path,
+        json,
+        directory,
+        asset_root,
+        account,
+        client_id,
+        auth_xuid,
+        launcher_name,
+        launcher_version,
+        class_path,
+        jvm_args,
+        None,
+        &types::FeatureFlags::default(),
+    );
+
+    Ok(process.spawn()?)
+}
+
+/// Builds the command [`launch_game`] runs, without starting it
+#[allow(clippy::too_many_arguments)]
+pub fn launch_command(
+    java_path: &str,
+    json: &types::VersionJson,
+    directory: &Path,
+    asset_root: &Path,
+
+    account: &Account,
+    client_id: &str,
+    auth_xuid: &str,
+
+    launcher_name: &str,
+    launcher_version: &str,
+    class_path: &str,
+    // Passed to the JVM before the ones from the version json, e.g. memory limits
+    jvm_args: &[String],
+    // Swaps the main class and adds arguments, its libraries go in `class_path`
+    loader: Option<&loader::LoaderProfile>,
+    // Decides which of the version's feature gated game arguments are passed, and fills them
+    features: &types::FeatureFlags,
+) -> std::process::Command {
     let mut process = std::process::Command::new(java_path);
     let natives_dir = directory.join("natives");
 
+    process.args(jvm_args.iter().filter(|arg| !arg.is_empty()));
+
     for arg in &json.arguments.jvm {
         if let Some(rules) = &arg.rules {
             if !rules.applies() {
@@ -511,24 +744,26 @@ pub fn launch_game(
         }
     }
 
-    process.arg(json.main_class());
+    if let Some(loader) = loader {
+        process.args(&loader.arguments.jvm);
+        process.arg(&loader.main_class);
+    } else {
+        process.arg(json.main_class());
+    }
 
-    for arg in &json.arguments.game {
-        match &arg {
-            types::GameElement::GameClass(_) => {
-                // This is left empty, as I have not setup support for any of the features here
-            }
-            types::GameElement::String(arg) => {
-                let arg = apply_mc_args(
-                    arg, json, directory, asset_root, account, client_id, auth_xuid,
-                );
+    for arg in features.arguments(&json.arguments.game) {
+        let arg = apply_mc_args(
+            &arg, json, directory, asset_root, account, client_id, auth_xuid,
+        );
 
-                process.arg(arg);
-            }
-        }
+        process.arg(arg);
+    }
+
+    if let Some(loader) = loader {
+        process.args(&loader.arguments.game);
     }
 
-    process.spawn().unwrap();
+    process
 }
 
 fn apply_jvm_args(
@@ -568,19 +803,24 @@ fn apply_mc_args(
         .replace("${clientid}", client_id)
         .replace("${auth_xuid}", auth_xuid)
         .replace("${user_properties}", "{}")
-        .replace("${user_type}", "msa")
+        .replace(
+            "${user_type}",
+            if account.offline { "legacy" } else { "msa" },
+        )
         .replace("${version_type}", json.release_type())
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::{AtomicU64, Ordering};
     use std::{fs, path::Path};
 
     use reqwest::Client;
     use tokio::io::AsyncWriteExt;
 
-    use crate::AsyncLauncher;
+    use crate::checksum::Checksum;
+    use crate::types::{FeatureFlags, GameElement};
+    use crate::{bytes, extract_native, file_matches, write_file, AsyncLauncher};
 
     #[tokio::test]
     async fn test_version_types() {
@@ -601,6 +841,109 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_feature_flags() {
+        let launcher = AsyncLauncher::new(Client::new());
+        let manifest = launcher
+            .get_version_manifest(Path::new("./Versions"))
+            .await
+            .unwrap();
+        let _ = fs::create_dir("./Versions");
+        let resolution = FeatureFlags::default().enable("has_custom_resolution");
+        for version in manifest.versions.iter() {
+            let json = launcher
+                .get_version_json(version, Path::new("./Versions"))
+                .await
+                .unwrap();
+            for arg in &json.arguments.game {
+                let GameElement::GameClass(class) = arg else {
+                    continue;
+                };
+                let Some(rules) = &class.rules else {
+                    continue;
+                };
+                // Nothing that needs a feature is passed unless it's forced on
+                if rules
+                    .iter()
+                    .all(|rule| rule.features.0.values().any(|on| *on))
+                {
+                    assert!(!class.applies(&FeatureFlags::default()), "{}", version.id);
+                }
+                if rules
+                    .iter()
+                    .any(|rule| rule.features.0.keys().eq(["has_custom_resolution"]))
+                {
+                    assert!(class.applies(&resolution), "{}", version.id);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_native() {
+        use async_zip::tokio::write::ZipFileWriter;
+        use async_zip::{Compression, ZipEntryBuilder};
+
+        let dir = std::env::temp_dir().join("synth_natives_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let jar = dir.join("natives.jar");
+
+        let file = tokio::fs::File::create(&jar).await.unwrap();
+        let mut writer = ZipFileWriter::with_tokio(file);
+        for name in ["good", "bad"] {
+            for extension in ["so", "dll", "dylib"] {
+                let entry = ZipEntryBuilder::new(
+                    format!("natives/{name}.{extension}").into(),
+                    Compression::Stored,
+                );
+                writer
+                    .write_entry_whole(entry, name.as_bytes())
+                    .await
+                    .unwrap();
+            }
+        }
+        writer
+            .write_entry_whole(
+                ZipEntryBuilder::new(
+                    "META-INF/MANIFEST.MF".to_string().into(),
+                    Compression::Stored,
+                ),
+                b"",
+            )
+            .await
+            .unwrap();
+        writer.close().await.unwrap();
+
+        // Breaks the local headers of the bad entries, the central directory is left whole
+        let mut bytes = fs::read(&jar).unwrap();
+        let headers: Vec<_> = bytes
+            .windows(4)
+            .enumerate()
+            .filter(|(_, window)| *window == b"PK\x03\x04")
+            .map(|(idx, _)| idx)
+            .collect();
+        for idx in &headers[3..6] {
+            bytes[*idx] = 0;
+        }
+        fs::write(&jar, bytes).unwrap();
+
+        let natives = dir.join("natives");
+        let skipped = extract_native(&natives, &jar).await.unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].starts_with("bad."));
+        let extracted: Vec<_> = fs::read_dir(&natives).unwrap().collect();
+        assert_eq!(extracted.len(), 1);
+        let good = extracted[0].as_ref().unwrap().path();
+        assert_eq!(fs::read(good).unwrap(), b"good");
+
+        // A jar that isn't a zip is an error instead of a panic
+        fs::write(&jar, "not a zip").unwrap();
+        assert!(extract_native(&natives, &jar).await.is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[tokio::test]
     async fn test_assets() {
         let launcher = AsyncLauncher::new(Client::new());
@@ -691,4 +1034,29 @@ mod tests {
 
         tokio::fs::write("./1.20.3.jar", &stream).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_write_file_checksum() {
+        let path = std::env::temp_dir().join("synth_write_file_test");
+        let sha1 = Checksum::Sha1("a9993e364706816aba3e25717850c26c9cd0d89d".into());
+        let chunks =
+            || futures::stream::iter(["a", "bc"].map(|chunk| Ok(bytes::Bytes::from(chunk))));
+
+        let mut file = tokio::fs::File::create(&path).await.unwrap();
+        let finished = AtomicU64::new(0);
+        write_file(&mut file, &mut chunks(), &finished, Some(&sha1))
+            .await
+            .unwrap();
+        assert_eq!(finished.load(Ordering::Relaxed), 3);
+        assert!(file_matches(&path, 3, &sha1).await.unwrap());
+        assert!(!file_matches(&path, 4, &sha1).await.unwrap());
+
+        let other = Checksum::Sha1("0000000000000000000000000000000000000000".into());
+        let mut file = tokio::fs::File::create(&path).await.unwrap();
+        let result = write_file(&mut file, &mut chunks(), &finished, Some(&other)).await;
+        assert!(result.is_err());
+
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert!(!file_matches(&path, 3, &sha1).await.unwrap());
+    }
 }
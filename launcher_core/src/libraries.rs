@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::error::{invalid_data, zip_error};
+use crate::loader::LoaderProfile;
+use crate::types::VersionJson;
+use crate::{native_file_name, Error};
+
+/// What [`prune`] found, and removed unless it was a dry run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Installed versions and loader profiles whose libraries are kept
+    pub versions: usize,
+    /// Library jars and extracted natives none of them refer to
+    pub files: usize,
+    /// The size of those files
+    pub bytes: u64,
+}
+
+/// Removes jars from `directory/libraries` and natives from `directory/natives` that no version
+/// json in `directory/versions` refers to, launching a version again downloads them back.
+/// A dry run only counts them. Without any version nothing is removed
+pub async fn prune(directory: &Path, dry_run: bool) -> Result<PruneReport, Error> {
+    let live = live_libraries(&directory.join("versions")).await?;
+    let mut report = PruneReport {
+        versions: live.versions,
+        ..Default::default()
+    };
+    let dry_run = dry_run || live.versions == 0;
+
+    let libraries = directory.join("libraries");
+    for (path, relative) in files(&libraries).await? {
+        // Anything that isn't a jar wasn't put there by the launcher
+        if !relative.ends_with(".jar") || live.jars.contains(&relative) {
+            continue;
+        }
+        remove(&path, dry_run, &mut report).await?;
+    }
+    if !dry_run {
+        remove_empty_dirs(&libraries).await?;
+    }
+
+    // Which natives are still used can only be told from the jars they came from
+    let Some(natives) = live_natives(&libraries, &live.native_jars).await? else {
+        return Ok(report);
+    };
+    for (path, name) in files(&directory.join("natives")).await? {
+        if name.contains('/') || native_file_name(&name).is_none() || natives.contains(&name) {
+            continue;
+        }
+        remove(&path, dry_run, &mut report).await?;
+    }
+
+    Ok(report)
+}
+
+#[derive(Default)]
+struct LiveLibraries {
+    versions: usize,
+    /// Paths relative to the libraries folder, joined with `/`
+    jars: HashSet<String>,
+    native_jars: Vec<String>,
+}
+
+/// Every library the installed versions refer to. A version json that can't be read
+/// stops the pruning, its libraries would be removed otherwise
+async fn live_libraries(versions_dir: &Path) -> Result<LiveLibraries, Error> {
+    let mut live = LiveLibraries::default();
+    let mut entries = match tokio::fs::read_dir(versions_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(live),
+        Err(e) => return Err(e.into()),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        let id = entry.file_name().to_string_lossy().into_owned();
+        let path = entry.path().join(format!("{id}.json"));
+        let buffer = match tokio::fs::read(&path).await {
+            Ok(buffer) => buffer,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if let Ok(version) = serde_json::from_slice::<VersionJson>(&buffer) {
+            for library in version.libraries.iter() {
+                let Some(artifact) = &library.downloads else {
+                    continue;
+                };
+                if library.rule.native() {
+                    live.native_jars.push(artifact.path.clone());
+                }
+                live.jars.insert(artifact.path.clone());
+            }
+        } else {
+            let profile: LoaderProfile = serde_json::from_slice(&buffer)
+                .map_err(|e| invalid_data(format!("{}: {e}", path.display())))?;
+            live.jars.extend(
+                profile
+                    .libraries
+                    .iter()
+                    .filter_map(|library| library.path()),
+            );
+        }
+        live.versions += 1;
+    }
+
+    Ok(live)
+}
+
+/// The names extracted from the native jars, None if one of them is missing
+async fn live_natives(
+    libraries: &Path,
+    native_jars: &[String],
+) -> Result<Option<HashSet<String>>, Error> {
+    let mut live = HashSet::new();
+    for jar in native_jars {
+        let path = libraries.join(jar);
+        if !tokio::fs::try_exists(&path).await? {
+            return Ok(None);
+        }
+
+        let reader = async_zip::tokio::read::fs::ZipFileReader::new(&path)
+            .await
+            .map_err(zip_error)?;
+        for entry in reader.file().entries() {
+            if let Some(name) = entry.filename().as_str().ok().and_then(native_file_name) {
+                live.insert(name.to_string());
+            }
+        }
+    }
+    Ok(Some(live))
+}
+
+/// Every file below `root`, with its path relative to it joined with `/`
+async fn files(root: &Path) -> Result<Vec<(PathBuf, String)>, Error> {
+    let mut files = Vec::new();
+    let mut dirs = vec![(root.to_path_buf(), String::new())];
+
+    while let Some((dir, prefix)) = dirs.pop() {
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let relative = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}/{name}")
+            };
+
+            if entry.file_type().await?.is_dir() {
+                dirs.push((entry.path(), relative));
+            } else {
+                files.push((entry.path(), relative));
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+async fn remove(path: &Path, dry_run: bool, report: &mut PruneReport) -> Result<(), Error> {
+    report.files += 1;
+    report.bytes += tokio::fs::metadata(path).await?.len();
+    if !dry_run {
+        tokio::fs::remove_file(path).await?;
+    }
+    Ok(())
+}
+
+/// Removes the folders below `root` that are left empty, deepest first
+async fn remove_empty_dirs(root: &Path) -> Result<(), Error> {
+    let mut dirs = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                stack.push(entry.path());
+                dirs.push(entry.path());
+            }
+        }
+    }
+
+    // Parents always come before their children
+    for dir in dirs.iter().rev() {
+        // Fails while something is left in it, which is fine
+        let _ = tokio::fs::remove_dir(dir).await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prune, PruneReport};
+
+    const KEPT: &str = "org/ow2/asm/asm/9.6/asm-9.6.jar";
+    const UNUSED: &str = "org/lwjgl/lwjgl/3.2.1/lwjgl-3.2.1.jar";
+
+    #[tokio::test]
+    async fn test_prune() {
+        let root = std::env::temp_dir().join("synth_libraries_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let libraries = root.join("libraries");
+        std::fs::create_dir_all(root.join("versions/fabric-loader")).unwrap();
+        std::fs::create_dir_all(libraries.join(KEPT).parent().unwrap()).unwrap();
+        std::fs::create_dir_all(libraries.join(UNUSED).parent().unwrap()).unwrap();
+
+        let profile = r#"{
+            "id": "fabric-loader",
+            "inheritsFrom": "1.20.4",
+            "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+            "libraries": [{"name": "org.ow2.asm:asm:9.6", "url": "https://maven.fabricmc.net/"}]
+        }"#;
+        std::fs::write(
+            root.join("versions/fabric-loader/fabric-loader.json"),
+            profile,
+        )
+        .unwrap();
+        std::fs::write(libraries.join(KEPT), "kept").unwrap();
+        std::fs::write(libraries.join(UNUSED), "unused").unwrap();
+        std::fs::write(libraries.join("org/lwjgl/notes.txt"), "mine").unwrap();
+
+        let expected = PruneReport {
+            versions: 1,
+            files: 1,
+            bytes: 6,
+        };
+        assert_eq!(prune(&root, true).await.unwrap(), expected);
+        assert!(libraries.join(UNUSED).exists());
+
+        assert_eq!(prune(&root, false).await.unwrap(), expected);
+        assert!(!libraries.join(UNUSED).exists());
+        assert!(!libraries.join("org/lwjgl/lwjgl").exists());
+        assert!(libraries.join("org/lwjgl/notes.txt").exists());
+        assert!(libraries.join(KEPT).exists());
+
+        // Without a version nothing goes
+        std::fs::remove_dir_all(root.join("versions")).unwrap();
+        prune(&root, false).await.unwrap();
+        assert!(libraries.join(KEPT).exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
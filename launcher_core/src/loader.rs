@@ -0,0 +1,313 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::checksum;
+use crate::http;
+use crate::Error;
+
+const FABRIC_META: &str = "https://meta.fabricmc.net/v2";
+const QUILT_META: &str = "https://meta.quiltmc.org/v3";
+
+/// Mod loaders that are installed from their meta server
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Loader {
+    Fabric,
+    Quilt,
+}
+
+impl Loader {
+    pub const ALL: [Loader; 2] = [Loader::Fabric, Loader::Quilt];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Loader::Fabric => "Fabric",
+            Loader::Quilt => "Quilt",
+        }
+    }
+
+    /// The loader's name on Modrinth
+    pub fn modrinth_name(&self) -> &'static str {
+        match self {
+            Loader::Fabric => "fabric",
+            Loader::Quilt => "quilt",
+        }
+    }
+
+    fn meta_url(&self) -> &'static str {
+        match self {
+            Loader::Fabric => FABRIC_META,
+            Loader::Quilt => QUILT_META,
+        }
+    }
+
+    /// The id the profile is stored under in the versions folder, the same one the
+    /// official installers use, e.g. `fabric-loader-0.15.3-1.20.1`
+    pub fn profile_id(&self, game_version: &str, loader_version: &str) -> String {
+        format!(
+            "{}-loader-{loader_version}-{game_version}",
+            self.modrinth_name()
+        )
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LoaderVersion {
+    pub version: String,
+    // Only Fabric marks stable builds, see `is_stable`
+    #[serde(default)]
+    stable: Option<bool>,
+}
+
+impl LoaderVersion {
+    /// Quilt doesn't mark its builds, its betas have a suffix like `-beta.9`
+    pub fn is_stable(&self) -> bool {
+        self.stable.unwrap_or(!self.version.contains('-'))
+    }
+}
+
+#[derive(Deserialize)]
+struct MetaEntry {
+    loader: LoaderVersion,
+}
+
+/// What the loader adds on top of the vanilla version json
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoaderProfile {
+    pub id: String,
+    pub inherits_from: String,
+    pub main_class: String,
+    #[serde(default)]
+    pub arguments: ProfileArguments,
+    pub libraries: Vec<MavenLibrary>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ProfileArguments {
+    #[serde(default)]
+    pub game: Vec<String>,
+    #[serde(default)]
+    pub jvm: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MavenLibrary {
+    /// Maven coordinates, `group:artifact:version`
+    pub name: String,
+    /// The repository the library is downloaded from
+    pub url: String,
+    #[serde(default)]
+    pub sha1: Option<String>,
+}
+
+impl MavenLibrary {
+    /// Where the jar lives in a maven repository, and in the libraries folder
+    pub fn path(&self) -> Option<String> {
+        let mut parts = self.name.split(':');
+        let (group, artifact, version) = (parts.next()?, parts.next()?, parts.next()?);
+        let classifier = parts.next().map(|c| format!("-{c}")).unwrap_or_default();
+        Some(format!(
+            "{}/{artifact}/{version}/{artifact}-{version}{classifier}.jar",
+            group.replace('.', "/")
+        ))
+    }
+}
+
+/// Loader versions that support the game version, newest first
+pub async fn loader_versions(
+    client: &reqwest::Client,
+    loader: Loader,
+    game_version: &str,
+) -> Result<Vec<LoaderVersion>, Error> {
+    let request = client.get(format!(
+        "{}/versions/loader/{game_version}",
+        loader.meta_url()
+    ));
+    let entries: Vec<MetaEntry> = http::send(request)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(entries.into_iter().map(|entry| entry.loader).collect())
+}
+
+/// The newest stable build if it's newer than `installed`. The versions are newest first, like
+/// [`loader_versions`] returns them, and a build that isn't listed anymore counts as older
+pub fn newer_stable<'a>(
+    versions: &'a [LoaderVersion],
+    installed: &str,
+) -> Option<&'a LoaderVersion> {
+    versions
+        .iter()
+        .take_while(|version| version.version != installed)
+        .find(|version| version.is_stable())
+}
+
+/// The version of [`newer_stable`] out of the meta server's builds for the game version
+pub async fn stable_update(
+    client: &reqwest::Client,
+    loader: Loader,
+    game_version: &str,
+    installed: &str,
+) -> Result<Option<String>, Error> {
+    let versions = loader_versions(client, loader, game_version).await?;
+    Ok(newer_stable(&versions, installed).map(|version| version.version.clone()))
+}
+
+/// Downloads the loader's profile into the versions folder and its libraries into the
+/// libraries folder. Files that are already there are kept, so this can run again
+pub async fn install(
+    client: &reqwest::Client,
+    loader: Loader,
+    game_version: &str,
+    loader_version: &str,
+    directory: &Path,
+) -> Result<LoaderProfile, Error> {
+    let id = loader.profile_id(game_version, loader_version);
+    let file = profile_path(&directory.join("versions"), &id);
+    let profile = if tokio::fs::try_exists(&file).await? {
+        serde_json::from_slice(&tokio::fs::read(&file).await?)?
+    } else {
+        let url = format!(
+            "{}/versions/loader/{game_version}/{loader_version}/profile/json",
+            loader.meta_url()
+        );
+        let request = client.get(url);
+        let buf = http::send(request)
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let profile = serde_json::from_slice(&buf)?;
+        tokio::fs::create_dir_all(file.parent().unwrap()).await?;
+        tokio::fs::write(&file, &buf).await?;
+        profile
+    };
+
+    let libraries = directory.join("libraries");
+    download_libraries(client, &profile, &libraries).await?;
+
+    Ok(profile)
+}
+
+async fn download_libraries(
+    client: &reqwest::Client,
+    profile: &LoaderProfile,
+    directory: &Path,
+) -> Result<(), Error> {
+    for library in &profile.libraries {
+        let Some(path) = library.path() else {
+            continue;
+        };
+        let file = directory.join(&path);
+        if tokio::fs::try_exists(&file).await? {
+            continue;
+        }
+
+        let request = client.get(format!("{}/{path}", library.url.trim_end_matches('/')));
+        let buf = http::send(request)
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        if let Some(sha1) = &library.sha1 {
+            if checksum::sha1(&buf) != *sha1 {
+                let message = format!("{} failed its checksum", library.name);
+                return Err(
+                    tokio::io::Error::new(tokio::io::ErrorKind::InvalidData, message).into(),
+                );
+            }
+        }
+
+        tokio::fs::create_dir_all(file.parent().unwrap()).await?;
+        tokio::fs::write(&file, &buf).await?;
+    }
+
+    Ok(())
+}
+
+/// Reads a profile written by [`install`], `directory` is the launcher's folder
+pub fn read_profile(
+    directory: &Path,
+    loader: Loader,
+    game_version: &str,
+    loader_version: &str,
+) -> Result<LoaderProfile, Error> {
+    let id = loader.profile_id(game_version, loader_version);
+    let buf = std::fs::read(profile_path(&directory.join("versions"), &id))?;
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+/// The loader's libraries in the same format as
+/// [`crate::AsyncLauncher::download_libraries_and_get_path`], put it in front of the vanilla ones
+pub fn class_path(profile: &LoaderProfile, libraries_dir: &Path) -> String {
+    let mut path = String::new();
+    let dir = libraries_dir.to_string_lossy();
+    for library in profile.libraries.iter().filter_map(MavenLibrary::path) {
+        #[cfg(not(windows))]
+        path.extend([&*dir, "/", &library, ":"]);
+
+        #[cfg(windows)]
+        path.extend([&*dir, "/", &library, ";"]);
+    }
+    path
+}
+
+fn profile_path(versions_dir: &Path, id: &str) -> PathBuf {
+    versions_dir.join(id).join(format!("{id}.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{newer_stable, Loader, LoaderVersion, MavenLibrary};
+
+    #[test]
+    fn test_maven_path() {
+        let library = MavenLibrary {
+            name: "net.fabricmc:fabric-loader:0.15.3".to_string(),
+            url: "https://maven.fabricmc.net/".to_string(),
+            sha1: None,
+        };
+        assert_eq!(
+            library.path().unwrap(),
+            "net/fabricmc/fabric-loader/0.15.3/fabric-loader-0.15.3.jar"
+        );
+    }
+
+    #[test]
+    fn test_stable() {
+        let fabric: LoaderVersion =
+            serde_json::from_str(r#"{"version": "0.15.3", "stable": false}"#).unwrap();
+        let quilt: LoaderVersion = serde_json::from_str(r#"{"version": "0.23.0"}"#).unwrap();
+        let beta: LoaderVersion = serde_json::from_str(r#"{"version": "0.24.0-beta.1"}"#).unwrap();
+        assert!(!fabric.is_stable());
+        assert!(quilt.is_stable());
+        assert!(!beta.is_stable());
+
+        assert_eq!(
+            Loader::Quilt.profile_id("1.20.1", "0.23.0"),
+            "quilt-loader-0.23.0-1.20.1"
+        );
+    }
+
+    #[test]
+    fn test_newer_stable() {
+        let versions: Vec<LoaderVersion> = serde_json::from_str(
+            r#"[
+                {"version": "0.16.0", "stable": false},
+                {"version": "0.15.6", "stable": true},
+                {"version": "0.15.3", "stable": true}
+            ]"#,
+        )
+        .unwrap();
+        let newer = |installed| newer_stable(&versions, installed).map(|v| v.version.as_str());
+        assert_eq!(newer("0.15.3"), Some("0.15.6"));
+        assert_eq!(newer("0.15.6"), None);
+        // A beta that's newer than the latest stable is kept
+        assert_eq!(newer("0.16.0"), None);
+        assert_eq!(newer("0.14.0"), Some("0.15.6"));
+    }
+}
@@ -0,0 +1,112 @@
+/// Installed memory in MiB, None if it couldn't be read
+pub fn total_memory() -> Option<u64> {
+    total_bytes().map(|bytes| bytes / (1024 * 1024))
+}
+
+/// `-Xms` and `-Xmx` for the heap sizes in MiB, 0 leaves that size to the JVM.
+/// Pass these with the other JVM arguments to [`crate::launch_command`]
+pub fn heap_args(min: u32, max: u32) -> Vec<String> {
+    let mut args = Vec::with_capacity(2);
+    // A larger minimum than maximum stops the JVM from starting
+    let min = if max != 0 { min.min(max) } else { min };
+
+    if min != 0 {
+        args.push(format!("-Xms{min}M"));
+    }
+    if max != 0 {
+        args.push(format!("-Xmx{max}M"));
+    }
+
+    args
+}
+
+#[cfg(target_os = "linux")]
+fn total_bytes() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    parse_meminfo(&meminfo)
+}
+
+#[cfg(target_os = "macos")]
+fn total_bytes() -> Option<u64> {
+    let output = std::process::Command::new("sysctl")
+        .args(["-n", "hw.memsize"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(windows)]
+fn total_bytes() -> Option<u64> {
+    #[repr(C)]
+    struct MemoryStatusEx {
+        length: u32,
+        memory_load: u32,
+        total_phys: u64,
+        avail_phys: u64,
+        total_page_file: u64,
+        avail_page_file: u64,
+        total_virtual: u64,
+        avail_virtual: u64,
+        avail_extended_virtual: u64,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GlobalMemoryStatusEx(buffer: *mut MemoryStatusEx) -> i32;
+    }
+
+    let mut status = MemoryStatusEx {
+        length: std::mem::size_of::<MemoryStatusEx>() as u32,
+        memory_load: 0,
+        total_phys: 0,
+        avail_phys: 0,
+        total_page_file: 0,
+        avail_page_file: 0,
+        total_virtual: 0,
+        avail_virtual: 0,
+        avail_extended_virtual: 0,
+    };
+
+    // SAFETY: the length is set, which is all the call needs
+    let ok = unsafe { GlobalMemoryStatusEx(&mut status) };
+    (ok != 0).then_some(status.total_phys)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn total_bytes() -> Option<u64> {
+    None
+}
+
+/// Reads `MemTotal`, which /proc/meminfo gives in KiB
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kib: u64 = line
+        .trim_start_matches("MemTotal:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{heap_args, parse_meminfo};
+
+    #[test]
+    fn test_heap_args() {
+        assert!(heap_args(0, 0).is_empty());
+        assert_eq!(heap_args(0, 4096), ["-Xmx4096M"]);
+        assert_eq!(heap_args(1024, 4096), ["-Xms1024M", "-Xmx4096M"]);
+        assert_eq!(heap_args(8192, 4096), ["-Xms4096M", "-Xmx4096M"]);
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       16318412 kB\nMemFree:         1035580 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(16318412 * 1024));
+        assert_eq!(parse_meminfo("MemFree: 10 kB"), None);
+    }
+}
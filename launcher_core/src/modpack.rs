@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+use futures::{stream, TryStreamExt};
+use serde::Deserialize;
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+use crate::checksum::Checksum;
+use crate::error::{invalid_data, zip_error, ZipReader};
+use crate::http;
+use crate::progress::DownloadStats;
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackFormat {
+    /// `.mrpack`, files are listed with their download urls and hashes
+    Modrinth,
+    /// CurseForge zips only list project and file ids
+    CurseForge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackLoader {
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackInfo {
+    pub format: PackFormat,
+    pub name: String,
+    pub version: String,
+    pub game_version: String,
+    /// The loader and its version, None for vanilla packs
+    pub loader: Option<(PackLoader, String)>,
+    pub files: Vec<PackFile>,
+    /// Folders inside the zip copied over the instance, in order
+    overrides: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackFile {
+    /// Relative to the instance, None if the name is only known once downloaded
+    pub path: Option<String>,
+    pub urls: Vec<String>,
+    pub checksum: Option<Checksum>,
+    pub size: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModrinthIndex {
+    name: String,
+    version_id: String,
+    files: Vec<ModrinthFile>,
+    dependencies: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModrinthFile {
+    path: String,
+    hashes: HashMap<String, String>,
+    env: Option<HashMap<String, String>>,
+    downloads: Vec<String>,
+    file_size: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeManifest {
+    minecraft: CurseForgeMinecraft,
+    name: String,
+    #[serde(default)]
+    version: String,
+    files: Vec<CurseForgeFile>,
+    #[serde(default = "default_overrides")]
+    overrides: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeMinecraft {
+    version: String,
+    mod_loaders: Vec<CurseForgeLoader>,
+}
+
+#[derive(Deserialize)]
+struct CurseForgeLoader {
+    id: String,
+    #[serde(default)]
+    primary: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeFile {
+    #[serde(rename = "projectID")]
+    project_id: u64,
+    #[serde(rename = "fileID")]
+    file_id: u64,
+    #[serde(default = "default_required")]
+    required: bool,
+}
+
+fn default_overrides() -> String {
+    "overrides".into()
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// Reads the pack's metadata, telling the format apart by which manifest the zip contains
+pub async fn read_pack(pack: &Path) -> Result<PackInfo, Error> {
+    let reader = ZipReader::new(pack).await.map_err(zip_error)?;
+
+    if let Some(bytes) = read_entry(&reader, "modrinth.index.json").await? {
+        let index: ModrinthIndex = serde_json::from_slice(&bytes)?;
+        return Ok(modrinth_info(index));
+    }
+
+    if let Some(bytes) = read_entry(&reader, "manifest.json").await? {
+        let manifest: CurseForgeManifest = serde_json::from_slice(&bytes)?;
+        return Ok(curseforge_info(manifest));
+    }
+
+    Err(invalid_data(
+        "Not a modpack, expected a modrinth.index.json or manifest.json",
+    ))
+}
+
+fn modrinth_info(index: ModrinthIndex) -> PackInfo {
+    let game_version = index
+        .dependencies
+        .get("minecraft")
+        .cloned()
+        .unwrap_or_default();
+
+    let loader = [
+        ("fabric-loader", PackLoader::Fabric),
+        ("quilt-loader", PackLoader::Quilt),
+        ("forge", PackLoader::Forge),
+        ("neoforge", PackLoader::NeoForge),
+    ]
+    .into_iter()
+    .find_map(|(key, loader)| Some((loader, index.dependencies.get(key)?.clone())));
+
+    let files = index
+        .files
+        .into_iter()
+        // Files can be marked as server only
+        .filter(|file| {
+            let client = file.env.as_ref().and_then(|env| env.get("client"));
+            client.is_none_or(|client| client != "unsupported")
+        })
+        .map(|file| PackFile {
+            path: Some(file.path),
+            urls: file.downloads,
+            checksum: Checksum::from_hashes(&file.hashes, Some(file.file_size)),
+            size: Some(file.file_size),
+        })
+        .collect();
+
+    PackInfo {
+        format: PackFormat::Modrinth,
+        name: index.name,
+        version: index.version_id,
+        game_version,
+        loader,
+        files,
+        overrides: vec!["overrides".into(), "client-overrides".into()],
+    }
+}
+
+fn curseforge_info(manifest: CurseForgeManifest) -> PackInfo {
+    let loaders = &manifest.minecraft.mod_loaders;
+    let loader = loaders
+        .iter()
+        .find(|loader| loader.primary)
+        .or_else(|| loaders.first())
+        .and_then(|loader| {
+            let (name, version) = loader.id.split_once('-')?;
+            let loader = match name {
+                "fabric" => PackLoader::Fabric,
+                "quilt" => PackLoader::Quilt,
+                "forge" => PackLoader::Forge,
+                "neoforge" => PackLoader::NeoForge,
+                _ => return None,
+            };
+            Some((loader, version.to_string()))
+        });
+
+    let files = manifest
+        .files
+        .iter()
+        .filter(|file| file.required)
+        .map(|file| PackFile {
+            path: None,
+            urls: vec![format!(
+                "https://www.curseforge.com/api/v1/mods/{}/files/{}/download",
+                file.project_id, file.file_id
+            )],
+            checksum: None,
+            size: None,
+        })
+        .collect();
+
+    PackInfo {
+        format: PackFormat::CurseForge,
+        name: manifest.name,
+        version: manifest.version,
+        game_version: manifest.minecraft.version,
+        loader,
+        files,
+        overrides: vec![manifest.overrides],
+    }
+}
+
+/// Downloads every file of the pack into the instance directory and copies the overrides over it.
+/// Doesn't install the loader, that is left to the loader's installer
+pub async fn install_pack(
+    client: &reqwest::Client,
+    pack: &Path,
+    info: &PackInfo,
+    directory: &Path,
+    concurrency: usize,
+    stats: &DownloadStats,
+) -> Result<(), Error> {
+    stats.reset();
+    let known: u64 = info.files.iter().filter_map(|file| file.size).sum();
+    stats.total().store(known, Ordering::Relaxed);
+
+    tokio::fs::create_dir_all(directory).await?;
+
+    stream::iter(info.files.iter().map(Ok))
+        .try_for_each_concurrent(concurrency.max(1), |file| async move {
+            download_pack_file(client, file, directory, stats).await
+        })
+        .await?;
+
+    let reader = ZipReader::new(pack).await.map_err(zip_error)?;
+    for overrides in &info.overrides {
+        extract_folder(&reader, overrides, directory).await?;
+    }
+
+    Ok(())
+}
+
+async fn download_pack_file(
+    client: &reqwest::Client,
+    file: &PackFile,
+    directory: &Path,
+    stats: &DownloadStats,
+) -> Result<(), Error> {
+    // Files an interrupted install already downloaded are kept
+    if let (Some(path), Some(checksum)) = (&file.path, &file.checksum) {
+        let path = directory.join(safe_relative(path)?);
+        if let Ok(bytes) = tokio::fs::read(&path).await {
+            if checksum.matches(&bytes) {
+                stats
+                    .finished()
+                    .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+                return Ok(());
+            }
+        }
+    }
+
+    let mut last_error = None;
+
+    // Modrinth lists mirrors, the first that works is used
+    for url in &file.urls {
+        let response = match http::send(client.get(url))
+            .await
+            .and_then(|r| Ok(r.error_for_status()?))
+        {
+            Ok(response) => response,
+            Err(e) => {
+                last_error = Some(e);
+                continue;
+            }
+        };
+
+        if file.size.is_none() {
+            if let Some(len) = response.content_length() {
+                stats.total().fetch_add(len, Ordering::Relaxed);
+            }
+        }
+
+        let path = match &file.path {
+            Some(path) => path.clone(),
+            // CurseForge redirects to the file itself, which has the real name
+            None => {
+                let name = response
+                    .url()
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .unwrap_or_default();
+                format!("mods/{}", urlencoding_decode(name))
+            }
+        };
+        let path = directory.join(safe_relative(&path)?);
+
+        let bytes = response.bytes().await?;
+        if let Some(checksum) = &file.checksum {
+            if !checksum.matches(&bytes) {
+                last_error = Some(invalid_data(format!("{url} does not match its {checksum}")));
+                continue;
+            }
+        }
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, &bytes).await?;
+        stats
+            .finished()
+            .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+
+        return Ok(());
+    }
+
+    Err(last_error.unwrap_or_else(|| invalid_data("A pack file has no download urls")))
+}
+
+async fn read_entry(reader: &ZipReader, name: &str) -> Result<Option<Vec<u8>>, Error> {
+    let entries = reader.file().entries();
+    let Some(idx) = entries
+        .iter()
+        .position(|entry| entry.filename().as_str().ok() == Some(name))
+    else {
+        return Ok(None);
+    };
+
+    let mut buffer = Vec::with_capacity(entries[idx].uncompressed_size() as usize);
+    let mut entry = reader
+        .reader_with_entry(idx)
+        .await
+        .map_err(zip_error)?
+        .compat();
+    tokio::io::copy(&mut entry, &mut buffer).await?;
+
+    Ok(Some(buffer))
+}
+
+/// Copies everything under `folder/` in the zip into the directory, keeping the structure
+pub(crate) async fn extract_folder(
+    reader: &ZipReader,
+    folder: &str,
+    directory: &Path,
+) -> Result<(), Error> {
+    let prefix = format!("{folder}/");
+
+    for (idx, entry) in reader.file().entries().iter().enumerate() {
+        let Ok(name) = entry.filename().as_str() else {
+            continue;
+        };
+        let Some(relative) = name.strip_prefix(&prefix) else {
+            continue;
+        };
+        if relative.is_empty() || entry.dir().map_err(zip_error)? {
+            continue;
+        }
+
+        let path = directory.join(safe_relative(relative)?);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut entry = reader
+            .reader_with_entry(idx)
+            .await
+            .map_err(zip_error)?
+            .compat();
+        let mut file = tokio::fs::File::create(&path).await?;
+        tokio::io::copy(&mut entry, &mut file).await?;
+    }
+
+    Ok(())
+}
+
+/// Pack paths come from whoever made the pack, so they can't leave the instance directory
+pub(crate) fn safe_relative(path: &str) -> Result<PathBuf, Error> {
+    let path = Path::new(path);
+    let safe = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+
+    if safe && path.components().next().is_some() {
+        Ok(path.to_path_buf())
+    } else {
+        Err(invalid_data(format!(
+            "{} is outside the instance",
+            path.display()
+        )))
+    }
+}
+
+/// File names in urls are percent encoded, e.g. spaces become %20
+fn urlencoding_decode(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
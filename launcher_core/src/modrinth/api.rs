@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+use super::types;
+use crate::checksum;
+use crate::http::{self, USER_AGENT};
+
+const API_URL: &str = "https://api.modrinth.com/v2";
+
+/// Searches mods that have a version for both the loader and game version,
+/// loaders use Modrinth's names, e.g. `fabric`
+pub async fn search_mods(
+    client: &reqwest::Client,
+    query: &str,
+    loader: &str,
+    game_version: &str,
+    offset: u32,
+) -> Result<types::SearchResponse, crate::Error> {
+    let facets = serde_json::to_string(&[
+        [format!("categories:{loader}")],
+        [format!("versions:{game_version}")],
+        ["project_type:mod".to_string()],
+    ])?;
+
+    let request = client
+        .get(format!("{API_URL}/search"))
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .query(&[
+            ("query", query),
+            ("facets", &facets),
+            ("offset", &offset.to_string()),
+        ]);
+    Ok(http::send(request)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Versions of a project that support the loader and game version, newest first
+pub async fn project_versions(
+    client: &reqwest::Client,
+    project_id: &str,
+    loader: &str,
+    game_version: &str,
+) -> Result<Vec<types::ProjectVersion>, crate::Error> {
+    let loaders = serde_json::to_string(&[loader])?;
+    let game_versions = serde_json::to_string(&[game_version])?;
+
+    let request = client
+        .get(format!("{API_URL}/project/{project_id}/version"))
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .query(&[("loaders", loaders), ("game_versions", game_versions)]);
+    Ok(http::send(request)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Downloads the file into the directory, returning where it was written.
+/// Files that don't match their sha1 are not written
+pub async fn download_file(
+    client: &reqwest::Client,
+    file: &types::VersionFile,
+    directory: &Path,
+) -> Result<PathBuf, crate::Error> {
+    let request = client
+        .get(&file.url)
+        .header(reqwest::header::USER_AGENT, USER_AGENT);
+    let bytes = http::send(request)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    if checksum::sha1(&bytes) != file.hashes.sha1 {
+        return Err(tokio::io::Error::new(
+            tokio::io::ErrorKind::InvalidData,
+            format!("{} does not match its sha1", file.filename),
+        )
+        .into());
+    }
+
+    // Modrinth file names are user provided, so only the name itself is kept
+    let name = Path::new(&file.filename).file_name().ok_or_else(|| {
+        tokio::io::Error::new(tokio::io::ErrorKind::InvalidData, "Invalid file name")
+    })?;
+
+    tokio::fs::create_dir_all(directory).await?;
+    let path = directory.join(name);
+    tokio::fs::write(&path, &bytes).await?;
+
+    Ok(path)
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SearchResponse {
+    pub hits: Vec<SearchHit>,
+    pub offset: u32,
+    pub limit: u32,
+    pub total_hits: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SearchHit {
+    pub project_id: String,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub author: String,
+    pub downloads: u64,
+    pub icon_url: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ProjectVersion {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub version_number: String,
+    pub files: Vec<VersionFile>,
+}
+
+impl ProjectVersion {
+    /// The file Modrinth marks as primary, or the first one if none is
+    pub fn primary_file(&self) -> Option<&VersionFile> {
+        self.files
+            .iter()
+            .find(|file| file.primary)
+            .or_else(|| self.files.first())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct VersionFile {
+    pub url: String,
+    pub filename: String,
+    pub primary: bool,
+    pub size: u64,
+    pub hashes: FileHashes,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct FileHashes {
+    pub sha1: String,
+    pub sha512: String,
+}
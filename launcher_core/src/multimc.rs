@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+use serde::Deserialize;
+
+use crate::modpack::PackLoader;
+use crate::progress::DownloadStats;
+use crate::Error;
+
+/// An instance found in a MultiMC or Prism Launcher folder
+#[derive(Debug, Clone)]
+pub struct MultiMcInstance {
+    /// The instance's own folder, holding `instance.cfg`
+    pub directory: PathBuf,
+    /// `minecraft` or `.minecraft` inside the instance, None if it was never launched
+    pub game_dir: Option<PathBuf>,
+    pub name: String,
+    pub game_version: String,
+    /// The loader and its version, None for vanilla instances
+    pub loader: Option<(PackLoader, String)>,
+    /// Only set when the instance overrides the launcher's arguments
+    pub jvm_args: Vec<String>,
+    /// Minimum and maximum heap in MiB, only set when the instance overrides them
+    pub memory: Option<(u32, u32)>,
+    pub group: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MmcPack {
+    components: Vec<Component>,
+}
+
+#[derive(Deserialize)]
+struct Component {
+    uid: String,
+    #[serde(default)]
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct InstanceGroups {
+    groups: HashMap<String, InstanceGroup>,
+}
+
+#[derive(Deserialize)]
+struct InstanceGroup {
+    #[serde(default)]
+    instances: Vec<String>,
+}
+
+/// Lists the instances of a MultiMC or Prism folder, `root` can be the launcher's folder
+/// or the `instances` folder in it. Folders that aren't instances are skipped
+pub async fn find_instances(root: &Path) -> Result<Vec<MultiMcInstance>, Error> {
+    let instances_dir = if tokio::fs::try_exists(root.join("instances")).await? {
+        root.join("instances")
+    } else {
+        root.to_path_buf()
+    };
+
+    // Groups are optional, and only change how the instances are listed
+    let groups = match tokio::fs::read(instances_dir.join("instgroups.json")).await {
+        Ok(buf) => serde_json::from_slice::<InstanceGroups>(&buf)
+            .map(|groups| groups.groups)
+            .unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    };
+
+    let mut instances = Vec::new();
+    let mut entries = tokio::fs::read_dir(&instances_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let directory = entry.path();
+        let (Ok(cfg), Ok(pack)) = (
+            tokio::fs::read_to_string(directory.join("instance.cfg")).await,
+            tokio::fs::read(directory.join("mmc-pack.json")).await,
+        ) else {
+            continue;
+        };
+        let Ok(pack) = serde_json::from_slice::<MmcPack>(&pack) else {
+            continue;
+        };
+
+        let id = entry.file_name().to_string_lossy().to_string();
+        let group = groups
+            .iter()
+            .find(|(_, group)| group.instances.contains(&id))
+            .map(|(name, _)| name.clone());
+
+        if let Some(mut instance) = parse_instance(directory.clone(), &cfg, pack) {
+            instance.group = group;
+            for name in ["minecraft", ".minecraft"] {
+                if tokio::fs::try_exists(directory.join(name)).await? {
+                    instance.game_dir = Some(directory.join(name));
+                    break;
+                }
+            }
+            instances.push(instance);
+        }
+    }
+
+    instances.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(instances)
+}
+
+/// Copies the instance's game folder into `destination`, the original is left as it was
+pub async fn copy_instance(
+    instance: &MultiMcInstance,
+    destination: &Path,
+    stats: &DownloadStats,
+) -> Result<(), Error> {
+    stats.reset();
+    tokio::fs::create_dir_all(destination).await?;
+
+    let Some(game_dir) = &instance.game_dir else {
+        return Ok(());
+    };
+
+    let files = list_files(game_dir).await?;
+    let total = files.iter().map(|(_, size)| size).sum();
+    stats.total().store(total, Ordering::Relaxed);
+
+    for (relative, size) in files {
+        let target = destination.join(&relative);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::copy(game_dir.join(&relative), &target).await?;
+        stats.finished().fetch_add(size, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// Every file under `dir`, relative to it, with its size
+pub(crate) async fn list_files(dir: &Path) -> Result<Vec<(PathBuf, u64)>, Error> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+
+    while let Some(relative) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(dir.join(&relative)).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            let path = relative.join(entry.file_name());
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                files.push((path, entry.metadata().await?.len()));
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn parse_instance(directory: PathBuf, cfg: &str, pack: MmcPack) -> Option<MultiMcInstance> {
+    let cfg = parse_cfg(cfg);
+    let enabled = |key: &str| cfg.get(key).is_some_and(|value| *value == "true");
+
+    let mut game_version = None;
+    let mut loader = None;
+    for component in pack.components {
+        let kind = match component.uid.as_str() {
+            "net.minecraft" => {
+                game_version = Some(component.version);
+                continue;
+            }
+            "net.fabricmc.fabric-loader" => PackLoader::Fabric,
+            "org.quiltmc.quilt-loader" => PackLoader::Quilt,
+            "net.minecraftforge" => PackLoader::Forge,
+            "net.neoforged" => PackLoader::NeoForge,
+            _ => continue,
+        };
+        loader = Some((kind, component.version));
+    }
+
+    let jvm_args = match cfg.get("JvmArgs") {
+        Some(args) if enabled("OverrideJavaArgs") => {
+            args.split_whitespace().map(String::from).collect()
+        }
+        _ => Vec::new(),
+    };
+
+    let memory = enabled("OverrideMemory").then(|| {
+        let size = |key: &str| cfg.get(key).and_then(|value| value.parse::<u32>().ok());
+        (
+            size("MinMemAlloc").unwrap_or(0),
+            size("MaxMemAlloc").unwrap_or(0),
+        )
+    });
+
+    Some(MultiMcInstance {
+        name: cfg.get("name").map_or_else(
+            || {
+                directory
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            },
+            |name| name.to_string(),
+        ),
+        directory,
+        game_dir: None,
+        game_version: game_version?,
+        loader,
+        jvm_args,
+        memory,
+        group: None,
+    })
+}
+
+/// `instance.cfg` is an ini file, Prism puts everything under `[General]`
+fn parse_cfg(cfg: &str) -> HashMap<&str, &str> {
+    cfg.lines()
+        .filter(|line| !line.starts_with('['))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((key.trim(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_instance, MmcPack};
+    use crate::modpack::PackLoader;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_instance() {
+        let cfg = "[General]\nname=\"Fabulously Optimized\"\nOverrideMemory=true\n\
+                   MaxMemAlloc=4096\nMinMemAlloc=512\nJvmArgs=-XX:+UseG1GC\n";
+        let pack = r#"{"components": [
+            {"uid": "net.minecraft", "version": "1.20.1"},
+            {"uid": "net.fabricmc.fabric-loader", "version": "0.15.3"}
+        ], "formatVersion": 1}"#;
+        let pack: MmcPack = serde_json::from_str(pack).unwrap();
+
+        let instance = parse_instance(PathBuf::from("fo"), cfg, pack).unwrap();
+        assert_eq!(instance.name, "Fabulously Optimized");
+        assert_eq!(instance.game_version, "1.20.1");
+        assert_eq!(
+            instance.loader,
+            Some((PackLoader::Fabric, "0.15.3".to_string()))
+        );
+        assert_eq!(instance.memory, Some((512, 4096)));
+        // Not overridden, so the args are left to the launcher
+        assert!(instance.jvm_args.is_empty());
+    }
+}
@@ -0,0 +1,432 @@
+use crate::error::invalid_data;
+use crate::Error;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+/// Minecraft refuses deeper files too, so a broken one can't overflow the stack
+const MAX_DEPTH: usize = 512;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+pub type Compound = BTreeMap<String, Tag>;
+
+/// A tag of the Named Binary Tag format that level.dat, servers.dat and most of the game's
+/// other files use
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// Every element has the same type, [`Nbt::write`] fails otherwise
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    /// The tag at the keys, each one in the compound the last one found
+    pub fn get(&self, path: &[&str]) -> Option<&Tag> {
+        path.iter()
+            .try_fold(self, |tag, key| tag.as_compound()?.get(*key))
+    }
+
+    /// Bytes are also used for booleans, anything but 0 is true
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_i8().map(|byte| byte != 0)
+    }
+
+    pub fn as_i8(&self) -> Option<i8> {
+        match self {
+            Tag::Byte(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Any of the integer tags that fit
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Tag::Byte(value) => Some((*value).into()),
+            Tag::Short(value) => Some((*value).into()),
+            Tag::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Any of the integer tags
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Long(value) => Some(*value),
+            _ => self.as_i32().map(i64::from),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Tag::Float(value) => Some((*value).into()),
+            Tag::Double(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<Tag>> {
+        match self {
+            Tag::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Tag::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound_mut(&mut self) -> Option<&mut Compound> {
+        match self {
+            Tag::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+}
+
+/// A whole file, the root compound and its name, which is usually empty
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Nbt {
+    pub name: String,
+    pub root: Tag,
+}
+
+impl Default for Tag {
+    fn default() -> Self {
+        Tag::Compound(Compound::new())
+    }
+}
+
+impl Nbt {
+    /// Gzipped files like level.dat are decompressed first, servers.dat isn't compressed.
+    /// Strings are read as UTF-8, the few characters Java writes differently come out
+    /// replaced
+    pub fn read(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decompressed = Vec::new();
+        let mut reader = if bytes.starts_with(&GZIP_MAGIC) {
+            flate2::read::GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+            Reader(&decompressed)
+        } else {
+            Reader(bytes)
+        };
+
+        if reader.u8()? != 10 {
+            return Err(invalid_data("The NBT doesn't start with a compound"));
+        }
+        let name = reader.string()?;
+        let root = reader.payload(10, 0)?;
+        Ok(Self { name, root })
+    }
+
+    /// Gzipped like level.dat, or not like servers.dat
+    pub fn write(&self, gzip: bool) -> Result<Vec<u8>, Error> {
+        if !matches!(self.root, Tag::Compound(_)) {
+            return Err(invalid_data("The NBT root has to be a compound"));
+        }
+        let mut buf = vec![10];
+        write_string(&mut buf, &self.name);
+        write_payload(&mut buf, &self.root)?;
+
+        if !gzip {
+            return Ok(buf);
+        }
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&buf)?;
+        Ok(encoder.finish()?)
+    }
+
+    pub fn read_file(path: &std::path::Path) -> Result<Self, Error> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+        Self::read(&bytes)
+    }
+
+    /// Written next to the file first, so a crash can't leave half of it
+    pub fn write_file(&self, path: &std::path::Path, gzip: bool) -> Result<(), Error> {
+        let temp = path.with_extension("dat_tmp");
+        std::fs::write(&temp, self.write(gzip)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let bytes = self.bytes(N)?;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&[u8], Error> {
+        if self.0.len() < len {
+            return Err(invalid_data("The NBT ends in the middle of a tag"));
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_be_bytes(self.take()?))
+    }
+
+    /// Lengths of arrays and lists, negative ones are empty
+    fn len(&mut self) -> Result<usize, Error> {
+        Ok(self.i32()?.max(0) as usize)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let len = u16::from_be_bytes(self.take()?) as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+
+    /// Reads `len` numbers of `N` bytes each
+    fn array<const N: usize, T>(&mut self, from: fn([u8; N]) -> T) -> Result<Vec<T>, Error> {
+        let len = self.len()?;
+        // Checked first so a broken length can't allocate more than the file has
+        let bytes = self.bytes(len.saturating_mul(N))?;
+        Ok(bytes
+            .chunks_exact(N)
+            .map(|chunk| from(chunk.try_into().unwrap()))
+            .collect())
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, Error> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_data("The NBT is nested too deep"));
+        }
+        let tag = match id {
+            1 => Tag::Byte(i8::from_be_bytes(self.take()?)),
+            2 => Tag::Short(i16::from_be_bytes(self.take()?)),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(i64::from_be_bytes(self.take()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.take()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.take()?)),
+            7 => Tag::ByteArray(self.array(i8::from_be_bytes)?),
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element = self.u8()?;
+                let len = self.len()?;
+                // Every element is at least a byte, more than that can't be in the file
+                if len > self.0.len() && element != 0 {
+                    return Err(invalid_data("The NBT ends in the middle of a list"));
+                }
+                let mut list = Vec::new();
+                if element != 0 {
+                    for _ in 0..len {
+                        list.push(self.payload(element, depth + 1)?);
+                    }
+                }
+                Tag::List(list)
+            }
+            10 => {
+                let mut compound = Compound::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    compound.insert(name, self.payload(id, depth + 1)?);
+                }
+                Tag::Compound(compound)
+            }
+            11 => Tag::IntArray(self.array(i32::from_be_bytes)?),
+            12 => Tag::LongArray(self.array(i64::from_be_bytes)?),
+            _ => return Err(invalid_data(format!("{id} isn't an NBT tag"))),
+        };
+        Ok(tag)
+    }
+}
+
+fn write_string(buf: &mut Vec<u8>, string: &str) {
+    // Longer strings can't be written, the game would cut them too
+    let mut len = string.len().min(u16::MAX as usize);
+    while !string.is_char_boundary(len) {
+        len -= 1;
+    }
+    buf.extend_from_slice(&(len as u16).to_be_bytes());
+    buf.extend_from_slice(&string.as_bytes()[..len]);
+}
+
+fn write_len(buf: &mut Vec<u8>, len: usize) -> Result<(), Error> {
+    let len = i32::try_from(len).map_err(|_| invalid_data("The NBT array is too long"))?;
+    buf.extend_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+fn write_payload(buf: &mut Vec<u8>, tag: &Tag) -> Result<(), Error> {
+    match tag {
+        Tag::Byte(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Short(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Int(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Long(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Float(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Double(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::ByteArray(values) => {
+            write_len(buf, values.len())?;
+            buf.extend(values.iter().map(|value| *value as u8));
+        }
+        Tag::String(value) => write_string(buf, value),
+        Tag::List(list) => {
+            // Empty lists are written without an element type, like the game does
+            let element = list.first().map_or(0, Tag::id);
+            if list.iter().any(|tag| tag.id() != element) {
+                return Err(invalid_data("An NBT list has elements of different types"));
+            }
+            buf.push(element);
+            write_len(buf, list.len())?;
+            for tag in list {
+                write_payload(buf, tag)?;
+            }
+        }
+        Tag::Compound(compound) => {
+            for (name, tag) in compound {
+                buf.push(tag.id());
+                write_string(buf, name);
+                write_payload(buf, tag)?;
+            }
+            buf.push(0);
+        }
+        Tag::IntArray(values) => {
+            write_len(buf, values.len())?;
+            values
+                .iter()
+                .for_each(|value| buf.extend_from_slice(&value.to_be_bytes()));
+        }
+        Tag::LongArray(values) => {
+            write_len(buf, values.len())?;
+            values
+                .iter()
+                .for_each(|value| buf.extend_from_slice(&value.to_be_bytes()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Compound, Nbt, Tag};
+
+    fn servers_dat() -> Nbt {
+        let server = Compound::from([
+            ("name".to_string(), Tag::String("Minecraft Server".into())),
+            ("ip".to_string(), Tag::String("mc.example.com".into())),
+            ("acceptTextures".to_string(), Tag::Byte(1)),
+        ]);
+        let root = Compound::from([
+            (
+                "servers".to_string(),
+                Tag::List(vec![Tag::Compound(server)]),
+            ),
+            ("empty".to_string(), Tag::List(vec![])),
+            ("seed".to_string(), Tag::Long(-4_172_144_997_902_289_642)),
+            ("heights".to_string(), Tag::LongArray(vec![1, -2, 3])),
+            ("colors".to_string(), Tag::ByteArray(vec![-1, 0, 1])),
+            ("spawn".to_string(), Tag::IntArray(vec![0, 64, 0])),
+            ("rain".to_string(), Tag::Float(0.5)),
+        ]);
+        Nbt {
+            name: String::new(),
+            root: Tag::Compound(root),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let nbt = servers_dat();
+        for gzip in [false, true] {
+            let bytes = nbt.write(gzip).unwrap();
+            assert_eq!(bytes.starts_with(&[0x1f, 0x8b]), gzip);
+            assert_eq!(Nbt::read(&bytes).unwrap(), nbt);
+        }
+    }
+
+    #[test]
+    fn test_accessors() {
+        let nbt = servers_dat();
+        let servers = nbt.root.get(&["servers"]).and_then(Tag::as_list).unwrap();
+        assert_eq!(
+            servers[0].get(&["ip"]).and_then(Tag::as_str),
+            Some("mc.example.com")
+        );
+        assert_eq!(
+            servers[0].get(&["acceptTextures"]).and_then(Tag::as_bool),
+            Some(true)
+        );
+        assert_eq!(
+            servers[0].get(&["acceptTextures"]).and_then(Tag::as_i64),
+            Some(1)
+        );
+        assert_eq!(nbt.root.get(&["rain"]).and_then(Tag::as_f64), Some(0.5));
+        assert_eq!(nbt.root.get(&["servers", "ip"]), None);
+        assert_eq!(nbt.root.get(&[]), Some(&nbt.root));
+    }
+
+    #[test]
+    fn test_broken() {
+        let bytes = servers_dat().write(false).unwrap();
+        assert!(Nbt::read(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Nbt::read(&[8, 0, 0]).is_err());
+
+        // A list claiming more elements than the file has
+        let list = [10, 0, 0, 9, 0, 1, b'l', 1, 0x7f, 0xff, 0xff, 0xff];
+        assert!(Nbt::read(&list).is_err());
+
+        let mixed = Nbt {
+            name: String::new(),
+            root: Tag::Compound(Compound::from([(
+                "mixed".to_string(),
+                Tag::List(vec![Tag::Byte(1), Tag::Int(1)]),
+            )])),
+        };
+        assert!(mixed.write(false).is_err());
+    }
+}
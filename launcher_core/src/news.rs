@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::http;
+use crate::Error;
+
+/// The patch notes the official launcher shows
+const PATCH_NOTES_URL: &str = "https://launchercontent.mojang.com/v2/javaPatchNotes.json";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PatchNotes {
+    pub entries: Vec<PatchNote>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchNote {
+    pub id: String,
+    pub title: String,
+    /// The game version the notes are for, e.g. `1.20.4`
+    pub version: String,
+    /// `release` or `snapshot`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// RFC 3339, e.g. `2023-12-07T12:56:20.000Z`
+    #[serde(default)]
+    pub date: String,
+    /// A summary of the notes, without formatting
+    #[serde(default)]
+    pub short_text: String,
+}
+
+/// Fetches the Java Edition patch notes, newest first, and keeps a copy at `cache`.
+/// The copy is read instead when the fetch fails, so the notes still show offline
+pub async fn patch_notes(client: &reqwest::Client, cache: &Path) -> Result<PatchNotes, Error> {
+    let buf = match fetch_patch_notes(client).await {
+        Ok(buf) => {
+            tokio::fs::write(cache, &buf).await?;
+            buf
+        }
+        Err(e) => match tokio::fs::read(cache).await {
+            Ok(buf) => buf,
+            Err(_) => return Err(e),
+        },
+    };
+
+    parse_patch_notes(&buf)
+}
+
+async fn fetch_patch_notes(client: &reqwest::Client) -> Result<Vec<u8>, Error> {
+    let request = client.get(PATCH_NOTES_URL);
+    let bytes = http::send(request)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(bytes.to_vec())
+}
+
+fn parse_patch_notes(buf: &[u8]) -> Result<PatchNotes, Error> {
+    let mut notes: PatchNotes = serde_json::from_slice(buf)?;
+    // RFC 3339 dates in the same offset sort as text
+    notes.entries.sort_by(|a, b| b.date.cmp(&a.date));
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_patch_notes;
+
+    #[test]
+    fn test_parse_patch_notes() {
+        let json = r#"{"version": 1, "entries": [
+            {"title": "Minecraft: Java Edition 1.20.3", "type": "release", "version": "1.20.3",
+             "image": {"title": "1.20.3", "url": "/images/1.20.3.jpg"},
+             "contentPath": "1.20.3.json", "id": "1.20.3", "date": "2023-12-05T12:00:00.000Z",
+             "shortText": "Bug fixes"},
+            {"title": "Minecraft: Java Edition 1.20.4", "type": "release", "version": "1.20.4",
+             "id": "1.20.4", "date": "2023-12-07T12:56:20.000Z", "shortText": "A fix"}
+        ]}"#;
+
+        let notes = parse_patch_notes(json.as_bytes()).unwrap();
+        assert_eq!(notes.entries[0].id, "1.20.4");
+        assert_eq!(notes.entries[1].short_text, "Bug fixes");
+        assert_eq!(notes.entries[1].kind, "release");
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use futures::{stream, StreamExt, TryStreamExt};
+
+use crate::checksum::{sha1, Checksum};
+use crate::types::{AssetIndex, AssetIndexJson, VersionJson};
+use crate::{file_matches, http, java, AsyncLauncher, Error};
+
+/// The folders a version is downloaded into, every frontend lays them out like
+/// [`Directories::new`]
+#[derive(Debug, Clone)]
+pub struct Directories {
+    pub versions: PathBuf,
+    pub assets: PathBuf,
+    pub libraries: PathBuf,
+    /// None if the game runs on a Java that isn't downloaded by the launcher
+    pub runtimes: Option<PathBuf>,
+}
+
+impl Directories {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            versions: root.join("versions"),
+            assets: root.join("assets"),
+            libraries: root.join("libraries"),
+            runtimes: Some(root.join("runtimes")),
+        }
+    }
+}
+
+/// Files that would be downloaded and their size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Planned {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl Planned {
+    pub fn add(&mut self, bytes: u64) {
+        self.files += 1;
+        self.bytes += bytes;
+    }
+
+    fn and(self, other: Planned) -> Planned {
+        Planned {
+            files: self.files + other.files,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
+
+/// What [`AsyncLauncher::plan_downloads`] found missing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DownloadPlan {
+    /// The asset index too, if it isn't there
+    pub assets: Planned,
+    pub libraries: Planned,
+    pub jar: Planned,
+    pub runtime: Planned,
+}
+
+impl DownloadPlan {
+    pub fn total(&self) -> Planned {
+        self.assets
+            .and(self.libraries)
+            .and(self.jar)
+            .and(self.runtime)
+    }
+
+    /// Everything is already downloaded
+    pub fn is_empty(&self) -> bool {
+        self.total().files == 0
+    }
+}
+
+pub(crate) async fn plan(
+    launcher: &AsyncLauncher,
+    json: &VersionJson,
+    dirs: &Directories,
+) -> Result<DownloadPlan, Error> {
+    let concurrency = launcher.concurrency();
+    let mut plan = DownloadPlan::default();
+
+    // Libraries are checked the same way downloading them does
+    let mut paths = HashSet::new();
+    let libraries = json.libraries.iter().filter_map(|library| {
+        let artifact = library.downloads.as_ref()?;
+        if !library.rule.apply() || !paths.insert(&artifact.path) {
+            return None;
+        }
+        let checksum = Checksum::Sha1(artifact.sha1.clone());
+        Some((dirs.libraries.join(&artifact.path), artifact.size, checksum))
+    });
+    plan.libraries = missing(libraries.collect(), concurrency).await?;
+
+    let client = &json.downloads.client;
+    let jar = dirs
+        .versions
+        .join(&json.id)
+        .join(format!("{}.jar", json.id));
+    let checksum = Checksum::Sha1(client.sha1.clone());
+    plan.jar = missing(vec![(jar, client.size, checksum)], concurrency).await?;
+
+    let index = asset_index(launcher, &json.asset_index, &dirs.assets, &mut plan.assets).await?;
+    // Objects can share a hash, they're the same file
+    let mut hashes = HashSet::new();
+    let objects = index.objects.values().filter_map(|object| {
+        if !hashes.insert(&object.hash) {
+            return None;
+        }
+        let path = dirs.assets.join("objects").join(&object.hash[0..=1]);
+        let checksum = Checksum::Sha1(object.hash.clone());
+        Some((path.join(&object.hash), object.size, checksum))
+    });
+    plan.assets = plan
+        .assets
+        .and(missing(objects.collect(), concurrency).await?);
+
+    if let Some(runtimes) = &dirs.runtimes {
+        let component = match &json.java_version {
+            Some(java_version) => java_version.component.as_str(),
+            None => java::component_for_major(8),
+        };
+        plan.runtime = java::plan_runtime(launcher.client(), component, runtimes).await?;
+    }
+
+    Ok(plan)
+}
+
+/// The index from the assets folder if it's there and whole, otherwise it's downloaded into
+/// memory and counted
+async fn asset_index(
+    launcher: &AsyncLauncher,
+    asset_index: &AssetIndex,
+    assets: &Path,
+    planned: &mut Planned,
+) -> Result<AssetIndexJson, Error> {
+    let file = assets
+        .join("indexes")
+        .join(format!("{}.json", asset_index.id));
+    match tokio::fs::read(&file).await {
+        Ok(buf) if sha1(&buf) == asset_index.sha1 => return Ok(serde_json::from_slice(&buf)?),
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    let request = launcher.client().get(&asset_index.url);
+    let buf = http::send(request)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    planned.add(buf.len() as u64);
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+/// The files that don't exist or don't match their checksum
+async fn missing(
+    files: Vec<(PathBuf, u64, Checksum)>,
+    concurrency: usize,
+) -> Result<Planned, Error> {
+    stream::iter(files)
+        .map(|(path, size, checksum)| async move {
+            let matches = file_matches(&path, size, &checksum).await?;
+            Ok::<_, Error>((!matches).then_some(size))
+        })
+        .buffer_unordered(concurrency)
+        .try_fold(Planned::default(), |mut planned, size| async move {
+            if let Some(size) = size {
+                planned.add(size);
+            }
+            Ok(planned)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{missing, DownloadPlan, Planned};
+    use crate::checksum::{sha1, Checksum};
+
+    #[tokio::test]
+    async fn test_missing() {
+        let dir = std::env::temp_dir().join("synth_plan_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("whole.jar"), "whole").unwrap();
+        std::fs::write(dir.join("broken.jar"), "brokem").unwrap();
+
+        let files = vec![
+            (dir.join("whole.jar"), 5, Checksum::Sha1(sha1(b"whole"))),
+            (dir.join("broken.jar"), 6, Checksum::Sha1(sha1(b"broken"))),
+            (dir.join("absent.jar"), 7, Checksum::Sha1(sha1(b"absent"))),
+        ];
+        let planned = missing(files, 2).await.unwrap();
+        assert_eq!(
+            planned,
+            Planned {
+                files: 2,
+                bytes: 13
+            }
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_total() {
+        let mut plan = DownloadPlan::default();
+        assert!(plan.is_empty());
+        plan.assets.add(100);
+        plan.assets.add(20);
+        plan.jar.add(3);
+        assert_eq!(
+            plan.total(),
+            Planned {
+                files: 3,
+                bytes: 123
+            }
+        );
+        assert!(!plan.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How far back samples are kept when calculating the current speed
+const SPEED_WINDOW: Duration = Duration::from_secs(3);
+
+/// Byte counts for a download, updated by the download and read by whoever shows progress.
+/// The atomics are passed to the download functions, e.g. [`crate::AsyncLauncher::download_jar`]
+#[derive(Default)]
+pub struct DownloadStats {
+    total: AtomicU64,
+    finished: AtomicU64,
+    samples: Mutex<VecDeque<(Instant, u64)>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    pub total: u64,
+    pub finished: u64,
+    /// Averaged over the last few seconds
+    pub bytes_per_second: f64,
+    /// None while nothing is being downloaded
+    pub eta: Option<Duration>,
+}
+
+impl Snapshot {
+    /// Between 0 and 1, a download without a known size counts as empty
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.finished as f64 / self.total as f64).min(1.0) as f32
+        }
+    }
+}
+
+impl DownloadStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn total(&self) -> &AtomicU64 {
+        &self.total
+    }
+
+    pub fn finished(&self) -> &AtomicU64 {
+        &self.finished
+    }
+
+    /// Clears the counts and speed history before reusing the stats for another download
+    pub fn reset(&self) {
+        self.total.store(0, Ordering::Relaxed);
+        self.finished.store(0, Ordering::Relaxed);
+        self.samples.lock().unwrap().clear();
+    }
+
+    /// Reads the current counts, and records them to calculate the speed.
+    /// The speed is only as accurate as this is called often
+    pub fn snapshot(&self) -> Snapshot {
+        let total = self.total.load(Ordering::Relaxed);
+        let finished = self.finished.load(Ordering::Relaxed);
+        let now = Instant::now();
+
+        let mut samples = self.samples.lock().unwrap();
+        samples.push_back((now, finished));
+        while let Some(&(time, _)) = samples.front() {
+            if now.duration_since(time) > SPEED_WINDOW && samples.len() > 2 {
+                samples.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let bytes_per_second = match (samples.front(), samples.back()) {
+            (Some(&(start, from)), Some(&(end, to))) if end > start => {
+                to.saturating_sub(from) as f64 / end.duration_since(start).as_secs_f64()
+            }
+            _ => 0.0,
+        };
+
+        let eta = if bytes_per_second > 0.0 && finished < total {
+            Some(Duration::from_secs_f64(
+                (total - finished) as f64 / bytes_per_second,
+            ))
+        } else {
+            None
+        };
+
+        Snapshot {
+            total,
+            finished,
+            bytes_per_second,
+            eta,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// Disabled packs get this suffix, the game doesn't list them until it's removed
+const DISABLED: &str = ".disabled";
+
+/// A zip or folder in an instance's resourcepacks folder
+#[derive(Debug, Clone)]
+pub struct ResourcePack {
+    pub path: PathBuf,
+    /// The file or folder name, without the disabled suffix
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// Resource packs in the folder, sorted by name. A missing folder has no packs
+pub fn list_packs(directory: &Path) -> Result<Vec<ResourcePack>, Error> {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut packs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let (name, enabled) = pack_name(&file_name);
+        // Folder packs are unpacked zips, anything else in the folder isn't a pack
+        if !name.ends_with(".zip") && !entry.file_type()?.is_dir() {
+            continue;
+        }
+        packs.push(ResourcePack {
+            name: name.to_string(),
+            enabled,
+            path: entry.path(),
+        });
+    }
+
+    packs.sort_by_key(|pack| pack.name.to_lowercase());
+    Ok(packs)
+}
+
+/// Renames the pack to enable or disable it, returning it with its new path
+pub fn set_enabled(pack: &ResourcePack, enabled: bool) -> Result<ResourcePack, Error> {
+    let file_name = if enabled {
+        pack.name.clone()
+    } else {
+        format!("{}{DISABLED}", pack.name)
+    };
+    let path = pack.path.with_file_name(file_name);
+    std::fs::rename(&pack.path, &path)?;
+
+    Ok(ResourcePack {
+        path,
+        name: pack.name.clone(),
+        enabled,
+    })
+}
+
+pub fn remove_pack(pack: &ResourcePack) -> Result<(), Error> {
+    if pack.path.is_dir() {
+        std::fs::remove_dir_all(&pack.path)?;
+    } else {
+        std::fs::remove_file(&pack.path)?;
+    }
+    Ok(())
+}
+
+/// The pack's name and whether it's enabled
+fn pack_name(file_name: &str) -> (&str, bool) {
+    match file_name.strip_suffix(DISABLED) {
+        Some(name) => (name, false),
+        None => (file_name, true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pack_name;
+
+    #[test]
+    fn test_pack_name() {
+        assert_eq!(pack_name("Faithful.zip"), ("Faithful.zip", true));
+        assert_eq!(pack_name("Faithful.zip.disabled"), ("Faithful.zip", false));
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_zip::tokio::write::ZipFileWriter;
+use serde::{Deserialize, Serialize};
+
+use crate::archives::{add_folder, CompressionLevel};
+use crate::error::{invalid_data, zip_error, ZipReader};
+use crate::modpack::{extract_folder, safe_relative};
+use crate::Error;
+
+/// A world in an instance's saves folder
+#[derive(Debug, Clone)]
+pub struct World {
+    pub directory: PathBuf,
+    /// The folder name, the name shown in game is stored in level.dat
+    pub name: String,
+    /// When level.dat was last written, the game saves it when the world is closed
+    pub last_played: Option<SystemTime>,
+}
+
+/// A zip made by [`backup_world`]
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// The folder name of the world it was made from
+    pub world: String,
+    pub created: SystemTime,
+}
+
+/// How many backups of each world [`prune_backups`] keeps, the newest one is always kept.
+/// Days and weeks are in UTC, weeks start on Monday
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Retention {
+    /// The newest backup of each of the last days that have one
+    pub daily: usize,
+    /// The newest backup of each of the last weeks that have one, a backup can count for
+    /// both its day and its week
+    pub weekly: usize,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            daily: 7,
+            weekly: 4,
+        }
+    }
+}
+
+/// How [`backup_played_worlds`] backs up worlds, and which backups it keeps afterwards
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BackupPolicy {
+    pub retention: Retention,
+    pub compression: CompressionLevel,
+}
+
+/// Worlds in the saves folder, most recently played first. A missing folder has no worlds
+pub fn list_worlds(saves_dir: &Path) -> Result<Vec<World>, Error> {
+    let entries = match std::fs::read_dir(saves_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut worlds = Vec::new();
+    for entry in entries {
+        let directory = entry?.path();
+        let Ok(level) = std::fs::metadata(directory.join("level.dat")) else {
+            continue;
+        };
+        worlds.push(World {
+            name: directory
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            last_played: level.modified().ok(),
+            directory,
+        });
+    }
+
+    worlds.sort_by_key(|world| std::cmp::Reverse(world.last_played));
+    Ok(worlds)
+}
+
+/// Backups in the folder, newest first. Zips that weren't made by [`backup_world`] are skipped
+pub fn list_backups(backups_dir: &Path) -> Result<Vec<Backup>, Error> {
+    let entries = match std::fs::read_dir(backups_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut backups = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("zip") {
+            continue;
+        }
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let Some((world, secs)) = parse_backup_name(&stem) else {
+            continue;
+        };
+        backups.push(Backup {
+            world,
+            created: UNIX_EPOCH + Duration::from_secs(secs),
+            path,
+        });
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+    Ok(backups)
+}
+
+/// Zips the world into `backups_dir`, named after the world and the time. The world's
+/// folder is the top folder in the zip, like in the game's own backups
+pub async fn backup_world(
+    world: &Path,
+    backups_dir: &Path,
+    compression: CompressionLevel,
+) -> Result<PathBuf, Error> {
+    let name = world
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    tokio::fs::create_dir_all(backups_dir).await?;
+    let path = backups_dir.join(format!("{name}-{secs}.zip"));
+
+    let mut writer = ZipFileWriter::with_tokio(tokio::fs::File::create(&path).await?);
+    add_folder(&mut writer, world, &name, compression).await?;
+    writer.close().await.map_err(zip_error)?;
+
+    Ok(path)
+}
+
+/// Backs up the worlds that were played since their newest backup, then removes the backups
+/// the retention doesn't keep. Meant to run after the game exits, returns the new backups
+pub async fn backup_played_worlds(
+    saves_dir: &Path,
+    backups_dir: &Path,
+    policy: &BackupPolicy,
+) -> Result<Vec<PathBuf>, Error> {
+    let backups = list_backups(backups_dir)?;
+
+    let mut made = Vec::new();
+    for world in list_worlds(saves_dir)? {
+        // Newest first, so this is the last backup of the world
+        let newest = backups
+            .iter()
+            .find(|backup| backup.world == world.name)
+            .map(|backup| backup.created);
+        if newest.is_some() && world.last_played <= newest {
+            continue;
+        }
+        made.push(backup_world(&world.directory, backups_dir, policy.compression).await?);
+    }
+
+    prune_backups(backups_dir, policy.retention)?;
+    Ok(made)
+}
+
+/// Removes the backups in the folder the retention doesn't keep, returns the removed ones
+pub fn prune_backups(backups_dir: &Path, retention: Retention) -> Result<Vec<PathBuf>, Error> {
+    let backups = list_backups(backups_dir)?;
+    let mut removed = Vec::new();
+    for backup in expired(&backups, retention) {
+        std::fs::remove_file(&backup.path)?;
+        removed.push(backup.path.clone());
+    }
+    Ok(removed)
+}
+
+/// The backups the retention doesn't keep, each world is counted on its own
+pub fn expired(backups: &[Backup], retention: Retention) -> Vec<&Backup> {
+    let mut worlds: HashMap<&str, Vec<&Backup>> = HashMap::new();
+    for backup in backups {
+        worlds.entry(&backup.world).or_default().push(backup);
+    }
+
+    let mut expired = Vec::new();
+    for mut backups in worlds.into_values() {
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+
+        let mut days = Vec::new();
+        let mut weeks = Vec::new();
+        for (idx, backup) in backups.into_iter().enumerate() {
+            let secs = backup
+                .created
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let day = secs / (24 * 60 * 60);
+            // The epoch was a Thursday
+            let week = (day + 3) / 7;
+
+            let mut keep = idx == 0;
+            if days.len() < retention.daily && !days.contains(&day) {
+                days.push(day);
+                keep = true;
+            }
+            if weeks.len() < retention.weekly && !weeks.contains(&week) {
+                weeks.push(week);
+                keep = true;
+            }
+            if !keep {
+                expired.push(backup);
+            }
+        }
+    }
+    expired
+}
+
+/// Puts the world in the backup back into `saves_dir`, replacing the world's current folder.
+/// The backup is extracted next to it first, so a failed restore leaves the world as it was
+pub async fn restore_backup(backup: &Path, saves_dir: &Path) -> Result<(), Error> {
+    let reader = ZipReader::new(backup).await.map_err(zip_error)?;
+    let world = reader
+        .file()
+        .entries()
+        .first()
+        .and_then(|entry| entry.filename().as_str().ok())
+        .and_then(|name| name.split('/').next())
+        .filter(|world| !world.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| invalid_data("The backup is empty"))?;
+    let target = saves_dir.join(safe_relative(&world)?);
+
+    let staging = saves_dir.join(format!(".{world}.restoring"));
+    if tokio::fs::try_exists(&staging).await? {
+        tokio::fs::remove_dir_all(&staging).await?;
+    }
+    extract_folder(&reader, &world, &staging).await?;
+
+    if tokio::fs::try_exists(&target).await? {
+        tokio::fs::remove_dir_all(&target).await?;
+    }
+    tokio::fs::rename(&staging, &target).await?;
+
+    Ok(())
+}
+
+/// Backups are named `<world>-<unix time>`, world names can contain dashes themselves
+fn parse_backup_name(stem: &str) -> Option<(String, u64)> {
+    let (world, secs) = stem.rsplit_once('-')?;
+    Some((world.to_string(), secs.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expired, parse_backup_name, Backup, Retention};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_parse_backup_name() {
+        assert_eq!(
+            parse_backup_name("New World-1700000000"),
+            Some(("New World".to_string(), 1700000000))
+        );
+        assert_eq!(
+            parse_backup_name("my-world-1700000000"),
+            Some(("my-world".to_string(), 1700000000))
+        );
+        assert_eq!(parse_backup_name("screenshots"), None);
+    }
+
+    #[test]
+    fn test_expired() {
+        // Monday the 6th of November 2023, at noon
+        let monday = 1699272000;
+        let backup = |world: &str, secs: u64| Backup {
+            path: format!("{world}-{secs}.zip").into(),
+            world: world.into(),
+            created: UNIX_EPOCH + Duration::from_secs(secs),
+        };
+        let backups = [
+            backup("World", monday + 2 * DAY + 60),
+            backup("World", monday + 2 * DAY),
+            backup("World", monday + DAY),
+            backup("World", monday),
+            backup("World", monday - DAY),
+            backup("World", monday - 8 * DAY),
+            backup("Other", monday - 30 * DAY),
+        ];
+        let names = |retention| {
+            let mut names: Vec<_> = expired(&backups, retention)
+                .into_iter()
+                .map(|backup| backup.path.to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        };
+
+        // The newest of each of the last two days, and of the last two weeks
+        let retention = Retention {
+            daily: 2,
+            weekly: 2,
+        };
+        assert_eq!(
+            names(retention),
+            [
+                format!("World-{}.zip", monday - 8 * DAY),
+                format!("World-{}.zip", monday),
+                format!("World-{}.zip", monday + 2 * DAY),
+            ]
+        );
+
+        // The newest backup of each world is always kept
+        let retention = Retention {
+            daily: 0,
+            weekly: 0,
+        };
+        assert_eq!(names(retention).len(), 5);
+    }
+}
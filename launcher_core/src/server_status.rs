@@ -0,0 +1,330 @@
+use crate::error::invalid_data;
+use crate::Error;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Used when an address doesn't have a port
+pub const DEFAULT_PORT: u16 = 25565;
+/// By convention, for clients that don't know which version the server runs
+const ANY_PROTOCOL: i32 = -1;
+/// Far more than a status with a favicon needs, so a broken server can't make us allocate
+/// whatever it claims
+const MAX_PACKET: usize = 1 << 21;
+
+/// What a server answers a Server List Ping with, what the multiplayer screen shows
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    pub version: StatusVersion,
+    /// Servers can hide their player count
+    pub players: Option<Players>,
+    /// A chat component, see [`ServerStatus::motd`]
+    #[serde(default)]
+    pub description: Value,
+    /// `data:image/png;base64,` and a 64x64 png
+    pub favicon: Option<String>,
+    /// Measured with the ping packet, the server doesn't send it
+    #[serde(skip)]
+    pub latency: Duration,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StatusVersion {
+    pub name: String,
+    pub protocol: i32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Players {
+    pub max: i64,
+    pub online: i64,
+    /// Some of the players online, servers often list none or fake ones
+    #[serde(default)]
+    pub sample: Vec<PlayerSample>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: String,
+}
+
+impl ServerStatus {
+    /// The description as plain text, without its colors and formatting codes
+    pub fn motd(&self) -> String {
+        let mut text = String::new();
+        flatten(&self.description, &mut text);
+
+        let mut motd = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            // A code is the section sign and the character after it
+            if c == '§' {
+                chars.next();
+            } else {
+                motd.push(c);
+            }
+        }
+        motd
+    }
+}
+
+fn flatten(component: &Value, text: &mut String) {
+    match component {
+        Value::String(part) => text.push_str(part),
+        Value::Array(parts) => parts.iter().for_each(|part| flatten(part, text)),
+        Value::Object(object) => {
+            if let Some(Value::String(part)) = object.get("text") {
+                text.push_str(part);
+            }
+            if let Some(extra) = object.get("extra") {
+                flatten(extra, text);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Asks the server at `address` for its status with the Server List Ping that clients since
+/// 1.7 use. The address is `host`, `host:port` or `[ipv6]:port`, SRV records aren't looked
+/// up so servers that rely on one need their port. Fails with [`std::io::ErrorKind::TimedOut`]
+/// if the whole exchange takes longer than `timeout`
+pub async fn status(address: &str, timeout: Duration) -> Result<ServerStatus, Error> {
+    let (host, port) = parse_address(address)?;
+    match tokio::time::timeout(timeout, exchange(host, port)).await {
+        Ok(result) => result,
+        Err(_) => {
+            let message = format!("{address} didn't answer in time");
+            Err(std::io::Error::new(std::io::ErrorKind::TimedOut, message).into())
+        }
+    }
+}
+
+fn parse_address(address: &str) -> Result<(&str, u16), Error> {
+    let address = address.trim();
+    let (host, port) = if let Some(rest) = address.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or_else(|| invalid_data(format!("{address} is missing a ]")))?;
+        (host, rest.strip_prefix(':'))
+    } else {
+        match address.rsplit_once(':') {
+            // More than one colon is an IPv6 address without a port
+            Some((host, port)) if !host.contains(':') => (host, Some(port)),
+            _ => (address, None),
+        }
+    };
+
+    if host.is_empty() {
+        return Err(invalid_data("The server address is empty"));
+    }
+    let port = match port {
+        Some(port) => port
+            .parse()
+            .map_err(|_| invalid_data(format!("{port} isn't a port")))?,
+        None => DEFAULT_PORT,
+    };
+    Ok((host, port))
+}
+
+async fn exchange(host: &str, port: u16) -> Result<ServerStatus, Error> {
+    let mut stream = TcpStream::connect((host, port)).await?;
+
+    let mut handshake = Vec::new();
+    write_var_int(&mut handshake, 0x00);
+    write_var_int(&mut handshake, ANY_PROTOCOL);
+    write_string(&mut handshake, host);
+    handshake.extend_from_slice(&port.to_be_bytes());
+    // The status state, 2 would be logging in
+    write_var_int(&mut handshake, 1);
+    write_packet(&mut stream, &handshake).await?;
+
+    let sent = Instant::now();
+    write_packet(&mut stream, &[0x00]).await?;
+    let response = read_packet(&mut stream).await?;
+    let mut body = response.as_slice();
+    if read_var_int(&mut body)? != 0x00 {
+        return Err(invalid_data("The server didn't answer with its status"));
+    }
+    let mut status: ServerStatus = serde_json::from_str(&read_string(&mut body)?)?;
+    status.latency = sent.elapsed();
+
+    // Some servers close the connection instead of answering, then the status has to do
+    let mut ping = vec![0x01];
+    ping.extend_from_slice(&0i64.to_be_bytes());
+    let sent = Instant::now();
+    if write_packet(&mut stream, &ping).await.is_ok() && read_packet(&mut stream).await.is_ok() {
+        status.latency = sent.elapsed();
+    }
+
+    Ok(status)
+}
+
+/// Sends the packet with its length in front
+async fn write_packet(stream: &mut (impl AsyncWrite + Unpin), packet: &[u8]) -> Result<(), Error> {
+    let mut buf = Vec::with_capacity(packet.len() + 5);
+    write_var_int(&mut buf, packet.len() as i32);
+    buf.extend_from_slice(packet);
+    stream.write_all(&buf).await?;
+    Ok(())
+}
+
+/// The packet without its length, the id is still in front
+async fn read_packet(stream: &mut (impl AsyncRead + Unpin)) -> Result<Vec<u8>, Error> {
+    // A VarInt is at most 5 bytes, the last one doesn't have the continuation bit
+    let mut length = Vec::with_capacity(5);
+    loop {
+        let byte = stream.read_u8().await?;
+        length.push(byte);
+        if byte & 0x80 == 0 || length.len() == 5 {
+            break;
+        }
+    }
+    let length = read_var_int(&mut length.as_slice())?;
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|length| *length <= MAX_PACKET)
+        .ok_or_else(|| invalid_data(format!("The server sent a packet of {length} bytes")))?;
+
+    let mut packet = vec![0; length];
+    stream.read_exact(&mut packet).await?;
+    Ok(packet)
+}
+
+fn write_var_int(buf: &mut Vec<u8>, value: i32) {
+    // Negative numbers are written as their two's complement, so always take 5 bytes
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+}
+
+/// Reads from the front of the slice and moves it past the VarInt
+fn read_var_int(buf: &mut &[u8]) -> Result<i32, Error> {
+    let bytes = *buf;
+    let mut value = 0u32;
+    for (position, byte) in bytes.iter().enumerate().take(5) {
+        value |= ((byte & 0x7F) as u32) << (7 * position);
+        if byte & 0x80 == 0 {
+            *buf = &bytes[position + 1..];
+            return Ok(value as i32);
+        }
+    }
+    Err(invalid_data("The server sent a broken VarInt"))
+}
+
+fn write_string(buf: &mut Vec<u8>, string: &str) {
+    write_var_int(buf, string.len() as i32);
+    buf.extend_from_slice(string.as_bytes());
+}
+
+fn read_string(buf: &mut &[u8]) -> Result<String, Error> {
+    let length = read_var_int(buf)?;
+    let bytes = *buf;
+    let string = usize::try_from(length)
+        .ok()
+        .and_then(|length| bytes.get(..length))
+        .ok_or_else(|| invalid_data("The server sent a string longer than its packet"))?;
+    *buf = &bytes[string.len()..];
+    Ok(String::from_utf8_lossy(string).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_address, read_packet, read_string, read_var_int, status, write_packet, write_string,
+        write_var_int, ServerStatus, DEFAULT_PORT,
+    };
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_var_int() {
+        let cases: [(i32, &[u8]); 5] = [
+            (0, &[0x00]),
+            (127, &[0x7F]),
+            (255, &[0xFF, 0x01]),
+            (25565, &[0xDD, 0xC7, 0x01]),
+            (-1, &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]),
+        ];
+        for (value, bytes) in cases {
+            let mut buf = Vec::new();
+            write_var_int(&mut buf, value);
+            assert_eq!(buf, bytes);
+            let mut slice = buf.as_slice();
+            assert_eq!(read_var_int(&mut slice).unwrap(), value);
+            assert!(slice.is_empty());
+        }
+        assert!(read_var_int(&mut [0x80, 0x80].as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("mc.example.com").unwrap(),
+            ("mc.example.com", DEFAULT_PORT)
+        );
+        assert_eq!(
+            parse_address(" mc.example.com:25566 ").unwrap(),
+            ("mc.example.com", 25566)
+        );
+        assert_eq!(parse_address("[::1]:25566").unwrap(), ("::1", 25566));
+        assert_eq!(parse_address("::1").unwrap(), ("::1", DEFAULT_PORT));
+        assert!(parse_address("mc.example.com:port").is_err());
+        assert!(parse_address("").is_err());
+    }
+
+    #[test]
+    fn test_motd() {
+        let status: ServerStatus = serde_json::from_str(
+            r#"{
+                "version": {"name": "1.20.4", "protocol": 765},
+                "description": {"text": "§aA ", "extra": ["Minecraft ", {"text": "Server"}]}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(status.motd(), "A Minecraft Server");
+        assert!(status.players.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let handshake = read_packet(&mut stream).await.unwrap();
+            let mut body = &handshake[1..];
+            assert_eq!(read_var_int(&mut body).unwrap(), -1);
+            assert_eq!(read_string(&mut body).unwrap(), "127.0.0.1");
+            assert_eq!(read_packet(&mut stream).await.unwrap(), [0x00]);
+
+            let mut response = vec![0x00];
+            let json = r#"{"version": {"name": "1.20.4", "protocol": 765},
+                "players": {"max": 20, "online": 1, "sample": [{"name": "Steve", "id": "0"}]},
+                "description": "A Minecraft Server"}"#;
+            write_string(&mut response, json);
+            write_packet(&mut stream, &response).await.unwrap();
+
+            let ping = read_packet(&mut stream).await.unwrap();
+            write_packet(&mut stream, &ping).await.unwrap();
+        });
+
+        let address = format!("127.0.0.1:{port}");
+        let status = status(&address, Duration::from_secs(5)).await.unwrap();
+        server.await.unwrap();
+        assert_eq!(status.version.protocol, 765);
+        let players = status.players.as_ref().unwrap();
+        assert_eq!((players.online, players.max), (1, 20));
+        assert_eq!(players.sample[0].name, "Steve");
+        assert_eq!(status.motd(), "A Minecraft Server");
+    }
+}
@@ -0,0 +1,88 @@
+use crate::types::Version;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// When 1.8 came out, older versions ask skin servers that are long gone
+const RELEASE_1_8: &str = "2014-09-02T08:00:00+00:00";
+
+/// How versions before 1.8 get their skins back, see [`needed`]. Either can be set, the
+/// game is pointed at a community service that answers the old skin urls
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SkinFix {
+    /// `host:port` of an HTTP proxy, the old urls are plain HTTP. Empty for none
+    pub proxy: String,
+    /// A java agent jar that rewrites the urls inside the game
+    pub agent: Option<PathBuf>,
+}
+
+impl SkinFix {
+    pub fn is_set(&self) -> bool {
+        !self.proxy.trim().is_empty() || self.agent.is_some()
+    }
+
+    /// Passed with the other JVM arguments to [`crate::launch_command`]
+    pub fn jvm_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let proxy = self.proxy.trim();
+        match proxy.rsplit_once(':') {
+            Some((host, port)) => {
+                args.push(format!("-Dhttp.proxyHost={host}"));
+                args.push(format!("-Dhttp.proxyPort={port}"));
+            }
+            // Java uses port 80 without one
+            None if !proxy.is_empty() => args.push(format!("-Dhttp.proxyHost={proxy}")),
+            None => {}
+        }
+        if let Some(agent) = &self.agent {
+            args.push(format!("-javaagent:{}", agent.display()));
+        }
+        args
+    }
+}
+
+/// Whether the version is old enough for its skins to be broken. The manifest's times are
+/// all UTC, so they compare as text
+pub fn needed(version: &Version) -> bool {
+    !version.release_time.is_empty() && version.release_time.as_str() < RELEASE_1_8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{needed, SkinFix};
+    use crate::types::{Type, Version};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_jvm_args() {
+        let mut fix = SkinFix::default();
+        assert!(!fix.is_set());
+        assert!(fix.jvm_args().is_empty());
+
+        fix.proxy = " skins.example.com:11707 ".into();
+        fix.agent = Some(PathBuf::from("/opt/skinfix.jar"));
+        assert_eq!(
+            fix.jvm_args(),
+            [
+                "-Dhttp.proxyHost=skins.example.com",
+                "-Dhttp.proxyPort=11707",
+                "-javaagent:/opt/skinfix.jar"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_needed() {
+        let version = |release_time: &str| Version {
+            id: String::new(),
+            version_type: Type::Release,
+            url: String::new(),
+            time: String::new(),
+            release_time: release_time.into(),
+        };
+        // 1.7.10 and 1.8
+        assert!(needed(&version("2014-05-14T17:29:23+00:00")));
+        assert!(!needed(&version("2014-09-02T08:00:00+00:00")));
+        assert!(!needed(&version("")));
+    }
+}
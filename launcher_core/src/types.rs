@@ -1,5 +1,6 @@
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
 use std::ops::Deref;
 use std::sync::Arc;
 
@@ -130,7 +131,7 @@ pub struct AssetIndex {
 }
 
 #[skip_serializing_none]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Library {
     pub downloads: Option<Artifact>,
@@ -258,7 +259,7 @@ pub struct Natives {
 }
 
 #[skip_serializing_none]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Rule {
     pub action: Action,
@@ -285,7 +286,7 @@ impl Rule {
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[serde(deny_unknown_fields)]
 pub enum Action {
@@ -294,14 +295,14 @@ pub enum Action {
 }
 
 #[skip_serializing_none]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Os {
     pub name: OsName,
     pub version: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum OsName {
     Windows,
@@ -309,7 +310,7 @@ pub enum OsName {
     Osx,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Artifact {
     pub sha1: String,
@@ -433,18 +434,6 @@ impl<'de> Deserialize<'de> for Arguments {
                     })
                     .collect();
 
-                t.game.iter().for_each(|g| {
-                    if let GameElement::GameClass(g) = &g {
-                        if let Some(r) = &g.rules {
-                            for x in r {
-                                if x.action == Action::Disallow {
-                                    panic!()
-                                }
-                            }
-                        }
-                    }
-                });
-
                 Arguments { jvm, game: t.game }
             }
             TempArgs::String(s) => Arguments {
@@ -491,6 +480,21 @@ pub struct GameClass {
     pub value: Box<[String]>,
 }
 
+impl GameClass {
+    /// Like Mojang's launcher, the arguments are left out unless the last rule that matches
+    /// allows them
+    pub fn applies(&self, flags: &FeatureFlags) -> bool {
+        let Some(rules) = &self.rules else {
+            return true;
+        };
+        rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(flags))
+            .is_some_and(|rule| rule.action == Action::Allow)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct GameRule {
@@ -498,25 +502,114 @@ pub struct GameRule {
     pub features: Features,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct Features {
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub is_demo_user: bool,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub has_custom_resolution: bool,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub has_quick_plays_support: bool,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub is_quick_play_singleplayer: bool,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub is_quick_play_multiplayer: bool,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub is_quick_play_realms: bool,
+impl GameRule {
+    /// Every feature the rule names is on or off like it wants
+    pub fn matches(&self, flags: &FeatureFlags) -> bool {
+        self.features
+            .0
+            .iter()
+            .all(|(name, enabled)| flags.is_enabled(name) == *enabled)
+    }
 }
 
-fn is_false(b: &bool) -> bool {
-    !b
+/// The features a rule depends on by name, like `has_custom_resolution`, and whether they
+/// need to be on. Names the launcher doesn't know are kept too
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Features(pub BTreeMap<String, bool>);
+
+/// The placeholders only feature gated arguments use, they're filled from [`FeatureFlags`]
+const FEATURE_PLACEHOLDERS: [&str; 6] = [
+    "resolution_width",
+    "resolution_height",
+    "quickPlayPath",
+    "quickPlaySingleplayer",
+    "quickPlayMultiplayer",
+    "quickPlayRealms",
+];
+
+/// Features forced on or off for a launch, the ones that aren't named are off. Features with
+/// arguments that need a value, like the resolution, are turned on with the method for it.
+/// Turned on by name alone, their arguments are left out since the game can't use them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureFlags {
+    forced: BTreeMap<String, bool>,
+    values: BTreeMap<&'static str, String>,
+}
+
+impl FeatureFlags {
+    pub fn enable(mut self, name: impl Into<String>) -> Self {
+        self.forced.insert(name.into(), true);
+        self
+    }
+
+    pub fn disable(mut self, name: impl Into<String>) -> Self {
+        self.forced.insert(name.into(), false);
+        self
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.forced.get(name).copied().unwrap_or(false)
+    }
+
+    /// The window size the game starts with
+    pub fn resolution(mut self, width: u32, height: u32) -> Self {
+        self.values.insert("resolution_width", width.to_string());
+        self.values.insert("resolution_height", height.to_string());
+        self.enable("has_custom_resolution")
+    }
+
+    /// Where the game writes what it joined, relative to the game directory
+    pub fn quick_play_path(mut self, path: impl Into<String>) -> Self {
+        self.values.insert("quickPlayPath", path.into());
+        self.enable("has_quick_plays_support")
+    }
+
+    /// Opens the world with that folder name once the game has started
+    pub fn quick_play_singleplayer(mut self, world: impl Into<String>) -> Self {
+        self.values.insert("quickPlaySingleplayer", world.into());
+        self.enable("is_quick_play_singleplayer")
+    }
+
+    /// Joins the server, an address like `mc.example.com:25565`
+    pub fn quick_play_multiplayer(mut self, address: impl Into<String>) -> Self {
+        self.values.insert("quickPlayMultiplayer", address.into());
+        self.enable("is_quick_play_multiplayer")
+    }
+
+    /// Joins the realm with that id
+    pub fn quick_play_realms(mut self, realm: impl Into<String>) -> Self {
+        self.values.insert("quickPlayRealms", realm.into());
+        self.enable("is_quick_play_realms")
+    }
+
+    /// The arguments of the game classes that apply, with the feature placeholders filled.
+    /// Other placeholders are left for the launch to fill
+    pub fn arguments(&self, game: &[GameElement]) -> Vec<String> {
+        let mut arguments = vec![];
+        for element in game {
+            let values = match element {
+                GameElement::String(arg) => std::slice::from_ref(arg),
+                GameElement::GameClass(class) if class.applies(self) => &class.value[..],
+                GameElement::GameClass(_) => continue,
+            };
+            let filled: Option<Vec<_>> = values.iter().map(|arg| self.fill(arg)).collect();
+            arguments.extend(filled.unwrap_or_default());
+        }
+        arguments
+    }
+
+    /// None if the argument needs a value that wasn't given
+    fn fill(&self, arg: &str) -> Option<String> {
+        let mut arg = arg.to_string();
+        for name in FEATURE_PLACEHOLDERS {
+            let placeholder = format!("${{{name}}}");
+            if arg.contains(&placeholder) {
+                arg = arg.replace(&placeholder, self.values.get(name)?);
+            }
+        }
+        Some(arg)
+    }
 }
 
 #[skip_serializing_none]
@@ -636,3 +729,90 @@ where
 
     deserializer.deserialize_any(StringOrBoxArray(std::marker::PhantomData))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Arguments, FeatureFlags};
+
+    #[test]
+    fn test_game_rules() {
+        let arguments = r#"{
+            "game": [
+                "--username",
+                "${auth_player_name}",
+                {
+                    "rules": [{ "action": "allow", "features": { "has_custom_resolution": true } }],
+                    "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]
+                },
+                {
+                    "rules": [
+                        { "action": "allow", "features": { "is_quick_play_multiplayer": true } }
+                    ],
+                    "value": ["--quickPlayMultiplayer", "${quickPlayMultiplayer}"]
+                },
+                {
+                    "rules": [
+                        { "action": "allow", "features": {} },
+                        { "action": "disallow", "features": { "is_demo_user": true } }
+                    ],
+                    "value": "--full"
+                },
+                {
+                    "rules": [{ "action": "allow", "features": { "has_future_feature": true } }],
+                    "value": "--future"
+                }
+            ],
+            "jvm": []
+        }"#;
+        let arguments: Arguments = serde_json::from_str(arguments).unwrap();
+        let game = &arguments.game[..];
+
+        assert_eq!(
+            FeatureFlags::default().arguments(game),
+            ["--username", "${auth_player_name}", "--full"]
+        );
+
+        let flags = FeatureFlags::default()
+            .resolution(854, 480)
+            .quick_play_multiplayer("mc.example.com:25565")
+            .enable("is_demo_user")
+            .enable("has_future_feature");
+        assert_eq!(
+            flags.arguments(game),
+            [
+                "--username",
+                "${auth_player_name}",
+                "--width",
+                "854",
+                "--height",
+                "480",
+                "--quickPlayMultiplayer",
+                "mc.example.com:25565",
+                "--future"
+            ]
+        );
+
+        // A later override wins
+        let flags = flags
+            .disable("is_demo_user")
+            .disable("has_custom_resolution");
+        assert_eq!(
+            flags.arguments(game),
+            [
+                "--username",
+                "${auth_player_name}",
+                "--quickPlayMultiplayer",
+                "mc.example.com:25565",
+                "--full",
+                "--future"
+            ]
+        );
+
+        // Without a value the arguments would reach the game unfilled
+        let flags = FeatureFlags::default().enable("has_custom_resolution");
+        assert_eq!(
+            flags.arguments(game),
+            ["--username", "${auth_player_name}", "--full"]
+        );
+    }
+}